@@ -0,0 +1,19 @@
+-- ============================================
+-- Migración 007: Motivo de completado forzado en fragmentos
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Un fragmento cuya distribución no cuadra con lo planificado
+-- (cantidades por tipo de actividad y horas) no puede marcarse como
+-- completado, salvo que el Leader lo fuerce indicando un motivo.
+-- ============================================
+
+ALTER TABLE balance_fragments
+ADD COLUMN IF NOT EXISTS completion_override_reason TEXT DEFAULT NULL;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('007', 'Add completion_override_reason to balance_fragments')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Columna completion_override_reason añadida a balance_fragments' as status;
@@ -0,0 +1,28 @@
+-- ============================================
+-- Migración 008: Ciclo de vida de los balances
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Estados: draft → open → in_review → approved → archived (+ reopen → open)
+-- Los cambios de estado se hacen solo a través de
+-- POST /api/balances/<id>/transitions
+-- ============================================
+
+-- Mapear los estados anteriores
+-- in_progress: los SubjectLeaders estaban llenando sus fragmentos
+UPDATE balances SET status = 'open' WHERE status = 'in_progress';
+-- completed: todos los fragmentos completos, pendiente de revisión
+UPDATE balances SET status = 'in_review' WHERE status = 'completed';
+
+-- Restringir los valores permitidos
+ALTER TABLE balances DROP CONSTRAINT IF EXISTS balances_status_check;
+ALTER TABLE balances
+ADD CONSTRAINT balances_status_check
+CHECK (status IN ('draft', 'open', 'in_review', 'approved', 'archived'));
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('008', 'Balance lifecycle statuses')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT status, COUNT(*) FROM balances GROUP BY status;
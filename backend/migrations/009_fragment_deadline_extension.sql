@@ -0,0 +1,18 @@
+-- ============================================
+-- Migración 009: Prórroga de fecha límite por fragmento
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Tras la fecha límite del balance los SubjectLeaders ya no pueden editar
+-- su fragmento, salvo que el Leader del balance les conceda una prórroga.
+-- ============================================
+
+ALTER TABLE balance_fragments
+ADD COLUMN IF NOT EXISTS deadline_extension DATE DEFAULT NULL;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('009', 'Add deadline_extension to balance_fragments')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Columna deadline_extension añadida a balance_fragments' as status;
@@ -0,0 +1,47 @@
+-- ============================================
+-- Migración 010: Historial de revisiones de fragmentos
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Cada guardado de un fragmento crea una revisión con autor, fecha y estado.
+-- Restaurar una revisión antigua crea una revisión nueva (restored_from).
+-- Los fragmentos existentes reciben su contenido actual como revisión 1.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS balance_fragment_revisions (
+    id SERIAL PRIMARY KEY,
+    fragment_id INTEGER NOT NULL REFERENCES balance_fragments(id) ON DELETE CASCADE,
+    revision_number INTEGER NOT NULL,
+
+    -- Copia de los datos y el estado guardados
+    data JSONB NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL,
+
+    -- Autor (se conserva el nombre aunque el usuario se elimine)
+    author_id INTEGER REFERENCES usuarios(id) ON DELETE SET NULL,
+    author_name TEXT NOT NULL,
+
+    -- Número de revisión restaurada, si la revisión es una restauración
+    restored_from INTEGER DEFAULT NULL,
+
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+
+    UNIQUE(fragment_id, revision_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_balance_fragment_revisions_fragment_id
+ON balance_fragment_revisions(fragment_id);
+
+-- Revisión 1 con los datos que ya tenían los fragmentos, atribuida al jefe de asignatura
+INSERT INTO balance_fragment_revisions (fragment_id, revision_number, data, status, author_id, author_name, created_at)
+SELECT f.id, 1, f.data, f.status, u.id, COALESCE(u.user_name, 'sistema'), COALESCE(f.updated_at, f.created_at)
+FROM balance_fragments f
+LEFT JOIN usuarios u ON u.id = f.subject_leader_id
+ON CONFLICT (fragment_id, revision_number) DO NOTHING;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('010', 'Create balance_fragment_revisions table')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Tabla balance_fragment_revisions creada correctamente' as status;
@@ -0,0 +1,21 @@
+-- ============================================
+-- Migración 011: Control de concurrencia optimista
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Cada escritura incrementa `version`. Los GET la devuelven como ETag y
+-- las escrituras con If-Match desactualizado se rechazan con 409 Conflict.
+-- ============================================
+
+ALTER TABLE balances
+ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE balance_fragments
+ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('011', 'Add version columns for optimistic concurrency')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Columnas version añadidas a balances y balance_fragments' as status;
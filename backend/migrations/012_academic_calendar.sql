@@ -0,0 +1,43 @@
+-- ============================================
+-- Migración 012: Calendario académico institucional
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Un calendario por curso académico ('2025-2026') con feriados, vacaciones
+-- y sesiones de exámenes. Los balances lo referencian y sus propios
+-- non_academic_periods se suman a los períodos del calendario.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS academic_calendars (
+    id SERIAL PRIMARY KEY,
+    academic_year_text TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS academic_calendar_periods (
+    id SERIAL PRIMARY KEY,
+    calendar_id INTEGER NOT NULL REFERENCES academic_calendars(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('holiday', 'vacation', 'exam_session')),
+    name TEXT NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+
+    CHECK (start_date <= end_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_academic_calendar_periods_calendar_id
+ON academic_calendar_periods(calendar_id);
+
+-- Calendario que usa cada balance (NULL = solo sus propios períodos)
+ALTER TABLE balances
+ADD COLUMN IF NOT EXISTS calendar_id INTEGER REFERENCES academic_calendars(id) ON DELETE SET NULL;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('012', 'Create academic calendar tables and balances.calendar_id')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Tablas academic_calendars y academic_calendar_periods creadas correctamente' as status;
@@ -0,0 +1,23 @@
+-- ============================================
+-- Migración 013: Suscripción iCalendar por usuario
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Cada usuario puede generar un token secreto para suscribirse desde su
+-- aplicación de calendario a los fragmentos que lidera. Regenerar o
+-- eliminar el token revoca la URL anterior.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS calendar_feed_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL UNIQUE REFERENCES usuarios(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('013', 'Create calendar_feed_tokens table')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Tabla calendar_feed_tokens creada correctamente' as status;
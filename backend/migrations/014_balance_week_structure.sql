@@ -0,0 +1,20 @@
+-- ============================================
+-- Migración 014: Estructura semanal configurable por balance
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- `week_structure` define las celdas por semana lectiva, los días laborables
+-- (ISO, 1 = lunes ... 7 = domingo) y las celdas de consultas y exámenes.
+-- NULL equivale a la estructura estándar:
+-- { "slots_per_week": 4, "working_days": [1,2,3,4,5], "consultas_slots": 4, "exam_slots": 5 }
+-- ============================================
+
+ALTER TABLE balances
+ADD COLUMN IF NOT EXISTS week_structure JSONB;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('014', 'Add week_structure column to balances')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Columna week_structure añadida a balances' as status;
@@ -0,0 +1,75 @@
+-- ============================================
+-- Migración 015: Catálogo de tipos de actividad
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Los tipos de actividad dejan de estar fijos en el código y en las columnas
+-- c … ef de asignaturas. El plan de cada asignatura pasa a ser una fila por
+-- tipo en asignatura_activity_plans. Añadir un tipo nuevo ("Visita") es un
+-- INSERT (o POST /api/activity-types), sin migración.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS activity_types (
+    code TEXT PRIMARY KEY CHECK (code ~ '^[A-Z0-9]{1,8}$'),
+    label TEXT NOT NULL,
+    hours INTEGER NOT NULL DEFAULT 2 CHECK (hours >= 0),   -- Horas por ocurrencia
+    is_critical BOOLEAN NOT NULL DEFAULT FALSE,             -- Máximo 2 por semana, en rojo
+    is_exam BOOLEAN NOT NULL DEFAULT FALSE,                 -- Cuenta como evaluación
+    counts_as_class_hours BOOLEAN NOT NULL DEFAULT TRUE,    -- Suma a las horas presenciales
+    schedulable BOOLEAN NOT NULL DEFAULT TRUE,              -- Se puede colocar en la cuadrícula
+    color TEXT CHECK (color ~ '^#[0-9A-Fa-f]{6}$'),
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO activity_types (code, label, hours, is_critical, is_exam, counts_as_class_hours, schedulable, color, sort_order) VALUES
+    ('C',  'Conferencia',             2, FALSE, FALSE, TRUE,  TRUE,  '#DBEAFE', 1),
+    ('CP', 'Clase Práctica',          2, FALSE, FALSE, TRUE,  TRUE,  '#DCFCE7', 2),
+    ('S',  'Seminario',               2, FALSE, FALSE, TRUE,  TRUE,  '#FEF9C3', 3),
+    ('PL', 'Práctica de Laboratorio', 2, FALSE, FALSE, TRUE,  TRUE,  '#E0E7FF', 4),
+    ('TE', 'Tarea Extraclase',        0, TRUE,  FALSE, FALSE, TRUE,  '#FEE2E2', 5),
+    ('T',  'Taller',                  2, TRUE,  FALSE, TRUE,  TRUE,  '#FEE2E2', 6),
+    ('PP', 'Prueba Parcial',          2, TRUE,  TRUE,  TRUE,  TRUE,  '#FECACA', 7),
+    ('EC', 'Examen Comprobatorio',    2, FALSE, TRUE,  TRUE,  TRUE,  '#FFEDD5', 8),
+    ('TC', 'Trabajo de Curso',        2, FALSE, FALSE, TRUE,  FALSE, '#F3E8FF', 9),
+    ('EF', 'Examen Final',            2, FALSE, TRUE,  TRUE,  FALSE, '#FFE4E6', 10)
+ON CONFLICT (code) DO NOTHING;
+
+-- Cantidad planificada de cada tipo por asignatura
+CREATE TABLE IF NOT EXISTS asignatura_activity_plans (
+    asignatura_id INTEGER NOT NULL REFERENCES asignaturas(id) ON DELETE CASCADE,
+    activity_code TEXT NOT NULL REFERENCES activity_types(code) ON UPDATE CASCADE ON DELETE RESTRICT,
+    planned INTEGER NOT NULL CHECK (planned >= 0),
+    PRIMARY KEY (asignatura_id, activity_code)
+);
+
+-- Copiar los planes existentes desde las columnas c … ef
+INSERT INTO asignatura_activity_plans (asignatura_id, activity_code, planned)
+SELECT a.id, v.code, v.planned
+FROM asignaturas a
+CROSS JOIN LATERAL (VALUES
+    ('C', a.c), ('CP', a.cp), ('S', a.s), ('PL', a.pl), ('TE', a.te),
+    ('T', a.t), ('PP', a.pp), ('EC', a.ec), ('TC', a.tc), ('EF', a.ef)
+) AS v(code, planned)
+WHERE v.planned IS NOT NULL
+ON CONFLICT (asignatura_id, activity_code) DO NOTHING;
+
+ALTER TABLE asignaturas
+DROP COLUMN IF EXISTS c,
+DROP COLUMN IF EXISTS cp,
+DROP COLUMN IF EXISTS s,
+DROP COLUMN IF EXISTS pl,
+DROP COLUMN IF EXISTS te,
+DROP COLUMN IF EXISTS t,
+DROP COLUMN IF EXISTS pp,
+DROP COLUMN IF EXISTS ec,
+DROP COLUMN IF EXISTS tc,
+DROP COLUMN IF EXISTS ef;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('015', 'Create activity_types and asignatura_activity_plans, drop asignaturas.c..ef')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Catálogo de tipos de actividad creado correctamente' as status;
@@ -0,0 +1,24 @@
+-- ============================================
+-- Migración 016: Archivado de fragmentos al quitar asignaturas de un balance
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Quitar una asignatura de un balance no borra su fragmento: se marca con
+-- `archived_at` y deja de contar para el progreso, las reglas y las
+-- exportaciones. Volver a añadir la asignatura lo recupera con sus datos.
+-- ============================================
+
+ALTER TABLE balance_fragments
+ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP DEFAULT NULL;
+
+-- Índice parcial para las consultas de fragmentos activos por balance
+CREATE INDEX IF NOT EXISTS idx_balance_fragments_active
+ON balance_fragments(balance_id)
+WHERE archived_at IS NULL;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('016', 'Add archived_at column to balance_fragments')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Columna archived_at añadida a balance_fragments' as status;
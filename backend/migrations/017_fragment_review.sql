@@ -0,0 +1,45 @@
+-- ============================================
+-- Migración 017: Revisión de fragmentos con comentarios
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- El jefe de asignatura entrega su fragmento (status 'submitted') y el Leader
+-- del balance lo aprueba ('completed') o pide cambios ('changes_requested')
+-- con comentarios anclados a una semana o celda de la cuadrícula.
+-- Cada comentario sin parent_id abre un hilo que se marca como resuelto.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS fragment_review_comments (
+    id SERIAL PRIMARY KEY,
+    fragment_id INTEGER NOT NULL REFERENCES balance_fragments(id) ON DELETE CASCADE,
+
+    -- Respuesta dentro de un hilo (NULL = comentario que abre el hilo)
+    parent_id INTEGER REFERENCES fragment_review_comments(id) ON DELETE CASCADE,
+
+    -- Ancla en la cuadrícula (NULL = comentario general del fragmento)
+    section TEXT DEFAULT NULL,  -- 'week', 'consultas', 'examenes'
+    week INTEGER DEFAULT NULL,  -- 1-based, solo en semanas lectivas
+    day INTEGER DEFAULT NULL,   -- 1-based, NULL = toda la semana
+
+    body TEXT NOT NULL,
+
+    -- Autor (se conserva el nombre aunque el usuario se elimine)
+    author_id INTEGER REFERENCES usuarios(id) ON DELETE SET NULL,
+    author_name TEXT NOT NULL,
+
+    -- Resolución del hilo (solo en el comentario raíz)
+    resolved_at TIMESTAMP DEFAULT NULL,
+    resolved_by_name TEXT DEFAULT NULL,
+
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_fragment_review_comments_fragment_id
+ON fragment_review_comments(fragment_id);
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('017', 'Create fragment_review_comments table')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Tabla fragment_review_comments creada correctamente' as status;
@@ -0,0 +1,61 @@
+-- ============================================
+-- Migración 018: Notificaciones por correo
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Avisos por correo a los jefes de asignatura (balance creado, fragmento
+-- asignado, fecha límite próxima, cambios pedidos en la revisión).
+-- Cada aviso se guarda en notification_outbox y un proceso en segundo plano
+-- lo envía por SMTP; los envíos fallidos se reintentan con espera creciente
+-- hasta agotar notification_max_attempts (status 'failed').
+-- La configuración SMTP se guarda en system_settings (categoría 'notifications').
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS notification_outbox (
+    id SERIAL PRIMARY KEY,
+    event TEXT NOT NULL,  -- 'balance_created', 'fragment_assigned', 'deadline_approaching', 'changes_requested'
+
+    -- Destinatario (se conserva la dirección aunque el usuario se elimine)
+    recipient_id INTEGER REFERENCES usuarios(id) ON DELETE SET NULL,
+    recipient_email TEXT NOT NULL,
+    recipient_name TEXT NOT NULL,
+
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+
+    -- Entrega
+    status TEXT NOT NULL DEFAULT 'pending',  -- 'pending', 'sent', 'failed'
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT DEFAULT NULL,
+    sent_at TIMESTAMP DEFAULT NULL,
+
+    -- Evita repetir avisos periódicos (p. ej. 'deadline:<balance>:<asignatura>:<fecha>')
+    dedup_key TEXT UNIQUE DEFAULT NULL,
+
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_outbox_pending
+ON notification_outbox(next_attempt_at) WHERE status = 'pending';
+
+-- Configuración SMTP (por defecto, un servidor local tipo MailHog)
+INSERT INTO system_settings (key, value, description, category) VALUES
+    ('notifications_enabled', 'false', 'Enviar avisos por correo a los jefes de asignatura', 'notifications'),
+    ('smtp_host', 'localhost', 'Servidor SMTP', 'notifications'),
+    ('smtp_port', '1025', 'Puerto del servidor SMTP', 'notifications'),
+    ('smtp_security', 'none', 'Cifrado de la conexión: none, starttls o tls', 'notifications'),
+    ('smtp_username', '', 'Usuario SMTP (vacío si el servidor no pide autenticación)', 'notifications'),
+    ('smtp_password', '', 'Contraseña SMTP (no se muestra; déjala vacía para conservarla)', 'notifications'),
+    ('smtp_from', 'Balance de Carga <no-reply@localhost>', 'Remitente de los avisos', 'notifications'),
+    ('notifications_app_url', '', 'URL de la aplicación para los enlaces de los correos', 'notifications'),
+    ('notification_max_attempts', '5', 'Intentos de envío antes de dar un aviso por fallido', 'notifications'),
+    ('deadline_reminder_days', '3', 'Días de antelación del aviso de fecha límite', 'notifications')
+ON CONFLICT (key) DO NOTHING;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('018', 'Create notification_outbox table and SMTP settings')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Tabla notification_outbox creada correctamente' as status;
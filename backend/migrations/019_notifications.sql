@@ -0,0 +1,41 @@
+-- ============================================
+-- Migración 019: Bandeja de notificaciones en la aplicación
+-- Ejecutar en Supabase SQL Editor
+-- ============================================
+-- Avisos que cada usuario ve en la aplicación: fragmento asignado, editado
+-- por otra persona, aprobado, cambios pedidos y cambios de fecha límite.
+-- Se generan en el servidor junto con los correos de notification_outbox,
+-- pero no dependen de que el envío por correo esté activado.
+-- ============================================
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES usuarios(id) ON DELETE CASCADE,
+    event TEXT NOT NULL,  -- mismo valor que notification_outbox.event
+
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+
+    -- Fragmento al que se refiere (para abrirlo desde la bandeja)
+    balance_id INTEGER REFERENCES balances(id) ON DELETE CASCADE,
+    asignatura_id INTEGER REFERENCES asignaturas(id) ON DELETE CASCADE,
+
+    read_at TIMESTAMP DEFAULT NULL,
+    -- Avisos periódicos (fecha límite próxima): uno por clave, con o sin correo
+    dedup_key TEXT UNIQUE DEFAULT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_id
+ON notifications(user_id, id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_unread
+ON notifications(user_id) WHERE read_at IS NULL;
+
+-- Registrar migración
+INSERT INTO schema_migrations (version, description)
+VALUES ('019', 'Create notifications table')
+ON CONFLICT (version) DO NOTHING;
+
+-- Verificar
+SELECT 'Tabla notifications creada correctamente' as status;
@@ -15,16 +15,6 @@ pub struct Model {
     pub year: String,
     #[sea_orm(column_type = "Text")]
     pub semester: String,
-    pub c: Option<i32>,
-    pub cp: Option<i32>,
-    pub s: Option<i32>,
-    pub pl: Option<i32>,
-    pub te: Option<i32>,
-    pub t: Option<i32>,
-    pub pp: Option<i32>,
-    pub ec: Option<i32>,
-    pub tc: Option<i32>,
-    pub ef: Option<i32>,
     pub hours: i32,
     pub date_start: DateTime,
     pub date_end: DateTime,
@@ -33,8 +23,12 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::asignatura_activity_plans::Entity")]
+    AsignaturaActivityPlans,
     #[sea_orm(has_many = "super::balance_fragments::Entity")]
     BalanceFragments,
+    #[sea_orm(has_many = "super::notifications::Entity")]
+    Notifications,
     #[sea_orm(
         belongs_to = "super::usuarios::Entity",
         from = "Column::LeaderId",
@@ -45,12 +39,24 @@ pub enum Relation {
     Usuarios,
 }
 
+impl Related<super::asignatura_activity_plans::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AsignaturaActivityPlans.def()
+    }
+}
+
 impl Related<super::balance_fragments::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::BalanceFragments.def()
     }
 }
 
+impl Related<super::notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notifications.def()
+    }
+}
+
 impl Related<super::usuarios::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Usuarios.def()
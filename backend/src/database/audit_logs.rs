@@ -68,6 +68,7 @@ pub enum EventType {
     AccessDenied,
     SettingsUpdated,
     Export,
+    StatusChange,
 }
 
 impl EventType {
@@ -83,6 +84,7 @@ impl EventType {
             EventType::AccessDenied => "ACCESS_DENIED",
             EventType::SettingsUpdated => "SETTINGS_UPDATED",
             EventType::Export => "EXPORT",
+            EventType::StatusChange => "STATUS_CHANGE",
         }
     }
 }
@@ -112,6 +114,7 @@ pub enum EntityType {
     Session,
     Fragment,
     Settings,
+    Calendar,
 }
 
 impl EntityType {
@@ -123,6 +126,7 @@ impl EntityType {
             EntityType::Session => "SESSION",
             EntityType::Fragment => "FRAGMENT",
             EntityType::Settings => "SETTINGS",
+            EntityType::Calendar => "CALENDAR",
         }
     }
 }
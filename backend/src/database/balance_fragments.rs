@@ -18,6 +18,11 @@ pub struct Model {
     pub completed_at: Option<DateTime>,
     pub created_at: Option<DateTime>,
     pub updated_at: Option<DateTime>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub completion_override_reason: Option<String>,
+    pub deadline_extension: Option<Date>,
+    pub version: i32,
+    pub archived_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -30,6 +35,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Asignaturas,
+    #[sea_orm(has_many = "super::balance_fragment_revisions::Entity")]
+    BalanceFragmentRevisions,
     #[sea_orm(
         belongs_to = "super::balances::Entity",
         from = "Column::BalanceId",
@@ -38,6 +45,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Balances,
+    #[sea_orm(has_many = "super::fragment_review_comments::Entity")]
+    FragmentReviewComments,
     #[sea_orm(
         belongs_to = "super::usuarios::Entity",
         from = "Column::SubjectLeaderId",
@@ -54,12 +63,24 @@ impl Related<super::asignaturas::Entity> for Entity {
     }
 }
 
+impl Related<super::balance_fragment_revisions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BalanceFragmentRevisions.def()
+    }
+}
+
 impl Related<super::balances::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Balances.def()
     }
 }
 
+impl Related<super::fragment_review_comments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::FragmentReviewComments.def()
+    }
+}
+
 impl Related<super::usuarios::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Usuarios.def()
@@ -29,12 +29,26 @@ pub struct Model {
     pub non_academic_periods: Json,
     pub created_at: Option<DateTime>,
     pub updated_at: Option<DateTime>,
+    pub version: i32,
+    pub calendar_id: Option<i32>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub week_structure: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::academic_calendars::Entity",
+        from = "Column::CalendarId",
+        to = "super::academic_calendars::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    AcademicCalendars,
     #[sea_orm(has_many = "super::balance_fragments::Entity")]
     BalanceFragments,
+    #[sea_orm(has_many = "super::notifications::Entity")]
+    Notifications,
     #[sea_orm(
         belongs_to = "super::usuarios::Entity",
         from = "Column::UserId",
@@ -45,12 +59,24 @@ pub enum Relation {
     Usuarios,
 }
 
+impl Related<super::academic_calendars::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AcademicCalendars.def()
+    }
+}
+
 impl Related<super::balance_fragments::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::BalanceFragments.def()
     }
 }
 
+impl Related<super::notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notifications.def()
+    }
+}
+
 impl Related<super::usuarios::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Usuarios.def()
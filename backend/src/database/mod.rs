@@ -2,10 +2,19 @@
 
 pub mod prelude;
 
+pub mod academic_calendar_periods;
+pub mod academic_calendars;
+pub mod activity_types;
+pub mod asignatura_activity_plans;
 pub mod asignaturas;
 pub mod audit_logs;
+pub mod balance_fragment_revisions;
 pub mod balance_fragments;
 pub mod balances;
+pub mod calendar_feed_tokens;
+pub mod fragment_review_comments;
+pub mod notification_outbox;
+pub mod notifications;
 pub mod schema_migrations;
 pub mod system_settings;
 pub mod usuarios;
@@ -1,9 +1,18 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.19
 
+pub use super::academic_calendar_periods::Entity as AcademicCalendarPeriods;
+pub use super::academic_calendars::Entity as AcademicCalendars;
+pub use super::activity_types::Entity as ActivityTypes;
+pub use super::asignatura_activity_plans::Entity as AsignaturaActivityPlans;
 pub use super::asignaturas::Entity as Asignaturas;
 pub use super::audit_logs::Entity as AuditLogs;
+pub use super::balance_fragment_revisions::Entity as BalanceFragmentRevisions;
 pub use super::balance_fragments::Entity as BalanceFragments;
 pub use super::balances::Entity as Balances;
+pub use super::calendar_feed_tokens::Entity as CalendarFeedTokens;
+pub use super::fragment_review_comments::Entity as FragmentReviewComments;
+pub use super::notification_outbox::Entity as NotificationOutbox;
+pub use super::notifications::Entity as Notifications;
 pub use super::schema_migrations::Entity as SchemaMigrations;
 pub use super::system_settings::Entity as SystemSettings;
 pub use super::usuarios::Entity as Usuarios;
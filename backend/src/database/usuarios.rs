@@ -28,10 +28,18 @@ pub enum Relation {
     Asignaturas,
     #[sea_orm(has_many = "super::audit_logs::Entity")]
     AuditLogs,
+    #[sea_orm(has_many = "super::balance_fragment_revisions::Entity")]
+    BalanceFragmentRevisions,
     #[sea_orm(has_many = "super::balance_fragments::Entity")]
     BalanceFragments,
     #[sea_orm(has_many = "super::balances::Entity")]
     Balances,
+    #[sea_orm(has_one = "super::calendar_feed_tokens::Entity")]
+    CalendarFeedTokens,
+    #[sea_orm(has_many = "super::notification_outbox::Entity")]
+    NotificationOutbox,
+    #[sea_orm(has_many = "super::notifications::Entity")]
+    Notifications,
 }
 
 impl Related<super::asignaturas::Entity> for Entity {
@@ -46,6 +54,12 @@ impl Related<super::audit_logs::Entity> for Entity {
     }
 }
 
+impl Related<super::balance_fragment_revisions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BalanceFragmentRevisions.def()
+    }
+}
+
 impl Related<super::balance_fragments::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::BalanceFragments.def()
@@ -58,4 +72,22 @@ impl Related<super::balances::Entity> for Entity {
     }
 }
 
+impl Related<super::calendar_feed_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CalendarFeedTokens.def()
+    }
+}
+
+impl Related<super::notification_outbox::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NotificationOutbox.def()
+    }
+}
+
+impl Related<super::notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notifications.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -9,6 +9,7 @@ pub use rocket::http::Status;
 pub use rocket::response::Redirect;
 pub use rocket::serde::json::Json;
 use rocket::Rocket;
+use rocket::data::{Limits, ToByteUnit};
 pub use rocket::State;
 pub use rocket::form::FromForm;
 pub use sea_orm::{Database, DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, Set, ActiveModelTrait};
@@ -40,6 +41,7 @@ use routes::login::{
 
 use routes::manager::{
     create_user,
+    import_users,
     delete_user,
     list_users,
     modify_user,
@@ -48,6 +50,7 @@ use routes::manager::{
     create_asignatura,
     list_asignaturas,
     update_asignatura,
+    reassign_asignatura_leader,
     delete_asignatura,
     list_subject_leaders
 };
@@ -56,12 +59,66 @@ use routes::balance::{
     list_balances,
     get_balance,
     create_balance,
+    clone_balance,
     update_balance,
+    transition_balance,
+    add_balance_subjects,
+    remove_balance_subject,
     delete_balance,
     get_pending_fragments,
     get_fragment,
     update_fragment,
-    export_balance_excel
+    set_fragment_extension,
+    reassign_fragment,
+    get_unassigned_fragments,
+    export_balance_excel,
+    export_period_excel,
+    validate_balance,
+    get_balance_load,
+    get_balance_calendar,
+    get_fragment_reconciliation
+};
+
+use routes::asignatura_catalog::{
+    export_asignaturas,
+    import_asignaturas
+};
+
+use routes::balance_import::{
+    import_new_balance,
+    import_into_balance
+};
+
+use routes::calendar::{
+    list_calendars,
+    get_calendar,
+    create_calendar,
+    update_calendar,
+    delete_calendar,
+    add_calendar_period,
+    delete_calendar_period
+};
+
+use routes::ical::{
+    export_balance_ics,
+    export_fragment_ics,
+    get_calendar_feed,
+    regenerate_calendar_feed,
+    revoke_calendar_feed,
+    calendar_feed
+};
+
+use routes::revisions::{
+    list_fragment_revisions,
+    diff_fragment_revisions,
+    restore_fragment_revision
+};
+
+use routes::reviews::{
+    review_fragment,
+    list_fragment_comments,
+    add_fragment_comment,
+    resolve_comment_thread
 };
 
 use routes::audit::{
@@ -72,6 +129,23 @@ use routes::audit::{
     export_audit_logs
 };
 
+use routes::activity_types::{
+    list_activity_types,
+    create_activity_type,
+    update_activity_type,
+    delete_activity_type
+};
+
+use routes::notifications::{
+    list_notifications,
+    count_unread_notifications,
+    mark_notification_read,
+    mark_all_notifications_read,
+    list_outbox,
+    retry_outbox_entry,
+    send_test_email
+};
+
 use routes::settings::{
     list_settings,
     update_settings,
@@ -96,6 +170,15 @@ pub async fn run() -> Rocket<Build> {
     
     let audit_log_ip = routes::settings::load_audit_log_ip_setting(&db).await;
     utils::audit::set_audit_log_ip(audit_log_ip);
+
+    let activity_types = routes::activity_types::load_activity_types(&db).await;
+    utils::activity_types::set_activity_types(activity_types);
+
+    let notification_settings = routes::settings::load_notification_settings(&db).await;
+    utils::notifications::set_notification_settings(notification_settings);
+    
+    // Cola de notificaciones por correo: la envía y encola los avisos de fecha límite
+    utils::notifications::spawn_worker(db.clone());
     
     // NOTA: Se ha removido el Cronjob automático a petición para mayor seguridad de las trazas.
     // La limpieza de logs antiguos ahora se realiza exclusivamente de forma manual desde el panel
@@ -110,7 +193,9 @@ pub async fn run() -> Rocket<Build> {
     // Configuración para despliegue (leer PORT y bindear 0.0.0.0)
     let figment = rocket::Config::figment()
         .merge(("port", std::env::var("PORT").unwrap_or_else(|_| "8000".to_string()).parse::<u16>().unwrap_or(8000)))
-        .merge(("address", "0.0.0.0"));
+        .merge(("address", "0.0.0.0"))
+        // La plantilla Excel de la facultad supera el límite por defecto de 1 MiB
+        .merge(("limits", Limits::default().limit("file", 10.mebibytes()).limit("data-form", 10.mebibytes())));
 
     let mut rocket = rocket::custom(figment)
         .manage(AppState { db, rate_limiter })
@@ -119,6 +204,7 @@ pub async fn run() -> Rocket<Build> {
         .mount("/api", routes![
             login_json,
             create_user,
+            import_users,
             delete_user,
             list_users,
             modify_user,
@@ -129,25 +215,80 @@ pub async fn run() -> Rocket<Build> {
             create_asignatura,
             list_asignaturas,
             update_asignatura,
+            reassign_asignatura_leader,
             delete_asignatura,
+            export_asignaturas,
+            import_asignaturas,
             list_subject_leaders,
+            // Rutas del catálogo de tipos de actividad
+            list_activity_types,
+            create_activity_type,
+            update_activity_type,
+            delete_activity_type,
             // Rutas de balances
             list_balances,
             get_balance,
             create_balance,
+            clone_balance,
             update_balance,
+            transition_balance,
+            add_balance_subjects,
+            remove_balance_subject,
             delete_balance,
             export_balance_excel,
+            export_period_excel,
+            validate_balance,
+            get_balance_load,
+            get_balance_calendar,
+            // Rutas de importación desde la plantilla Excel
+            import_new_balance,
+            import_into_balance,
             // Rutas de fragmentos
             get_pending_fragments,
             get_fragment,
             update_fragment,
+            set_fragment_extension,
+            reassign_fragment,
+            get_unassigned_fragments,
+            get_fragment_reconciliation,
+            // Rutas de revisiones de fragmentos
+            list_fragment_revisions,
+            diff_fragment_revisions,
+            restore_fragment_revision,
+            // Rutas de revisión de fragmentos con comentarios
+            review_fragment,
+            list_fragment_comments,
+            add_fragment_comment,
+            resolve_comment_thread,
+            // Rutas del calendario académico
+            list_calendars,
+            get_calendar,
+            create_calendar,
+            update_calendar,
+            delete_calendar,
+            add_calendar_period,
+            delete_calendar_period,
+            // Rutas de exportación iCalendar
+            export_balance_ics,
+            export_fragment_ics,
+            get_calendar_feed,
+            regenerate_calendar_feed,
+            revoke_calendar_feed,
+            calendar_feed,
             // Rutas de auditoría
             list_audit_logs,
             list_security_logs,
             get_audit_stats,
             cleanup_audit_logs,
             export_audit_logs,
+            // Rutas de la cola de notificaciones por correo
+            list_notifications,
+            count_unread_notifications,
+            mark_notification_read,
+            mark_all_notifications_read,
+            list_outbox,
+            retry_outbox_entry,
+            send_test_email,
             // Rutas de configuración
             list_settings,
             update_settings,
@@ -0,0 +1,314 @@
+//! Rutas del catálogo de tipos de actividad
+//!
+//! - Cualquier usuario autenticado puede consultar el catálogo
+//! - El Leader crea, modifica y elimina tipos. Cada cambio recarga el catálogo
+//!   en memoria que usan la validación, las reglas y las exportaciones
+
+use crate::utils::jwt::{AuthenticatedUser, LeaderUser};
+use crate::utils::audit;
+use crate::utils::activity_types::{builtin_activity_types, set_activity_types, ActivityType};
+use crate::utils::validation::sanitize_text;
+use crate::database::audit_logs::{EventType, AuditCategory};
+use crate::database::{activity_types, asignatura_activity_plans, balance_fragments};
+use crate::types::{ApiResponse, ApiResponseWithData};
+use crate::*;
+use rocket::{delete, get, post, put};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::Deserialize;
+use std::net::SocketAddr;
+
+// ============================================================================
+// ESTRUCTURAS DE REQUEST/RESPONSE
+// ============================================================================
+
+/// Campos editables de un tipo de actividad (el código no se puede cambiar)
+#[derive(Debug, Deserialize)]
+pub struct ActivityTypeInput {
+    pub label: String,
+    pub hours: i32,
+    #[serde(default)]
+    pub is_critical: bool,
+    #[serde(default)]
+    pub is_exam: bool,
+    #[serde(default = "default_true")]
+    pub counts_as_class_hours: bool,
+    #[serde(default = "default_true")]
+    pub schedulable: bool,
+    pub color: Option<String>,
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Request para crear un tipo de actividad
+#[derive(Debug, Deserialize)]
+pub struct CreateActivityTypeRequest {
+    pub code: String,
+    #[serde(flatten)]
+    pub fields: ActivityTypeInput,
+}
+
+type ActivityTypeResult = Result<Json<ApiResponseWithData<ActivityType>>, (Status, Json<ApiResponseWithData<ActivityType>>)>;
+
+// ============================================================================
+// FUNCIONES AUXILIARES
+// ============================================================================
+
+impl From<activity_types::Model> for ActivityType {
+    fn from(model: activity_types::Model) -> Self {
+        Self {
+            code: model.code,
+            label: model.label,
+            hours: model.hours,
+            is_critical: model.is_critical,
+            is_exam: model.is_exam,
+            counts_as_class_hours: model.counts_as_class_hours,
+            schedulable: model.schedulable,
+            color: model.color,
+            sort_order: model.sort_order,
+        }
+    }
+}
+
+impl ActivityTypeInput {
+    fn into_activity_type(self, code: String) -> ActivityType {
+        ActivityType {
+            code: code.trim().to_uppercase(),
+            label: sanitize_text(self.label.trim()),
+            hours: self.hours,
+            is_critical: self.is_critical,
+            is_exam: self.is_exam,
+            counts_as_class_hours: self.counts_as_class_hours,
+            schedulable: self.schedulable,
+            color: self.color.map(|c| c.trim().to_uppercase()).filter(|c| !c.is_empty()),
+            sort_order: self.sort_order,
+        }
+    }
+}
+
+/// Carga el catálogo desde la base de datos. Si falla o está vacío se usan
+/// los tipos por defecto para no dejar la aplicación sin actividades
+pub async fn load_activity_types(db: &DatabaseConnection) -> Vec<ActivityType> {
+    match activity_types::Entity::find().all(db).await {
+        Ok(types) if !types.is_empty() => types.into_iter().map(ActivityType::from).collect(),
+        _ => builtin_activity_types(),
+    }
+}
+
+async fn reload_activity_types(db: &DatabaseConnection) {
+    set_activity_types(load_activity_types(db).await);
+}
+
+/// Indica si algún fragmento tiene el código en su cuadrícula
+async fn is_used_in_fragments(db: &DatabaseConnection, code: &str) -> Result<bool, DbErr> {
+    Ok(balance_fragments::Entity::find()
+        .filter(Expr::cust_with_values("jsonb_exists(data -> 'values', $1)", [code]))
+        .count(db)
+        .await?
+        > 0)
+}
+
+fn activity_type_error(status: Status, message: impl Into<String>) -> (Status, Json<ApiResponseWithData<ActivityType>>) {
+    (status, Json(ApiResponseWithData::error(message.into())))
+}
+
+// ============================================================================
+// RUTAS
+// ============================================================================
+
+/// Listar el catálogo de tipos de actividad
+#[get("/activity-types")]
+pub async fn list_activity_types(
+    db: &State<AppState>,
+    _user: AuthenticatedUser,
+) -> Json<ApiResponseWithData<Vec<ActivityType>>> {
+    match activity_types::Entity::find()
+        .order_by_asc(activity_types::Column::SortOrder)
+        .order_by_asc(activity_types::Column::Code)
+        .all(&db.db)
+        .await
+    {
+        Ok(types) => Json(ApiResponseWithData::success(
+            "Tipos de actividad obtenidos exitosamente".to_string(),
+            types.into_iter().map(ActivityType::from).collect(),
+        )),
+        Err(e) => Json(ApiResponseWithData::error(format!("Error al obtener los tipos de actividad: {}", e))),
+    }
+}
+
+/// Crear un tipo de actividad (Solo Leader)
+#[post("/activity-types", format = "json", data = "<request>")]
+pub async fn create_activity_type(
+    request: Json<CreateActivityTypeRequest>,
+    db: &State<AppState>,
+    leader: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> ActivityTypeResult {
+    let leader_id = leader.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let CreateActivityTypeRequest { code, fields } = request.into_inner();
+
+    let activity_type = fields.into_activity_type(code);
+    activity_type.validate().map_err(|e| activity_type_error(Status::BadRequest, e))?;
+
+    match activity_types::Entity::find_by_id(activity_type.code.clone()).one(&db.db).await {
+        Ok(Some(_)) => return Err(activity_type_error(Status::Conflict, format!(
+            "Ya existe el tipo de actividad '{}'", activity_type.code
+        ))),
+        Ok(None) => {}
+        Err(e) => return Err(activity_type_error(Status::InternalServerError, format!("Error: {}", e))),
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    activity_types::ActiveModel {
+        code: Set(activity_type.code.clone()),
+        label: Set(activity_type.label.clone()),
+        hours: Set(activity_type.hours),
+        is_critical: Set(activity_type.is_critical),
+        is_exam: Set(activity_type.is_exam),
+        counts_as_class_hours: Set(activity_type.counts_as_class_hours),
+        schedulable: Set(activity_type.schedulable),
+        color: Set(activity_type.color.clone()),
+        sort_order: Set(activity_type.sort_order),
+        created_at: Set(Some(now)),
+        updated_at: Set(Some(now)),
+    }
+    .insert(&db.db)
+    .await
+    .map_err(|e| activity_type_error(Status::InternalServerError, format!("Error al crear el tipo de actividad: {}", e)))?;
+
+    reload_activity_types(&db.db).await;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Create,
+        AuditCategory::Functional,
+        format!("Leader '{}' creó el tipo de actividad '{}' ({})", leader.0.user_name, activity_type.code, activity_type.label),
+    )
+    .user(leader_id, &leader.0.user_name)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    Ok(Json(ApiResponseWithData::success("Tipo de actividad creado exitosamente".to_string(), activity_type)))
+}
+
+/// Modificar un tipo de actividad (Solo Leader)
+/// No se puede dejar fuera de la cuadrícula un tipo que ya usan los fragmentos
+#[put("/activity-types/<code>", format = "json", data = "<request>")]
+pub async fn update_activity_type(
+    code: &str,
+    request: Json<ActivityTypeInput>,
+    db: &State<AppState>,
+    leader: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> ActivityTypeResult {
+    let leader_id = leader.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let existing = match activity_types::Entity::find_by_id(code.to_string()).one(&db.db).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return Err(activity_type_error(Status::NotFound, "Tipo de actividad no encontrado")),
+        Err(e) => return Err(activity_type_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    let activity_type = request.into_inner().into_activity_type(existing.code.clone());
+    activity_type.validate().map_err(|e| activity_type_error(Status::BadRequest, e))?;
+
+    if existing.schedulable && !activity_type.schedulable {
+        match is_used_in_fragments(&db.db, &existing.code).await {
+            Ok(true) => return Err(activity_type_error(Status::Conflict, format!(
+                "El tipo '{}' ya está programado en algún fragmento y no se puede quitar de la cuadrícula", existing.code
+            ))),
+            Ok(false) => {}
+            Err(e) => return Err(activity_type_error(Status::InternalServerError, format!("Error: {}", e))),
+        }
+    }
+
+    let mut active: activity_types::ActiveModel = existing.into();
+    active.label = Set(activity_type.label.clone());
+    active.hours = Set(activity_type.hours);
+    active.is_critical = Set(activity_type.is_critical);
+    active.is_exam = Set(activity_type.is_exam);
+    active.counts_as_class_hours = Set(activity_type.counts_as_class_hours);
+    active.schedulable = Set(activity_type.schedulable);
+    active.color = Set(activity_type.color.clone());
+    active.sort_order = Set(activity_type.sort_order);
+    active.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+    active
+        .update(&db.db)
+        .await
+        .map_err(|e| activity_type_error(Status::InternalServerError, format!("Error al actualizar el tipo de actividad: {}", e)))?;
+
+    reload_activity_types(&db.db).await;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Leader '{}' actualizó el tipo de actividad '{}'", leader.0.user_name, activity_type.code),
+    )
+    .user(leader_id, &leader.0.user_name)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    Ok(Json(ApiResponseWithData::success("Tipo de actividad actualizado exitosamente".to_string(), activity_type)))
+}
+
+/// Eliminar un tipo de actividad (Solo Leader)
+/// Solo si ninguna asignatura lo planifica y ningún fragmento lo tiene programado
+#[delete("/activity-types/<code>")]
+pub async fn delete_activity_type(
+    code: &str,
+    db: &State<AppState>,
+    leader: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponse>, (Status, Json<ApiResponse>)> {
+    let leader_id = leader.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let internal = |e: DbErr| (Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))));
+
+    let existing = match activity_types::Entity::find_by_id(code.to_string()).one(&db.db).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Tipo de actividad no encontrado".to_string())))),
+        Err(e) => return Err(internal(e)),
+    };
+
+    let planned = asignatura_activity_plans::Entity::find()
+        .filter(asignatura_activity_plans::Column::ActivityCode.eq(existing.code.as_str()))
+        .count(&db.db)
+        .await
+        .map_err(internal)?;
+    if planned > 0 {
+        return Err((Status::Conflict, Json(ApiResponse::error(format!(
+            "El tipo '{}' está en el plan de {} asignatura(s); quítelo de ellas antes de eliminarlo", existing.code, planned
+        )))));
+    }
+    if is_used_in_fragments(&db.db, &existing.code).await.map_err(internal)? {
+        return Err((Status::Conflict, Json(ApiResponse::error(format!(
+            "El tipo '{}' está programado en algún fragmento y no se puede eliminar", existing.code
+        )))));
+    }
+
+    activity_types::Entity::delete_by_id(existing.code.clone())
+        .exec(&db.db)
+        .await
+        .map_err(internal)?;
+
+    reload_activity_types(&db.db).await;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Delete,
+        AuditCategory::Functional,
+        format!("Leader '{}' eliminó el tipo de actividad '{}'", leader.0.user_name, existing.code),
+    )
+    .user(leader_id, &leader.0.user_name)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    Ok(Json(ApiResponse::success("Tipo de actividad eliminado exitosamente".to_string())))
+}
@@ -0,0 +1,316 @@
+//! Importación y exportación masiva del catálogo de asignaturas
+//!
+//! La importación acepta CSV o XLSX (`multipart/form-data`, campo `file`) y
+//! hace upsert por (nombre, año, semestre). Sin `commit=true` solo devuelve el
+//! informe; con errores en alguna fila no se aplica ningún cambio.
+
+use crate::utils::jwt::LeaderUser;
+use crate::utils::audit;
+use crate::utils::activity_types::activity_types;
+use crate::utils::asignatura_catalog::{
+    check_unique_leaders, parse_catalog, write_catalog_csv, write_catalog_xlsx, CatalogEntry,
+};
+use crate::utils::notifications::{self, Notification};
+use crate::utils::table_import::{read_table, RowError};
+use crate::database::audit_logs::{EventType, AuditCategory};
+use crate::database::{asignaturas, usuarios};
+use crate::routes::balance::ExcelFile;
+use crate::types::{ApiResponse, ApiResponseWithData};
+use crate::*;
+use rocket::form::Form;
+use rocket::fs::TempFile;
+use rocket::http::ContentType;
+use rocket::{get, post};
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use tokio::io::AsyncReadExt;
+
+// ============================================================================
+// ESTRUCTURAS DE REQUEST/RESPONSE
+// ============================================================================
+
+/// Formulario de importación del catálogo
+#[derive(FromForm)]
+pub struct CatalogImportForm<'r> {
+    pub file: TempFile<'r>,
+    #[field(default = false)]
+    pub commit: bool,   // false = solo informe
+}
+
+/// Acción prevista para una fila válida
+#[derive(Debug, Serialize)]
+pub struct CatalogRowAction {
+    pub row: usize,
+    pub name: String,
+    pub year: String,
+    pub semester: String,
+    pub action: &'static str,   // "create" | "update"
+}
+
+/// Informe de la importación del catálogo
+#[derive(Debug, Serialize)]
+pub struct CatalogImportReport {
+    pub committed: bool,
+    pub created: usize,
+    pub updated: usize,
+    /// Fragmentos abiertos que pasaron a otro jefe al cambiar el de su asignatura
+    pub fragments_reassigned: u64,
+    pub rows: Vec<CatalogRowAction>,
+    pub errors: Vec<RowError>,
+}
+
+type CatalogResult = Result<Json<ApiResponseWithData<CatalogImportReport>>, (Status, Json<ApiResponseWithData<CatalogImportReport>>)>;
+
+/// Custom responder for CSV file download
+pub struct CsvFile {
+    pub data: Vec<u8>,
+    pub filename: String,
+}
+
+impl<'r> rocket::response::Responder<'r, 'static> for CsvFile {
+    fn respond_to(self, _request: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        rocket::Response::build()
+            .header(ContentType::CSV)
+            .raw_header("Content-Disposition", format!("attachment; filename=\"{}\"", self.filename))
+            .sized_body(self.data.len(), std::io::Cursor::new(self.data))
+            .ok()
+    }
+}
+
+/// Catálogo exportado en el formato solicitado
+#[derive(rocket::Responder)]
+pub enum CatalogExportFile {
+    Csv(CsvFile),
+    Excel(ExcelFile),
+}
+
+fn catalog_error(status: Status, message: impl Into<String>) -> (Status, Json<ApiResponseWithData<CatalogImportReport>>) {
+    (status, Json(ApiResponseWithData::error(message.into())))
+}
+
+// ============================================================================
+// RUTAS
+// ============================================================================
+
+/// Exportar el catálogo de asignaturas (Solo Leader)
+/// GET /api/asignaturas/export?format=csv|xlsx
+#[get("/asignaturas/export?<format>")]
+pub async fn export_asignaturas(
+    format: Option<&str>,
+    db: &State<AppState>,
+    _leader: LeaderUser,
+) -> Result<CatalogExportFile, (Status, Json<ApiResponse>)> {
+    let as_xlsx = match format.unwrap_or("csv") {
+        "csv" => false,
+        "xlsx" => true,
+        other => return Err((Status::BadRequest, Json(ApiResponse::error(format!("Formato de exportación no soportado: {}", other))))),
+    };
+
+    let rows = asignaturas::Entity::find()
+        .find_also_related(usuarios::Entity)
+        .order_by_asc(asignaturas::Column::Year)
+        .order_by_asc(asignaturas::Column::Semester)
+        .order_by_asc(asignaturas::Column::Name)
+        .all(&db.db)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error al obtener las asignaturas: {}", e)))))?;
+
+    let ids: Vec<i32> = rows.iter().map(|(a, _)| a.id).collect();
+    let mut plans = utils::db::load_activity_plans(&db.db, &ids)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error al obtener los planes de actividades: {}", e)))))?;
+
+    let entries: Vec<CatalogEntry> = rows
+        .into_iter()
+        .map(|(asignatura, leader)| {
+            let plan = plans.remove(&asignatura.id).unwrap_or_default();
+            (asignatura, leader.map(|l| l.user_name).unwrap_or_default(), plan)
+        })
+        .collect();
+
+    let types = activity_types();
+    let filename = format!("asignaturas_{}", chrono::Local::now().format("%Y%m%d"));
+    if as_xlsx {
+        let data = write_catalog_xlsx(&entries, &types)
+            .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error generando Excel: {}", e)))))?;
+        return Ok(CatalogExportFile::Excel(ExcelFile { data, filename: format!("{}.xlsx", filename) }));
+    }
+
+    let data = write_catalog_csv(&entries, &types)
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error generando CSV: {}", e)))))?;
+    Ok(CatalogExportFile::Csv(CsvFile { data, filename: format!("{}.csv", filename) }))
+}
+
+/// Importar el catálogo de asignaturas desde CSV o XLSX (Solo Leader)
+/// POST /api/asignaturas/import
+#[post("/asignaturas/import", data = "<form>")]
+pub async fn import_asignaturas(
+    form: Form<CatalogImportForm<'_>>,
+    db: &State<AppState>,
+    leader: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> CatalogResult {
+    let leader_id = leader.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let mut bytes = Vec::new();
+    let mut reader = form.file.open().await
+        .map_err(|e| catalog_error(Status::BadRequest, format!("No se pudo leer el archivo: {}", e)))?;
+    reader.read_to_end(&mut bytes).await
+        .map_err(|e| catalog_error(Status::BadRequest, format!("No se pudo leer el archivo: {}", e)))?;
+
+    let (rows, mut errors) = read_table(bytes)
+        .and_then(|table| parse_catalog(&table, &activity_types()))
+        .map_err(|e| catalog_error(Status::UnprocessableEntity, e))?;
+
+    // Jefes de asignatura por username
+    let leaders: HashMap<String, i32> = usuarios::Entity::find()
+        .filter(usuarios::Column::Role.eq("subjectLeader"))
+        .all(&db.db)
+        .await
+        .map_err(|e| catalog_error(Status::InternalServerError, format!("Error al obtener jefes de asignatura: {}", e)))?
+        .into_iter()
+        .map(|u| (u.user_name, u.id))
+        .collect();
+
+    let existing: HashMap<(String, String, String), asignaturas::Model> = asignaturas::Entity::find()
+        .all(&db.db)
+        .await
+        .map_err(|e| catalog_error(Status::InternalServerError, format!("Error al obtener las asignaturas: {}", e)))?
+        .into_iter()
+        .map(|a| ((a.name.clone(), a.year.clone(), a.semester.clone()), a))
+        .collect();
+
+    let mut planned = Vec::new();
+    let mut seen = HashSet::new();
+    for row in rows {
+        let Some(&subject_leader_id) = leaders.get(&row.leader_user_name) else {
+            errors.push(RowError::new(row.row, format!("No se encontró un jefe de asignatura con username '{}'", row.leader_user_name)));
+            continue;
+        };
+        if !seen.insert(row.key()) {
+            errors.push(RowError::new(row.row, format!("'{}' ({} año, semestre {}) está repetida en el archivo", row.name, row.year, row.semester)));
+            continue;
+        }
+        planned.push((row, subject_leader_id));
+    }
+
+    // Cada jefe de asignatura dirige una sola asignatura
+    let leading: HashMap<i32, (String, String, String)> =
+        existing.iter().map(|(key, a)| (a.leader_id, key.clone())).collect();
+    let (planned, leader_errors) = check_unique_leaders(planned, &leading);
+    errors.extend(leader_errors);
+    errors.sort_by_key(|e| e.row);
+
+    let mut report = CatalogImportReport {
+        committed: false,
+        created: 0,
+        updated: 0,
+        fragments_reassigned: 0,
+        rows: Vec::new(),
+        errors,
+    };
+    for (row, _) in &planned {
+        let action = if existing.contains_key(&row.key()) {
+            report.updated += 1;
+            "update"
+        } else {
+            report.created += 1;
+            "create"
+        };
+        report.rows.push(CatalogRowAction {
+            row: row.row,
+            name: row.name.clone(),
+            year: row.year.clone(),
+            semester: row.semester.clone(),
+            action,
+        });
+    }
+
+    if !form.commit {
+        return Ok(Json(ApiResponseWithData::success("Informe de importación generado".to_string(), report)));
+    }
+    if !report.errors.is_empty() {
+        return Err((Status::UnprocessableEntity, Json(ApiResponseWithData {
+            message: format!("El archivo tiene {} filas con errores; no se importó ninguna asignatura", report.errors.len()),
+            alert: "error".to_string(),
+            data: Some(report),
+        })));
+    }
+
+    // Las columnas de actividades vacías conservan el plan de las asignaturas existentes
+    let existing_ids: Vec<i32> = existing.values().map(|a| a.id).collect();
+    let mut existing_plans = utils::db::load_activity_plans(&db.db, &existing_ids)
+        .await
+        .map_err(|e| catalog_error(Status::InternalServerError, format!("Error al obtener los planes de actividades: {}", e)))?;
+
+    let txn = db.db.begin().await
+        .map_err(|e| catalog_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let mut reassigned_fragments = Vec::new();
+    for (row, subject_leader_id) in &planned {
+        let result = match existing.get(&row.key()) {
+            Some(asignatura) => {
+                let mut active: asignaturas::ActiveModel = asignatura.clone().into();
+                active.leader_id = Set(*subject_leader_id);
+                row.apply_to(&mut active);
+                match active.update(&txn).await {
+                    // Un cambio de jefe se propaga a los fragmentos abiertos de la asignatura
+                    Ok(a) if a.leader_id != asignatura.leader_id => {
+                        utils::db::propagate_subject_leader(&txn, a.id, a.leader_id)
+                            .await
+                            .map(|ids| {
+                                report.fragments_reassigned += ids.len() as u64;
+                                reassigned_fragments.extend(ids);
+                                a.id
+                            })
+                    }
+                    other => other.map(|a| a.id),
+                }
+            }
+            None => {
+                let now = chrono::Utc::now().naive_utc();
+                let mut active = asignaturas::ActiveModel {
+                    leader_id: Set(*subject_leader_id),
+                    name: Set(row.name.clone()),
+                    year: Set(row.year.clone()),
+                    semester: Set(row.semester.clone()),
+                    date_start: Set(now),
+                    date_end: Set(now),
+                    ..Default::default()
+                };
+                row.apply_to(&mut active);
+                active.insert(&txn).await.map(|a| a.id)
+            }
+        };
+        let result = match result {
+            Ok(asignatura_id) => {
+                let plan = row.merged_plan(&existing_plans.remove(&asignatura_id).unwrap_or_default());
+                utils::db::replace_activity_plan(&txn, asignatura_id, &plan).await
+            }
+            Err(e) => Err(e),
+        };
+        result.map_err(|e| catalog_error(Status::InternalServerError, format!("Error en la fila {}: {}", row.row, e)))?;
+    }
+
+    txn.commit().await
+        .map_err(|e| catalog_error(Status::InternalServerError, format!("Error al importar: {}", e)))?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Create,
+        AuditCategory::Functional,
+        format!("Leader '{}' importó el catálogo de asignaturas ({} creadas, {} actualizadas)",
+            leader.0.user_name, report.created, report.updated),
+    )
+    .user(leader_id, &leader.0.user_name)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    let _ = notifications::notify_fragments(&db.db, leader_id, &reassigned_fragments, Notification::FragmentAssigned).await;
+
+    report.committed = true;
+    Ok(Json(ApiResponseWithData::success("Catálogo importado exitosamente".to_string(), report)))
+}
@@ -5,12 +5,29 @@
 //! - Se crean fragmentos automáticamente para cada asignatura
 //! - SubjectLeaders llenan sus fragmentos correspondientes
 
-use crate::utils::jwt::{AuthenticatedUser, LeaderUser, LeaderOrSubjectLeaderUser};
+use crate::utils::jwt::{AuthenticatedUser, Claims, LeaderUser, LeaderOrSubjectLeaderUser};
 use crate::utils::audit;
 use crate::database::audit_logs::{EventType, AuditCategory, EntityType};
 use crate::*;
 use crate::types::{ApiResponse, ApiResponseWithData};
-use crate::database::{balances, balance_fragments, asignaturas, usuarios};
+use crate::database::{academic_calendars, balances, balance_fragments, asignaturas, usuarios};
+use crate::utils::academic_calendar::{exam_session_starts, merge_periods};
+use crate::utils::balance_rules::{evaluate_balance, RuleViolation, ScheduledSubject};
+use crate::utils::reconciliation::{reconcile, FragmentReconciliation};
+use crate::utils::activity_types::ActivityPlan;
+use crate::utils::balance_lifecycle::{
+    plan_transition, status_after_subject_change, BalanceAction, BalanceStatus, TransitionContext, TransitionError,
+};
+use crate::utils::concurrency::{expected_version, IfMatch, WithETag};
+use crate::utils::fragment_access::{check_fragment_edit, effective_deadline, FragmentEditRequest};
+use crate::utils::fragment_review::FragmentStatus;
+use crate::utils::fragment_data::{FragmentData, FragmentDataError, FragmentLayout, FragmentPayload, WeekStructure};
+use crate::utils::load_analytics::{analyze_load, LoadReport, LoadThresholds};
+use crate::utils::notifications::{self, Notification};
+use crate::utils::week_calendar::{build_balance_calendar, calculate_week_dates, BalanceCalendar};
+use crate::utils::validation::sanitize_text;
+use crate::routes::calendar::{find_calendar_for_year, load_calendar_periods};
+use crate::routes::revisions::save_fragment_with_revision;
 use rocket::{post, get, put, delete};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
@@ -47,6 +64,21 @@ pub struct CreateBalanceRequest {
     pub allow_leader_edit: Option<bool>, // Si el leader puede editar fragmentos
     pub asignaturas: Vec<SelectedSubject>, // Asignaturas a incluir
     pub non_academic_periods: Option<Vec<NonAcademicPeriod>>, // Períodos no académicos
+    pub calendar_id: Option<i32>,       // Por defecto, el calendario del curso académico
+    pub week_structure: Option<WeekStructure>, // Por defecto, la estructura estándar
+}
+
+/// Request para crear el balance del siguiente curso a partir de uno existente (Leader)
+#[derive(Debug, Deserialize)]
+pub struct CloneBalanceRequest {
+    pub academic_year_text: String,     // '2026-2027'
+    pub start_date: String,             // 'YYYY-MM-DD'
+    pub weeks: Option<i32>,             // Por defecto, las del balance original
+    pub deadline: Option<String>,       // Fecha límite opcional 'YYYY-MM-DD'
+    pub non_academic_periods: Option<Vec<NonAcademicPeriod>>,
+    pub copy_distribution: Option<bool>, // Copiar la distribución de cada fragmento
+    pub week_offset: Option<i32>,       // Desplazamiento de semanas al copiar la distribución
+    pub week_structure: Option<WeekStructure>, // Por defecto, la del balance original
 }
 
 /// Request para actualizar metadatos del balance (Leader)
@@ -59,15 +91,55 @@ pub struct UpdateBalanceRequest {
     pub weeks: Option<i32>,
     pub deadline: Option<String>,
     pub allow_leader_edit: Option<bool>,
-    pub status: Option<String>,
+    pub status: Option<String>,  // Solo se acepta a través de /transitions
     pub non_academic_periods: Option<Vec<NonAcademicPeriod>>, // Períodos no académicos
+    pub calendar_id: Option<i32>,    // Calendario académico institucional
+    pub week_structure: Option<WeekStructure>, // Solo si ningún fragmento tiene datos
+    pub version: Option<i32>,    // Alternativa a la cabecera If-Match
+}
+
+/// Request para añadir asignaturas a un balance existente (Leader)
+#[derive(Debug, Deserialize)]
+pub struct AddBalanceSubjectsRequest {
+    pub asignaturas: Vec<SelectedSubject>,
+    pub version: Option<i32>,    // Alternativa a la cabecera If-Match
+}
+
+/// Request para cambiar el estado de un balance (Leader)
+#[derive(Debug, Deserialize)]
+pub struct BalanceTransitionRequest {
+    pub action: BalanceAction,
+    pub version: Option<i32>,    // Alternativa a la cabecera If-Match
+}
+
+/// Resultado de una transición de estado
+#[derive(Debug, Serialize)]
+pub struct BalanceTransitionResponse {
+    pub balance_id: i32,
+    pub action: BalanceAction,
+    pub from: BalanceStatus,
+    pub to: BalanceStatus,
 }
 
 /// Request para actualizar un fragmento (SubjectLeader)
 #[derive(Debug, Deserialize)]
 pub struct UpdateFragmentRequest {
-    pub data: serde_json::Value,  // Distribución semanal de la asignatura
+    pub data: FragmentPayload,    // Distribución semanal de la asignatura
     pub status: Option<String>,   // 'pending', 'in_progress', 'completed'
+    pub override_reason: Option<String>, // Motivo del Leader para completar un fragmento descuadrado
+    pub version: Option<i32>,     // Alternativa a la cabecera If-Match
+}
+
+/// Request para conceder o retirar una prórroga de un fragmento (Leader del balance)
+#[derive(Debug, Deserialize)]
+pub struct FragmentExtensionRequest {
+    pub deadline: Option<String>,  // 'YYYY-MM-DD', null retira la prórroga
+}
+
+/// Request para entregar un fragmento a otro jefe de asignatura (Leader del balance)
+#[derive(Debug, Deserialize)]
+pub struct FragmentReassignRequest {
+    pub subject_leader_id: i32,
 }
 
 /// Información del SubjectLeader para respuesta
@@ -87,17 +159,8 @@ pub struct AsignaturaInfo {
     pub year: String,
     pub semester: String,
     pub hours: i32,
-    // Cantidades planificadas por tipo de clase
-    pub c: Option<i32>,   // Conferencias
-    pub cp: Option<i32>,  // Clases Prácticas
-    pub s: Option<i32>,   // Seminarios
-    pub pl: Option<i32>,  // Prácticas de Laboratorio
-    pub te: Option<i32>,  // Trabajo Extraclase
-    pub t: Option<i32>,   // Taller
-    pub pp: Option<i32>,  // Pruebas Parciales
-    pub ec: Option<i32>,  // Encuentro Comprobatorio
-    pub tc: Option<i32>,  // Trabajo de Curso
-    pub ef: Option<i32>,  // Examen Final
+    /// Cantidades planificadas por código de actividad
+    pub plan: ActivityPlan,
 }
 
 /// Respuesta de fragmento con información completa
@@ -111,6 +174,9 @@ pub struct FragmentResponse {
     pub subject_leader: Option<SubjectLeaderInfo>,
     pub status: String,
     pub data: serde_json::Value,
+    pub completion_override_reason: Option<String>,
+    pub deadline_extension: Option<String>,  // Prórroga concedida por el Leader 'YYYY-MM-DD'
+    pub version: i32,                        // Versión para control de concurrencia (ETag)
     pub completed_at: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
@@ -130,7 +196,10 @@ pub struct BalanceResponse {
     pub status: String,
     pub deadline: Option<String>,
     pub allow_leader_edit: bool,
-    pub non_academic_periods: Vec<NonAcademicPeriod>,
+    pub non_academic_periods: Vec<NonAcademicPeriod>,  // Períodos propios, además de los del calendario
+    pub calendar_id: Option<i32>,  // Calendario académico institucional
+    pub week_structure: WeekStructure,  // Celdas por semana y días laborables
+    pub version: i32,  // Versión para control de concurrencia (ETag)
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
     pub fragments: Vec<FragmentResponse>,
@@ -143,6 +212,8 @@ pub struct BalanceProgress {
     pub total: usize,
     pub pending: usize,
     pub in_progress: usize,
+    pub submitted: usize,          // Entregados, pendientes de revisión
+    pub changes_requested: usize,  // Devueltos con comentarios
     pub completed: usize,
     pub percentage: f32,
 }
@@ -158,6 +229,7 @@ pub struct BalanceListItem {
     pub academic_year_text: String,
     pub start_date: String,
     pub weeks: i32,
+    pub week_structure: WeekStructure,
     pub status: String,
     pub deadline: Option<String>,
     pub non_academic_periods: Vec<NonAcademicPeriod>,
@@ -165,6 +237,26 @@ pub struct BalanceListItem {
     pub progress: BalanceProgress,
 }
 
+/// Resultado de validar las reglas semanales de un balance
+#[derive(Debug, Serialize)]
+pub struct BalanceValidationReport {
+    pub balance_id: i32,
+    pub valid: bool,
+    pub violations: Vec<RuleViolation>,
+}
+
+/// Detalle adjunto cuando se rechaza la actualización de un fragmento
+#[derive(Debug, Default, Serialize)]
+pub struct FragmentRejection {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<RuleViolation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reconciliation: Option<FragmentReconciliation>,
+    /// Copia actual del servidor cuando la escritura llega con una versión desactualizada
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<FragmentResponse>,
+}
+
 /// Fragmento pendiente para dashboard de SubjectLeader
 #[derive(Debug, Serialize)]
 pub struct PendingFragment {
@@ -182,15 +274,23 @@ pub struct PendingFragment {
 // ============================================================================
 
 /// Genera el nombre del balance automáticamente
-fn generate_balance_name(academic_year: &str, period: &str, academic_year_text: &str) -> String {
+pub(crate) fn generate_balance_name(academic_year: &str, period: &str, academic_year_text: &str) -> String {
     format!("{} Año - Período {} ({})", academic_year, period, academic_year_text)
 }
 
+/// Fragmentos vigentes (los de asignaturas retiradas del balance quedan
+/// archivados con `archived_at` y no se muestran ni cuentan para el progreso)
+pub(crate) fn active_fragments() -> Select<balance_fragments::Entity> {
+    balance_fragments::Entity::find().filter(balance_fragments::Column::ArchivedAt.is_null())
+}
+
 /// Calcula el progreso de un balance basado en sus fragmentos
 fn calculate_progress(fragments: &[balance_fragments::Model]) -> BalanceProgress {
     let total = fragments.len();
     let pending = fragments.iter().filter(|f| f.status == "pending").count();
     let in_progress = fragments.iter().filter(|f| f.status == "in_progress").count();
+    let submitted = fragments.iter().filter(|f| f.status == "submitted").count();
+    let changes_requested = fragments.iter().filter(|f| f.status == "changes_requested").count();
     let completed = fragments.iter().filter(|f| f.status == "completed").count();
     let percentage = if total > 0 {
         (completed as f32 / total as f32) * 100.0
@@ -202,11 +302,409 @@ fn calculate_progress(fragments: &[balance_fragments::Model]) -> BalanceProgress
         total,
         pending,
         in_progress,
+        submitted,
+        changes_requested,
         completed,
         percentage,
     }
 }
 
+/// Construye la respuesta completa de un fragmento (asignatura y SubjectLeader incluidos)
+pub(crate) async fn build_fragment_response(db: &DatabaseConnection, fragment: balance_fragments::Model) -> FragmentResponse {
+    // Obtener info de asignatura
+    let plan = utils::db::load_activity_plan(db, fragment.asignatura_id).await.unwrap_or_default();
+    let asignatura_info = asignaturas::Entity::find_by_id(fragment.asignatura_id)
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .map(|a| AsignaturaInfo {
+            id: a.id,
+            name: a.name,
+            year: a.year,
+            semester: a.semester,
+            hours: a.hours,
+            plan,
+        });
+
+    // Obtener info del subject_leader
+    let subject_leader_info = if let Some(leader_id) = fragment.subject_leader_id {
+        usuarios::Entity::find_by_id(leader_id)
+            .one(db)
+            .await
+            .ok()
+            .flatten()
+            .map(|u| SubjectLeaderInfo {
+                id: u.id,
+                user_name: u.user_name,
+                name: u.name,
+                email: u.email,
+            })
+    } else {
+        None
+    };
+
+    FragmentResponse {
+        id: fragment.id,
+        balance_id: fragment.balance_id,
+        asignatura_id: fragment.asignatura_id,
+        asignatura: asignatura_info,
+        subject_leader_id: fragment.subject_leader_id,
+        subject_leader: subject_leader_info,
+        status: fragment.status,
+        data: fragment.data,
+        completion_override_reason: fragment.completion_override_reason,
+        deadline_extension: fragment.deadline_extension.map(|d| d.to_string()),
+        version: fragment.version,
+        completed_at: fragment.completed_at.map(|dt| dt.to_string()),
+        created_at: fragment.created_at.map(|dt| dt.to_string()),
+        updated_at: fragment.updated_at.map(|dt| dt.to_string()),
+    }
+}
+
+/// Construye la respuesta completa de un balance con sus fragmentos y progreso
+pub(crate) async fn build_balance_response(db: &DatabaseConnection, balance: balances::Model) -> Result<BalanceResponse, DbErr> {
+    let fragments = active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance.id))
+        .all(db)
+        .await?;
+
+    let progress = calculate_progress(&fragments);
+
+    let mut fragment_responses = Vec::with_capacity(fragments.len());
+    for fragment in fragments {
+        fragment_responses.push(build_fragment_response(db, fragment).await);
+    }
+
+    // Parsear períodos no académicos del JSON
+    let non_academic_periods: Vec<NonAcademicPeriod> =
+        serde_json::from_value(balance.non_academic_periods.clone())
+            .unwrap_or_default();
+
+    Ok(BalanceResponse {
+        id: balance.id,
+        user_id: balance.user_id,
+        name: balance.name,
+        academic_year: balance.academic_year,
+        period: balance.period,
+        academic_year_text: balance.academic_year_text,
+        start_date: balance.start_date.to_string(),
+        weeks: balance.weeks,
+        status: balance.status,
+        deadline: balance.deadline.map(|d| d.to_string()),
+        allow_leader_edit: balance.allow_leader_edit,
+        non_academic_periods,
+        calendar_id: balance.calendar_id,
+        week_structure: WeekStructure::from_stored(balance.week_structure.as_ref()),
+        version: balance.version,
+        created_at: balance.created_at.map(|dt| dt.to_string()),
+        updated_at: balance.updated_at.map(|dt| dt.to_string()),
+        fragments: fragment_responses,
+        progress,
+    })
+}
+
+/// Períodos del balance: los días sin clases (feriados y vacaciones del
+/// calendario académico que referencia más los propios del balance) y el
+/// inicio de las sesiones de exámenes del calendario
+async fn load_balance_periods(
+    db: &DatabaseConnection,
+    balance: &balances::Model,
+) -> Result<(Vec<(NaiveDate, NaiveDate, String)>, Vec<NaiveDate>), DbErr> {
+    let calendar = match balance.calendar_id {
+        Some(calendar_id) => load_calendar_periods(db, calendar_id).await?,
+        None => Vec::new(),
+    };
+    Ok((merge_periods(&calendar, &parse_non_academic_periods(balance)), exam_session_starts(&calendar)))
+}
+
+/// Calendario con las fechas reales de cada celda del balance
+pub(crate) async fn load_balance_calendar(db: &DatabaseConnection, balance: &balances::Model) -> Result<BalanceCalendar, DbErr> {
+    let (non_academic_periods, exam_sessions) = load_balance_periods(db, balance).await?;
+    Ok(build_balance_calendar(balance.start_date, &balance_layout(balance), &non_academic_periods, &exam_sessions))
+}
+
+/// Cuadrícula del balance según su estructura semanal (la estándar si no tiene)
+pub(crate) fn balance_layout(balance: &balances::Model) -> FragmentLayout {
+    WeekStructure::from_stored(balance.week_structure.as_ref()).layout(balance.weeks)
+}
+
+/// Valida la estructura semanal recibida y la convierte al JSON que se guarda
+fn week_structure_value(structure: &WeekStructure) -> Result<serde_json::Value, String> {
+    structure.validate()?;
+    serde_json::to_value(structure).map_err(|e| format!("Error: {}", e))
+}
+
+/// Leader y Admin ven todos los balances; el resto solo aquellos en los que lidera un fragmento
+pub(crate) async fn can_view_balance(db: &DatabaseConnection, claims: &Claims, balance_id: i32) -> bool {
+    if claims.role == "leader" || claims.role == "admin" {
+        return true;
+    }
+    let user_id = claims.sub.parse::<i32>().unwrap_or(0);
+    active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .filter(balance_fragments::Column::SubjectLeaderId.eq(user_id))
+        .one(db)
+        .await
+        .ok()
+        .flatten()
+        .is_some()
+}
+
+/// Comprueba que el calendario existe, o busca el del curso académico si no se indica
+pub(crate) async fn resolve_calendar_id(
+    db: &DatabaseConnection,
+    calendar_id: Option<i32>,
+    academic_year_text: &str,
+) -> Result<Option<i32>, String> {
+    match calendar_id {
+        Some(id) => match academic_calendars::Entity::find_by_id(id).one(db).await {
+            Ok(Some(_)) => Ok(Some(id)),
+            Ok(None) => Err(format!("Calendario {} no encontrado", id)),
+            Err(e) => Err(format!("Error: {}", e)),
+        },
+        None => find_calendar_for_year(db, academic_year_text).await.map_err(|e| format!("Error: {}", e)),
+    }
+}
+
+/// Períodos no académicos del balance como rangos de fechas (los inválidos se ignoran)
+fn parse_non_academic_periods(balance: &balances::Model) -> Vec<(NaiveDate, NaiveDate, String)> {
+    serde_json::from_value::<Vec<NonAcademicPeriod>>(balance.non_academic_periods.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|p| {
+            let start = NaiveDate::parse_from_str(&p.start, "%Y-%m-%d").ok()?;
+            let end = NaiveDate::parse_from_str(&p.end, "%Y-%m-%d").ok()?;
+            Some((start, end, p.name))
+        })
+        .collect()
+}
+
+/// Construye la distribución semanal de cada fragmento del balance para el motor de reglas.
+/// Si se indica `pending`, los datos de esa asignatura sustituyen a los guardados
+/// (se usa para validar una actualización antes de persistirla).
+pub(crate) async fn load_scheduled_subjects(
+    db: &DatabaseConnection,
+    balance: &balances::Model,
+    pending: Option<(i32, &FragmentData)>,
+) -> Result<Vec<ScheduledSubject>, DbErr> {
+    let layout = balance_layout(balance);
+    let fragments = active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance.id))
+        .find_also_related(asignaturas::Entity)
+        .all(db)
+        .await?;
+
+    Ok(fragments
+        .into_iter()
+        .map(|(fragment, asignatura)| {
+            let data = match pending {
+                Some((asignatura_id, data)) if asignatura_id == fragment.asignatura_id => data.clone(),
+                _ => FragmentData::from_stored(&fragment.data, &layout),
+            };
+            ScheduledSubject {
+                asignatura_id: fragment.asignatura_id,
+                name: asignatura
+                    .map(|a| a.name)
+                    .unwrap_or_else(|| format!("Asignatura {}", fragment.asignatura_id)),
+                data,
+            }
+        })
+        .collect())
+}
+
+/// Concilia los datos de un fragmento con el plan de su asignatura
+pub(crate) async fn reconcile_fragment(
+    db: &DatabaseConnection,
+    asignatura: &asignaturas::Model,
+    data: &FragmentData,
+) -> Result<FragmentReconciliation, DbErr> {
+    let plan = utils::db::load_activity_plan(db, asignatura.id).await?;
+    Ok(reconcile(&plan, asignatura.hours, data.activities()))
+}
+
+/// Respuesta de error para la actualización de fragmentos
+pub(crate) fn fragment_error(status: Status, message: impl Into<String>) -> (Status, Json<ApiResponseWithData<FragmentRejection>>) {
+    (status, Json(ApiResponseWithData::error(message.into())))
+}
+
+/// Respuesta de error para la actualización de balances
+fn balance_error(status: Status, message: impl Into<String>) -> (Status, Json<ApiResponseWithData<BalanceResponse>>) {
+    (status, Json(ApiResponseWithData::error(message.into())))
+}
+
+/// Respuesta 409 con la copia actual del balance en el servidor
+async fn balance_conflict(
+    db: &DatabaseConnection,
+    current: balances::Model,
+) -> (Status, Json<ApiResponseWithData<BalanceResponse>>) {
+    let message = "El balance fue modificado por otro usuario. Revisa la versión actual antes de guardar".to_string();
+    match build_balance_response(db, current).await {
+        Ok(response) => (Status::Conflict, Json(ApiResponseWithData {
+            message,
+            alert: "error".to_string(),
+            data: Some(response),
+        })),
+        Err(_) => balance_error(Status::Conflict, message),
+    }
+}
+
+/// Tras añadir o retirar asignaturas recalcula el estado del balance y guarda
+/// una nueva versión (escritura condicional sobre la versión leída)
+async fn save_subject_change<C: ConnectionTrait>(
+    db: &C,
+    balance: balances::Model,
+) -> Result<balances::Model, DbErr> {
+    let fragments = active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance.id))
+        .all(db)
+        .await?;
+    let ctx = TransitionContext {
+        total_fragments: fragments.len(),
+        completed_fragments: fragments.iter().filter(|f| f.status == "completed").count(),
+        rule_violations: 0,
+    };
+    let status = BalanceStatus::parse(&balance.status).map(|from| status_after_subject_change(from, &ctx));
+
+    let read_version = balance.version;
+    let mut active_model: balances::ActiveModel = balance.into();
+    if let Some(status) = status {
+        active_model.status = Set(status.as_str().to_string());
+    }
+    active_model.version = Set(read_version + 1);
+    active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+
+    balances::Entity::update(active_model)
+        .filter(balances::Column::Version.eq(read_version))
+        .exec(db)
+        .await
+}
+
+/// Audita como cambio de estado la vuelta a `open` de un balance en revisión
+/// al que se le añaden o retiran asignaturas
+async fn audit_subject_status_change(
+    db: &DatabaseConnection,
+    user: &LeaderUser,
+    balance_id: i32,
+    from: &str,
+    to: &str,
+    ip: &str,
+) {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let _ = audit::AuditLogBuilder::new(
+        EventType::StatusChange,
+        AuditCategory::Functional,
+        format!("Usuario '{}' cambió el estado del balance ID {} de '{}' a '{}' (cambio de asignaturas)",
+            user.0.user_name, balance_id, from, to),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Balance, balance_id)
+    .ip(ip)
+    .save(db)
+    .await;
+}
+
+/// Respuesta 409 con la copia actual del fragmento en el servidor
+pub(crate) async fn fragment_conflict(
+    db: &DatabaseConnection,
+    current: balance_fragments::Model,
+) -> (Status, Json<ApiResponseWithData<FragmentRejection>>) {
+    (Status::Conflict, Json(ApiResponseWithData {
+        message: "El fragmento fue modificado por otro usuario. Revisa la versión actual antes de guardar".to_string(),
+        alert: "error".to_string(),
+        data: Some(FragmentRejection {
+            current: Some(build_fragment_response(db, current).await),
+            ..Default::default()
+        }),
+    }))
+}
+
+/// Comprueba que el usuario puede editar el fragmento: propietario, allow_leader_edit,
+/// fecha límite (o prórroga) y estado del balance. Los rechazos por permisos se auditan.
+pub(crate) async fn authorize_fragment_edit(
+    db: &DatabaseConnection,
+    claims: &Claims,
+    balance: &balances::Model,
+    fragment: &balance_fragments::Model,
+    ip: &str,
+) -> Result<(), (Status, Json<ApiResponseWithData<FragmentRejection>>)> {
+    let user_id = claims.sub.parse::<i32>().unwrap_or(0);
+
+    let access = check_fragment_edit(&FragmentEditRequest {
+        user_id,
+        role: &claims.role,
+        subject_leader_id: fragment.subject_leader_id,
+        allow_leader_edit: balance.allow_leader_edit,
+        deadline: balance.deadline,
+        deadline_extension: fragment.deadline_extension,
+        today: chrono::Utc::now().date_naive(),
+    });
+    if let Err(reason) = access {
+        let _ = audit::log_access_denied(
+            db,
+            Some(user_id),
+            Some(&claims.user_name),
+            &format!("fragmento de asignatura ID {} en balance ID {} ({})", fragment.asignatura_id, balance.id, reason),
+            ip,
+        ).await;
+        return Err(fragment_error(Status::Forbidden, reason));
+    }
+
+    // Un fragmento entregado espera la revisión del Leader y uno aprobado solo
+    // se reabre pidiendo cambios
+    if claims.role != "leader"
+        && let Some(status) = FragmentStatus::parse(&fragment.status).filter(|s| s.locked_for_owner())
+    {
+        let message = if status == FragmentStatus::Completed {
+            "El fragmento está aprobado; no puede editarse salvo que el Leader pida cambios"
+        } else {
+            "El fragmento está entregado y pendiente de revisión; no puede editarse hasta que el Leader lo revise"
+        };
+        return Err(fragment_error(Status::Conflict, message));
+    }
+
+    // En revisión, aprobado o archivado el balance queda congelado
+    if !BalanceStatus::parse(&balance.status).is_some_and(|s| s.allows_fragment_edits()) {
+        return Err(fragment_error(
+            Status::Conflict,
+            format!("El balance está en estado '{}' y sus fragmentos no pueden editarse", balance.status),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Evalúa las reglas semanales con los datos pendientes de guardar.
+/// Solo bloquean las violaciones en las que participa esta asignatura.
+pub(crate) async fn check_fragment_rules(
+    db: &DatabaseConnection,
+    balance: &balances::Model,
+    asignatura_id: i32,
+    data: &FragmentData,
+) -> Result<(), (Status, Json<ApiResponseWithData<FragmentRejection>>)> {
+    let subjects = load_scheduled_subjects(db, balance, Some((asignatura_id, data)))
+        .await
+        .map_err(|e| fragment_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+
+    let violations: Vec<RuleViolation> = evaluate_balance(&subjects)
+        .into_iter()
+        .filter(|v| v.involves(asignatura_id))
+        .collect();
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    Err((Status::UnprocessableEntity, Json(ApiResponseWithData {
+        message: format!(
+            "La distribución incumple las reglas de la facultad: {}",
+            violations.iter().map(|v| v.message.as_str()).collect::<Vec<_>>().join("; ")
+        ),
+        alert: "error".to_string(),
+        data: Some(FragmentRejection { violations, ..Default::default() }),
+    })))
+}
+
 // ============================================================================
 // RUTAS DE BALANCE (Leader)
 // ============================================================================
@@ -232,6 +730,7 @@ pub async fn list_balances(
         // SubjectLeader ve solo balances donde tiene fragmentos
         balances::Entity::find()
             .inner_join(balance_fragments::Entity)
+            .filter(balance_fragments::Column::ArchivedAt.is_null())
             .filter(balance_fragments::Column::SubjectLeaderId.eq(user_id))
             .order_by_desc(balances::Column::CreatedAt)
             .all(&db.db)
@@ -244,7 +743,7 @@ pub async fn list_balances(
             
             for balance in balances_list {
                 // Obtener fragmentos para calcular progreso
-                let fragments = balance_fragments::Entity::find()
+                let fragments = active_fragments()
                     .filter(balance_fragments::Column::BalanceId.eq(balance.id))
                     .all(&db.db)
                     .await
@@ -266,6 +765,7 @@ pub async fn list_balances(
                     academic_year_text: balance.academic_year_text,
                     start_date: balance.start_date.to_string(),
                     weeks: balance.weeks,
+                    week_structure: WeekStructure::from_stored(balance.week_structure.as_ref()),
                     status: balance.status,
                     deadline: balance.deadline.map(|d| d.to_string()),
                     non_academic_periods,
@@ -292,10 +792,7 @@ pub async fn get_balance(
     balance_id: i32,
     db: &State<AppState>,
     user: AuthenticatedUser,
-) -> Json<ApiResponseWithData<BalanceResponse>> {
-    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
-    let user_role = &user.0.role;
-
+) -> Result<WithETag<Json<ApiResponseWithData<BalanceResponse>>>, Json<ApiResponseWithData<BalanceResponse>>> {
     // Obtener el balance
     let balance_result = balances::Entity::find_by_id(balance_id)
         .one(&db.db)
@@ -303,124 +800,24 @@ pub async fn get_balance(
 
     let balance = match balance_result {
         Ok(Some(b)) => b,
-        Ok(None) => return Json(ApiResponseWithData::error("Balance no encontrado".to_string())),
-        Err(e) => return Json(ApiResponseWithData::error(format!("Error: {}", e))),
+        Ok(None) => return Err(Json(ApiResponseWithData::error("Balance no encontrado".to_string()))),
+        Err(e) => return Err(Json(ApiResponseWithData::error(format!("Error: {}", e)))),
     };
 
     // Verificar permisos: Leader ve todo, SubjectLeader solo si tiene fragmento
-    if user_role != "leader" && user_role != "admin" {
-        let has_fragment = balance_fragments::Entity::find()
-            .filter(balance_fragments::Column::BalanceId.eq(balance_id))
-            .filter(balance_fragments::Column::SubjectLeaderId.eq(user_id))
-            .one(&db.db)
-            .await
-            .unwrap_or(None);
-
-        if has_fragment.is_none() {
-            return Json(ApiResponseWithData::error(
-                "No tienes permiso para ver este balance".to_string(),
-            ));
-        }
+    if !can_view_balance(&db.db, &user.0, balance_id).await {
+        return Err(Json(ApiResponseWithData::error(
+            "No tienes permiso para ver este balance".to_string(),
+        )));
     }
 
-    // Obtener fragmentos con información de asignatura y subject_leader
-    let fragments_result = balance_fragments::Entity::find()
-        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
-        .all(&db.db)
-        .await;
-
-    let fragments = match fragments_result {
-        Ok(f) => f,
-        Err(e) => return Json(ApiResponseWithData::error(format!("Error al obtener fragmentos: {}", e))),
-    };
-
-    let mut fragment_responses = Vec::new();
-
-    for fragment in &fragments {
-        // Obtener info de asignatura
-        let asignatura_info = asignaturas::Entity::find_by_id(fragment.asignatura_id)
-            .one(&db.db)
-            .await
-            .ok()
-            .flatten()
-            .map(|a| AsignaturaInfo {
-                id: a.id,
-                name: a.name,
-                year: a.year,
-                semester: a.semester,
-                hours: a.hours,
-                c: a.c,
-                cp: a.cp,
-                s: a.s,
-                pl: a.pl,
-                te: a.te,
-                t: a.t,
-                pp: a.pp,
-                ec: a.ec,
-                tc: a.tc,
-                ef: a.ef,
-            });
-
-        // Obtener info de subject_leader
-        let subject_leader_info = if let Some(leader_id) = fragment.subject_leader_id {
-            usuarios::Entity::find_by_id(leader_id)
-                .one(&db.db)
-                .await
-                .ok()
-                .flatten()
-                .map(|u| SubjectLeaderInfo {
-                    id: u.id,
-                    user_name: u.user_name,
-                    name: u.name,
-                    email: u.email,
-                })
-        } else {
-            None
-        };
-
-        fragment_responses.push(FragmentResponse {
-            id: fragment.id,
-            balance_id: fragment.balance_id,
-            asignatura_id: fragment.asignatura_id,
-            asignatura: asignatura_info,
-            subject_leader_id: fragment.subject_leader_id,
-            subject_leader: subject_leader_info,
-            status: fragment.status.clone(),
-            data: fragment.data.clone(),
-            completed_at: fragment.completed_at.map(|dt| dt.to_string()),
-            created_at: fragment.created_at.map(|dt| dt.to_string()),
-            updated_at: fragment.updated_at.map(|dt| dt.to_string()),
-        });
+    match build_balance_response(&db.db, balance).await {
+        Ok(response) => Ok(WithETag {
+            version: response.version,
+            inner: Json(ApiResponseWithData::success("Balance obtenido exitosamente".to_string(), response)),
+        }),
+        Err(e) => Err(Json(ApiResponseWithData::error(format!("Error al obtener fragmentos: {}", e)))),
     }
-
-    let progress = calculate_progress(&fragments);
-
-    // Parsear períodos no académicos del JSON
-    let non_academic_periods: Vec<NonAcademicPeriod> = 
-        serde_json::from_value(balance.non_academic_periods.clone())
-            .unwrap_or_default();
-
-    Json(ApiResponseWithData::success(
-        "Balance obtenido exitosamente".to_string(),
-        BalanceResponse {
-            id: balance.id,
-            user_id: balance.user_id,
-            name: balance.name,
-            academic_year: balance.academic_year,
-            period: balance.period,
-            academic_year_text: balance.academic_year_text,
-            start_date: balance.start_date.to_string(),
-            weeks: balance.weeks,
-            status: balance.status,
-            deadline: balance.deadline.map(|d| d.to_string()),
-            allow_leader_edit: balance.allow_leader_edit,
-            non_academic_periods,
-            created_at: balance.created_at.map(|dt| dt.to_string()),
-            updated_at: balance.updated_at.map(|dt| dt.to_string()),
-            fragments: fragment_responses,
-            progress,
-        },
-    ))
 }
 
 /// Crear un nuevo balance con sus fragmentos (Solo Leader)
@@ -462,6 +859,16 @@ pub async fn create_balance(
         None
     };
 
+    let calendar_id = match resolve_calendar_id(&db.db, data.calendar_id, &data.academic_year_text).await {
+        Ok(id) => id,
+        Err(e) => return Json(ApiResponseWithData::error(e)),
+    };
+
+    let week_structure = match data.week_structure.as_ref().map(week_structure_value).transpose() {
+        Ok(value) => value,
+        Err(e) => return Json(ApiResponseWithData::error(format!("Estructura semanal inválida: {}", e))),
+    };
+
     // Generar nombre
     let name = generate_balance_name(&data.academic_year, &data.period, &data.academic_year_text);
 
@@ -474,11 +881,13 @@ pub async fn create_balance(
         academic_year_text: Set(data.academic_year_text.clone()),
         start_date: Set(start_date),
         weeks: Set(data.weeks),
-        status: Set("draft".to_string()),
+        status: Set(BalanceStatus::Draft.as_str().to_string()),
         deadline: Set(deadline),
         allow_leader_edit: Set(data.allow_leader_edit.unwrap_or(false)),
         subjects: Set(serde_json::json!([])), // Deprecated, usamos fragments
-        non_academic_periods: Set(serde_json::to_value(&data.non_academic_periods.clone().unwrap_or_default()).unwrap_or(serde_json::json!([]))),
+        non_academic_periods: Set(serde_json::to_value(data.non_academic_periods.clone().unwrap_or_default()).unwrap_or(serde_json::json!([]))),
+        calendar_id: Set(calendar_id),
+        week_structure: Set(week_structure),
         ..Default::default()
     };
 
@@ -491,6 +900,7 @@ pub async fn create_balance(
 
     // Crear fragmentos para cada asignatura
     let mut fragment_responses = Vec::new();
+    let mut fragment_ids = Vec::new();
     let mut errors = Vec::new();
 
     for selected in data.asignaturas {
@@ -529,6 +939,8 @@ pub async fn create_balance(
 
         match new_fragment.insert(&db.db).await {
             Ok(fragment) => {
+                fragment_ids.push(fragment.id);
+
                 // Obtener info del subject_leader
                 let subject_leader_info = if let Some(leader_id) = subject_leader_id {
                     usuarios::Entity::find_by_id(leader_id)
@@ -556,21 +968,15 @@ pub async fn create_balance(
                         year: asignatura.year.clone(),
                         semester: asignatura.semester.clone(),
                         hours: asignatura.hours,
-                        c: asignatura.c,
-                        cp: asignatura.cp,
-                        s: asignatura.s,
-                        pl: asignatura.pl,
-                        te: asignatura.te,
-                        t: asignatura.t,
-                        pp: asignatura.pp,
-                        ec: asignatura.ec,
-                        tc: asignatura.tc,
-                        ef: asignatura.ef,
+                        plan: utils::db::load_activity_plan(&db.db, asignatura.id).await.unwrap_or_default(),
                     }),
                     subject_leader_id: fragment.subject_leader_id,
                     subject_leader: subject_leader_info,
                     status: fragment.status,
                     data: fragment.data,
+                    completion_override_reason: fragment.completion_override_reason,
+                    deadline_extension: fragment.deadline_extension.map(|d| d.to_string()),
+                    version: fragment.version,
                     completed_at: fragment.completed_at.map(|dt| dt.to_string()),
                     created_at: fragment.created_at.map(|dt| dt.to_string()),
                     updated_at: fragment.updated_at.map(|dt| dt.to_string()),
@@ -594,10 +1000,15 @@ pub async fn create_balance(
     .save(&db.db)
     .await;
 
+    // Avisar a los jefes de asignatura
+    let _ = notifications::notify_fragments(&db.db, user_id, &fragment_ids, Notification::BalanceCreated).await;
+
     let progress = BalanceProgress {
         total: fragment_responses.len(),
         pending: fragment_responses.len(),
         in_progress: 0,
+        submitted: 0,
+        changes_requested: 0,
         completed: 0,
         percentage: 0.0,
     };
@@ -628,6 +1039,9 @@ pub async fn create_balance(
             deadline: inserted_balance.deadline.map(|d| d.to_string()),
             allow_leader_edit: inserted_balance.allow_leader_edit,
             non_academic_periods,
+            calendar_id: inserted_balance.calendar_id,
+            week_structure: WeekStructure::from_stored(inserted_balance.week_structure.as_ref()),
+            version: inserted_balance.version,
             created_at: inserted_balance.created_at.map(|dt| dt.to_string()),
             updated_at: inserted_balance.updated_at.map(|dt| dt.to_string()),
             fragments: fragment_responses,
@@ -636,74 +1050,292 @@ pub async fn create_balance(
     ))
 }
 
-/// Actualizar metadatos de un balance (Solo Leader)
-#[put("/balances/<balance_id>", format = "json", data = "<balance_data>")]
-pub async fn update_balance(
+/// Crear el balance de un nuevo curso a partir de uno existente (Solo Leader)
+/// Copia la selección de asignaturas y, opcionalmente, la distribución de cada
+/// fragmento adaptada al nuevo número de semanas. Los fragmentos se asignan al
+/// leader_id actual de cada asignatura.
+#[post("/balances/<balance_id>/clone", format = "json", data = "<clone_data>")]
+pub async fn clone_balance(
     balance_id: i32,
-    balance_data: Json<UpdateBalanceRequest>,
+    clone_data: Json<CloneBalanceRequest>,
     db: &State<AppState>,
     user: LeaderUser,
     remote_addr: Option<SocketAddr>,
-) -> Json<ApiResponse> {
+) -> Result<Json<ApiResponseWithData<BalanceResponse>>, (Status, Json<ApiResponseWithData<BalanceResponse>>)> {
     let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
-    let data = balance_data.into_inner();
+    let data = clone_data.into_inner();
     let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
 
-    // Obtener el balance
-    let balance = match balances::Entity::find_by_id(balance_id)
-        .one(&db.db)
-        .await
-    {
+    let source = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
         Ok(Some(b)) => b,
-        Ok(None) => return Json(ApiResponse::error("Balance no encontrado".to_string())),
-        Err(e) => return Json(ApiResponse::error(format!("Error: {}", e))),
+        Ok(None) => return Err(balance_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error: {}", e))),
     };
 
-    let mut active_model: balances::ActiveModel = balance.into();
+    let start_date = NaiveDate::parse_from_str(&data.start_date, "%Y-%m-%d").map_err(|_| {
+        balance_error(Status::BadRequest, "Formato de fecha de inicio inválido. Use YYYY-MM-DD")
+    })?;
 
-    // Actualizar campos si se proporcionan
-    if let Some(ay) = data.academic_year {
-        active_model.academic_year = Set(ay);
-    }
-    if let Some(p) = data.period {
-        active_model.period = Set(p);
+    let deadline = match data.deadline {
+        Some(ref dl) => Some(NaiveDate::parse_from_str(dl, "%Y-%m-%d").map_err(|_| {
+            balance_error(Status::BadRequest, "Formato de fecha límite inválido. Use YYYY-MM-DD")
+        })?),
+        None => None,
+    };
+
+    let weeks = data.weeks.unwrap_or(source.weeks);
+    if weeks <= 0 {
+        return Err(balance_error(Status::BadRequest, "El número de semanas debe ser mayor que cero"));
     }
-    if let Some(ayt) = data.academic_year_text {
-        active_model.academic_year_text = Set(ayt);
+
+    let academic_year_text = sanitize_text(&data.academic_year_text);
+    let name = generate_balance_name(&source.academic_year, &source.period, &academic_year_text);
+    let calendar_id = resolve_calendar_id(&db.db, None, &academic_year_text)
+        .await
+        .map_err(|e| balance_error(Status::InternalServerError, e))?;
+    let copy_distribution = data.copy_distribution.unwrap_or(false);
+    let week_offset = data.week_offset.unwrap_or(0);
+    let source_layout = balance_layout(&source);
+
+    // Sin estructura nueva se conserva la del balance original
+    let week_structure = match data.week_structure {
+        Some(ref structure) => Some(week_structure_value(structure).map_err(|e| {
+            balance_error(Status::BadRequest, format!("Estructura semanal inválida: {}", e))
+        })?),
+        None => source.week_structure.clone(),
+    };
+    if copy_distribution && !WeekStructure::from_stored(week_structure.as_ref()).layout(weeks).same_grid(&source_layout) {
+        return Err(balance_error(
+            Status::BadRequest,
+            "No se puede copiar la distribución a un balance con otra estructura semanal (celdas por semana, consultas, exámenes o días lectivos)",
+        ));
     }
-    if let Some(sd) = data.start_date {
-        match NaiveDate::parse_from_str(&sd, "%Y-%m-%d") {
-            Ok(date) => active_model.start_date = Set(date),
-            Err(_) => return Json(ApiResponse::error("Formato de fecha inválido".to_string())),
+
+    let source_fragments = active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(source.id))
+        .all(&db.db)
+        .await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+
+    let txn = db.db.begin().await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let new_balance = balances::ActiveModel {
+        user_id: Set(user_id),
+        name: Set(name.clone()),
+        academic_year: Set(source.academic_year.clone()),
+        period: Set(source.period.clone()),
+        academic_year_text: Set(academic_year_text),
+        start_date: Set(start_date),
+        weeks: Set(weeks),
+        status: Set(BalanceStatus::Draft.as_str().to_string()),
+        deadline: Set(deadline),
+        allow_leader_edit: Set(source.allow_leader_edit),
+        subjects: Set(serde_json::json!([])), // Deprecated, usamos fragments
+        non_academic_periods: Set(serde_json::to_value(data.non_academic_periods.unwrap_or_default()).unwrap_or(serde_json::json!([]))),
+        calendar_id: Set(calendar_id),
+        week_structure: Set(week_structure),
+        ..Default::default()
+    };
+
+    let inserted_balance = new_balance.insert(&txn).await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error al crear el balance: {}", e)))?;
+
+    let mut warnings = Vec::new();
+    let mut fragment_ids = Vec::new();
+    for fragment in source_fragments {
+        // Las asignaturas eliminadas desde el curso anterior no se copian
+        let asignatura = match asignaturas::Entity::find_by_id(fragment.asignatura_id).one(&txn).await {
+            Ok(Some(a)) => a,
+            Ok(None) => {
+                warnings.push(format!("Asignatura {} no encontrada", fragment.asignatura_id));
+                continue;
+            }
+            Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error al buscar asignatura: {}", e))),
+        };
+
+        let subject_leader_id = if asignatura.leader_id > 0 {
+            Some(asignatura.leader_id)
+        } else {
+            None
+        };
+
+        let (status, fragment_data) = if copy_distribution {
+            let copied = FragmentData::from_stored(&fragment.data, &source_layout)
+                .shift_weeks(week_offset, weeks as usize);
+            let status = if copied.activities().next().is_some() { "in_progress" } else { "pending" };
+            (status, copied.to_value())
+        } else {
+            ("pending", serde_json::json!({}))
+        };
+
+        let inserted = balance_fragments::ActiveModel {
+            balance_id: Set(inserted_balance.id),
+            asignatura_id: Set(asignatura.id),
+            subject_leader_id: Set(subject_leader_id),
+            status: Set(status.to_string()),
+            data: Set(fragment_data),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error al crear fragmento para asignatura {}: {}", asignatura.id, e)))?;
+        fragment_ids.push(inserted.id);
+    }
+
+    txn.commit().await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error al crear el balance: {}", e)))?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Create,
+        AuditCategory::Functional,
+        format!("Leader '{}' creó el balance '{}' a partir del balance ID {}{}",
+            user.0.user_name, name, balance_id, if copy_distribution { " (con distribución)" } else { "" }),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Balance, inserted_balance.id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    let _ = notifications::notify_fragments(&db.db, user_id, &fragment_ids, Notification::BalanceCreated).await;
+
+    let response = build_balance_response(&db.db, inserted_balance)
+        .await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let message = if warnings.is_empty() {
+        "Balance clonado exitosamente".to_string()
+    } else {
+        format!("Balance clonado con advertencias: {}", warnings.join(", "))
+    };
+
+    Ok(Json(ApiResponseWithData::success(message, response)))
+}
+
+/// Actualizar metadatos de un balance (Solo Leader)
+#[put("/balances/<balance_id>", format = "json", data = "<balance_data>")]
+pub async fn update_balance(
+    balance_id: i32,
+    balance_data: Json<UpdateBalanceRequest>,
+    db: &State<AppState>,
+    user: LeaderUser,
+    if_match: IfMatch,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponse>, (Status, Json<ApiResponseWithData<BalanceResponse>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let data = balance_data.into_inner();
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    // Obtener el balance
+    let balance = match balances::Entity::find_by_id(balance_id)
+        .one(&db.db)
+        .await
+    {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(balance_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    // Rechazar escrituras hechas sobre una copia desactualizada
+    if let Some(expected) = expected_version(&if_match, data.version)
+        && expected != balance.version
+    {
+        return Err(balance_conflict(&db.db, balance).await);
+    }
+
+    let read_version = balance.version;
+    let previous_deadline = balance.deadline;
+    let current_layout = balance_layout(&balance);
+    let current_structure = WeekStructure::from_stored(balance.week_structure.as_ref());
+    let mut active_model: balances::ActiveModel = balance.into();
+
+    // Actualizar campos si se proporcionan
+    if let Some(ay) = data.academic_year {
+        active_model.academic_year = Set(ay);
+    }
+    if let Some(p) = data.period {
+        active_model.period = Set(p);
+    }
+    if let Some(ayt) = data.academic_year_text {
+        active_model.academic_year_text = Set(ayt);
+    }
+    if let Some(sd) = data.start_date {
+        match NaiveDate::parse_from_str(&sd, "%Y-%m-%d") {
+            Ok(date) => active_model.start_date = Set(date),
+            Err(_) => return Err(balance_error(Status::BadRequest, "Formato de fecha inválido")),
         }
     }
     if let Some(w) = data.weeks {
+        if w <= 0 {
+            return Err(balance_error(Status::BadRequest, "El número de semanas debe ser mayor que cero"));
+        }
         active_model.weeks = Set(w);
     }
+    let mut deadline_changed = false;
     if let Some(dl) = data.deadline {
         match NaiveDate::parse_from_str(&dl, "%Y-%m-%d") {
-            Ok(date) => active_model.deadline = Set(Some(date)),
-            Err(_) => return Json(ApiResponse::error("Formato de fecha límite inválido".to_string())),
+            Ok(date) => {
+                deadline_changed = previous_deadline != Some(date);
+                active_model.deadline = Set(Some(date));
+            }
+            Err(_) => return Err(balance_error(Status::BadRequest, "Formato de fecha límite inválido")),
         }
     }
     if let Some(ale) = data.allow_leader_edit {
         active_model.allow_leader_edit = Set(ale);
     }
-    if let Some(s) = data.status {
-        if !["draft", "in_progress", "completed"].contains(&s.as_str()) {
-            return Json(ApiResponse::error("Estado inválido".to_string()));
-        }
-        active_model.status = Set(s);
+    if data.status.is_some() {
+        return Err(balance_error(
+            Status::BadRequest,
+            "El estado del balance se cambia mediante POST /api/balances/<id>/transitions",
+        ));
     }
     if let Some(nap) = data.non_academic_periods {
         active_model.non_academic_periods = Set(
             serde_json::to_value(&nap).unwrap_or(serde_json::json!([]))
         );
     }
+    if let Some(calendar_id) = data.calendar_id {
+        match academic_calendars::Entity::find_by_id(calendar_id).one(&db.db).await {
+            Ok(Some(_)) => active_model.calendar_id = Set(Some(calendar_id)),
+            Ok(None) => return Err(balance_error(Status::BadRequest, format!("Calendario {} no encontrado", calendar_id))),
+            Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error: {}", e))),
+        }
+    }
+    if let Some(ref structure) = data.week_structure {
+        let value = week_structure_value(structure)
+            .map_err(|e| balance_error(Status::BadRequest, format!("Estructura semanal inválida: {}", e)))?;
+        active_model.week_structure = Set(Some(value));
+    }
+    // Cambiar las semanas o la estructura reinterpreta el array `values` (semanas,
+    // luego consultas y exámenes): solo se permite mientras ningún fragmento tenga actividades
+    let new_weeks = data.weeks.unwrap_or(current_layout.weeks as i32);
+    let new_layout = data.week_structure.as_ref().unwrap_or(&current_structure).layout(new_weeks);
+    if new_layout != current_layout {
+        let fragments = active_fragments()
+            .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+            .all(&db.db)
+            .await
+            .map_err(|e| balance_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+        if fragments.iter().any(|f| FragmentData::from_stored(&f.data, &current_layout).activities().next().is_some()) {
+            return Err(balance_error(
+                Status::Conflict,
+                "No se pueden cambiar las semanas ni la estructura semanal de un balance con fragmentos ya rellenados",
+            ));
+        }
+    }
 
+    active_model.version = Set(read_version + 1);
     active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
 
-    match active_model.update(&db.db).await {
+    // Escritura condicional: solo si nadie ha guardado desde que se leyó
+    let result = balances::Entity::update(active_model)
+        .filter(balances::Column::Version.eq(read_version))
+        .exec(&db.db)
+        .await;
+
+    match result {
         Ok(_) => {
             let _ = audit::AuditLogBuilder::new(
                 EventType::Update,
@@ -716,9 +1348,399 @@ pub async fn update_balance(
             .save(&db.db)
             .await;
 
-            Json(ApiResponse::success("Balance actualizado exitosamente".to_string()))
+            // Los fragmentos con prórroga conservan su propia fecha límite
+            if deadline_changed {
+                let fragment_ids: Vec<i32> = active_fragments()
+                    .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+                    .filter(balance_fragments::Column::DeadlineExtension.is_null())
+                    .all(&db.db)
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|f| f.id)
+                    .collect();
+                let _ = notifications::notify_fragments(&db.db, user_id, &fragment_ids, Notification::DeadlineChanged).await;
+            }
+
+            Ok(Json(ApiResponse::success("Balance actualizado exitosamente".to_string())))
+        }
+        Err(DbErr::RecordNotUpdated) => {
+            match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+                Ok(Some(current)) => Err(balance_conflict(&db.db, current).await),
+                _ => Err(balance_error(Status::Conflict, "El balance fue modificado por otro usuario")),
+            }
+        }
+        Err(e) => Err(balance_error(Status::InternalServerError, format!("Error al actualizar: {}", e))),
+    }
+}
+
+/// Cambiar el estado de un balance según su ciclo de vida
+/// POST /api/balances/<id>/transitions  { "action": "open" | "submit_for_review" | "approve" | "archive" | "reopen" }
+#[post("/balances/<balance_id>/transitions", format = "json", data = "<transition>")]
+pub async fn transition_balance(
+    balance_id: i32,
+    transition: Json<BalanceTransitionRequest>,
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+    if_match: IfMatch,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<BalanceTransitionResponse>>, (Status, Json<ApiResponseWithData<BalanceResponse>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let BalanceTransitionRequest { action, version } = transition.into_inner();
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(balance_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    // Un Leader solo cambia el estado de sus balances; el admin solo puede archivar
+    // (lo decide `plan_transition` por rol)
+    if user.0.role == "leader" && balance.user_id != user_id {
+        let _ = audit::log_access_denied(
+            &db.db,
+            Some(user_id),
+            Some(&user.0.user_name),
+            &format!("balance ID {} ({}, no es el Leader del balance)", balance_id, action),
+            &ip_str,
+        ).await;
+        return Err(balance_error(Status::Forbidden, "Solo el Leader del balance puede cambiar su estado"));
+    }
+
+    // Rechazar transiciones pedidas sobre una copia desactualizada
+    if let Some(expected) = expected_version(&if_match, version)
+        && expected != balance.version
+    {
+        return Err(balance_conflict(&db.db, balance).await);
+    }
+
+    let Some(from) = BalanceStatus::parse(&balance.status) else {
+        return Err(balance_error(
+            Status::InternalServerError,
+            format!("El balance tiene un estado desconocido: '{}'", balance.status),
+        ));
+    };
+
+    // Precondiciones: fragmentos completados y reglas semanales
+    let fragments = active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .all(&db.db)
+        .await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+
+    let subjects = load_scheduled_subjects(&db.db, &balance, None)
+        .await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+
+    let ctx = TransitionContext {
+        total_fragments: fragments.len(),
+        completed_fragments: fragments.iter().filter(|f| f.status == "completed").count(),
+        rule_violations: evaluate_balance(&subjects).len(),
+    };
+
+    let to = match plan_transition(from, action, &user.0.role, &ctx) {
+        Ok(to) => to,
+        Err(e) => {
+            let status = match e {
+                TransitionError::InvalidTransition { .. } => Status::Conflict,
+                TransitionError::Forbidden { .. } => {
+                    let _ = audit::log_access_denied(
+                        &db.db,
+                        Some(user_id),
+                        Some(&user.0.user_name),
+                        &format!("balance ID {} ({})", balance_id, action),
+                        &ip_str,
+                    ).await;
+                    Status::Forbidden
+                }
+                TransitionError::PreconditionFailed(_) => Status::UnprocessableEntity,
+            };
+            return Err(balance_error(status, e.to_string()));
+        }
+    };
+
+    // Escritura condicional: si el balance cambió desde que se comprobaron las
+    // precondiciones, la transición se rechaza con la copia actual
+    let balance_version = balance.version;
+    let mut active_model: balances::ActiveModel = balance.into();
+    active_model.status = Set(to.as_str().to_string());
+    active_model.version = Set(balance_version + 1);
+    active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+
+    match balances::Entity::update(active_model)
+        .filter(balances::Column::Version.eq(balance_version))
+        .exec(&db.db)
+        .await
+    {
+        Ok(_) => {}
+        Err(DbErr::RecordNotUpdated) => {
+            return match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+                Ok(Some(current)) => Err(balance_conflict(&db.db, current).await),
+                _ => Err(balance_error(Status::Conflict, "El balance fue modificado por otro usuario")),
+            };
+        }
+        Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error al actualizar: {}", e))),
+    }
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::StatusChange,
+        AuditCategory::Functional,
+        format!("Usuario '{}' cambió el estado del balance ID {} de '{}' a '{}' ({})",
+            user.0.user_name, balance_id, from, to, action),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Balance, balance_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    Ok(Json(ApiResponseWithData::success(
+        format!("Estado del balance actualizado a '{}'", to),
+        BalanceTransitionResponse { balance_id, action, from, to },
+    )))
+}
+
+/// Añadir asignaturas a un balance existente (Solo Leader)
+/// Crea fragmentos pendientes; si la asignatura se había retirado antes, recupera
+/// su fragmento archivado con los datos que tenía
+#[post("/balances/<balance_id>/asignaturas", format = "json", data = "<request>")]
+pub async fn add_balance_subjects(
+    balance_id: i32,
+    request: Json<AddBalanceSubjectsRequest>,
+    db: &State<AppState>,
+    user: LeaderUser,
+    if_match: IfMatch,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<BalanceResponse>>, (Status, Json<ApiResponseWithData<BalanceResponse>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let data = request.into_inner();
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(balance_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    if BalanceStatus::parse(&balance.status).is_some_and(|s| !s.allows_subject_changes()) {
+        return Err(balance_error(
+            Status::Conflict,
+            format!("No se pueden añadir asignaturas a un balance en estado '{}'; reábrelo antes", balance.status),
+        ));
+    }
+
+    if let Some(expected) = expected_version(&if_match, data.version)
+        && expected != balance.version
+    {
+        return Err(balance_conflict(&db.db, balance).await);
+    }
+
+    let mut asignatura_ids: Vec<i32> = data.asignaturas.iter().map(|s| s.asignatura_id).collect();
+    asignatura_ids.sort_unstable();
+    asignatura_ids.dedup();
+    if asignatura_ids.is_empty() {
+        return Err(balance_error(Status::BadRequest, "Debe indicar al menos una asignatura"));
+    }
+
+    let previous_status = balance.status.clone();
+    let txn = db.db.begin().await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error: {}", e)))?;
+    let mut added = Vec::new();
+    let mut fragment_ids = Vec::new();
+
+    for asignatura_id in asignatura_ids {
+        let asignatura = match asignaturas::Entity::find_by_id(asignatura_id).one(&txn).await {
+            Ok(Some(a)) => a,
+            Ok(None) => return Err(balance_error(Status::BadRequest, format!("Asignatura {} no encontrada", asignatura_id))),
+            Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error al buscar asignatura: {}", e))),
+        };
+        let subject_leader_id = (asignatura.leader_id > 0).then_some(asignatura.leader_id);
+
+        // Se buscan también los fragmentos archivados: (balance_id, asignatura_id) es único
+        let existing = balance_fragments::Entity::find()
+            .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+            .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
+            .one(&txn)
+            .await
+            .map_err(|e| balance_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+
+        let result = match existing {
+            Some(f) if f.archived_at.is_none() => {
+                return Err(balance_error(
+                    Status::Conflict,
+                    format!("La asignatura '{}' ya forma parte del balance", asignatura.name),
+                ));
+            }
+            Some(f) => {
+                let fragment_version = f.version;
+                let mut active_model: balance_fragments::ActiveModel = f.into();
+                active_model.archived_at = Set(None);
+                active_model.subject_leader_id = Set(subject_leader_id);
+                active_model.version = Set(fragment_version + 1);
+                active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+                active_model.update(&txn).await
+            }
+            None => {
+                balance_fragments::ActiveModel {
+                    balance_id: Set(balance_id),
+                    asignatura_id: Set(asignatura.id),
+                    subject_leader_id: Set(subject_leader_id),
+                    status: Set("pending".to_string()),
+                    data: Set(serde_json::json!({})),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await
+            }
+        };
+        match result {
+            Ok(fragment) => fragment_ids.push(fragment.id),
+            Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error al guardar fragmento: {}", e))),
+        }
+        added.push(asignatura.name);
+    }
+
+    let updated = match save_subject_change(&txn, balance).await {
+        Ok(b) => b,
+        Err(DbErr::RecordNotUpdated) => {
+            drop(txn);
+            return match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+                Ok(Some(current)) => Err(balance_conflict(&db.db, current).await),
+                _ => Err(balance_error(Status::Conflict, "El balance fue modificado por otro usuario")),
+            };
         }
-        Err(e) => Json(ApiResponse::error(format!("Error al actualizar: {}", e))),
+        Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error al actualizar: {}", e))),
+    };
+    txn.commit().await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let status_note = if updated.status != previous_status {
+        audit_subject_status_change(&db.db, &user, balance_id, &previous_status, &updated.status, &ip_str).await;
+        format!(" (estado '{}' → '{}')", previous_status, updated.status)
+    } else {
+        String::new()
+    };
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Leader '{}' añadió al balance ID {} las asignaturas: {}{}",
+            user.0.user_name, balance_id, added.join(", "), status_note),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Balance, balance_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    let _ = notifications::notify_fragments(&db.db, user_id, &fragment_ids, Notification::FragmentAssigned).await;
+
+    match build_balance_response(&db.db, updated).await {
+        Ok(response) => Ok(Json(ApiResponseWithData::success("Asignaturas añadidas al balance".to_string(), response))),
+        Err(e) => Err(balance_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e))),
+    }
+}
+
+/// Retirar una asignatura de un balance (Solo Leader)
+/// El fragmento no se borra: queda archivado con sus datos y revisiones
+#[delete("/balances/<balance_id>/asignaturas/<asignatura_id>")]
+pub async fn remove_balance_subject(
+    balance_id: i32,
+    asignatura_id: i32,
+    db: &State<AppState>,
+    user: LeaderUser,
+    if_match: IfMatch,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<BalanceResponse>>, (Status, Json<ApiResponseWithData<BalanceResponse>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(balance_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    if BalanceStatus::parse(&balance.status).is_some_and(|s| !s.allows_subject_changes()) {
+        return Err(balance_error(
+            Status::Conflict,
+            format!("No se pueden retirar asignaturas de un balance en estado '{}'; reábrelo antes", balance.status),
+        ));
+    }
+
+    if let Some(expected) = expected_version(&if_match, None)
+        && expected != balance.version
+    {
+        return Err(balance_conflict(&db.db, balance).await);
+    }
+
+    let fragments = active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .all(&db.db)
+        .await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+
+    let Some(fragment) = fragments.iter().find(|f| f.asignatura_id == asignatura_id).cloned() else {
+        return Err(balance_error(Status::NotFound, "La asignatura no forma parte del balance"));
+    };
+
+    // Solo un balance en borrador puede quedarse sin asignaturas
+    if fragments.len() == 1 && BalanceStatus::parse(&balance.status) != Some(BalanceStatus::Draft) {
+        return Err(balance_error(
+            Status::Conflict,
+            "No se puede retirar la única asignatura de un balance que ya no está en borrador",
+        ));
+    }
+
+    let previous_status = balance.status.clone();
+    let txn = db.db.begin().await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let fragment_version = fragment.version;
+    let mut active_model: balance_fragments::ActiveModel = fragment.into();
+    let now = chrono::Utc::now().naive_utc();
+    active_model.archived_at = Set(Some(now));
+    active_model.version = Set(fragment_version + 1);
+    active_model.updated_at = Set(Some(now));
+    if let Err(e) = active_model.update(&txn).await {
+        return Err(balance_error(Status::InternalServerError, format!("Error al archivar fragmento: {}", e)));
+    }
+
+    let updated = match save_subject_change(&txn, balance).await {
+        Ok(b) => b,
+        Err(DbErr::RecordNotUpdated) => {
+            drop(txn);
+            return match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+                Ok(Some(current)) => Err(balance_conflict(&db.db, current).await),
+                _ => Err(balance_error(Status::Conflict, "El balance fue modificado por otro usuario")),
+            };
+        }
+        Err(e) => return Err(balance_error(Status::InternalServerError, format!("Error al actualizar: {}", e))),
+    };
+    txn.commit().await
+        .map_err(|e| balance_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let status_note = if updated.status != previous_status {
+        audit_subject_status_change(&db.db, &user, balance_id, &previous_status, &updated.status, &ip_str).await;
+        format!(" (estado '{}' → '{}')", previous_status, updated.status)
+    } else {
+        String::new()
+    };
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Leader '{}' retiró la asignatura ID {} del balance ID {}{}",
+            user.0.user_name, asignatura_id, balance_id, status_note),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Balance, balance_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    match build_balance_response(&db.db, updated).await {
+        Ok(response) => Ok(Json(ApiResponseWithData::success("Asignatura retirada del balance".to_string(), response))),
+        Err(e) => Err(balance_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e))),
     }
 }
 
@@ -764,6 +1786,116 @@ pub async fn delete_balance(
     }
 }
 
+/// Validar las reglas semanales de la facultad sobre todos los fragmentos del balance
+/// GET /api/balances/<id>/validation
+#[get("/balances/<balance_id>/validation")]
+pub async fn validate_balance(
+    balance_id: i32,
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<ApiResponseWithData<BalanceValidationReport>>, (Status, Json<ApiResponseWithData<BalanceValidationReport>>)> {
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponseWithData::error("Balance no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error: {}", e))))),
+    };
+
+    // Mismos permisos que para ver el balance
+    if !can_view_balance(&db.db, &user.0, balance_id).await {
+        return Err((Status::Forbidden, Json(ApiResponseWithData::error(
+            "No tienes permiso para ver este balance".to_string(),
+        ))));
+    }
+
+    let subjects = load_scheduled_subjects(&db.db, &balance, None)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error al obtener fragmentos: {}", e)))))?;
+
+    let violations = evaluate_balance(&subjects);
+
+    Ok(Json(ApiResponseWithData::success(
+        "Validación del balance completada".to_string(),
+        BalanceValidationReport {
+            balance_id,
+            valid: violations.is_empty(),
+            violations,
+        },
+    )))
+}
+
+/// Carga semanal del estudiante combinando todos los fragmentos del balance (Leader)
+/// GET /api/balances/<id>/load?max_hours=36&max_critical=2&max_exams=2
+#[get("/balances/<balance_id>/load?<max_hours>&<max_critical>&<max_exams>")]
+pub async fn get_balance_load(
+    balance_id: i32,
+    max_hours: Option<i32>,
+    max_critical: Option<usize>,
+    max_exams: Option<usize>,
+    db: &State<AppState>,
+    _user: LeaderUser,
+) -> Result<Json<ApiResponseWithData<LoadReport>>, (Status, Json<ApiResponseWithData<LoadReport>>)> {
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponseWithData::error("Balance no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error: {}", e))))),
+    };
+
+    let subjects = load_scheduled_subjects(&db.db, &balance, None)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error al obtener fragmentos: {}", e)))))?;
+
+    let defaults = LoadThresholds::default();
+    let thresholds = LoadThresholds {
+        max_hours: max_hours.unwrap_or(defaults.max_hours),
+        max_critical: max_critical.unwrap_or(defaults.max_critical),
+        max_exams: max_exams.unwrap_or(defaults.max_exams),
+    };
+
+    let (non_academic_periods, _) = load_balance_periods(&db.db, &balance)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error al obtener el calendario: {}", e)))))?;
+    let layout = balance_layout(&balance);
+    let week_dates = calculate_week_dates(balance.start_date, &layout, &non_academic_periods);
+    let report = analyze_load(&subjects, layout.weeks, &week_dates, thresholds);
+
+    Ok(Json(ApiResponseWithData::success(
+        "Carga semanal calculada exitosamente".to_string(),
+        report,
+    )))
+}
+
+/// Calendario del balance: fechas reales de cada celda de las semanas lectivas,
+/// consultas y exámenes, y los días saltados por períodos no académicos.
+/// GET /api/balances/<id>/calendar
+#[get("/balances/<balance_id>/calendar")]
+pub async fn get_balance_calendar(
+    balance_id: i32,
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<ApiResponseWithData<BalanceCalendar>>, (Status, Json<ApiResponseWithData<BalanceCalendar>>)> {
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponseWithData::error("Balance no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error: {}", e))))),
+    };
+
+    // Mismos permisos que para ver el balance
+    if !can_view_balance(&db.db, &user.0, balance_id).await {
+        return Err((Status::Forbidden, Json(ApiResponseWithData::error(
+            "No tienes permiso para ver este balance".to_string(),
+        ))));
+    }
+
+    let calendar = load_balance_calendar(&db.db, &balance)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error al obtener el calendario: {}", e)))))?;
+
+    Ok(Json(ApiResponseWithData::success(
+        "Calendario del balance obtenido exitosamente".to_string(),
+        calendar,
+    )))
+}
+
 // ============================================================================
 // RUTAS DE FRAGMENTOS (SubjectLeader)
 // ============================================================================
@@ -776,7 +1908,7 @@ pub async fn get_pending_fragments(
 ) -> Json<ApiResponseWithData<Vec<PendingFragment>>> {
     let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
 
-    let fragments = match balance_fragments::Entity::find()
+    let fragments = match active_fragments()
         .filter(balance_fragments::Column::SubjectLeaderId.eq(user_id))
         .filter(balance_fragments::Column::Status.ne("completed"))
         .all(&db.db)
@@ -811,7 +1943,7 @@ pub async fn get_pending_fragments(
                 asignatura_id: fragment.asignatura_id,
                 asignatura_name: a.name,
                 status: fragment.status,
-                deadline: b.deadline.map(|d| d.to_string()),
+                deadline: effective_deadline(b.deadline, fragment.deadline_extension).map(|d| d.to_string()),
             });
         }
     }
@@ -829,12 +1961,12 @@ pub async fn get_fragment(
     asignatura_id: i32,
     db: &State<AppState>,
     user: LeaderOrSubjectLeaderUser,
-) -> Result<Json<ApiResponseWithData<FragmentResponse>>, (Status, Json<ApiResponseWithData<FragmentResponse>>)> {
+) -> Result<WithETag<Json<ApiResponseWithData<FragmentResponse>>>, (Status, Json<ApiResponseWithData<FragmentResponse>>)> {
     let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
     let user_role = &user.0.role;
 
     // Buscar el fragmento
-    let fragment = match balance_fragments::Entity::find()
+    let fragment = match active_fragments()
         .filter(balance_fragments::Column::BalanceId.eq(balance_id))
         .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
         .one(&db.db)
@@ -846,70 +1978,63 @@ pub async fn get_fragment(
     };
 
     // Verificar permisos: Leader ve todo, SubjectLeader solo su fragmento
-    if user_role != "leader" && user_role != "admin" {
-        if fragment.subject_leader_id != Some(user_id) {
-            return Err((Status::Forbidden, Json(ApiResponseWithData::error(
-                "No tienes permiso para ver este fragmento".to_string(),
-            ))));
-        }
+    if user_role != "leader" && user_role != "admin" && fragment.subject_leader_id != Some(user_id) {
+        return Err((Status::Forbidden, Json(ApiResponseWithData::error(
+            "No tienes permiso para ver este fragmento".to_string(),
+        ))));
     }
 
-    // Obtener info de asignatura
-    let asignatura_info = asignaturas::Entity::find_by_id(fragment.asignatura_id)
-        .one(&db.db)
-        .await
-        .ok()
-        .flatten()
-        .map(|a| AsignaturaInfo {
-            id: a.id,
-            name: a.name,
-            year: a.year,
-            semester: a.semester,
-            hours: a.hours,
-            c: a.c,
-            cp: a.cp,
-            s: a.s,
-            pl: a.pl,
-            te: a.te,
-            t: a.t,
-            pp: a.pp,
-            ec: a.ec,
-            tc: a.tc,
-            ef: a.ef,
-        });
+    let response = build_fragment_response(&db.db, fragment).await;
+    Ok(WithETag {
+        version: response.version,
+        inner: Json(ApiResponseWithData::success("Fragmento obtenido exitosamente".to_string(), response)),
+    })
+}
 
-    // Obtener info del subject_leader
-    let subject_leader_info = if let Some(leader_id) = fragment.subject_leader_id {
-        usuarios::Entity::find_by_id(leader_id)
-            .one(&db.db)
-            .await
-            .ok()
-            .flatten()
-            .map(|u| SubjectLeaderInfo {
-                id: u.id,
-                user_name: u.user_name,
-                name: u.name,
-                email: u.email,
-            })
-    } else {
-        None
+/// Conciliación plan vs programado de un fragmento
+/// GET /api/balances/<id>/fragments/<asig_id>/reconciliation
+#[get("/balances/<balance_id>/fragments/<asignatura_id>/reconciliation")]
+pub async fn get_fragment_reconciliation(
+    balance_id: i32,
+    asignatura_id: i32,
+    db: &State<AppState>,
+    user: LeaderOrSubjectLeaderUser,
+) -> Result<Json<ApiResponseWithData<FragmentReconciliation>>, (Status, Json<ApiResponseWithData<FragmentReconciliation>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let user_role = &user.0.role;
+
+    let (fragment, asignatura) = match active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
+        .find_also_related(asignaturas::Entity)
+        .one(&db.db)
+        .await
+    {
+        Ok(Some((f, Some(a)))) => (f, a),
+        Ok(_) => return Err((Status::NotFound, Json(ApiResponseWithData::error("Fragmento no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error: {}", e))))),
     };
 
+    if user_role != "leader" && user_role != "admin" && fragment.subject_leader_id != Some(user_id) {
+        return Err((Status::Forbidden, Json(ApiResponseWithData::error(
+            "No tienes permiso para ver este fragmento".to_string(),
+        ))));
+    }
+
+    let layout = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => balance_layout(&b),
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponseWithData::error("Balance no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error: {}", e))))),
+    };
+
+    let data = FragmentData::from_stored(&fragment.data, &layout);
+    let reconciliation = reconcile_fragment(&db.db, &asignatura, &data)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponseWithData::error(format!("Error: {}", e)))))?;
+
     Ok(Json(ApiResponseWithData::success(
-        "Fragmento obtenido exitosamente".to_string(),
-        FragmentResponse {
-            id: fragment.id,
-            balance_id: fragment.balance_id,
-            asignatura_id: fragment.asignatura_id,
-            asignatura: asignatura_info,
-            subject_leader_id: fragment.subject_leader_id,
-            subject_leader: subject_leader_info,
-            status: fragment.status,
-            data: fragment.data,
-            completed_at: fragment.completed_at.map(|dt| dt.to_string()),
-            created_at: fragment.created_at.map(|dt| dt.to_string()),
-            updated_at: fragment.updated_at.map(|dt| dt.to_string()),
-        },
+        "Conciliación obtenida exitosamente".to_string(),
+        reconciliation,
     )))
 }
 
@@ -921,46 +2046,115 @@ pub async fn update_fragment(
     fragment_data: Json<UpdateFragmentRequest>,
     db: &State<AppState>,
     user: LeaderOrSubjectLeaderUser,
+    if_match: IfMatch,
     remote_addr: Option<SocketAddr>,
-) -> Result<Json<ApiResponse>, (Status, Json<ApiResponse>)> {
+) -> Result<Json<ApiResponse>, (Status, Json<ApiResponseWithData<FragmentRejection>>)> {
     let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
     let user_role = &user.0.role;
     let data = fragment_data.into_inner();
     let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
 
     // Buscar el fragmento
-    let fragment = match balance_fragments::Entity::find()
+    let fragment = match active_fragments()
         .filter(balance_fragments::Column::BalanceId.eq(balance_id))
         .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
         .one(&db.db)
         .await
     {
         Ok(Some(f)) => f,
-        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Fragmento no encontrado".to_string())))),
-        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+        Ok(None) => return Err(fragment_error(Status::NotFound, "Fragmento no encontrado")),
+        Err(e) => return Err(fragment_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(fragment_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(fragment_error(Status::InternalServerError, format!("Error: {}", e))),
     };
 
-    // Verificar permisos
-    let is_leader = user_role == "leader" || user_role == "admin";
-    let is_owner = fragment.subject_leader_id == Some(user_id);
+    authorize_fragment_edit(&db.db, &user.0, &balance, &fragment, &ip_str).await?;
 
-    // Leader y Admin siempre pueden editar cualquier fragmento
-    // SubjectLeader solo puede editar su propio fragmento
-    if !is_leader && !is_owner {
-        return Err((Status::Forbidden, Json(ApiResponse::error(
-            "No tienes permiso para editar este fragmento".to_string(),
-        ))));
+    // Rechazar escrituras hechas sobre una copia desactualizada
+    if let Some(expected) = expected_version(&if_match, data.version)
+        && expected != fragment.version
+    {
+        return Err(fragment_conflict(&db.db, fragment).await);
+    }
+
+    // Validar la estructura y los códigos de actividad antes de evaluar nada
+    let fragment_grid = FragmentData::from_payload(data.data, &balance_layout(&balance))
+        .map_err(|e| {
+            let status = match e {
+                FragmentDataError::UnknownActivity { .. } => Status::UnprocessableEntity,
+                _ => Status::BadRequest,
+            };
+            fragment_error(status, e.to_string())
+        })?;
+
+    // Evaluar las reglas semanales con los nuevos datos antes de guardar
+    check_fragment_rules(&db.db, &balance, asignatura_id, &fragment_grid).await?;
+
+    let asignatura = match asignaturas::Entity::find_by_id(asignatura_id).one(&db.db).await {
+        Ok(Some(a)) => a,
+        Ok(None) => return Err(fragment_error(Status::NotFound, "Asignatura no encontrada")),
+        Err(e) => return Err(fragment_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    // Entregar, aprobar y pedir cambios se hace mediante /review; el Leader
+    // puede seguir completando directamente un fragmento que edita él mismo
+    match data.status.as_deref().map(FragmentStatus::parse) {
+        None | Some(Some(FragmentStatus::Pending | FragmentStatus::InProgress)) => {}
+        Some(Some(FragmentStatus::Completed)) if user_role == "leader" => {}
+        Some(Some(_)) => {
+            return Err(fragment_error(
+                Status::BadRequest,
+                "Para entregar o revisar el fragmento usa POST /api/balances/<id>/fragments/<asig>/review",
+            ));
+        }
+        Some(None) => return Err(fragment_error(Status::BadRequest, "Estado inválido")),
+    }
+
+    // Un fragmento completado debe cuadrar con lo planificado (cantidades por tipo y horas).
+    // Solo un Leader puede forzarlo indicando el motivo, que queda registrado.
+    let effective_status = data.status.clone().unwrap_or_else(|| fragment.status.clone());
+    let mut override_reason = None;
+    if effective_status == "completed" {
+        let reconciliation = reconcile_fragment(&db.db, &asignatura, &fragment_grid)
+            .await
+            .map_err(|e| fragment_error(Status::InternalServerError, format!("Error: {}", e)))?;
+        if !reconciliation.balanced {
+            let reason = data.override_reason
+                .as_deref()
+                .map(sanitize_text)
+                .filter(|r| !r.is_empty());
+
+            match reason {
+                Some(r) if user_role == "leader" => override_reason = Some(r),
+                _ => {
+                    let message = if reason.is_some() {
+                        "Solo un Leader puede completar un fragmento que no cuadra con lo planificado"
+                    } else {
+                        "El fragmento no cuadra con lo planificado y no puede marcarse como completado"
+                    };
+                    return Err((Status::UnprocessableEntity, Json(ApiResponseWithData {
+                        message: message.to_string(),
+                        alert: "error".to_string(),
+                        data: Some(FragmentRejection { reconciliation: Some(reconciliation), ..Default::default() }),
+                    })));
+                }
+            }
+        }
     }
 
     // Actualizar el fragmento
+    let fragment_id = fragment.id;
     let mut active_model: balance_fragments::ActiveModel = fragment.into();
-    active_model.data = Set(data.data);
+    active_model.data = Set(fragment_grid.to_value());
     active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+    active_model.completion_override_reason = Set(override_reason.clone());
 
+    let completed_now = data.status.as_deref() == Some("completed");
     if let Some(status) = data.status {
-        if !["pending", "in_progress", "completed"].contains(&status.as_str()) {
-            return Err((Status::BadRequest, Json(ApiResponse::error("Estado inválido".to_string()))));
-        }
         active_model.status = Set(status.clone());
 
         // Si se marca como completado, guardar timestamp
@@ -969,16 +2163,24 @@ pub async fn update_fragment(
         }
     }
 
-    match active_model.update(&db.db).await {
-        Ok(_) => {
-            // Obtener nombre de asignatura para el log
-            let asignatura_name = asignaturas::Entity::find_by_id(asignatura_id)
-                .one(&db.db)
-                .await
-                .ok()
-                .flatten()
-                .map(|a| a.name)
-                .unwrap_or_else(|| "Desconocida".to_string());
+    // Guardar el fragmento junto con una nueva revisión del historial
+    match save_fragment_with_revision(&db.db, active_model, &user.0, None).await {
+        Ok((updated, _)) => {
+            let asignatura_name = asignatura.name;
+
+            if let Some(ref reason) = override_reason {
+                let _ = audit::AuditLogBuilder::new(
+                    EventType::Update,
+                    AuditCategory::Functional,
+                    format!("Leader '{}' forzó el completado del fragmento de '{}' en balance ID {} sin cuadrar con lo planificado. Motivo: {}",
+                        user.0.user_name, asignatura_name, balance_id, reason),
+                )
+                .user(user_id, &user.0.user_name)
+                .entity(EntityType::Fragment, updated.id)
+                .ip(&ip_str)
+                .save(&db.db)
+                .await;
+            }
 
             let _ = audit::AuditLogBuilder::new(
                 EventType::Update,
@@ -992,57 +2194,256 @@ pub async fn update_fragment(
             .save(&db.db)
             .await;
 
-            // Actualizar estado del balance si todos los fragmentos están completos
-            update_balance_status_if_complete(&db.db, balance_id).await;
+            // Avisar al jefe de asignatura si otra persona tocó su fragmento
+            let actor = user.0.name.clone();
+            let _ = notifications::notify_fragments(&db.db, user_id, &[fragment_id], |fragment| {
+                if completed_now {
+                    Notification::FragmentApproved { fragment, reviewer: actor.clone() }
+                } else {
+                    Notification::FragmentEdited { fragment, editor: actor.clone() }
+                }
+            })
+            .await;
 
             Ok(Json(ApiResponse::success("Fragmento actualizado exitosamente".to_string())))
         }
-        Err(e) => Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error al actualizar: {}", e))))),
+        Err(DbErr::RecordNotUpdated) => {
+            match balance_fragments::Entity::find_by_id(fragment_id).one(&db.db).await {
+                Ok(Some(current)) => Err(fragment_conflict(&db.db, current).await),
+                _ => Err(fragment_error(Status::Conflict, "El fragmento fue modificado por otro usuario")),
+            }
+        }
+        Err(e) => Err(fragment_error(Status::InternalServerError, format!("Error al actualizar: {}", e))),
     }
 }
 
-/// Actualiza el estado del balance a 'completed' si todos los fragmentos están completos
-async fn update_balance_status_if_complete(db: &DatabaseConnection, balance_id: i32) {
-    let fragments = balance_fragments::Entity::find()
+/// Conceder o retirar una prórroga de la fecha límite para un fragmento
+/// Solo el Leader que creó el balance puede hacerlo
+#[put("/balances/<balance_id>/fragments/<asignatura_id>/extension", format = "json", data = "<extension>")]
+pub async fn set_fragment_extension(
+    balance_id: i32,
+    asignatura_id: i32,
+    extension: Json<FragmentExtensionRequest>,
+    db: &State<AppState>,
+    user: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponse>, (Status, Json<ApiResponse>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Balance no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
+
+    if balance.user_id != user_id {
+        let _ = audit::log_access_denied(
+            &db.db,
+            Some(user_id),
+            Some(&user.0.user_name),
+            &format!("prórroga de fragmento en balance ID {} (no es el Leader del balance)", balance_id),
+            &ip_str,
+        ).await;
+        return Err((Status::Forbidden, Json(ApiResponse::error(
+            "Solo el Leader del balance puede conceder prórrogas".to_string(),
+        ))));
+    }
+
+    let deadline = match extension.into_inner().deadline {
+        Some(dl) => match NaiveDate::parse_from_str(&dl, "%Y-%m-%d") {
+            Ok(date) => Some(date),
+            Err(_) => return Err((Status::BadRequest, Json(ApiResponse::error("Formato de fecha de prórroga inválido".to_string())))),
+        },
+        None => None,
+    };
+
+    let fragment = match active_fragments()
         .filter(balance_fragments::Column::BalanceId.eq(balance_id))
-        .all(db)
+        .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
+        .one(&db.db)
         .await
-        .unwrap_or_default();
+    {
+        Ok(Some(f)) => f,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Fragmento no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
 
-    let all_completed = !fragments.is_empty() && 
-        fragments.iter().all(|f| f.status == "completed");
+    let fragment_id = fragment.id;
+    let fragment_version = fragment.version;
+    let mut active_model: balance_fragments::ActiveModel = fragment.into();
+    active_model.deadline_extension = Set(deadline);
+    active_model.version = Set(fragment_version + 1);
+    active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
 
-    if all_completed {
-        if let Ok(Some(balance)) = balances::Entity::find_by_id(balance_id).one(db).await {
-            let mut active_model: balances::ActiveModel = balance.into();
-            active_model.status = Set("completed".to_string());
-            active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
-            let _ = active_model.update(db).await;
-        }
-    } else {
-        // Si hay al menos un fragmento in_progress, actualizar balance a in_progress
-        let any_in_progress = fragments.iter().any(|f| f.status == "in_progress" || f.status == "completed");
-        if any_in_progress {
-            if let Ok(Some(balance)) = balances::Entity::find_by_id(balance_id).one(db).await {
-                if balance.status == "draft" {
-                    let mut active_model: balances::ActiveModel = balance.into();
-                    active_model.status = Set("in_progress".to_string());
-                    active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
-                    let _ = active_model.update(db).await;
-                }
-            }
-        }
+    if let Err(e) = active_model.update(&db.db).await {
+        return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error al actualizar: {}", e)))));
+    }
+
+    let description = match deadline {
+        Some(d) => format!("Leader '{}' concedió prórroga hasta {} al fragmento de asignatura ID {} en balance ID {}",
+            user.0.user_name, d, asignatura_id, balance_id),
+        None => format!("Leader '{}' retiró la prórroga del fragmento de asignatura ID {} en balance ID {}",
+            user.0.user_name, asignatura_id, balance_id),
+    };
+    let _ = audit::AuditLogBuilder::new(EventType::Update, AuditCategory::Functional, description)
+        .user(user_id, &user.0.user_name)
+        .entity(EntityType::Fragment, fragment_id)
+        .ip(&ip_str)
+        .save(&db.db)
+        .await;
+
+    let _ = notifications::notify_fragments(&db.db, user_id, &[fragment_id], Notification::DeadlineChanged).await;
+
+    Ok(Json(ApiResponse::success("Prórroga actualizada exitosamente".to_string())))
+}
+
+/// Entregar un fragmento a otro jefe de asignatura
+/// Solo el Leader que creó el balance puede hacerlo y no en balances archivados
+#[put("/balances/<balance_id>/fragments/<asignatura_id>/leader", format = "json", data = "<request>")]
+pub async fn reassign_fragment(
+    balance_id: i32,
+    asignatura_id: i32,
+    request: Json<FragmentReassignRequest>,
+    db: &State<AppState>,
+    user: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponse>, (Status, Json<ApiResponse>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let subject_leader_id = request.subject_leader_id;
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Balance no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
+
+    if balance.user_id != user_id {
+        let _ = audit::log_access_denied(
+            &db.db,
+            Some(user_id),
+            Some(&user.0.user_name),
+            &format!("reasignación de fragmento en balance ID {} (no es el Leader del balance)", balance_id),
+            &ip_str,
+        ).await;
+        return Err((Status::Forbidden, Json(ApiResponse::error(
+            "Solo el Leader del balance puede reasignar fragmentos".to_string(),
+        ))));
+    }
+
+    if BalanceStatus::parse(&balance.status) == Some(BalanceStatus::Archived) {
+        return Err((Status::Conflict, Json(ApiResponse::error(
+            "No se pueden reasignar fragmentos de un balance archivado".to_string(),
+        ))));
+    }
+
+    let subject_leader = match utils::db::find_subject_leader(&db.db, subject_leader_id).await {
+        Ok(Some(u)) => u,
+        Ok(None) => return Err((Status::BadRequest, Json(ApiResponse::error(
+            "El usuario indicado no es un jefe de asignatura".to_string(),
+        )))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
+
+    let fragment = match active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
+        .one(&db.db)
+        .await
+    {
+        Ok(Some(f)) => f,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Fragmento no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
+
+    let fragment_id = fragment.id;
+    let previous_leader_id = fragment.subject_leader_id;
+    let fragment_version = fragment.version;
+    let mut active_model: balance_fragments::ActiveModel = fragment.into();
+    active_model.subject_leader_id = Set(Some(subject_leader_id));
+    active_model.version = Set(fragment_version + 1);
+    active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+
+    if let Err(e) = active_model.update(&db.db).await {
+        return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error al actualizar: {}", e)))));
+    }
+
+    let previous = previous_leader_id.map(|id| format!("ID {}", id)).unwrap_or_else(|| "sin jefe".to_string());
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Leader '{}' reasignó el fragmento de asignatura ID {} en balance ID {} ({} → '{}')",
+            user.0.user_name, asignatura_id, balance_id, previous, subject_leader.user_name),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Fragment, fragment_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    if previous_leader_id != Some(subject_leader_id) {
+        let _ = notifications::notify_fragments(&db.db, user_id, &[fragment_id], Notification::FragmentAssigned).await;
+    }
+
+    Ok(Json(ApiResponse::success("Fragmento reasignado exitosamente".to_string())))
+}
+
+/// Fragmentos sin jefe de asignatura en balances no archivados (Dashboard del Leader)
+#[get("/fragments/unassigned")]
+pub async fn get_unassigned_fragments(
+    db: &State<AppState>,
+    _user: LeaderUser,
+) -> Json<ApiResponseWithData<Vec<PendingFragment>>> {
+    let rows = match active_fragments()
+        .filter(balance_fragments::Column::SubjectLeaderId.is_null())
+        .find_also_related(balances::Entity)
+        .filter(balances::Column::Status.ne(BalanceStatus::Archived.as_str()))
+        .order_by_asc(balance_fragments::Column::BalanceId)
+        .all(&db.db)
+        .await
+    {
+        Ok(r) => r,
+        Err(e) => return Json(ApiResponseWithData::error(format!("Error: {}", e))),
+    };
+
+    let mut unassigned = Vec::new();
+    for (fragment, balance) in rows {
+        let Some(b) = balance else { continue };
+        let asignatura_name = asignaturas::Entity::find_by_id(fragment.asignatura_id)
+            .one(&db.db)
+            .await
+            .ok()
+            .flatten()
+            .map(|a| a.name)
+            .unwrap_or_else(|| format!("Asignatura {}", fragment.asignatura_id));
+
+        unassigned.push(PendingFragment {
+            fragment_id: fragment.id,
+            balance_id: fragment.balance_id,
+            balance_name: b.name,
+            asignatura_id: fragment.asignatura_id,
+            asignatura_name,
+            status: fragment.status,
+            deadline: effective_deadline(b.deadline, fragment.deadline_extension).map(|d| d.to_string()),
+        });
     }
+
+    Json(ApiResponseWithData::success(
+        "Fragmentos sin jefe de asignatura obtenidos".to_string(),
+        unassigned,
+    ))
 }
 
 // ============================================================================
-// EXPORTACIÓN A EXCEL
+// EXPORTACIÓN A EXCEL Y PDF
 // ============================================================================
 
 use crate::utils::excel_export::{
-    BalanceExportConfig, FragmentExportData, ActivityPlan, 
-    generate_balance_excel, parse_fragment_data, parse_consultas_data, parse_examenes_data
+    BalanceExportConfig, FragmentExportData, PeriodCoverEntry, PeriodExportConfig,
+    generate_balance_excel, generate_period_excel
 };
+use crate::utils::pdf_export::generate_balance_pdf;
 use rocket::http::ContentType;
 
 /// Custom responder for Excel file download
@@ -1061,17 +2462,83 @@ impl<'r> rocket::response::Responder<'r, 'static> for ExcelFile {
     }
 }
 
-/// Exportar balance a Excel
-/// GET /api/balances/<id>/export
-#[get("/balances/<balance_id>/export")]
+/// Custom responder for PDF file download
+pub struct PdfFile {
+    pub data: Vec<u8>,
+    pub filename: String,
+}
+
+impl<'r> rocket::response::Responder<'r, 'static> for PdfFile {
+    fn respond_to(self, _request: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        rocket::Response::build()
+            .header(ContentType::PDF)
+            .raw_header("Content-Disposition", format!("attachment; filename=\"{}\"", self.filename))
+            .sized_body(self.data.len(), std::io::Cursor::new(self.data))
+            .ok()
+    }
+}
+
+/// Archivo exportado en el formato solicitado
+#[derive(rocket::Responder)]
+pub enum BalanceExportFile {
+    Excel(ExcelFile),
+    Pdf(PdfFile),
+}
+
+/// Datos de exportación de un balance: cuadrícula y plan de cada fragmento
+/// y períodos no académicos (calendario académico más los del balance)
+pub(crate) async fn build_export_config(
+    db: &DatabaseConnection,
+    balance: &balances::Model,
+    fragments: &[balance_fragments::Model],
+) -> Result<BalanceExportConfig, DbErr> {
+    let layout = balance_layout(balance);
+    let asignatura_ids: Vec<i32> = fragments.iter().map(|f| f.asignatura_id).collect();
+    let mut plans = utils::db::load_activity_plans(db, &asignatura_ids).await?;
+    let mut fragment_data = Vec::new();
+    for fragment in fragments {
+        if let Some(asig) = asignaturas::Entity::find_by_id(fragment.asignatura_id).one(db).await? {
+            fragment_data.push(FragmentExportData {
+                data: FragmentData::from_stored(&fragment.data, &layout),
+                plan: plans.remove(&asig.id).unwrap_or_default(),
+                name: asig.name,
+                hours: asig.hours,
+            });
+        }
+    }
+
+    let (non_academic_periods, exam_sessions) = load_balance_periods(db, balance).await?;
+
+    Ok(BalanceExportConfig {
+        academic_year: balance.academic_year.clone(),
+        period: balance.period.clone(),
+        academic_year_text: balance.academic_year_text.clone(),
+        start_date: balance.start_date,
+        layout,
+        fragments: fragment_data,
+        non_academic_periods,
+        exam_sessions,
+    })
+}
+
+/// Exportar balance a Excel (por defecto) o PDF
+/// GET /api/balances/<id>/export?format=xlsx|pdf
+#[get("/balances/<balance_id>/export?<format>")]
 pub async fn export_balance_excel(
     db: &State<AppState>,
     user: AuthenticatedUser,
     balance_id: i32,
-) -> Result<ExcelFile, (Status, Json<ApiResponse>)> {
+    format: Option<&str>,
+) -> Result<BalanceExportFile, (Status, Json<ApiResponse>)> {
     let _user_id = user.0.sub.parse::<i32>().unwrap_or(0);
     let _user_role = &user.0.role;
 
+    let as_pdf = match format.unwrap_or("xlsx") {
+        "xlsx" => false,
+        "pdf" => true,
+        other => return Err((Status::BadRequest, Json(ApiResponse::error(format!("Formato de exportación no soportado: {}", other))))),
+    };
+
     // Buscar el balance
     let balance = balances::Entity::find_by_id(balance_id)
         .one(&db.db)
@@ -1083,84 +2550,94 @@ pub async fn export_balance_excel(
     // Todos los usuarios autenticados pueden exportar el balance.
     
     // Obtener fragmentos con datos de asignatura
-    let fragments = balance_fragments::Entity::find()
+    let fragments = active_fragments()
         .filter(balance_fragments::Column::BalanceId.eq(balance_id))
         .all(&db.db)
         .await
         .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error obteniendo fragmentos: {}", e)))))?;
 
-    // Construir datos de exportación
-    let mut fragment_data = Vec::new();
-    for fragment in &fragments {
-        // Obtener info de la asignatura
-        let asignatura = asignaturas::Entity::find_by_id(fragment.asignatura_id)
-            .one(&db.db)
-            .await
-            .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e)))))?;
+    let config = build_export_config(&db.db, &balance, &fragments)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e)))))?;
 
-        if let Some(asig) = asignatura {
-            let weekly_data = parse_fragment_data(&fragment.data, balance.weeks);
-            let consultas_data = parse_consultas_data(&fragment.data, balance.weeks);
-            let examenes_data = parse_examenes_data(&fragment.data, balance.weeks);
-            
-            fragment_data.push(FragmentExportData {
-                name: asig.name,
-                hours: asig.hours,
-                weekly_data,
-                consultas_data,
-                examenes_data,
-                plan: ActivityPlan {
-                    c: asig.c.unwrap_or(0),
-                    cp: asig.cp.unwrap_or(0),
-                    s: asig.s.unwrap_or(0),
-                    pl: asig.pl.unwrap_or(0),
-                    te: asig.te.unwrap_or(0),
-                    t: asig.t.unwrap_or(0),
-                    pp: asig.pp.unwrap_or(0),
-                    ec: asig.ec.unwrap_or(0),
-                    tc: asig.tc.unwrap_or(0),
-                    ef: asig.ef.unwrap_or(0),
-                },
-            });
-        }
+    // Generate filename
+    let filename = format!(
+        "Balance_de_carga_Diurno_{}_{}_{}",
+        balance.academic_year_text.replace("-", "_"),
+        balance.period.replace(" ", "_"),
+        balance.academic_year.replace(" ", "_")
+    );
+
+    if as_pdf {
+        let pdf_bytes = generate_balance_pdf(&config)
+            .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error generando PDF: {}", e)))))?;
+        return Ok(BalanceExportFile::Pdf(PdfFile { data: pdf_bytes, filename: format!("{}.pdf", filename) }));
     }
 
-    // start_date is already NaiveDate (Date type from SeaORM)
-    let start_date = balance.start_date;
+    // Generate Excel
+    let excel_bytes = generate_balance_excel(&config)
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error generando Excel: {}", e)))))?;
 
-    // Parse non_academic_periods from Json
-    let non_academic_periods: Vec<(NaiveDate, NaiveDate, String)> = serde_json::from_value::<Vec<NonAcademicPeriod>>(balance.non_academic_periods.clone())
-        .unwrap_or_default()
-        .into_iter()
-        .filter_map(|p| {
-            let start = NaiveDate::parse_from_str(&p.start, "%Y-%m-%d").ok()?;
-            let end = NaiveDate::parse_from_str(&p.end, "%Y-%m-%d").ok()?;
-            Some((start, end, p.name))
-        })
-        .collect();
+    Ok(BalanceExportFile::Excel(ExcelFile { data: excel_bytes, filename: format!("{}.xlsx", filename) }))
+}
 
-    // Build export config
-    let config = BalanceExportConfig {
-        academic_year: balance.academic_year.clone(),
-        period: balance.period.clone(),
-        academic_year_text: balance.academic_year_text.clone(),
-        start_date,
-        weeks: balance.weeks,
-        fragments: fragment_data,
-        non_academic_periods,
-    };
+/// Exportar en un solo libro todos los balances de un período (1ro–4to), con portada (Leader)
+/// GET /api/periods/export?academic_year_text=2025-2026&period=1ero
+#[get("/periods/export?<academic_year_text>&<period>")]
+pub async fn export_period_excel(
+    academic_year_text: &str,
+    period: &str,
+    db: &State<AppState>,
+    _user: LeaderUser,
+) -> Result<ExcelFile, (Status, Json<ApiResponse>)> {
+    let db_error = |e: DbErr| (Status::InternalServerError, Json(ApiResponse::error(format!("Error de base de datos: {}", e))));
 
-    // Generate Excel
-    let excel_bytes = generate_balance_excel(&config)
+    let period_balances = balances::Entity::find()
+        .filter(balances::Column::AcademicYearText.eq(academic_year_text))
+        .filter(balances::Column::Period.eq(period))
+        .order_by_asc(balances::Column::AcademicYear)
+        .order_by_asc(balances::Column::Id)
+        .all(&db.db)
+        .await
+        .map_err(db_error)?;
+
+    if period_balances.is_empty() {
+        return Err((Status::NotFound, Json(ApiResponse::error(format!(
+            "No hay balances del período {} en el curso {}", period, academic_year_text
+        )))));
+    }
+
+    let mut balances_data = Vec::with_capacity(period_balances.len());
+    for balance in &period_balances {
+        let fragments = active_fragments()
+            .filter(balance_fragments::Column::BalanceId.eq(balance.id))
+            .all(&db.db)
+            .await
+            .map_err(db_error)?;
+
+        let entry = PeriodCoverEntry {
+            name: balance.name.clone(),
+            status: BalanceStatus::parse(&balance.status).map(|s| s.label().to_string()).unwrap_or_else(|| balance.status.clone()),
+            deadline: balance.deadline,
+            completed_fragments: fragments.iter().filter(|f| f.status == "completed").count(),
+            total_fragments: fragments.len(),
+        };
+        let config = build_export_config(&db.db, balance, &fragments).await.map_err(db_error)?;
+        balances_data.push((entry, config));
+    }
+
+    let config = PeriodExportConfig {
+        period: period.to_string(),
+        academic_year_text: academic_year_text.to_string(),
+        balances: balances_data,
+    };
+    let data = generate_period_excel(&config)
         .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error generando Excel: {}", e)))))?;
 
-    // Generate filename
     let filename = format!(
-        "Balance_de_carga_Diurno_{}_{}_{}.xlsx",
-        balance.academic_year_text.replace("-", "_"),
-        balance.period.replace(" ", "_"),
-        balance.academic_year.replace(" ", "_")
+        "Balance_de_carga_Diurno_{}_{}.xlsx",
+        academic_year_text.replace("-", "_"),
+        period.replace(" ", "_")
     );
-
-    Ok(ExcelFile { data: excel_bytes, filename })
+    Ok(ExcelFile { data, filename })
 }
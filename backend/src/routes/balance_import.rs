@@ -0,0 +1,558 @@
+//! Importación de balances desde la plantilla Excel de la facultad
+//!
+//! El archivo se sube como `multipart/form-data` (campo `file`). Sin
+//! `commit=true` solo se devuelve el informe (dry-run): asignaturas
+//! emparejadas por nombre, las que no existen, códigos de actividad
+//! desconocidos, celdas fuera de la estructura y diferencias en el número de
+//! semanas, fragmentos entregados o aprobados (que no se importan) y
+//! violaciones de las reglas semanales. Con `commit=true` se crea o actualiza
+//! el balance y sus fragmentos, guardando una revisión de cada fragmento
+//! importado; si la hoja incumple las reglas no se importa nada.
+
+use crate::utils::jwt::{Claims, LeaderUser};
+use crate::utils::audit;
+use crate::utils::balance_lifecycle::BalanceStatus;
+use crate::utils::balance_rules::{evaluate_balance, RuleViolation, ScheduledSubject};
+use crate::utils::excel_import::{
+    academic_year_from_sheet, normalize_name, read_template, select_sheet, to_fragment_data, TemplateSheet,
+};
+use crate::utils::fragment_data::{FragmentData, FragmentLayout};
+use crate::utils::fragment_review::FragmentStatus;
+use crate::utils::validation::sanitize_text;
+use crate::utils::notifications::{self, Notification};
+use crate::database::audit_logs::{EventType, AuditCategory, EntityType};
+use crate::database::{asignaturas, balances, balance_fragments};
+use crate::routes::balance::{
+    active_fragments, balance_layout, generate_balance_name, load_scheduled_subjects, resolve_calendar_id,
+};
+use crate::routes::revisions::save_fragment_with_revision;
+use crate::types::ApiResponseWithData;
+use crate::*;
+use chrono::NaiveDate;
+use rocket::form::Form;
+use rocket::fs::TempFile;
+use rocket::post;
+use sea_orm::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use tokio::io::AsyncReadExt;
+
+// ============================================================================
+// ESTRUCTURAS DE REQUEST/RESPONSE
+// ============================================================================
+
+/// Formulario de importación
+#[derive(FromForm)]
+pub struct BalanceImportForm<'r> {
+    pub file: TempFile<'r>,
+    pub sheet: Option<String>,              // Por defecto, '{año}.ICS' o la primera hoja con cuadrícula
+    #[field(default = false)]
+    pub commit: bool,                       // false = solo informe
+    // Solo al crear un balance nuevo
+    pub academic_year: Option<String>,      // Por defecto, el del nombre de la hoja
+    pub period: Option<String>,
+    pub academic_year_text: Option<String>,
+    pub deadline: Option<String>,
+}
+
+/// Asignatura de la plantilla emparejada con una asignatura existente
+#[derive(Debug, Serialize)]
+pub struct ImportedSubject {
+    pub row: usize,
+    pub name: String,
+    pub asignatura_id: i32,
+    pub asignatura_name: String,
+    pub activities: usize,
+    pub existing_fragment: bool,
+}
+
+/// Fila de la plantilla sin asignatura con ese nombre
+#[derive(Debug, Serialize)]
+pub struct UnmatchedSubject {
+    pub row: usize,
+    pub name: String,
+}
+
+/// Asignatura cuyo fragmento está entregado o aprobado: la hoja no lo sobrescribe
+#[derive(Debug, Serialize)]
+pub struct LockedSubject {
+    pub row: usize,
+    pub name: String,
+    pub asignatura_id: i32,
+    pub status: String,
+}
+
+/// Celda que no se importa
+#[derive(Debug, Serialize)]
+pub struct ImportCellIssue {
+    pub row: usize,
+    pub subject: String,
+    pub position: String,
+    pub code: String,
+}
+
+/// Informe de la importación (dry-run o aplicada)
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub sheet: String,
+    pub committed: bool,
+    pub balance_id: Option<i32>,
+    pub start_date: Option<String>,
+    pub weeks_in_file: usize,
+    pub weeks_in_balance: usize,
+    pub week_mismatch: bool,
+    pub subjects: Vec<ImportedSubject>,
+    pub unmatched_subjects: Vec<UnmatchedSubject>,
+    pub unknown_codes: Vec<ImportCellIssue>,
+    pub dropped_cells: Vec<ImportCellIssue>,
+    pub locked_subjects: Vec<LockedSubject>,
+    /// Violaciones de las reglas semanales en las que participa alguna asignatura importada
+    pub rule_violations: Vec<RuleViolation>,
+}
+
+type ImportResult = Result<Json<ApiResponseWithData<ImportReport>>, (Status, Json<ApiResponseWithData<ImportReport>>)>;
+
+// ============================================================================
+// FUNCIONES AUXILIARES
+// ============================================================================
+
+fn import_error(status: Status, message: impl Into<String>) -> (Status, Json<ApiResponseWithData<ImportReport>>) {
+    (status, Json(ApiResponseWithData::error(message.into())))
+}
+
+/// Rechazo que incluye el informe para que el cliente pueda mostrarlo
+fn import_rejection(status: Status, message: impl Into<String>, report: ImportReport) -> (Status, Json<ApiResponseWithData<ImportReport>>) {
+    (status, Json(ApiResponseWithData {
+        message: message.into(),
+        alert: "error".to_string(),
+        data: Some(report),
+    }))
+}
+
+async fn read_upload(file: &TempFile<'_>) -> Result<Vec<u8>, (Status, Json<ApiResponseWithData<ImportReport>>)> {
+    let mut bytes = Vec::new();
+    let mut reader = file
+        .open()
+        .await
+        .map_err(|e| import_error(Status::BadRequest, format!("No se pudo leer el archivo: {}", e)))?;
+    reader
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|e| import_error(Status::BadRequest, format!("No se pudo leer el archivo: {}", e)))?;
+    Ok(bytes)
+}
+
+async fn load_sheet(
+    form: &BalanceImportForm<'_>,
+    academic_year: Option<&str>,
+) -> Result<TemplateSheet, (Status, Json<ApiResponseWithData<ImportReport>>)> {
+    let bytes = read_upload(&form.file).await?;
+    read_template(bytes)
+        .and_then(|sheets| select_sheet(sheets, form.sheet.as_deref(), academic_year))
+        .map_err(|e| import_error(Status::UnprocessableEntity, e.to_string()))
+}
+
+/// Entregado o aprobado: pendiente de revisión o ya revisado por el Leader
+fn is_locked_for_import(status: &str) -> bool {
+    matches!(FragmentStatus::parse(status), Some(FragmentStatus::Submitted | FragmentStatus::Completed))
+}
+
+/// Empareja las filas de la hoja con asignaturas y convierte su cuadrícula.
+/// `existing` es el estado de cada fragmento activo del balance, por asignatura
+async fn plan_import(
+    db: &DatabaseConnection,
+    sheet: &TemplateSheet,
+    layout: &FragmentLayout,
+    academic_year: &str,
+    existing: &HashMap<i32, String>,
+) -> Result<(ImportReport, Vec<(asignaturas::Model, FragmentData)>), DbErr> {
+    // Si hay varias asignaturas con el mismo nombre, se prefiere la del año del balance
+    let mut by_name: HashMap<String, asignaturas::Model> = HashMap::new();
+    for asignatura in asignaturas::Entity::find().all(db).await? {
+        let key = normalize_name(&asignatura.name);
+        let replace = by_name.get(&key).is_none_or(|current| current.year != academic_year && asignatura.year == academic_year);
+        if replace {
+            by_name.insert(key, asignatura);
+        }
+    }
+
+    let mut report = ImportReport {
+        sheet: sheet.name.clone(),
+        committed: false,
+        balance_id: None,
+        start_date: sheet.start_date.map(|d| d.to_string()),
+        weeks_in_file: sheet.weeks,
+        weeks_in_balance: layout.weeks,
+        week_mismatch: sheet.weeks != layout.weeks,
+        subjects: Vec::new(),
+        unmatched_subjects: Vec::new(),
+        unknown_codes: Vec::new(),
+        dropped_cells: Vec::new(),
+        locked_subjects: Vec::new(),
+        rule_violations: Vec::new(),
+    };
+    let mut fragments = Vec::new();
+    let mut seen = HashSet::new();
+
+    for subject in &sheet.subjects {
+        let Some(asignatura) = by_name.get(&normalize_name(&subject.name)) else {
+            report.unmatched_subjects.push(UnmatchedSubject { row: subject.row, name: subject.name.clone() });
+            continue;
+        };
+        if !seen.insert(asignatura.id) {
+            continue;
+        }
+        if let Some(status) = existing.get(&asignatura.id).filter(|s| is_locked_for_import(s)) {
+            report.locked_subjects.push(LockedSubject {
+                row: subject.row,
+                name: subject.name.clone(),
+                asignatura_id: asignatura.id,
+                status: status.clone(),
+            });
+            continue;
+        }
+
+        let grid = to_fragment_data(subject, layout);
+        let issue = |i: crate::utils::excel_import::CellIssue| ImportCellIssue {
+            row: subject.row,
+            subject: subject.name.clone(),
+            position: i.position,
+            code: i.code,
+        };
+        report.unknown_codes.extend(grid.unknown_codes.into_iter().map(issue));
+        report.dropped_cells.extend(grid.dropped.into_iter().map(issue));
+        report.subjects.push(ImportedSubject {
+            row: subject.row,
+            name: subject.name.clone(),
+            asignatura_id: asignatura.id,
+            asignatura_name: asignatura.name.clone(),
+            activities: grid.data.activities().count(),
+            existing_fragment: existing.contains_key(&asignatura.id),
+        });
+        fragments.push((asignatura.clone(), grid.data));
+    }
+
+    Ok((report, fragments))
+}
+
+/// Violaciones de las reglas semanales en las que participa alguna asignatura
+/// importada, con sus cuadrículas en lugar de los datos guardados (`stored`)
+fn import_violations(
+    mut stored: Vec<ScheduledSubject>,
+    fragments: &[(asignaturas::Model, FragmentData)],
+) -> Vec<RuleViolation> {
+    stored.retain(|s| fragments.iter().all(|(a, _)| a.id != s.asignatura_id));
+    stored.extend(fragments.iter().map(|(asignatura, data)| ScheduledSubject {
+        asignatura_id: asignatura.id,
+        name: asignatura.name.clone(),
+        data: data.clone(),
+    }));
+    evaluate_balance(&stored)
+        .into_iter()
+        .filter(|v| fragments.iter().any(|(a, _)| v.involves(a.id)))
+        .collect()
+}
+
+/// Rechazo del commit cuando la hoja incumple las reglas semanales
+fn rules_rejection(report: ImportReport) -> (Status, Json<ApiResponseWithData<ImportReport>>) {
+    let message = format!(
+        "La distribución incumple las reglas de la facultad: {}",
+        report.rule_violations.iter().map(|v| v.message.as_str()).collect::<Vec<_>>().join("; ")
+    );
+    import_rejection(Status::UnprocessableEntity, message, report)
+}
+
+/// Guarda la cuadrícula importada en el fragmento (creándolo si no existe) con su revisión.
+/// Si la asignatura se había retirado del balance, su fragmento archivado se recupera.
+/// Devuelve el id del fragmento y si se ha añadido al balance (nuevo o recuperado)
+async fn save_imported_fragment(
+    txn: &DatabaseTransaction,
+    balance_id: i32,
+    asignatura: &asignaturas::Model,
+    data: FragmentData,
+    claims: &Claims,
+) -> Result<(i32, bool), DbErr> {
+    let existing = balance_fragments::Entity::find()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .filter(balance_fragments::Column::AsignaturaId.eq(asignatura.id))
+        .one(txn)
+        .await?;
+    let added = existing.as_ref().is_none_or(|f| f.archived_at.is_some());
+    let fragment = match existing {
+        Some(f) => f,
+        None => {
+            balance_fragments::ActiveModel {
+                balance_id: Set(balance_id),
+                asignatura_id: Set(asignatura.id),
+                subject_leader_id: Set((asignatura.leader_id > 0).then_some(asignatura.leader_id)),
+                status: Set("pending".to_string()),
+                data: Set(serde_json::json!({})),
+                ..Default::default()
+            }
+            .insert(txn)
+            .await?
+        }
+    };
+
+    // Los datos importados no están revisados: un fragmento entregado o aprobado
+    // (p. ej. uno archivado que se recupera) vuelve a estar en curso o pendiente
+    let has_activities = data.activities().next().is_some();
+    let reopen = has_activities || is_locked_for_import(&fragment.status);
+    let mut active_model: balance_fragments::ActiveModel = fragment.into();
+    active_model.data = Set(data.to_value());
+    active_model.archived_at = Set(None);
+    active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+    if reopen {
+        let status = if has_activities { FragmentStatus::InProgress } else { FragmentStatus::Pending };
+        active_model.status = Set(status.as_str().to_string());
+        active_model.completed_at = Set(None);
+        active_model.completion_override_reason = Set(None);
+    }
+
+    let (saved, _) = save_fragment_with_revision(txn, active_model, claims, None).await?;
+    Ok((saved.id, added))
+}
+
+// ============================================================================
+// RUTAS
+// ============================================================================
+
+/// Importar la plantilla como un balance nuevo (Solo Leader)
+/// POST /api/balances/import
+#[post("/balances/import", data = "<form>")]
+pub async fn import_new_balance(
+    form: Form<BalanceImportForm<'_>>,
+    db: &State<AppState>,
+    user: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> ImportResult {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let requested_year = form.academic_year.as_deref().map(sanitize_text).filter(|y| !y.is_empty());
+    let sheet = load_sheet(&form, requested_year.as_deref()).await?;
+    let academic_year = match requested_year.or_else(|| academic_year_from_sheet(&sheet.name).map(str::to_string)) {
+        Some(year) => year,
+        None => return Err(import_error(Status::BadRequest, "Indique el año académico (academic_year)")),
+    };
+
+    let layout = FragmentLayout::for_weeks(sheet.weeks as i32);
+    let (mut report, fragments) = plan_import(&db.db, &sheet, &layout, &academic_year, &HashMap::new())
+        .await
+        .map_err(|e| import_error(Status::InternalServerError, format!("Error al obtener asignaturas: {}", e)))?;
+    report.rule_violations = import_violations(Vec::new(), &fragments);
+
+    if !form.commit {
+        return Ok(Json(ApiResponseWithData::success("Informe de importación generado".to_string(), report)));
+    }
+    if !report.rule_violations.is_empty() {
+        return Err(rules_rejection(report));
+    }
+
+    // Crear el balance
+    let period = form.period.as_deref().map(sanitize_text).filter(|p| !p.is_empty());
+    let academic_year_text = form.academic_year_text.as_deref().map(sanitize_text).filter(|t| !t.is_empty());
+    let (Some(period), Some(academic_year_text)) = (period, academic_year_text) else {
+        return Err(import_rejection(Status::BadRequest, "Indique el período y el curso académico para crear el balance", report));
+    };
+    let Some(start_date) = sheet.start_date else {
+        return Err(import_rejection(Status::UnprocessableEntity, "La hoja no indica la fecha de inicio de la primera semana", report));
+    };
+    if fragments.is_empty() {
+        return Err(import_rejection(Status::UnprocessableEntity, "Ninguna asignatura de la hoja coincide con las asignaturas registradas", report));
+    }
+    let deadline = match form.deadline.as_deref().filter(|d| !d.is_empty()) {
+        Some(dl) => Some(NaiveDate::parse_from_str(dl, "%Y-%m-%d").map_err(|_| {
+            import_error(Status::BadRequest, "Formato de fecha límite inválido. Use YYYY-MM-DD")
+        })?),
+        None => None,
+    };
+
+    let name = generate_balance_name(&academic_year, &period, &academic_year_text);
+    let calendar_id = resolve_calendar_id(&db.db, None, &academic_year_text)
+        .await
+        .map_err(|e| import_error(Status::InternalServerError, e))?;
+
+    let txn = db.db.begin().await
+        .map_err(|e| import_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let inserted_balance = balances::ActiveModel {
+        user_id: Set(user_id),
+        name: Set(name.clone()),
+        academic_year: Set(academic_year),
+        period: Set(period),
+        academic_year_text: Set(academic_year_text),
+        start_date: Set(start_date),
+        weeks: Set(sheet.weeks as i32),
+        status: Set(BalanceStatus::Draft.as_str().to_string()),
+        deadline: Set(deadline),
+        allow_leader_edit: Set(false),
+        subjects: Set(serde_json::json!([])), // Deprecated, usamos fragments
+        non_academic_periods: Set(serde_json::json!([])),
+        calendar_id: Set(calendar_id),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| import_error(Status::InternalServerError, format!("Error al crear el balance: {}", e)))?;
+
+    let mut fragment_ids = Vec::new();
+    for (asignatura, data) in fragments {
+        let (fragment_id, _) = save_imported_fragment(&txn, inserted_balance.id, &asignatura, data, &user.0)
+            .await
+            .map_err(|e| import_error(Status::InternalServerError, format!("Error al importar '{}': {}", asignatura.name, e)))?;
+        fragment_ids.push(fragment_id);
+    }
+
+    txn.commit().await
+        .map_err(|e| import_error(Status::InternalServerError, format!("Error al crear el balance: {}", e)))?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Create,
+        AuditCategory::Functional,
+        format!("Leader '{}' importó el balance '{}' desde la hoja '{}' ({} asignaturas)",
+            user.0.user_name, name, sheet.name, report.subjects.len()),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Balance, inserted_balance.id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    let _ = notifications::notify_fragments(&db.db, user_id, &fragment_ids, Notification::BalanceCreated).await;
+
+    report.committed = true;
+    report.balance_id = Some(inserted_balance.id);
+    Ok(Json(ApiResponseWithData::success("Balance importado exitosamente".to_string(), report)))
+}
+
+/// Importar la plantilla sobre un balance existente (Solo Leader)
+/// POST /api/balances/<id>/import
+#[post("/balances/<balance_id>/import", data = "<form>")]
+pub async fn import_into_balance(
+    balance_id: i32,
+    form: Form<BalanceImportForm<'_>>,
+    db: &State<AppState>,
+    user: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> ImportResult {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(import_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(import_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    if balance.user_id != user_id {
+        let _ = audit::log_access_denied(
+            &db.db,
+            Some(user_id),
+            Some(&user.0.user_name),
+            &format!("importación en balance ID {} (no es el Leader del balance)", balance_id),
+            &ip_str,
+        ).await;
+        return Err(import_error(Status::Forbidden, "Solo el Leader del balance puede importar sobre él"));
+    }
+
+    let sheet = load_sheet(&form, Some(&balance.academic_year)).await?;
+
+    let existing: HashMap<i32, String> = active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .all(&db.db)
+        .await
+        .map_err(|e| import_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?
+        .into_iter()
+        .map(|f| (f.asignatura_id, f.status))
+        .collect();
+
+    let layout = balance_layout(&balance);
+    let (mut report, fragments) = plan_import(&db.db, &sheet, &layout, &balance.academic_year, &existing)
+        .await
+        .map_err(|e| import_error(Status::InternalServerError, format!("Error al obtener asignaturas: {}", e)))?;
+    report.balance_id = Some(balance_id);
+
+    // Las reglas se evalúan solo si la cuadrícula de la hoja encaja con la del balance
+    if !report.week_mismatch {
+        let stored = load_scheduled_subjects(&db.db, &balance, None)
+            .await
+            .map_err(|e| import_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+        report.rule_violations = import_violations(stored, &fragments);
+    }
+
+    if !form.commit {
+        return Ok(Json(ApiResponseWithData::success("Informe de importación generado".to_string(), report)));
+    }
+
+    let editable = BalanceStatus::parse(&balance.status).is_some_and(|s| s.allows_fragment_edits());
+    if !editable {
+        return Err(import_rejection(Status::Conflict, format!("No se puede importar sobre un balance en estado '{}'", balance.status), report));
+    }
+    if report.week_mismatch {
+        return Err(import_rejection(
+            Status::UnprocessableEntity,
+            format!("La hoja tiene {} semanas y el balance {}. Ajuste el número de semanas antes de importar", report.weeks_in_file, report.weeks_in_balance),
+            report,
+        ));
+    }
+    if fragments.is_empty() {
+        let message = if report.locked_subjects.is_empty() {
+            "Ninguna asignatura de la hoja coincide con las asignaturas registradas"
+        } else {
+            "Todas las asignaturas de la hoja tienen el fragmento entregado o aprobado"
+        };
+        return Err(import_rejection(Status::UnprocessableEntity, message, report));
+    }
+    if !report.rule_violations.is_empty() {
+        return Err(rules_rejection(report));
+    }
+
+    let txn = db.db.begin().await
+        .map_err(|e| import_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let mut added_fragments = Vec::new();
+    let mut edited_fragments = Vec::new();
+    for (asignatura, data) in fragments {
+        let (fragment_id, added) = save_imported_fragment(&txn, balance_id, &asignatura, data, &user.0)
+            .await
+            .map_err(|e| match e {
+                DbErr::RecordNotUpdated => import_error(Status::Conflict, format!("El fragmento de '{}' fue modificado por otro usuario", asignatura.name)),
+                e => import_error(Status::InternalServerError, format!("Error al importar '{}': {}", asignatura.name, e)),
+            })?;
+        if added {
+            added_fragments.push(fragment_id);
+        } else {
+            edited_fragments.push(fragment_id);
+        }
+    }
+
+    txn.commit().await
+        .map_err(|e| import_error(Status::InternalServerError, format!("Error al importar: {}", e)))?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Leader '{}' importó {} asignaturas desde la hoja '{}' en el balance '{}'",
+            user.0.user_name, report.subjects.len(), sheet.name, balance.name),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Balance, balance_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    // Las asignaturas que la hoja añade al balance se avisan como fragmentos asignados
+    // y las que ya estaban, como fragmentos modificados
+    let _ = notifications::notify_fragments(&db.db, user_id, &added_fragments, Notification::FragmentAssigned).await;
+    let editor = user.0.name.clone();
+    let _ = notifications::notify_fragments(&db.db, user_id, &edited_fragments, |fragment| Notification::FragmentEdited {
+        fragment,
+        editor: editor.clone(),
+    })
+    .await;
+
+    report.committed = true;
+    Ok(Json(ApiResponseWithData::success("Plantilla importada exitosamente".to_string(), report)))
+}
@@ -0,0 +1,475 @@
+//! Rutas del calendario académico institucional
+//!
+//! - Cualquier usuario autenticado puede consultar los calendarios
+//! - Leader y Admin crean, modifican y eliminan calendarios y sus períodos
+
+use crate::utils::jwt::{AuthenticatedUser, LeaderOrAdminUser};
+use crate::utils::audit;
+use crate::utils::academic_calendar::{CalendarPeriod, CalendarPeriodInput, CalendarPeriodKind};
+use crate::utils::validation::sanitize_text;
+use crate::database::audit_logs::{EventType, AuditCategory, EntityType};
+use crate::database::{academic_calendars, academic_calendar_periods, balances};
+use crate::types::{ApiResponse, ApiResponseWithData};
+use crate::*;
+use rocket::{delete, get, post, put};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+// ============================================================================
+// ESTRUCTURAS DE REQUEST/RESPONSE
+// ============================================================================
+
+/// Request para crear un calendario
+#[derive(Debug, Deserialize)]
+pub struct CreateCalendarRequest {
+    pub academic_year_text: String,     // '2025-2026'
+    pub name: String,
+    pub periods: Option<Vec<CalendarPeriodInput>>,
+}
+
+/// Request para actualizar un calendario
+/// Si se envía `periods`, sustituye a todos los períodos existentes
+#[derive(Debug, Deserialize)]
+pub struct UpdateCalendarRequest {
+    pub academic_year_text: Option<String>,
+    pub name: Option<String>,
+    pub periods: Option<Vec<CalendarPeriodInput>>,
+}
+
+/// Período del calendario para respuesta
+#[derive(Debug, Serialize)]
+pub struct CalendarPeriodResponse {
+    pub id: i32,
+    pub kind: String,
+    pub name: String,
+    pub start: String,
+    pub end: String,
+}
+
+/// Calendario con sus períodos
+#[derive(Debug, Serialize)]
+pub struct CalendarResponse {
+    pub id: i32,
+    pub academic_year_text: String,
+    pub name: String,
+    pub periods: Vec<CalendarPeriodResponse>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// Balances existentes del curso que pasan a usar el calendario (solo al crearlo)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balances_linked: Option<u64>,
+}
+
+// ============================================================================
+// FUNCIONES AUXILIARES
+// ============================================================================
+
+impl From<academic_calendar_periods::Model> for CalendarPeriodResponse {
+    fn from(period: academic_calendar_periods::Model) -> Self {
+        Self {
+            id: period.id,
+            kind: period.kind,
+            name: period.name,
+            start: period.start_date.to_string(),
+            end: period.end_date.to_string(),
+        }
+    }
+}
+
+async fn find_periods(db: &impl ConnectionTrait, calendar_id: i32) -> Result<Vec<academic_calendar_periods::Model>, DbErr> {
+    academic_calendar_periods::Entity::find()
+        .filter(academic_calendar_periods::Column::CalendarId.eq(calendar_id))
+        .order_by_asc(academic_calendar_periods::Column::StartDate)
+        .all(db)
+        .await
+}
+
+/// Períodos validados de un calendario, para el cálculo de fechas de los balances
+pub(crate) async fn load_calendar_periods(db: &DatabaseConnection, calendar_id: i32) -> Result<Vec<CalendarPeriod>, DbErr> {
+    Ok(find_periods(db, calendar_id)
+        .await?
+        .into_iter()
+        .filter_map(|p| {
+            Some(CalendarPeriod {
+                kind: CalendarPeriodKind::parse(&p.kind)?,
+                name: p.name,
+                start: p.start_date,
+                end: p.end_date,
+            })
+        })
+        .collect())
+}
+
+/// Calendario del curso académico indicado, si existe
+pub(crate) async fn find_calendar_for_year(db: &DatabaseConnection, academic_year_text: &str) -> Result<Option<i32>, DbErr> {
+    Ok(academic_calendars::Entity::find()
+        .filter(academic_calendars::Column::AcademicYearText.eq(academic_year_text))
+        .one(db)
+        .await?
+        .map(|c| c.id))
+}
+
+async fn build_calendar_response(db: &DatabaseConnection, calendar: academic_calendars::Model) -> Result<CalendarResponse, DbErr> {
+    let periods = find_periods(db, calendar.id).await?;
+    Ok(CalendarResponse {
+        id: calendar.id,
+        academic_year_text: calendar.academic_year_text,
+        name: calendar.name,
+        periods: periods.into_iter().map(CalendarPeriodResponse::from).collect(),
+        created_at: calendar.created_at.map(|dt| dt.to_string()),
+        updated_at: calendar.updated_at.map(|dt| dt.to_string()),
+        balances_linked: None,
+    })
+}
+
+fn validate_periods(periods: &[CalendarPeriodInput]) -> Result<Vec<CalendarPeriod>, String> {
+    periods.iter().map(CalendarPeriodInput::validate).collect()
+}
+
+async fn insert_periods(db: &impl ConnectionTrait, calendar_id: i32, periods: Vec<CalendarPeriod>) -> Result<(), DbErr> {
+    for period in periods {
+        academic_calendar_periods::ActiveModel {
+            calendar_id: Set(calendar_id),
+            kind: Set(period.kind.as_str().to_string()),
+            name: Set(sanitize_text(&period.name)),
+            start_date: Set(period.start),
+            end_date: Set(period.end),
+            created_at: Set(Some(chrono::Utc::now().naive_utc())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+    Ok(())
+}
+
+fn calendar_error(status: Status, message: impl Into<String>) -> (Status, Json<ApiResponseWithData<CalendarResponse>>) {
+    (status, Json(ApiResponseWithData::error(message.into())))
+}
+
+// ============================================================================
+// RUTAS
+// ============================================================================
+
+/// Listar los calendarios académicos con sus períodos
+#[get("/calendars")]
+pub async fn list_calendars(
+    db: &State<AppState>,
+    _user: AuthenticatedUser,
+) -> Json<ApiResponseWithData<Vec<CalendarResponse>>> {
+    let calendars = match academic_calendars::Entity::find()
+        .order_by_desc(academic_calendars::Column::AcademicYearText)
+        .all(&db.db)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return Json(ApiResponseWithData::error(format!("Error al obtener calendarios: {}", e))),
+    };
+
+    let mut responses = Vec::with_capacity(calendars.len());
+    for calendar in calendars {
+        match build_calendar_response(&db.db, calendar).await {
+            Ok(r) => responses.push(r),
+            Err(e) => return Json(ApiResponseWithData::error(format!("Error al obtener períodos: {}", e))),
+        }
+    }
+
+    Json(ApiResponseWithData::success("Calendarios obtenidos exitosamente".to_string(), responses))
+}
+
+/// Obtener un calendario académico
+#[get("/calendars/<calendar_id>")]
+pub async fn get_calendar(
+    calendar_id: i32,
+    db: &State<AppState>,
+    _user: AuthenticatedUser,
+) -> Result<Json<ApiResponseWithData<CalendarResponse>>, (Status, Json<ApiResponseWithData<CalendarResponse>>)> {
+    let calendar = match academic_calendars::Entity::find_by_id(calendar_id).one(&db.db).await {
+        Ok(Some(c)) => c,
+        Ok(None) => return Err(calendar_error(Status::NotFound, "Calendario no encontrado")),
+        Err(e) => return Err(calendar_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    let response = build_calendar_response(&db.db, calendar)
+        .await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    Ok(Json(ApiResponseWithData::success("Calendario obtenido exitosamente".to_string(), response)))
+}
+
+/// Crear el calendario de un curso académico (Leader o Admin)
+#[post("/calendars", format = "json", data = "<calendar_data>")]
+pub async fn create_calendar(
+    calendar_data: Json<CreateCalendarRequest>,
+    db: &State<AppState>,
+    user: LeaderOrAdminUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<CalendarResponse>>, (Status, Json<ApiResponseWithData<CalendarResponse>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let data = calendar_data.into_inner();
+
+    let academic_year_text = sanitize_text(data.academic_year_text.trim());
+    if academic_year_text.is_empty() {
+        return Err(calendar_error(Status::BadRequest, "El curso académico es obligatorio"));
+    }
+
+    let periods = validate_periods(&data.periods.unwrap_or_default())
+        .map_err(|e| calendar_error(Status::BadRequest, e))?;
+
+    match find_calendar_for_year(&db.db, &academic_year_text).await {
+        Ok(Some(_)) => return Err(calendar_error(Status::Conflict, format!(
+            "Ya existe un calendario para el curso {}", academic_year_text
+        ))),
+        Ok(None) => {}
+        Err(e) => return Err(calendar_error(Status::InternalServerError, format!("Error: {}", e))),
+    }
+
+    let txn = db.db.begin().await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let calendar = academic_calendars::ActiveModel {
+        academic_year_text: Set(academic_year_text.clone()),
+        name: Set(sanitize_text(&data.name)),
+        created_at: Set(Some(now)),
+        updated_at: Set(Some(now)),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| calendar_error(Status::InternalServerError, format!("Error al crear el calendario: {}", e)))?;
+
+    insert_periods(&txn, calendar.id, periods)
+        .await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error al crear los períodos: {}", e)))?;
+
+    // Los balances del curso creados antes que el calendario también lo comparten
+    let linked = balances::Entity::update_many()
+        .col_expr(balances::Column::CalendarId, Expr::value(calendar.id))
+        .col_expr(balances::Column::Version, Expr::col(balances::Column::Version).add(1))
+        .col_expr(balances::Column::UpdatedAt, Expr::value(now))
+        .filter(balances::Column::AcademicYearText.eq(academic_year_text.clone()))
+        .filter(balances::Column::CalendarId.is_null())
+        .exec(&txn)
+        .await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error al asignar el calendario a los balances: {}", e)))?
+        .rows_affected;
+
+    txn.commit().await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Create,
+        AuditCategory::Functional,
+        format!("Usuario '{}' creó el calendario académico {} (asignado a {} balances existentes)",
+            user.0.user_name, academic_year_text, linked),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Calendar, calendar.id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    let mut response = build_calendar_response(&db.db, calendar)
+        .await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error: {}", e)))?;
+    response.balances_linked = Some(linked);
+
+    Ok(Json(ApiResponseWithData::success(
+        format!("Calendario creado exitosamente (asignado a {} balances existentes)", linked),
+        response,
+    )))
+}
+
+/// Actualizar un calendario académico (Leader o Admin)
+#[put("/calendars/<calendar_id>", format = "json", data = "<calendar_data>")]
+pub async fn update_calendar(
+    calendar_id: i32,
+    calendar_data: Json<UpdateCalendarRequest>,
+    db: &State<AppState>,
+    user: LeaderOrAdminUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<CalendarResponse>>, (Status, Json<ApiResponseWithData<CalendarResponse>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let data = calendar_data.into_inner();
+
+    let calendar = match academic_calendars::Entity::find_by_id(calendar_id).one(&db.db).await {
+        Ok(Some(c)) => c,
+        Ok(None) => return Err(calendar_error(Status::NotFound, "Calendario no encontrado")),
+        Err(e) => return Err(calendar_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    let periods = match data.periods {
+        Some(ref periods) => Some(validate_periods(periods).map_err(|e| calendar_error(Status::BadRequest, e))?),
+        None => None,
+    };
+
+    let mut active_model: academic_calendars::ActiveModel = calendar.into();
+    if let Some(text) = data.academic_year_text {
+        let text = sanitize_text(text.trim());
+        if let Ok(Some(other)) = find_calendar_for_year(&db.db, &text).await
+            && other != calendar_id
+        {
+            return Err(calendar_error(Status::Conflict, format!("Ya existe un calendario para el curso {}", text)));
+        }
+        active_model.academic_year_text = Set(text);
+    }
+    if let Some(name) = data.name {
+        active_model.name = Set(sanitize_text(&name));
+    }
+    active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+
+    let txn = db.db.begin().await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let updated = active_model.update(&txn).await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error al actualizar: {}", e)))?;
+
+    if let Some(periods) = periods {
+        academic_calendar_periods::Entity::delete_many()
+            .filter(academic_calendar_periods::Column::CalendarId.eq(calendar_id))
+            .exec(&txn)
+            .await
+            .map_err(|e| calendar_error(Status::InternalServerError, format!("Error al actualizar los períodos: {}", e)))?;
+        insert_periods(&txn, calendar_id, periods)
+            .await
+            .map_err(|e| calendar_error(Status::InternalServerError, format!("Error al actualizar los períodos: {}", e)))?;
+    }
+
+    txn.commit().await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Usuario '{}' actualizó el calendario académico {}", user.0.user_name, updated.academic_year_text),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Calendar, calendar_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    let response = build_calendar_response(&db.db, updated)
+        .await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    Ok(Json(ApiResponseWithData::success("Calendario actualizado exitosamente".to_string(), response)))
+}
+
+/// Eliminar un calendario académico (Leader o Admin)
+/// Los balances que lo usaban conservan solo sus propios períodos
+#[delete("/calendars/<calendar_id>")]
+pub async fn delete_calendar(
+    calendar_id: i32,
+    db: &State<AppState>,
+    user: LeaderOrAdminUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponse>, (Status, Json<ApiResponse>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let calendar = match academic_calendars::Entity::find_by_id(calendar_id).one(&db.db).await {
+        Ok(Some(c)) => c,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Calendario no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
+
+    if let Err(e) = academic_calendars::Entity::delete_by_id(calendar_id).exec(&db.db).await {
+        return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error al eliminar: {}", e)))));
+    }
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Delete,
+        AuditCategory::Functional,
+        format!("Usuario '{}' eliminó el calendario académico {}", user.0.user_name, calendar.academic_year_text),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Calendar, calendar_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    Ok(Json(ApiResponse::success("Calendario eliminado exitosamente".to_string())))
+}
+
+/// Añadir un período a un calendario (Leader o Admin)
+#[post("/calendars/<calendar_id>/periods", format = "json", data = "<period>")]
+pub async fn add_calendar_period(
+    calendar_id: i32,
+    period: Json<CalendarPeriodInput>,
+    db: &State<AppState>,
+    user: LeaderOrAdminUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<CalendarResponse>>, (Status, Json<ApiResponseWithData<CalendarResponse>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let calendar = match academic_calendars::Entity::find_by_id(calendar_id).one(&db.db).await {
+        Ok(Some(c)) => c,
+        Ok(None) => return Err(calendar_error(Status::NotFound, "Calendario no encontrado")),
+        Err(e) => return Err(calendar_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    let period = period.validate().map_err(|e| calendar_error(Status::BadRequest, e))?;
+    let description = format!("Usuario '{}' añadió el período '{}' ({} - {}) al calendario académico {}",
+        user.0.user_name, period.name, period.start, period.end, calendar.academic_year_text);
+
+    insert_periods(&db.db, calendar_id, vec![period])
+        .await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error al crear el período: {}", e)))?;
+
+    let _ = audit::AuditLogBuilder::new(EventType::Update, AuditCategory::Functional, description)
+        .user(user_id, &user.0.user_name)
+        .entity(EntityType::Calendar, calendar_id)
+        .ip(&ip_str)
+        .save(&db.db)
+        .await;
+
+    let response = build_calendar_response(&db.db, calendar)
+        .await
+        .map_err(|e| calendar_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    Ok(Json(ApiResponseWithData::success("Período añadido exitosamente".to_string(), response)))
+}
+
+/// Eliminar un período de un calendario (Leader o Admin)
+#[delete("/calendars/<calendar_id>/periods/<period_id>")]
+pub async fn delete_calendar_period(
+    calendar_id: i32,
+    period_id: i32,
+    db: &State<AppState>,
+    user: LeaderOrAdminUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponse>, (Status, Json<ApiResponse>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let result = academic_calendar_periods::Entity::delete_many()
+        .filter(academic_calendar_periods::Column::Id.eq(period_id))
+        .filter(academic_calendar_periods::Column::CalendarId.eq(calendar_id))
+        .exec(&db.db)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error al eliminar: {}", e)))))?;
+
+    if result.rows_affected == 0 {
+        return Err((Status::NotFound, Json(ApiResponse::error("Período no encontrado".to_string()))));
+    }
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Usuario '{}' eliminó el período ID {} del calendario académico ID {}", user.0.user_name, period_id, calendar_id),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Calendar, calendar_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    Ok(Json(ApiResponse::success("Período eliminado exitosamente".to_string())))
+}
@@ -0,0 +1,357 @@
+//! Exportación iCalendar (.ics) de balances y fragmentos
+//!
+//! - Balance completo y fragmento individual, con los mismos permisos que para verlos
+//! - Suscripción por usuario: URL con token secreto revocable que lista los
+//!   fragmentos que lidera en los balances abiertos (sin cabecera Authorization,
+//!   para que las aplicaciones de calendario puedan consultarla)
+
+use crate::utils::jwt::AuthenticatedUser;
+use crate::utils::audit;
+use crate::utils::balance_lifecycle::BalanceStatus;
+use crate::utils::fragment_data::FragmentData;
+use crate::utils::ical::{fragment_events, render_calendar, FragmentEvents, IcalEvent};
+use crate::database::audit_logs::{EventType, AuditCategory, EntityType};
+use crate::database::{asignaturas, balances, balance_fragments, calendar_feed_tokens};
+use crate::routes::balance::{active_fragments, balance_layout, can_view_balance, load_balance_calendar};
+use crate::types::{ApiResponse, ApiResponseWithData};
+use crate::*;
+use rand::distributions::{Alphanumeric, DistString};
+use rocket::http::ContentType;
+use rocket::{delete, get, post};
+use sea_orm::*;
+use serde::Serialize;
+use std::net::SocketAddr;
+
+/// Longitud del token secreto de suscripción
+const FEED_TOKEN_LENGTH: usize = 40;
+
+/// Respuesta `text/calendar`
+pub struct IcsFile {
+    pub body: String,
+    pub filename: String,
+}
+
+impl<'r> rocket::response::Responder<'r, 'static> for IcsFile {
+    fn respond_to(self, _request: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        rocket::Response::build()
+            .header(ContentType::new("text", "calendar").with_params(("charset", "utf-8")))
+            .raw_header("Content-Disposition", format!("inline; filename=\"{}\"", self.filename))
+            .sized_body(self.body.len(), std::io::Cursor::new(self.body.into_bytes()))
+            .ok()
+    }
+}
+
+/// Estado de la suscripción del usuario
+#[derive(Debug, Serialize)]
+pub struct CalendarFeedInfo {
+    pub token: String,
+    pub url: String,        // Ruta relativa al servidor de la API
+    pub created_at: Option<String>,
+}
+
+impl From<calendar_feed_tokens::Model> for CalendarFeedInfo {
+    fn from(model: calendar_feed_tokens::Model) -> Self {
+        Self {
+            url: format!("/api/calendar-feed/{}.ics", model.token),
+            token: model.token,
+            created_at: model.created_at.map(|dt| dt.to_string()),
+        }
+    }
+}
+
+// ============================================================================
+// FUNCIONES AUXILIARES
+// ============================================================================
+
+fn ics_error(status: Status, message: impl Into<String>) -> (Status, Json<ApiResponse>) {
+    (status, Json(ApiResponse::error(message.into())))
+}
+
+/// Eventos de los fragmentos indicados de un balance
+async fn balance_events(
+    db: &DatabaseConnection,
+    balance: &balances::Model,
+    fragments: Vec<(balance_fragments::Model, Option<asignaturas::Model>)>,
+) -> Result<Vec<IcalEvent>, DbErr> {
+    let calendar = load_balance_calendar(db, balance).await?;
+    let layout = balance_layout(balance);
+
+    Ok(fragments
+        .into_iter()
+        .flat_map(|(fragment, asignatura)| {
+            let data = FragmentData::from_stored(&fragment.data, &layout);
+            let subject_name = asignatura.map(|a| a.name).unwrap_or_else(|| format!("Asignatura {}", fragment.asignatura_id));
+            fragment_events(
+                &FragmentEvents {
+                    balance_id: balance.id,
+                    balance_name: &balance.name,
+                    fragment_id: fragment.id,
+                    subject_name: &subject_name,
+                    slots: data.slots().cloned().collect(),
+                },
+                &calendar,
+            )
+        })
+        .collect())
+}
+
+fn ics_filename(name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}.ics", safe)
+}
+
+// ============================================================================
+// RUTAS
+// ============================================================================
+
+/// Balance completo en formato iCalendar
+#[get("/balances/<balance_id>/ics")]
+pub async fn export_balance_ics(
+    balance_id: i32,
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<IcsFile, (Status, Json<ApiResponse>)> {
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(ics_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(ics_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    if !can_view_balance(&db.db, &user.0, balance_id).await {
+        return Err(ics_error(Status::Forbidden, "No tienes permiso para ver este balance"));
+    }
+
+    let fragments = active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .find_also_related(asignaturas::Entity)
+        .all(&db.db)
+        .await
+        .map_err(|e| ics_error(Status::InternalServerError, format!("Error al obtener fragmentos: {}", e)))?;
+
+    let events = balance_events(&db.db, &balance, fragments)
+        .await
+        .map_err(|e| ics_error(Status::InternalServerError, format!("Error al obtener el calendario: {}", e)))?;
+
+    Ok(IcsFile {
+        body: render_calendar(&balance.name, &events, chrono::Utc::now().naive_utc()),
+        filename: ics_filename(&balance.name),
+    })
+}
+
+/// Fragmento de una asignatura en formato iCalendar
+#[get("/balances/<balance_id>/fragments/<asignatura_id>/ics")]
+pub async fn export_fragment_ics(
+    balance_id: i32,
+    asignatura_id: i32,
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<IcsFile, (Status, Json<ApiResponse>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(&db.db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(ics_error(Status::NotFound, "Balance no encontrado")),
+        Err(e) => return Err(ics_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    let fragment = match active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
+        .find_also_related(asignaturas::Entity)
+        .one(&db.db)
+        .await
+    {
+        Ok(Some(f)) => f,
+        Ok(None) => return Err(ics_error(Status::NotFound, "Fragmento no encontrado")),
+        Err(e) => return Err(ics_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    if user.0.role != "leader" && user.0.role != "admin" && fragment.0.subject_leader_id != Some(user_id) {
+        return Err(ics_error(Status::Forbidden, "No tienes permiso para ver este fragmento"));
+    }
+
+    let name = match fragment.1 {
+        Some(ref asig) => format!("{} - {}", balance.name, asig.name),
+        None => balance.name.clone(),
+    };
+
+    let events = balance_events(&db.db, &balance, vec![fragment])
+        .await
+        .map_err(|e| ics_error(Status::InternalServerError, format!("Error al obtener el calendario: {}", e)))?;
+
+    Ok(IcsFile {
+        body: render_calendar(&name, &events, chrono::Utc::now().naive_utc()),
+        filename: ics_filename(&name),
+    })
+}
+
+/// Estado de la suscripción de calendario del usuario actual
+#[get("/calendar-feed")]
+pub async fn get_calendar_feed(
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Json<ApiResponseWithData<Option<CalendarFeedInfo>>> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+
+    match calendar_feed_tokens::Entity::find()
+        .filter(calendar_feed_tokens::Column::UserId.eq(user_id))
+        .one(&db.db)
+        .await
+    {
+        Ok(token) => Json(ApiResponseWithData::success(
+            "Suscripción obtenida exitosamente".to_string(),
+            token.map(CalendarFeedInfo::from),
+        )),
+        Err(e) => Json(ApiResponseWithData::error(format!("Error: {}", e))),
+    }
+}
+
+/// Crear o regenerar el token de suscripción (la URL anterior deja de funcionar)
+#[post("/calendar-feed")]
+pub async fn regenerate_calendar_feed(
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+    remote_addr: Option<SocketAddr>,
+) -> Json<ApiResponseWithData<CalendarFeedInfo>> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let token = Alphanumeric.sample_string(&mut rand::thread_rng(), FEED_TOKEN_LENGTH);
+
+    let existing = match calendar_feed_tokens::Entity::find()
+        .filter(calendar_feed_tokens::Column::UserId.eq(user_id))
+        .one(&db.db)
+        .await
+    {
+        Ok(existing) => existing,
+        Err(e) => return Json(ApiResponseWithData::error(format!("Error: {}", e))),
+    };
+
+    let result = match existing {
+        Some(model) => {
+            let mut active_model: calendar_feed_tokens::ActiveModel = model.into();
+            active_model.token = Set(token);
+            active_model.created_at = Set(Some(chrono::Utc::now().naive_utc()));
+            active_model.update(&db.db).await
+        }
+        None => {
+            calendar_feed_tokens::ActiveModel {
+                user_id: Set(user_id),
+                token: Set(token),
+                created_at: Set(Some(chrono::Utc::now().naive_utc())),
+                ..Default::default()
+            }
+            .insert(&db.db)
+            .await
+        }
+    };
+
+    match result {
+        Ok(model) => {
+            let _ = audit::AuditLogBuilder::new(
+                EventType::Update,
+                AuditCategory::Security,
+                format!("Usuario '{}' generó una nueva URL de suscripción de calendario", user.0.user_name),
+            )
+            .user(user_id, &user.0.user_name)
+            .entity(EntityType::User, user_id)
+            .ip(&ip_str)
+            .save(&db.db)
+            .await;
+
+            Json(ApiResponseWithData::success(
+                "URL de suscripción generada exitosamente".to_string(),
+                CalendarFeedInfo::from(model),
+            ))
+        }
+        Err(e) => Json(ApiResponseWithData::error(format!("Error al generar la suscripción: {}", e))),
+    }
+}
+
+/// Revocar la URL de suscripción
+#[delete("/calendar-feed")]
+pub async fn revoke_calendar_feed(
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+    remote_addr: Option<SocketAddr>,
+) -> Json<ApiResponse> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    match calendar_feed_tokens::Entity::delete_many()
+        .filter(calendar_feed_tokens::Column::UserId.eq(user_id))
+        .exec(&db.db)
+        .await
+    {
+        Ok(_) => {
+            let _ = audit::AuditLogBuilder::new(
+                EventType::Delete,
+                AuditCategory::Security,
+                format!("Usuario '{}' revocó su URL de suscripción de calendario", user.0.user_name),
+            )
+            .user(user_id, &user.0.user_name)
+            .entity(EntityType::User, user_id)
+            .ip(&ip_str)
+            .save(&db.db)
+            .await;
+
+            Json(ApiResponse::success("URL de suscripción revocada".to_string()))
+        }
+        Err(e) => Json(ApiResponse::error(format!("Error al revocar la suscripción: {}", e))),
+    }
+}
+
+/// Suscripción iCalendar: fragmentos que lidera el usuario en los balances abiertos
+/// GET /api/calendar-feed/<token>.ics (sin autenticación, el token es el secreto)
+#[get("/calendar-feed/<file>")]
+pub async fn calendar_feed(
+    file: &str,
+    db: &State<AppState>,
+) -> Result<IcsFile, (Status, Json<ApiResponse>)> {
+    let token = file.strip_suffix(".ics").unwrap_or(file);
+
+    let feed = match calendar_feed_tokens::Entity::find()
+        .filter(calendar_feed_tokens::Column::Token.eq(token))
+        .one(&db.db)
+        .await
+    {
+        Ok(Some(f)) => f,
+        Ok(None) => return Err(ics_error(Status::NotFound, "Suscripción no encontrada")),
+        Err(e) => return Err(ics_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    let open_balances = balances::Entity::find()
+        .filter(balances::Column::Status.eq(BalanceStatus::Open.as_str()))
+        .order_by_asc(balances::Column::StartDate)
+        .all(&db.db)
+        .await
+        .map_err(|e| ics_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let mut events = Vec::new();
+    for balance in open_balances {
+        let fragments = active_fragments()
+            .filter(balance_fragments::Column::BalanceId.eq(balance.id))
+            .filter(balance_fragments::Column::SubjectLeaderId.eq(feed.user_id))
+            .find_also_related(asignaturas::Entity)
+            .all(&db.db)
+            .await
+            .map_err(|e| ics_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+        if fragments.is_empty() {
+            continue;
+        }
+
+        events.extend(
+            balance_events(&db.db, &balance, fragments)
+                .await
+                .map_err(|e| ics_error(Status::InternalServerError, format!("Error: {}", e)))?,
+        );
+    }
+
+    Ok(IcsFile {
+        body: render_calendar("Balance de Carga", &events, chrono::Utc::now().naive_utc()),
+        filename: "balance-de-carga.ics".to_string(),
+    })
+}
@@ -1,13 +1,23 @@
 use crate::utils::jwt::{AdminUser, AuthenticatedUser, LeaderUser, LeaderOrSubjectLeaderUser};
-use crate::utils::validation::{validate_new_user, validate_profile, validate_subject, is_valid_password};
+use crate::utils::validation::{validate_new_user, validate_profile, validate_subject, is_valid_password, get_password_policy};
 use crate::utils::audit;
+use crate::utils::activity_types::{activity_types, ActivityPlan};
+use crate::utils::table_import::{read_table, RowError};
+use crate::utils::user_provisioning::{credentials_csv, parse_users};
+use crate::utils::notifications::{self, Notification};
+use crate::routes::asignatura_catalog::CsvFile;
 use crate::database::audit_logs::{AuditCategory, EntityType, EventType};
 use crate::*;
 use crate::types::{ApiResponse, ApiResponseWithData};
 use crate::{usuarios, asignaturas};
 use rocket::{post, get, put, delete};
-use serde::Deserialize;
+use rocket::form::Form;
+use rocket::fs::TempFile;
+use sea_orm::{TransactionTrait, ActiveModelTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::net::SocketAddr;
+use tokio::io::AsyncReadExt;
 
 #[derive(Deserialize)]
 pub struct NewUser {
@@ -51,6 +61,91 @@ pub async fn create_user(
     }
 }
 
+#[derive(FromForm)]
+pub struct UserImportForm<'r> {
+    pub file: TempFile<'r>,
+}
+
+/// POST /users/import - Bulk create users from a CSV (Admin only)
+/// Columnas: username, name, email, role. Todos se crean en una transacción con
+/// contraseña temporal y `must_change_password`; devuelve la hoja de credenciales.
+/// Si alguna fila es inválida no se crea ningún usuario y se devuelven los errores por fila.
+#[post("/users/import", data = "<form>")]
+pub async fn import_users(
+    form: Form<UserImportForm<'_>>,
+    db: &State<AppState>,
+    admin: AdminUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<CsvFile, (Status, Json<ApiResponseWithData<Vec<RowError>>>)> {
+    let fail = |status: Status, message: String| (status, Json(ApiResponseWithData::error(message)));
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let admin_id = admin.0.sub.parse::<i32>().unwrap_or(0);
+
+    let mut bytes = Vec::new();
+    let mut reader = form.file.open().await
+        .map_err(|e| fail(Status::BadRequest, format!("No se pudo leer el archivo: {}", e)))?;
+    reader.read_to_end(&mut bytes).await
+        .map_err(|e| fail(Status::BadRequest, format!("No se pudo leer el archivo: {}", e)))?;
+    let table = read_table(bytes).map_err(|e| fail(Status::UnprocessableEntity, e))?;
+
+    let existing = usuarios::Entity::find().all(&db.db).await
+        .map_err(|e| fail(Status::InternalServerError, format!("Error al obtener los usuarios: {}", e)))?;
+    let taken_user_names: HashSet<String> = existing.iter().map(|u| u.user_name.clone()).collect();
+    let taken_emails: HashSet<String> = existing.iter().map(|u| u.email.to_lowercase()).collect();
+
+    let (users, errors) = parse_users(&table, &get_password_policy(), &taken_user_names, &taken_emails)
+        .map_err(|e| fail(Status::UnprocessableEntity, e))?;
+    if !errors.is_empty() {
+        return Err((Status::UnprocessableEntity, Json(ApiResponseWithData {
+            message: format!("El archivo tiene {} filas con errores; no se creó ningún usuario", errors.len()),
+            alert: "error".to_string(),
+            data: Some(errors),
+        })));
+    }
+    if users.is_empty() {
+        return Err(fail(Status::UnprocessableEntity, "El archivo no contiene usuarios".to_string()));
+    }
+
+    // bcrypt es costoso: se calculan todos los hashes fuera del runtime async
+    let passwords: Vec<String> = users.iter().map(|u| u.password.clone()).collect();
+    let hashes = tokio::task::spawn_blocking(move || {
+        passwords.iter().map(|p| bcrypt::hash(p, bcrypt::DEFAULT_COST)).collect::<Result<Vec<_>, _>>()
+    })
+    .await
+    .map_err(|e| fail(Status::InternalServerError, format!("Error al generar contraseñas: {}", e)))?
+    .map_err(|e| fail(Status::InternalServerError, format!("Error al generar contraseñas: {}", e)))?;
+
+    let txn = db.db.begin().await
+        .map_err(|e| fail(Status::InternalServerError, format!("Error: {}", e)))?;
+    let mut created = Vec::with_capacity(users.len());
+    for (user, hash) in users.iter().zip(hashes) {
+        let inserted = usuarios::ActiveModel {
+            user_name: Set(user.user_name.clone()),
+            name: Set(user.name.clone()),
+            email: Set(user.email.clone()),
+            token: Set(hash),
+            role: Set(Some(user.role.clone())),
+            must_change_password: Set(true),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|e| fail(Status::InternalServerError, format!("Error en la fila {}: {}", user.row, e)))?;
+        created.push(inserted);
+    }
+    txn.commit().await
+        .map_err(|e| fail(Status::InternalServerError, format!("Error al crear los usuarios: {}", e)))?;
+
+    // Registrar en auditoría (una entrada por usuario)
+    for user in &created {
+        let _ = audit::log_user_created(&db.db, admin_id, &admin.0.user_name, user.id, &user.user_name, &ip_str).await;
+    }
+
+    let data = credentials_csv(&users)
+        .map_err(|e| fail(Status::InternalServerError, format!("Error generando la hoja de credenciales: {}", e)))?;
+    Ok(CsvFile { data, filename: format!("credenciales_{}.csv", chrono::Local::now().format("%Y%m%d_%H%M%S")) })
+}
+
 /// DELETE /users/<id> - Delete a user (Admin only)
 #[delete("/users/<user_id>")]
 pub async fn delete_user(
@@ -257,14 +352,37 @@ pub async fn create_asignatura(
 pub async fn list_asignaturas(
     db: &State<AppState>,
     user: LeaderOrSubjectLeaderUser,
-) -> Json<ApiResponseWithData<Vec<asignaturas::Model>>> {
+) -> Json<ApiResponseWithData<Vec<AsignaturaResponse>>> {
     let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
     let role = &user.0.role;
 
-    match utils::db::list_asignaturas(&db.db, user_id, role).await {
-        Ok(asignaturas) => Json(ApiResponseWithData::success("Asignaturas obtenidas exitosamente".to_string(), asignaturas)),
-        Err(e) => Json(ApiResponseWithData::error(format!("Error al obtener las asignaturas: {}", e))),
-    }
+    let asignaturas = match utils::db::list_asignaturas(&db.db, user_id, role).await {
+        Ok(asignaturas) => asignaturas,
+        Err(e) => return Json(ApiResponseWithData::error(format!("Error al obtener las asignaturas: {}", e))),
+    };
+
+    let ids: Vec<i32> = asignaturas.iter().map(|a| a.id).collect();
+    let mut plans = match utils::db::load_activity_plans(&db.db, &ids).await {
+        Ok(plans) => plans,
+        Err(e) => return Json(ApiResponseWithData::error(format!("Error al obtener los planes de actividades: {}", e))),
+    };
+
+    let response = asignaturas
+        .into_iter()
+        .map(|asignatura| AsignaturaResponse {
+            plan: plans.remove(&asignatura.id).unwrap_or_default(),
+            asignatura,
+        })
+        .collect();
+    Json(ApiResponseWithData::success("Asignaturas obtenidas exitosamente".to_string(), response))
+}
+
+/// Asignatura con su plan de actividades por código
+#[derive(Serialize)]
+pub struct AsignaturaResponse {
+    #[serde(flatten)]
+    pub asignatura: asignaturas::Model,
+    pub plan: ActivityPlan,
 }
 
 #[derive(Deserialize)]
@@ -272,16 +390,9 @@ pub struct UpdateAsignaturaRequest {
     pub name: String,
     pub year: String,
     pub semester: String,
-    pub c: Option<i32>,
-    pub cp: Option<i32>,
-    pub s: Option<i32>,
-    pub pl: Option<i32>,
-    pub te: Option<i32>,
-    pub t: Option<i32>,
-    pub pp: Option<i32>,
-    pub ec: Option<i32>,
-    pub tc: Option<i32>,
-    pub ef: Option<i32>,
+    /// Cantidades planificadas por código de actividad (p. ej. {"C": 12, "CP": 8}).
+    /// Si se omite, el plan guardado no cambia
+    pub plan: Option<ActivityPlan>,
     pub hours: i32,
     pub weeks: Option<i32>,
 }
@@ -300,6 +411,16 @@ pub async fn update_asignatura(
     let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
     let asignatura_name = asignatura_data.name.clone();
 
+    if let Some(plan) = &asignatura_data.plan {
+        let unknown = plan.unknown_codes(&activity_types());
+        if !unknown.is_empty() {
+            return Json(ApiResponse::error(format!("Tipos de actividad desconocidos: {}", unknown.join(", "))));
+        }
+        if let Some((code, _)) = plan.entries().find(|(_, planned)| *planned < 0) {
+            return Json(ApiResponse::error(format!("La cantidad planificada de '{}' no puede ser negativa", code)));
+        }
+    }
+
     match utils::db::update_asignatura(&db.db, asignatura_id, &asignatura_data.into_inner()).await {
         Ok(_) => {
             // Registrar en auditoría
@@ -319,6 +440,88 @@ pub async fn update_asignatura(
     }
 }
 
+#[derive(Deserialize)]
+pub struct ReassignLeaderRequest {
+    pub subject_leader_id: i32,
+}
+
+/// Resultado de cambiar el jefe de una asignatura
+#[derive(Serialize)]
+pub struct LeaderReassignment {
+    pub asignatura_id: i32,
+    pub subject_leader_id: i32,
+    /// Fragmentos de balances no archivados que pasaron al nuevo jefe
+    pub fragments_reassigned: u64,
+}
+
+/// PUT /asignaturas/<id>/leader - Change the subject leader (Leader only)
+/// Se propaga a todos los fragmentos de la asignatura en balances no archivados
+#[put("/asignaturas/<asignatura_id>/leader", format = "json", data = "<request>")]
+pub async fn reassign_asignatura_leader(
+    asignatura_id: i32,
+    request: Json<ReassignLeaderRequest>,
+    db: &State<AppState>,
+    leader: LeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> Json<ApiResponseWithData<LeaderReassignment>> {
+    let leader_id = leader.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let subject_leader_id = request.subject_leader_id;
+
+    let asignatura = match asignaturas::Entity::find_by_id(asignatura_id).one(&db.db).await {
+        Ok(Some(a)) => a,
+        Ok(None) => return Json(ApiResponseWithData::error("Asignatura no encontrada".to_string())),
+        Err(e) => return Json(ApiResponseWithData::error(format!("Error: {}", e))),
+    };
+
+    let subject_leader = match utils::db::find_subject_leader(&db.db, subject_leader_id).await {
+        Ok(Some(u)) => u,
+        Ok(None) => return Json(ApiResponseWithData::error("El usuario indicado no es un jefe de asignatura".to_string())),
+        Err(e) => return Json(ApiResponseWithData::error(format!("Error: {}", e))),
+    };
+
+    // Cada jefe de asignatura lleva una sola asignatura (leader_id es UNIQUE)
+    match asignaturas::Entity::find()
+        .filter(asignaturas::Column::LeaderId.eq(subject_leader_id))
+        .filter(asignaturas::Column::Id.ne(asignatura_id))
+        .one(&db.db)
+        .await
+    {
+        Ok(Some(other)) => return Json(ApiResponseWithData::error(format!(
+            "'{}' ya es jefe de la asignatura '{}'", subject_leader.user_name, other.name
+        ))),
+        Ok(None) => {}
+        Err(e) => return Json(ApiResponseWithData::error(format!("Error: {}", e))),
+    }
+
+    let asignatura_name = asignatura.name.clone();
+    let previous_leader_id = asignatura.leader_id;
+    match utils::db::reassign_asignatura_leader(&db.db, asignatura, subject_leader_id).await {
+        Ok(fragment_ids) => {
+            let fragments_reassigned = fragment_ids.len() as u64;
+            let _ = audit::AuditLogBuilder::new(
+                EventType::Update,
+                AuditCategory::Functional,
+                format!(
+                    "Leader '{}' cambió el jefe de la asignatura '{}' (ID {} → '{}'); {} fragmento(s) reasignado(s)",
+                    leader.0.user_name, asignatura_name, previous_leader_id, subject_leader.user_name, fragments_reassigned
+                ),
+            )
+            .user(leader_id, &leader.0.user_name)
+            .entity(EntityType::Subject, asignatura_id)
+            .ip(&ip_str)
+            .save(&db.db)
+            .await;
+            let _ = notifications::notify_fragments(&db.db, leader_id, &fragment_ids, Notification::FragmentAssigned).await;
+            Json(ApiResponseWithData::success(
+                "Jefe de asignatura actualizado exitosamente".to_string(),
+                LeaderReassignment { asignatura_id, subject_leader_id, fragments_reassigned },
+            ))
+        },
+        Err(e) => Json(ApiResponseWithData::error(format!("Error al cambiar el jefe de asignatura: {}", e))),
+    }
+}
+
 /// DELETE /asignaturas/<id> - Delete a subject (Leader only)
 #[delete("/asignaturas/<asignatura_id>")]
 pub async fn delete_asignatura(
@@ -1,5 +1,13 @@
+pub mod activity_types;
+pub mod asignatura_catalog;
 pub mod audit;
 pub mod balance;
+pub mod balance_import;
+pub mod calendar;
+pub mod ical;
 pub mod login;
 pub mod manager;
+pub mod notifications;
+pub mod reviews;
+pub mod revisions;
 pub mod settings;
\ No newline at end of file
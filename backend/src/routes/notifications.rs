@@ -0,0 +1,321 @@
+//! Rutas de notificaciones
+//!
+//! - Bandeja de la aplicación: cada usuario ve y marca como leídos sus avisos
+//! - Cola de correo: solo accesible por administradores
+
+use crate::utils::jwt::{AdminUser, AuthenticatedUser};
+use crate::utils::audit;
+use crate::utils::mailer::{MailTransport, OutgoingEmail, SmtpMailer};
+use crate::utils::notifications::{get_notification_settings, OutboxStatus};
+use crate::database::audit_logs::{EventType, AuditCategory};
+use crate::database::{notification_outbox, notifications};
+use crate::types::{ApiResponse, ApiResponseWithData};
+use crate::*;
+use rocket::{get, post, put};
+use sea_orm::sea_query::Expr;
+use sea_orm::{PaginatorTrait, QueryOrder, QuerySelect, Select};
+use serde::Serialize;
+
+/// Avisos devueltos por defecto (y como máximo) en la bandeja
+const INBOX_LIMIT: u64 = 50;
+const INBOX_MAX_LIMIT: u64 = 200;
+
+/// Aviso de la bandeja (serialización para frontend)
+#[derive(Debug, Serialize)]
+pub struct NotificationResponse {
+    pub id: i32,
+    pub event: String,
+    pub title: String,
+    pub message: String,
+    pub balance_id: Option<i32>,
+    pub asignatura_id: Option<i32>,
+    pub read_at: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl From<notifications::Model> for NotificationResponse {
+    fn from(n: notifications::Model) -> Self {
+        Self {
+            id: n.id,
+            event: n.event,
+            title: n.title,
+            message: n.message,
+            balance_id: n.balance_id,
+            asignatura_id: n.asignatura_id,
+            read_at: n.read_at.map(|dt| dt.to_string()),
+            created_at: n.created_at.map(|dt| dt.to_string()),
+        }
+    }
+}
+
+/// Número de avisos sin leer
+#[derive(Debug, Serialize)]
+pub struct UnreadCountResponse {
+    pub unread: u64,
+}
+
+/// Avisos sin leer del usuario
+fn unread_notifications(user_id: i32) -> Select<notifications::Entity> {
+    notifications::Entity::find()
+        .filter(notifications::Column::UserId.eq(user_id))
+        .filter(notifications::Column::ReadAt.is_null())
+}
+
+// ============================================================================
+// BANDEJA
+// ============================================================================
+
+/// Listar los avisos del usuario (más reciente primero)
+/// GET /api/notifications?unread=true&limit=50
+#[get("/notifications?<unread>&<limit>")]
+pub async fn list_notifications(
+    unread: Option<bool>,
+    limit: Option<u64>,
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Json<ApiResponseWithData<Vec<NotificationResponse>>> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let query = if unread.unwrap_or(false) {
+        unread_notifications(user_id)
+    } else {
+        notifications::Entity::find().filter(notifications::Column::UserId.eq(user_id))
+    };
+
+    match query
+        .order_by_desc(notifications::Column::Id)
+        .limit(limit.unwrap_or(INBOX_LIMIT).clamp(1, INBOX_MAX_LIMIT))
+        .all(&db.db)
+        .await
+    {
+        Ok(items) => Json(ApiResponseWithData::success(
+            "Notificaciones obtenidas".to_string(),
+            items.into_iter().map(NotificationResponse::from).collect(),
+        )),
+        Err(e) => Json(ApiResponseWithData::error(format!("Error al obtener las notificaciones: {}", e))),
+    }
+}
+
+/// Contar los avisos sin leer del usuario
+/// GET /api/notifications/unread-count
+#[get("/notifications/unread-count")]
+pub async fn count_unread_notifications(
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Json<ApiResponseWithData<UnreadCountResponse>> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    match unread_notifications(user_id).count(&db.db).await {
+        Ok(unread) => Json(ApiResponseWithData::success(
+            "Notificaciones sin leer obtenidas".to_string(),
+            UnreadCountResponse { unread },
+        )),
+        Err(e) => Json(ApiResponseWithData::error(format!("Error al contar las notificaciones: {}", e))),
+    }
+}
+
+/// Marcar un aviso como leído
+/// PUT /api/notifications/<id>/read
+#[put("/notifications/<notification_id>/read")]
+pub async fn mark_notification_read(
+    notification_id: i32,
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Json<ApiResponse> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let notification = match notifications::Entity::find_by_id(notification_id)
+        .filter(notifications::Column::UserId.eq(user_id))
+        .one(&db.db)
+        .await
+    {
+        Ok(Some(n)) => n,
+        Ok(None) => return Json(ApiResponse::error("Notificación no encontrada".to_string())),
+        Err(e) => return Json(ApiResponse::error(format!("Error: {}", e))),
+    };
+
+    if notification.read_at.is_some() {
+        return Json(ApiResponse::success("Notificación marcada como leída".to_string()));
+    }
+
+    let mut active_model: notifications::ActiveModel = notification.into();
+    active_model.read_at = Set(Some(chrono::Utc::now().naive_utc()));
+    match active_model.update(&db.db).await {
+        Ok(_) => Json(ApiResponse::success("Notificación marcada como leída".to_string())),
+        Err(e) => Json(ApiResponse::error(format!("Error al actualizar: {}", e))),
+    }
+}
+
+/// Marcar como leídos todos los avisos del usuario
+/// PUT /api/notifications/read-all
+#[put("/notifications/read-all")]
+pub async fn mark_all_notifications_read(
+    db: &State<AppState>,
+    user: AuthenticatedUser,
+) -> Json<ApiResponse> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    match notifications::Entity::update_many()
+        .col_expr(notifications::Column::ReadAt, Expr::value(chrono::Utc::now().naive_utc()))
+        .filter(notifications::Column::UserId.eq(user_id))
+        .filter(notifications::Column::ReadAt.is_null())
+        .exec(&db.db)
+        .await
+    {
+        Ok(result) => Json(ApiResponse::success(format!("{} notificación(es) marcada(s) como leída(s)", result.rows_affected))),
+        Err(e) => Json(ApiResponse::error(format!("Error al actualizar: {}", e))),
+    }
+}
+
+// ============================================================================
+// COLA DE CORREO (ADMIN)
+// ============================================================================
+
+/// Aviso de la cola (serialización para frontend)
+#[derive(Debug, Serialize)]
+pub struct OutboxEntryResponse {
+    pub id: i32,
+    pub event: String,
+    pub recipient_id: Option<i32>,
+    pub recipient_email: String,
+    pub recipient_name: String,
+    pub subject: String,
+    pub body: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: String,
+    pub last_error: Option<String>,
+    pub sent_at: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl From<notification_outbox::Model> for OutboxEntryResponse {
+    fn from(entry: notification_outbox::Model) -> Self {
+        Self {
+            id: entry.id,
+            event: entry.event,
+            recipient_id: entry.recipient_id,
+            recipient_email: entry.recipient_email,
+            recipient_name: entry.recipient_name,
+            subject: entry.subject,
+            body: entry.body,
+            status: entry.status,
+            attempts: entry.attempts,
+            next_attempt_at: entry.next_attempt_at.to_string(),
+            last_error: entry.last_error,
+            sent_at: entry.sent_at.map(|dt| dt.to_string()),
+            created_at: entry.created_at.map(|dt| dt.to_string()),
+        }
+    }
+}
+
+/// Listar los últimos avisos de la cola, opcionalmente por estado
+/// GET /api/notifications/outbox?status=failed
+#[get("/notifications/outbox?<status>")]
+pub async fn list_outbox(
+    status: Option<String>,
+    db: &State<AppState>,
+    _admin: AdminUser,
+) -> Json<ApiResponseWithData<Vec<OutboxEntryResponse>>> {
+    let mut query = notification_outbox::Entity::find();
+    if let Some(status) = status.as_deref() {
+        let Some(status) = OutboxStatus::parse(status) else {
+            return Json(ApiResponseWithData::error(format!("Estado '{}' no válido", status)));
+        };
+        query = query.filter(notification_outbox::Column::Status.eq(status.as_str()));
+    }
+
+    match query
+        .order_by_desc(notification_outbox::Column::Id)
+        .limit(100)
+        .all(&db.db)
+        .await
+    {
+        Ok(entries) => Json(ApiResponseWithData::success(
+            "Cola de notificaciones obtenida".to_string(),
+            entries.into_iter().map(OutboxEntryResponse::from).collect(),
+        )),
+        Err(e) => Json(ApiResponseWithData::error(format!("Error al obtener la cola de notificaciones: {}", e))),
+    }
+}
+
+/// Volver a encolar un aviso con todos sus intentos
+/// POST /api/notifications/outbox/<id>/retry
+#[post("/notifications/outbox/<entry_id>/retry")]
+pub async fn retry_outbox_entry(
+    entry_id: i32,
+    db: &State<AppState>,
+    admin: AdminUser,
+) -> Json<ApiResponse> {
+    let entry = match notification_outbox::Entity::find_by_id(entry_id).one(&db.db).await {
+        Ok(Some(e)) => e,
+        Ok(None) => return Json(ApiResponse::error("Aviso no encontrado".to_string())),
+        Err(e) => return Json(ApiResponse::error(format!("Error: {}", e))),
+    };
+
+    if OutboxStatus::parse(&entry.status) == Some(OutboxStatus::Sent) {
+        return Json(ApiResponse::error("El aviso ya se envió".to_string()));
+    }
+
+    let recipient = entry.recipient_email.clone();
+    let mut active_model: notification_outbox::ActiveModel = entry.into();
+    active_model.status = Set(OutboxStatus::Pending.as_str().to_string());
+    active_model.attempts = Set(0);
+    active_model.next_attempt_at = Set(chrono::Utc::now().naive_utc());
+    if let Err(e) = active_model.update(&db.db).await {
+        return Json(ApiResponse::error(format!("Error al actualizar: {}", e)));
+    }
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Admin '{}' reintentó el aviso ID {} para '{}'", admin.0.user_name, entry_id, recipient),
+    )
+    .user(admin.0.sub.parse().unwrap_or(0), &admin.0.user_name)
+    .ip(&admin.0.ip)
+    .save(&db.db)
+    .await;
+
+    Json(ApiResponse::success("Aviso encolado de nuevo".to_string()))
+}
+
+/// Enviar un correo de prueba al administrador con la configuración SMTP actual.
+/// No pasa por la cola, para ver el error al momento
+/// POST /api/notifications/test
+#[post("/notifications/test")]
+pub async fn send_test_email(
+    db: &State<AppState>,
+    admin: AdminUser,
+) -> Json<ApiResponse> {
+    let settings = get_notification_settings();
+    let mailer = match SmtpMailer::new(&settings.smtp) {
+        Ok(m) => m,
+        Err(e) => return Json(ApiResponse::error(e)),
+    };
+
+    let body = format!(
+        "Hola {},\n\nEste es un correo de prueba enviado desde el servidor SMTP {}:{}.\nSi lo recibes, la configuración de notificaciones es correcta.\n",
+        admin.0.name, settings.smtp.host, settings.smtp.port
+    );
+    let result = mailer
+        .send(&OutgoingEmail {
+            to_name: &admin.0.name,
+            to_email: &admin.0.email,
+            subject: "Correo de prueba de Balance de Carga",
+            body: &body,
+        })
+        .await;
+
+    let mut log = audit::AuditLogBuilder::new(
+        EventType::SettingsUpdated,
+        AuditCategory::Security,
+        format!("Admin '{}' envió un correo de prueba a '{}'", admin.0.user_name, admin.0.email),
+    )
+    .user(admin.0.sub.parse().unwrap_or(0), &admin.0.user_name)
+    .ip(&admin.0.ip);
+    if let Err(ref e) = result {
+        log = log.failed(e.clone());
+    }
+    let _ = log.save(&db.db).await;
+
+    match result {
+        Ok(()) => Json(ApiResponse::success(format!("Correo de prueba enviado a {}", admin.0.email))),
+        Err(e) => Json(ApiResponse::error(e)),
+    }
+}
@@ -0,0 +1,532 @@
+//! Revisión de fragmentos con comentarios
+//!
+//! El jefe de asignatura entrega su fragmento y el Leader del balance lo aprueba
+//! o pide cambios. Los comentarios se anclan a una semana o celda de la
+//! cuadrícula y forman hilos (comentario raíz + respuestas) que se resuelven.
+
+use crate::utils::jwt::{Claims, LeaderOrSubjectLeaderUser};
+use crate::utils::audit;
+use crate::utils::concurrency::{expected_version, IfMatch};
+use crate::utils::fragment_data::{FragmentData, FragmentLayout, GridSection};
+use crate::utils::fragment_review::{plan_review, CommentAnchor, FragmentStatus, ReviewAction, ReviewContext, ReviewError};
+use crate::utils::balance_lifecycle::BalanceStatus;
+use crate::utils::notifications::{self, Notification};
+use crate::utils::validation::sanitize_text;
+use crate::database::audit_logs::{EventType, AuditCategory, EntityType};
+use crate::database::{asignaturas, balance_fragments, fragment_review_comments};
+use crate::routes::balance::{
+    authorize_fragment_edit, balance_layout, fragment_conflict, fragment_error, reconcile_fragment, FragmentRejection,
+};
+use crate::routes::revisions::{load_fragment_for_view, save_fragment_with_revision};
+use crate::types::{ApiResponse, ApiResponseWithData};
+use crate::*;
+use rocket::{get, post, put};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// Longitud máxima del texto de un comentario
+const MAX_COMMENT_LENGTH: usize = 2000;
+
+// ============================================================================
+// ESTRUCTURAS DE REQUEST/RESPONSE
+// ============================================================================
+
+/// Comentario nuevo: abre un hilo (con ancla opcional) o responde a uno existente
+#[derive(Debug, Deserialize)]
+pub struct NewReviewComment {
+    pub body: String,
+    #[serde(default)]
+    pub anchor: CommentAnchor,
+    pub parent_id: Option<i32>,  // Hilo al que responde
+}
+
+/// Request para entregar, aprobar o pedir cambios en un fragmento
+#[derive(Debug, Deserialize)]
+pub struct FragmentReviewRequest {
+    pub action: ReviewAction,
+    #[serde(default)]
+    pub comments: Vec<NewReviewComment>,
+    pub override_reason: Option<String>, // Aprobar un fragmento que no cuadra (Leader)
+    pub version: Option<i32>,            // Alternativa a la cabecera If-Match
+}
+
+/// Request para resolver o reabrir un hilo
+#[derive(Debug, Deserialize)]
+pub struct ResolveThreadRequest {
+    pub resolved: bool,
+}
+
+/// Comentario de revisión
+#[derive(Debug, Serialize)]
+pub struct ReviewComment {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub anchor: CommentAnchor,
+    pub body: String,
+    pub author_id: Option<i32>,
+    pub author_name: String,
+    pub resolved_at: Option<String>,
+    pub resolved_by_name: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl From<fragment_review_comments::Model> for ReviewComment {
+    fn from(comment: fragment_review_comments::Model) -> Self {
+        Self {
+            id: comment.id,
+            parent_id: comment.parent_id,
+            anchor: CommentAnchor {
+                section: comment.section.as_deref().and_then(GridSection::parse),
+                week: comment.week.map(|w| w as usize),
+                day: comment.day.map(|d| d as usize),
+            },
+            body: comment.body,
+            author_id: comment.author_id,
+            author_name: comment.author_name,
+            resolved_at: comment.resolved_at.map(|dt| dt.to_string()),
+            resolved_by_name: comment.resolved_by_name,
+            created_at: comment.created_at.map(|dt| dt.to_string()),
+        }
+    }
+}
+
+/// Hilo de comentarios: comentario raíz con sus respuestas
+#[derive(Debug, Serialize)]
+pub struct CommentThread {
+    #[serde(flatten)]
+    pub comment: ReviewComment,
+    pub resolved: bool,
+    pub replies: Vec<ReviewComment>,
+}
+
+/// Resultado de una acción de revisión
+#[derive(Debug, Serialize)]
+pub struct FragmentReviewResponse {
+    pub balance_id: i32,
+    pub asignatura_id: i32,
+    pub action: ReviewAction,
+    pub from: FragmentStatus,
+    pub to: FragmentStatus,
+    pub version: i32,
+    pub comments: Vec<ReviewComment>,
+}
+
+// ============================================================================
+// FUNCIONES AUXILIARES
+// ============================================================================
+
+/// Comentario validado, listo para guardar
+struct ValidComment {
+    body: String,
+    anchor: CommentAnchor,
+    parent_id: Option<i32>,
+}
+
+/// Valida texto, ancla e hilo de los comentarios nuevos.
+/// Las respuestas heredan el ancla del hilo, así que no pueden indicar otra.
+fn validate_comments(
+    comments: Vec<NewReviewComment>,
+    layout: &FragmentLayout,
+    existing: &[fragment_review_comments::Model],
+) -> Result<Vec<ValidComment>, String> {
+    comments
+        .into_iter()
+        .map(|comment| {
+            let body = sanitize_text(&comment.body);
+            if body.is_empty() {
+                return Err("El comentario no puede estar vacío".to_string());
+            }
+            if body.chars().count() > MAX_COMMENT_LENGTH {
+                return Err(format!("El comentario no puede superar {} caracteres", MAX_COMMENT_LENGTH));
+            }
+            if let Some(parent_id) = comment.parent_id {
+                if !existing.iter().any(|c| c.id == parent_id && c.parent_id.is_none()) {
+                    return Err(format!("El hilo {} no existe en este fragmento", parent_id));
+                }
+                if comment.anchor != CommentAnchor::default() {
+                    return Err("Las respuestas usan el ancla de su hilo".to_string());
+                }
+            }
+            comment.anchor.validate(layout)?;
+            Ok(ValidComment { body, anchor: comment.anchor, parent_id: comment.parent_id })
+        })
+        .collect()
+}
+
+async fn insert_comment<C: ConnectionTrait>(
+    db: &C,
+    fragment_id: i32,
+    comment: ValidComment,
+    claims: &Claims,
+) -> Result<fragment_review_comments::Model, DbErr> {
+    fragment_review_comments::ActiveModel {
+        fragment_id: Set(fragment_id),
+        parent_id: Set(comment.parent_id),
+        section: Set(comment.anchor.section.map(|s| s.as_str().to_string())),
+        week: Set(comment.anchor.week.map(|w| w as i32)),
+        day: Set(comment.anchor.day.map(|d| d as i32)),
+        body: Set(comment.body),
+        author_id: Set(claims.sub.parse::<i32>().ok()),
+        author_name: Set(claims.user_name.clone()),
+        created_at: Set(Some(chrono::Utc::now().naive_utc())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+async fn load_comments(db: &DatabaseConnection, fragment_id: i32) -> Result<Vec<fragment_review_comments::Model>, DbErr> {
+    fragment_review_comments::Entity::find()
+        .filter(fragment_review_comments::Column::FragmentId.eq(fragment_id))
+        .order_by_asc(fragment_review_comments::Column::Id)
+        .all(db)
+        .await
+}
+
+/// Agrupa los comentarios en hilos (en orden de creación)
+fn group_threads(comments: Vec<fragment_review_comments::Model>) -> Vec<CommentThread> {
+    let (roots, replies): (Vec<_>, Vec<_>) = comments.into_iter().partition(|c| c.parent_id.is_none());
+    let mut threads: Vec<CommentThread> = roots
+        .into_iter()
+        .map(|root| CommentThread { resolved: root.resolved_at.is_some(), comment: root.into(), replies: Vec::new() })
+        .collect();
+    for reply in replies {
+        if let Some(thread) = threads.iter_mut().find(|t| Some(t.comment.id) == reply.parent_id) {
+            thread.replies.push(reply.into());
+        }
+    }
+    threads
+}
+
+// ============================================================================
+// RUTAS
+// ============================================================================
+
+/// Entregar, aprobar o pedir cambios en un fragmento
+/// POST /api/balances/<id>/fragments/<asig>/review
+/// { "action": "submit" | "approve" | "request_changes", "comments": [...], "override_reason": "..." }
+/// Entrega el jefe de asignatura; aprueba o pide cambios el Leader del balance.
+#[post("/balances/<balance_id>/fragments/<asignatura_id>/review", format = "json", data = "<request>")]
+pub async fn review_fragment(
+    balance_id: i32,
+    asignatura_id: i32,
+    request: Json<FragmentReviewRequest>,
+    db: &State<AppState>,
+    user: LeaderOrSubjectLeaderUser,
+    if_match: IfMatch,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<FragmentReviewResponse>>, (Status, Json<ApiResponseWithData<FragmentRejection>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let data = request.into_inner();
+    let action = data.action;
+
+    let (balance, fragment) = load_fragment_for_view(&db.db, &user.0, balance_id, asignatura_id)
+        .await
+        .map_err(|(status, json)| fragment_error(status, json.into_inner().message))?;
+
+    if action.is_reviewer_action() {
+        if user.0.role != "leader" || balance.user_id != user_id {
+            let _ = audit::log_access_denied(
+                &db.db,
+                Some(user_id),
+                Some(&user.0.user_name),
+                &format!("revisión del fragmento de asignatura ID {} en balance ID {} (no es el Leader del balance)", asignatura_id, balance_id),
+                &ip_str,
+            ).await;
+            return Err(fragment_error(Status::Forbidden, "Solo el Leader del balance puede revisar el fragmento"));
+        }
+        if !BalanceStatus::parse(&balance.status).is_some_and(|s| s.allows_fragment_edits()) {
+            return Err(fragment_error(
+                Status::Conflict,
+                format!("El balance está en estado '{}' y sus fragmentos no pueden revisarse", balance.status),
+            ));
+        }
+    } else {
+        authorize_fragment_edit(&db.db, &user.0, &balance, &fragment, &ip_str).await?;
+    }
+
+    // Rechazar acciones hechas sobre una copia desactualizada
+    if let Some(expected) = expected_version(&if_match, data.version)
+        && expected != fragment.version
+    {
+        return Err(fragment_conflict(&db.db, fragment).await);
+    }
+
+    let Some(from) = FragmentStatus::parse(&fragment.status) else {
+        return Err(fragment_error(
+            Status::InternalServerError,
+            format!("El fragmento tiene un estado desconocido: '{}'", fragment.status),
+        ));
+    };
+
+    let layout = balance_layout(&balance);
+    let existing = load_comments(&db.db, fragment.id)
+        .await
+        .map_err(|e| fragment_error(Status::InternalServerError, format!("Error al obtener comentarios: {}", e)))?;
+    let comments = validate_comments(data.comments, &layout, &existing)
+        .map_err(|e| fragment_error(Status::BadRequest, e))?;
+
+    let asignatura = match asignaturas::Entity::find_by_id(asignatura_id).one(&db.db).await {
+        Ok(Some(a)) => a,
+        Ok(None) => return Err(fragment_error(Status::NotFound, "Asignatura no encontrada")),
+        Err(e) => return Err(fragment_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+    let grid = FragmentData::from_stored(&fragment.data, &layout);
+    let reconciliation = reconcile_fragment(&db.db, &asignatura, &grid)
+        .await
+        .map_err(|e| fragment_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let override_reason = data.override_reason
+        .as_deref()
+        .map(sanitize_text)
+        .filter(|r| !r.is_empty() && action == ReviewAction::Approve && !reconciliation.balanced);
+
+    let ctx = ReviewContext {
+        balanced: reconciliation.balanced,
+        has_override_reason: override_reason.is_some(),
+        unresolved_threads: existing.iter().filter(|c| c.parent_id.is_none() && c.resolved_at.is_none()).count(),
+        new_comments: comments.len(),
+    };
+
+    let to = match plan_review(from, action, &ctx) {
+        Ok(to) => to,
+        Err(ReviewError::Unbalanced) => {
+            let message = if action == ReviewAction::Approve {
+                "El fragmento no cuadra con lo planificado: indica un motivo para aprobarlo igualmente"
+            } else {
+                "El fragmento no cuadra con lo planificado y no puede entregarse"
+            };
+            return Err((Status::UnprocessableEntity, Json(ApiResponseWithData {
+                message: message.to_string(),
+                alert: "error".to_string(),
+                data: Some(FragmentRejection { reconciliation: Some(reconciliation), ..Default::default() }),
+            })));
+        }
+        Err(e @ ReviewError::InvalidTransition { .. }) => return Err(fragment_error(Status::Conflict, e.to_string())),
+        Err(e) => return Err(fragment_error(Status::UnprocessableEntity, e.to_string())),
+    };
+
+    let fragment_id = fragment.id;
+    let now = chrono::Utc::now().naive_utc();
+    let mut active_model: balance_fragments::ActiveModel = fragment.into();
+    active_model.status = Set(to.as_str().to_string());
+    active_model.completed_at = Set((to == FragmentStatus::Completed).then_some(now));
+    active_model.completion_override_reason = Set(override_reason.clone());
+    active_model.updated_at = Set(Some(now));
+
+    // Cambio de estado (con su revisión) y comentarios en la misma transacción
+    let txn = db.db.begin()
+        .await
+        .map_err(|e| fragment_error(Status::InternalServerError, format!("Error: {}", e)))?;
+    let updated = match save_fragment_with_revision(&txn, active_model, &user.0, None).await {
+        Ok((updated, _)) => updated,
+        Err(DbErr::RecordNotUpdated) => {
+            drop(txn);
+            return match balance_fragments::Entity::find_by_id(fragment_id).one(&db.db).await {
+                Ok(Some(current)) => Err(fragment_conflict(&db.db, current).await),
+                _ => Err(fragment_error(Status::Conflict, "El fragmento fue modificado por otro usuario")),
+            };
+        }
+        Err(e) => return Err(fragment_error(Status::InternalServerError, format!("Error al actualizar: {}", e))),
+    };
+    let mut saved_comments = Vec::with_capacity(comments.len());
+    for comment in comments {
+        let saved = insert_comment(&txn, fragment_id, comment, &user.0)
+            .await
+            .map_err(|e| fragment_error(Status::InternalServerError, format!("Error al guardar comentario: {}", e)))?;
+        saved_comments.push(ReviewComment::from(saved));
+    }
+    txn.commit()
+        .await
+        .map_err(|e| fragment_error(Status::InternalServerError, format!("Error: {}", e)))?;
+
+    let reason_note = override_reason.map(|r| format!(". Aprobado sin cuadrar con lo planificado. Motivo: {}", r)).unwrap_or_default();
+    let _ = audit::AuditLogBuilder::new(
+        EventType::StatusChange,
+        AuditCategory::Functional,
+        format!("Usuario '{}' cambió el fragmento de '{}' en balance ID {} de '{}' a '{}' ({}, {} comentario(s)){}",
+            user.0.user_name, asignatura.name, balance_id, from, to, action, saved_comments.len(), reason_note),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Fragment, fragment_id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    // El jefe de asignatura recibe los cambios que se le piden (también por
+    // correo) y el aviso de aprobación
+    let reviewer = user.0.name.clone();
+    match action {
+        ReviewAction::RequestChanges => {
+            let comments: Vec<String> = saved_comments.iter().map(|c| c.body.clone()).collect();
+            let _ = notifications::notify_fragments(&db.db, user_id, &[fragment_id], |fragment| Notification::ChangesRequested {
+                fragment,
+                reviewer: reviewer.clone(),
+                comments: comments.clone(),
+            })
+            .await;
+        }
+        ReviewAction::Approve => {
+            let _ = notifications::notify_fragments(&db.db, user_id, &[fragment_id], |fragment| Notification::FragmentApproved {
+                fragment,
+                reviewer: reviewer.clone(),
+            })
+            .await;
+        }
+        ReviewAction::Submit => {}
+    }
+
+    Ok(Json(ApiResponseWithData::success(
+        format!("Estado del fragmento actualizado a '{}'", to),
+        FragmentReviewResponse {
+            balance_id,
+            asignatura_id,
+            action,
+            from,
+            to,
+            version: updated.version,
+            comments: saved_comments,
+        },
+    )))
+}
+
+/// Listar los hilos de comentarios de un fragmento
+/// GET /api/balances/<id>/fragments/<asig>/comments?unresolved=true
+#[get("/balances/<balance_id>/fragments/<asignatura_id>/comments?<unresolved>")]
+pub async fn list_fragment_comments(
+    balance_id: i32,
+    asignatura_id: i32,
+    unresolved: Option<bool>,
+    db: &State<AppState>,
+    user: LeaderOrSubjectLeaderUser,
+) -> Result<Json<ApiResponseWithData<Vec<CommentThread>>>, (Status, Json<ApiResponse>)> {
+    let (_, fragment) = load_fragment_for_view(&db.db, &user.0, balance_id, asignatura_id).await?;
+
+    let comments = load_comments(&db.db, fragment.id)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e)))))?;
+
+    let mut threads = group_threads(comments);
+    if unresolved.unwrap_or(false) {
+        threads.retain(|t| !t.resolved);
+    }
+
+    Ok(Json(ApiResponseWithData::success(
+        "Comentarios obtenidos exitosamente".to_string(),
+        threads,
+    )))
+}
+
+/// Abrir un hilo o responder a uno existente
+#[post("/balances/<balance_id>/fragments/<asignatura_id>/comments", format = "json", data = "<comment>")]
+pub async fn add_fragment_comment(
+    balance_id: i32,
+    asignatura_id: i32,
+    comment: Json<NewReviewComment>,
+    db: &State<AppState>,
+    user: LeaderOrSubjectLeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<ReviewComment>>, (Status, Json<ApiResponse>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let (balance, fragment) = load_fragment_for_view(&db.db, &user.0, balance_id, asignatura_id).await?;
+
+    if BalanceStatus::parse(&balance.status) == Some(BalanceStatus::Archived) {
+        return Err((Status::Conflict, Json(ApiResponse::error(
+            "No se pueden comentar fragmentos de un balance archivado".to_string(),
+        ))));
+    }
+
+    let existing = load_comments(&db.db, fragment.id)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e)))))?;
+    let mut valid = validate_comments(vec![comment.into_inner()], &balance_layout(&balance), &existing)
+        .map_err(|e| (Status::BadRequest, Json(ApiResponse::error(e))))?;
+
+    let saved = insert_comment(&db.db, fragment.id, valid.remove(0), &user.0)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error al guardar comentario: {}", e)))))?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Create,
+        AuditCategory::Functional,
+        format!("Usuario '{}' comentó el fragmento de asignatura ID {} en balance ID {}",
+            user.0.user_name, asignatura_id, balance_id),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Fragment, fragment.id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    Ok(Json(ApiResponseWithData::success(
+        "Comentario guardado exitosamente".to_string(),
+        ReviewComment::from(saved),
+    )))
+}
+
+/// Resolver o reabrir un hilo de comentarios
+/// PUT /api/balances/<id>/fragments/<asig>/comments/<comment_id>/resolve  { "resolved": true }
+#[put("/balances/<balance_id>/fragments/<asignatura_id>/comments/<comment_id>/resolve", format = "json", data = "<request>")]
+pub async fn resolve_comment_thread(
+    balance_id: i32,
+    asignatura_id: i32,
+    comment_id: i32,
+    request: Json<ResolveThreadRequest>,
+    db: &State<AppState>,
+    user: LeaderOrSubjectLeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<ReviewComment>>, (Status, Json<ApiResponse>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    let resolved = request.resolved;
+
+    let (_, fragment) = load_fragment_for_view(&db.db, &user.0, balance_id, asignatura_id).await?;
+
+    let root = match fragment_review_comments::Entity::find_by_id(comment_id)
+        .filter(fragment_review_comments::Column::FragmentId.eq(fragment.id))
+        .one(&db.db)
+        .await
+    {
+        Ok(Some(c)) if c.parent_id.is_none() => c,
+        Ok(Some(_)) => return Err((Status::BadRequest, Json(ApiResponse::error(
+            "Solo se resuelve el comentario que abre el hilo".to_string(),
+        )))),
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Comentario no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
+
+    let mut active_model: fragment_review_comments::ActiveModel = root.into();
+    if resolved {
+        active_model.resolved_at = Set(Some(chrono::Utc::now().naive_utc()));
+        active_model.resolved_by_name = Set(Some(user.0.user_name.clone()));
+    } else {
+        active_model.resolved_at = Set(None);
+        active_model.resolved_by_name = Set(None);
+    }
+
+    let updated = active_model
+        .update(&db.db)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error al actualizar: {}", e)))))?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Usuario '{}' {} el hilo {} del fragmento de asignatura ID {} en balance ID {}",
+            user.0.user_name, if resolved { "resolvió" } else { "reabrió" }, comment_id, asignatura_id, balance_id),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Fragment, fragment.id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    Ok(Json(ApiResponseWithData::success(
+        if resolved { "Hilo resuelto" } else { "Hilo reabierto" }.to_string(),
+        ReviewComment::from(updated),
+    )))
+}
@@ -0,0 +1,301 @@
+//! Historial de revisiones de fragmentos
+//!
+//! Cada guardado de un fragmento crea una revisión con autor, fecha y estado;
+//! el contenido previo al primer guardado queda como revisión 1.
+//! Permite listar revisiones, comparar dos de ellas celda a celda y restaurar
+//! una revisión antigua (que se guarda como revisión nueva).
+
+use crate::utils::jwt::{Claims, LeaderOrSubjectLeaderUser};
+use crate::utils::audit;
+use crate::utils::fragment_data::{CellChange, FragmentData};
+use crate::utils::fragment_revisions::baseline_revision;
+use crate::utils::notifications::{self, Notification};
+use crate::database::audit_logs::{EventType, AuditCategory, EntityType};
+use crate::database::{balances, balance_fragments, balance_fragment_revisions, usuarios};
+use crate::routes::balance::{active_fragments, authorize_fragment_edit, balance_layout, check_fragment_rules, fragment_error, FragmentRejection};
+use crate::types::{ApiResponse, ApiResponseWithData};
+use crate::*;
+use rocket::{get, post};
+use sea_orm::*;
+use serde::Serialize;
+use std::net::SocketAddr;
+
+// ============================================================================
+// ESTRUCTURAS DE RESPUESTA
+// ============================================================================
+
+/// Revisión de un fragmento (sin datos, para listados)
+#[derive(Debug, Serialize)]
+pub struct RevisionSummary {
+    pub revision_number: i32,
+    pub status: String,
+    pub author_id: Option<i32>,
+    pub author_name: String,
+    pub restored_from: Option<i32>,
+    pub created_at: Option<String>,
+}
+
+impl From<balance_fragment_revisions::Model> for RevisionSummary {
+    fn from(rev: balance_fragment_revisions::Model) -> Self {
+        Self {
+            revision_number: rev.revision_number,
+            status: rev.status,
+            author_id: rev.author_id,
+            author_name: rev.author_name,
+            restored_from: rev.restored_from,
+            created_at: rev.created_at.map(|dt| dt.to_string()),
+        }
+    }
+}
+
+/// Diferencias celda a celda entre dos revisiones
+#[derive(Debug, Serialize)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub changes: Vec<CellChange>,
+}
+
+// ============================================================================
+// FUNCIONES AUXILIARES
+// ============================================================================
+
+/// Guarda el fragmento y crea su revisión en la misma transacción. Si el
+/// fragmento aún no tiene revisiones, su contenido previo se guarda antes como
+/// revisión 1 para poder recuperarlo. La escritura solo se aplica si la versión en la base de datos sigue siendo
+/// la del modelo leído; si otro usuario guardó antes devuelve `DbErr::RecordNotUpdated`.
+pub(crate) async fn save_fragment_with_revision<C: ConnectionTrait + TransactionTrait>(
+    db: &C,
+    mut fragment: balance_fragments::ActiveModel,
+    claims: &Claims,
+    restored_from: Option<i32>,
+) -> Result<(balance_fragments::Model, balance_fragment_revisions::Model), DbErr> {
+    let read_version = *fragment.version.as_ref();
+    fragment.version = Set(read_version + 1);
+
+    let fragment_id = *fragment.id.as_ref();
+
+    let txn = db.begin().await?;
+
+    let has_revisions = balance_fragment_revisions::Entity::find()
+        .filter(balance_fragment_revisions::Column::FragmentId.eq(fragment_id))
+        .one(&txn)
+        .await?
+        .is_some();
+    if !has_revisions
+        && let Some(previous) = balance_fragments::Entity::find_by_id(fragment_id).one(&txn).await?
+        && previous.version == read_version
+    {
+        let subject_leader = match previous.subject_leader_id {
+            Some(id) => usuarios::Entity::find_by_id(id).one(&txn).await?,
+            None => None,
+        };
+        baseline_revision(&previous, subject_leader.as_ref()).insert(&txn).await?;
+    }
+
+    let updated = balance_fragments::Entity::update(fragment)
+        .filter(balance_fragments::Column::Version.eq(read_version))
+        .exec(&txn)
+        .await?;
+
+    let last_number = balance_fragment_revisions::Entity::find()
+        .filter(balance_fragment_revisions::Column::FragmentId.eq(updated.id))
+        .order_by_desc(balance_fragment_revisions::Column::RevisionNumber)
+        .one(&txn)
+        .await?
+        .map(|r| r.revision_number)
+        .unwrap_or(0);
+
+    let revision = balance_fragment_revisions::ActiveModel {
+        fragment_id: Set(updated.id),
+        revision_number: Set(last_number + 1),
+        data: Set(updated.data.clone()),
+        status: Set(updated.status.clone()),
+        author_id: Set(claims.sub.parse::<i32>().ok()),
+        author_name: Set(claims.user_name.clone()),
+        restored_from: Set(restored_from),
+        created_at: Set(Some(chrono::Utc::now().naive_utc())),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    txn.commit().await?;
+    Ok((updated, revision))
+}
+
+/// Busca balance y fragmento, y verifica que el usuario puede verlo
+/// (Leader ve todo, SubjectLeader solo su fragmento)
+pub(crate) async fn load_fragment_for_view(
+    db: &DatabaseConnection,
+    claims: &Claims,
+    balance_id: i32,
+    asignatura_id: i32,
+) -> Result<(balances::Model, balance_fragments::Model), (Status, Json<ApiResponse>)> {
+    let user_id = claims.sub.parse::<i32>().unwrap_or(0);
+
+    let fragment = match active_fragments()
+        .filter(balance_fragments::Column::BalanceId.eq(balance_id))
+        .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
+        .one(db)
+        .await
+    {
+        Ok(Some(f)) => f,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Fragmento no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
+
+    if claims.role != "leader" && claims.role != "admin" && fragment.subject_leader_id != Some(user_id) {
+        return Err((Status::Forbidden, Json(ApiResponse::error(
+            "No tienes permiso para ver este fragmento".to_string(),
+        ))));
+    }
+
+    let balance = match balances::Entity::find_by_id(balance_id).one(db).await {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error("Balance no encontrado".to_string())))),
+        Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+    };
+
+    Ok((balance, fragment))
+}
+
+async fn find_revision(
+    db: &DatabaseConnection,
+    fragment_id: i32,
+    revision_number: i32,
+) -> Result<Option<balance_fragment_revisions::Model>, DbErr> {
+    balance_fragment_revisions::Entity::find()
+        .filter(balance_fragment_revisions::Column::FragmentId.eq(fragment_id))
+        .filter(balance_fragment_revisions::Column::RevisionNumber.eq(revision_number))
+        .one(db)
+        .await
+}
+
+// ============================================================================
+// RUTAS
+// ============================================================================
+
+/// Listar las revisiones de un fragmento (más reciente primero)
+#[get("/balances/<balance_id>/fragments/<asignatura_id>/revisions")]
+pub async fn list_fragment_revisions(
+    balance_id: i32,
+    asignatura_id: i32,
+    db: &State<AppState>,
+    user: LeaderOrSubjectLeaderUser,
+) -> Result<Json<ApiResponseWithData<Vec<RevisionSummary>>>, (Status, Json<ApiResponse>)> {
+    let (_, fragment) = load_fragment_for_view(&db.db, &user.0, balance_id, asignatura_id).await?;
+
+    let revisions = balance_fragment_revisions::Entity::find()
+        .filter(balance_fragment_revisions::Column::FragmentId.eq(fragment.id))
+        .order_by_desc(balance_fragment_revisions::Column::RevisionNumber)
+        .all(&db.db)
+        .await
+        .map_err(|e| (Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e)))))?;
+
+    Ok(Json(ApiResponseWithData::success(
+        "Revisiones obtenidas exitosamente".to_string(),
+        revisions.into_iter().map(RevisionSummary::from).collect(),
+    )))
+}
+
+/// Comparar dos revisiones de un fragmento celda a celda
+/// GET /api/balances/<id>/fragments/<asig>/revisions/diff?from=1&to=3
+#[get("/balances/<balance_id>/fragments/<asignatura_id>/revisions/diff?<from>&<to>")]
+pub async fn diff_fragment_revisions(
+    balance_id: i32,
+    asignatura_id: i32,
+    from: i32,
+    to: i32,
+    db: &State<AppState>,
+    user: LeaderOrSubjectLeaderUser,
+) -> Result<Json<ApiResponseWithData<RevisionDiff>>, (Status, Json<ApiResponse>)> {
+    let (balance, fragment) = load_fragment_for_view(&db.db, &user.0, balance_id, asignatura_id).await?;
+    let layout = balance_layout(&balance);
+
+    let mut grids = Vec::with_capacity(2);
+    for number in [from, to] {
+        match find_revision(&db.db, fragment.id, number).await {
+            Ok(Some(rev)) => grids.push(FragmentData::from_stored(&rev.data, &layout)),
+            Ok(None) => return Err((Status::NotFound, Json(ApiResponse::error(format!("Revisión {} no encontrada", number))))),
+            Err(e) => return Err((Status::InternalServerError, Json(ApiResponse::error(format!("Error: {}", e))))),
+        }
+    }
+
+    Ok(Json(ApiResponseWithData::success(
+        "Diferencias obtenidas exitosamente".to_string(),
+        RevisionDiff { from, to, changes: grids[0].diff(&grids[1]) },
+    )))
+}
+
+/// Restaurar una revisión antigua como revisión nueva
+/// Un fragmento completado vuelve a 'in_progress', ya que la conciliación
+/// se hizo sobre otros datos.
+#[post("/balances/<balance_id>/fragments/<asignatura_id>/revisions/<revision_number>/restore")]
+pub async fn restore_fragment_revision(
+    balance_id: i32,
+    asignatura_id: i32,
+    revision_number: i32,
+    db: &State<AppState>,
+    user: LeaderOrSubjectLeaderUser,
+    remote_addr: Option<SocketAddr>,
+) -> Result<Json<ApiResponseWithData<RevisionSummary>>, (Status, Json<ApiResponseWithData<FragmentRejection>>)> {
+    let user_id = user.0.sub.parse::<i32>().unwrap_or(0);
+    let ip_str = remote_addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+
+    let (balance, fragment) = load_fragment_for_view(&db.db, &user.0, balance_id, asignatura_id)
+        .await
+        .map_err(|(status, json)| fragment_error(status, json.into_inner().message))?;
+
+    authorize_fragment_edit(&db.db, &user.0, &balance, &fragment, &ip_str).await?;
+
+    let revision = match find_revision(&db.db, fragment.id, revision_number).await {
+        Ok(Some(rev)) => rev,
+        Ok(None) => return Err(fragment_error(Status::NotFound, format!("Revisión {} no encontrada", revision_number))),
+        Err(e) => return Err(fragment_error(Status::InternalServerError, format!("Error: {}", e))),
+    };
+
+    let grid = FragmentData::from_stored(&revision.data, &balance_layout(&balance));
+    check_fragment_rules(&db.db, &balance, asignatura_id, &grid).await?;
+
+    let was_completed = fragment.status == "completed";
+    let mut active_model: balance_fragments::ActiveModel = fragment.into();
+    active_model.data = Set(grid.to_value());
+    active_model.updated_at = Set(Some(chrono::Utc::now().naive_utc()));
+    if was_completed {
+        active_model.status = Set("in_progress".to_string());
+        active_model.completed_at = Set(None);
+        active_model.completion_override_reason = Set(None);
+    }
+
+    let (updated, new_revision) = save_fragment_with_revision(&db.db, active_model, &user.0, Some(revision_number))
+        .await
+        .map_err(|e| match e {
+            DbErr::RecordNotUpdated => fragment_error(Status::Conflict, "El fragmento fue modificado por otro usuario durante la restauración"),
+            e => fragment_error(Status::InternalServerError, format!("Error al restaurar: {}", e)),
+        })?;
+
+    let _ = audit::AuditLogBuilder::new(
+        EventType::Update,
+        AuditCategory::Functional,
+        format!("Usuario '{}' restauró la revisión {} del fragmento de asignatura ID {} en balance ID {} (nueva revisión {})",
+            user.0.user_name, revision_number, asignatura_id, balance_id, new_revision.revision_number),
+    )
+    .user(user_id, &user.0.user_name)
+    .entity(EntityType::Fragment, updated.id)
+    .ip(&ip_str)
+    .save(&db.db)
+    .await;
+
+    let editor = user.0.name.clone();
+    let _ = notifications::notify_fragments(&db.db, user_id, &[updated.id], |fragment| Notification::FragmentEdited {
+        fragment,
+        editor: editor.clone(),
+    })
+    .await;
+
+    Ok(Json(ApiResponseWithData::success(
+        format!("Revisión {} restaurada exitosamente", revision_number),
+        RevisionSummary::from(new_revision),
+    )))
+}
@@ -14,8 +14,27 @@ use crate::database::system_settings;
 use crate::types::{ApiResponse, ApiResponseWithData};
 use crate::utils::audit::AuditLogBuilder;
 use crate::utils::jwt::{AdminUser, set_ip_validation};
+use crate::utils::mailer::{SmtpSecurity, SmtpSettings};
+use crate::utils::notifications::{NotificationSettings, set_notification_settings};
 use crate::AppState;
 
+/// Configuraciones que no se devuelven en el listado
+const SECRET_SETTINGS: [&str; 1] = ["smtp_password"];
+
+/// Configuraciones de la categoría `notifications`
+const NOTIFICATION_SETTINGS: [&str; 10] = [
+    "notifications_enabled",
+    "smtp_host",
+    "smtp_port",
+    "smtp_security",
+    "smtp_username",
+    "smtp_password",
+    "smtp_from",
+    "notifications_app_url",
+    "notification_max_attempts",
+    "deadline_reminder_days",
+];
+
 /// Response structure for a single setting
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SettingResponse {
@@ -32,6 +51,7 @@ pub struct SettingsGrouped {
     pub session: Vec<SettingResponse>,
     pub password: Vec<SettingResponse>,
     pub audit: Vec<SettingResponse>,
+    pub notifications: Vec<SettingResponse>,
 }
 
 /// Request structure for updating settings
@@ -88,12 +108,15 @@ pub async fn list_settings(
                 session: Vec::new(),
                 password: Vec::new(),
                 audit: Vec::new(),
+                notifications: Vec::new(),
             };
 
             for setting in settings {
+                // Los secretos no salen del servidor
+                let value = if SECRET_SETTINGS.contains(&setting.key.as_str()) { String::new() } else { setting.value };
                 let response = SettingResponse {
                     key: setting.key,
-                    value: setting.value,
+                    value,
                     description: setting.description,
                     category: setting.category.clone(),
                 };
@@ -103,6 +126,7 @@ pub async fn list_settings(
                     "session" => grouped.session.push(response),
                     "password" => grouped.password.push(response),
                     "audit" => grouped.audit.push(response),
+                    "notifications" => grouped.notifications.push(response),
                     _ => {} // Ignore unknown categories
                 }
             }
@@ -139,6 +163,18 @@ pub async fn update_settings(
             _ => json_value.to_string(),
         };
 
+        // Un secreto vacío conserva el valor guardado (el listado no lo devuelve)
+        if SECRET_SETTINGS.contains(&key.as_str()) && value.is_empty() {
+            continue;
+        }
+
+        if key == "smtp_security" && SmtpSecurity::parse(&value).is_none() {
+            return Json(ApiResponse {
+                message: "El cifrado SMTP debe ser 'none', 'starttls' o 'tls'".to_string(),
+                alert: "error".to_string(),
+            });
+        }
+
         // Validar que los valores numéricos no sean negativos (si son de tipo numérico o string)
         if let Ok(num_val) = value.parse::<i32>() {
              if num_val < 0 {
@@ -151,7 +187,7 @@ pub async fn update_settings(
              // Validación específica para retention_days mínima
              if key == "audit_retention_days" && num_val < 90 {
                 return Json(ApiResponse {
-                    message: format!("El tiempo de retención de auditoría debe ser mínimo de 90 días por seguridad"),
+                    message: "El tiempo de retención de auditoría debe ser mínimo de 90 días por seguridad".to_string(),
                     alert: "error".to_string(),
                 });
              }
@@ -175,7 +211,7 @@ pub async fn update_settings(
                     )
                     .user(admin.0.sub.parse().unwrap_or(0), &admin.0.user_name)
                     .ip(&admin.0.ip)
-                    .failed(&e.to_string())
+                    .failed(e.to_string())
                     .save(&db.db)
                     .await;
 
@@ -241,6 +277,12 @@ pub async fn update_settings(
         set_audit_log_ip(audit_log_ip);
     }
 
+    // Recargar la configuración de notificaciones
+    if updated_keys.iter().any(|k| NOTIFICATION_SETTINGS.contains(&k.as_str())) {
+        let settings = load_notification_settings(&db.db).await;
+        set_notification_settings(settings);
+    }
+
     Json(ApiResponse {
         message: "Configuraciones actualizadas correctamente".to_string(),
         alert: "success".to_string(),
@@ -317,3 +359,25 @@ pub async fn load_ip_validation_setting(db: &DatabaseConnection) -> bool {
 pub async fn load_audit_log_ip_setting(db: &DatabaseConnection) -> bool {
     get_setting_bool(db, "audit_log_ip", true).await
 }
+
+/// Load notification (SMTP) settings from database
+pub async fn load_notification_settings(db: &DatabaseConnection) -> NotificationSettings {
+    let defaults = NotificationSettings::default();
+    let smtp = SmtpSettings {
+        host: get_setting(db, "smtp_host", &defaults.smtp.host).await,
+        port: get_setting_i32(db, "smtp_port", defaults.smtp.port as i32).await.clamp(1, u16::MAX as i32) as u16,
+        security: SmtpSecurity::parse(&get_setting(db, "smtp_security", defaults.smtp.security.as_str()).await)
+            .unwrap_or(defaults.smtp.security),
+        username: get_setting(db, "smtp_username", "").await,
+        password: get_setting(db, "smtp_password", "").await,
+        from: get_setting(db, "smtp_from", &defaults.smtp.from).await,
+    };
+
+    NotificationSettings {
+        enabled: get_setting_bool(db, "notifications_enabled", defaults.enabled).await,
+        smtp,
+        app_url: get_setting(db, "notifications_app_url", "").await,
+        max_attempts: get_setting_i32(db, "notification_max_attempts", defaults.max_attempts).await.max(1),
+        reminder_days: get_setting_i32(db, "deadline_reminder_days", defaults.reminder_days as i32).await as i64,
+    }
+}
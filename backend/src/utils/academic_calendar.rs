@@ -0,0 +1,152 @@
+//! Calendario académico institucional
+//!
+//! Cada curso académico ('2025-2026') tiene un calendario con feriados,
+//! vacaciones y sesiones de exámenes, compartido por todos los balances del
+//! curso. Los feriados y vacaciones, junto con los períodos propios de cada
+//! balance, se saltan al calcular las fechas de las semanas; las sesiones de
+//! exámenes marcan dónde empiezan las semanas de consultas y exámenes finales.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Tipo de período del calendario
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarPeriodKind {
+    Holiday,
+    Vacation,
+    ExamSession,
+}
+
+impl CalendarPeriodKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalendarPeriodKind::Holiday => "holiday",
+            CalendarPeriodKind::Vacation => "vacation",
+            CalendarPeriodKind::ExamSession => "exam_session",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "holiday" => Some(CalendarPeriodKind::Holiday),
+            "vacation" => Some(CalendarPeriodKind::Vacation),
+            "exam_session" => Some(CalendarPeriodKind::ExamSession),
+            _ => None,
+        }
+    }
+}
+
+/// Período del calendario tal como llega en las peticiones
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalendarPeriodInput {
+    pub kind: CalendarPeriodKind,
+    pub name: String,
+    pub start: String,  // 'YYYY-MM-DD'
+    pub end: String,    // 'YYYY-MM-DD'
+}
+
+/// Período validado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarPeriod {
+    pub kind: CalendarPeriodKind,
+    pub name: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl CalendarPeriodInput {
+    /// Valida el formato de las fechas y que el inicio no sea posterior al fin
+    pub fn validate(&self) -> Result<CalendarPeriod, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("El nombre del período es obligatorio".to_string());
+        }
+        let parse = |value: &str| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| format!("Fecha inválida '{}' en el período '{}'. Use YYYY-MM-DD", value, name))
+        };
+        let start = parse(&self.start)?;
+        let end = parse(&self.end)?;
+        if start > end {
+            return Err(format!("El período '{}' termina antes de empezar", name));
+        }
+        Ok(CalendarPeriod { kind: self.kind, name: name.to_string(), start, end })
+    }
+}
+
+/// Une los feriados y vacaciones del calendario con los períodos propios del
+/// balance, ordenados por inicio. En ninguno de ellos hay clases, por lo que
+/// todos se saltan al calcular las fechas de las semanas. Las sesiones de
+/// exámenes no se saltan: en ellas caen las semanas finales (`exam_session_starts`).
+pub fn merge_periods(
+    calendar: &[CalendarPeriod],
+    balance_periods: &[(NaiveDate, NaiveDate, String)],
+) -> Vec<(NaiveDate, NaiveDate, String)> {
+    let mut merged: Vec<_> = calendar
+        .iter()
+        .filter(|p| p.kind != CalendarPeriodKind::ExamSession)
+        .map(|p| (p.start, p.end, p.name.clone()))
+        .chain(balance_periods.iter().cloned())
+        .collect();
+    merged.sort_by_key(|(start, end, _)| (*start, *end));
+    merged.dedup();
+    merged
+}
+
+/// Inicio de cada sesión de exámenes del calendario, en orden
+pub fn exam_session_starts(calendar: &[CalendarPeriod]) -> Vec<NaiveDate> {
+    let mut starts: Vec<NaiveDate> = calendar
+        .iter()
+        .filter(|p| p.kind == CalendarPeriodKind::ExamSession)
+        .map(|p| p.start)
+        .collect();
+    starts.sort();
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_validate_period() {
+        let input = CalendarPeriodInput {
+            kind: CalendarPeriodKind::Vacation,
+            name: " Receso docente ".to_string(),
+            start: "2026-07-01".to_string(),
+            end: "2026-08-31".to_string(),
+        };
+        assert_eq!(input.validate().unwrap().name, "Receso docente");
+
+        let reversed = CalendarPeriodInput { start: "2026-09-01".to_string(), ..input.clone() };
+        assert!(reversed.validate().is_err());
+
+        let invalid = CalendarPeriodInput { end: "31/08/2026".to_string(), ..input };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_merge_periods() {
+        let calendar = vec![
+            CalendarPeriod { kind: CalendarPeriodKind::Holiday, name: "Feriado".into(), start: date(1, 1), end: date(1, 2) },
+            CalendarPeriod { kind: CalendarPeriodKind::ExamSession, name: "Exámenes".into(), start: date(2, 2), end: date(2, 13) },
+        ];
+        let own = vec![
+            (date(1, 20), date(1, 23), "Semana de la ciencia".to_string()),
+            (date(1, 1), date(1, 2), "Feriado".to_string()),
+        ];
+
+        // La sesión de exámenes no es tiempo sin clases que saltar
+        let merged = merge_periods(&calendar, &own);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].2, "Feriado");
+        assert_eq!(merged[1].2, "Semana de la ciencia");
+
+        assert_eq!(exam_session_starts(&calendar), vec![date(2, 2)]);
+    }
+}
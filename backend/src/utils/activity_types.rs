@@ -0,0 +1,237 @@
+//! Tipos de actividad docente usados en los balances
+//!
+//! El catálogo vive en la tabla `activity_types` y se carga en memoria al
+//! arrancar (ver `routes::activity_types::load_activity_types`); cada cambio
+//! hecho desde la API lo vuelve a cargar. Es la fuente única que comparten la
+//! validación de fragmentos, las reglas del balance, la conciliación, la
+//! analítica de carga y las exportaciones. Mientras no se cargue se usan los
+//! tipos de `builtin_activity_types`, los mismos que siembra la migración 015.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Horas por defecto para códigos que no están en el catálogo
+pub const DEFAULT_HOURS_PER_ACTIVITY: i32 = 2;
+
+/// Longitud máxima de un código de actividad
+pub const MAX_CODE_LENGTH: usize = 8;
+
+/// Tipo de actividad del catálogo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityType {
+    /// Código que se escribe en la cuadrícula ("C", "CP", ...)
+    pub code: String,
+    pub label: String,
+    /// Horas que aporta cada ocurrencia
+    pub hours: i32,
+    /// Se colorea en rojo y no puede haber 3 o más en la misma semana
+    pub is_critical: bool,
+    /// Es una evaluación (cuenta para el umbral de evaluaciones por semana)
+    pub is_exam: bool,
+    /// Sus horas cuentan como horas presenciales de la asignatura
+    pub counts_as_class_hours: bool,
+    /// Se puede colocar en la cuadrícula; los que no (p. ej. TC, EF) solo se planifican
+    pub schedulable: bool,
+    /// Color de la UI en formato '#RRGGBB'
+    pub color: Option<String>,
+    pub sort_order: i32,
+}
+
+impl ActivityType {
+    /// Horas presenciales de una ocurrencia (0 si no cuenta como horas de clase)
+    pub fn class_hours(&self) -> i32 {
+        if self.counts_as_class_hours { self.hours } else { 0 }
+    }
+
+    /// Valida los campos editables de un tipo de actividad
+    pub fn validate(&self) -> Result<(), String> {
+        if self.code.is_empty()
+            || self.code.len() > MAX_CODE_LENGTH
+            || !self.code.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            return Err(format!(
+                "El código debe tener entre 1 y {} letras mayúsculas o dígitos",
+                MAX_CODE_LENGTH
+            ));
+        }
+        if self.label.trim().is_empty() {
+            return Err("El nombre del tipo de actividad es obligatorio".to_string());
+        }
+        if self.hours < 0 {
+            return Err("Las horas por ocurrencia no pueden ser negativas".to_string());
+        }
+        if let Some(ref color) = self.color
+            && !(color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            return Err(format!("Color inválido '{}' (use #RRGGBB)", color));
+        }
+        Ok(())
+    }
+}
+
+/// (código, nombre, horas, crítica, evaluación, horas de clase, en cuadrícula, color)
+type BuiltinType = (&'static str, &'static str, i32, bool, bool, bool, bool, &'static str);
+
+/// Tipos de actividad por defecto (los que siembra la migración 015)
+pub fn builtin_activity_types() -> Vec<ActivityType> {
+    let types: [BuiltinType; 10] = [
+        ("C", "Conferencia", 2, false, false, true, true, "#DBEAFE"),
+        ("CP", "Clase Práctica", 2, false, false, true, true, "#DCFCE7"),
+        ("S", "Seminario", 2, false, false, true, true, "#FEF9C3"),
+        ("PL", "Práctica de Laboratorio", 2, false, false, true, true, "#E0E7FF"),
+        ("TE", "Tarea Extraclase", 0, true, false, false, true, "#FEE2E2"),
+        ("T", "Taller", 2, true, false, true, true, "#FEE2E2"),
+        ("PP", "Prueba Parcial", 2, true, true, true, true, "#FECACA"),
+        ("EC", "Examen Comprobatorio", 2, false, true, true, true, "#FFEDD5"),
+        ("TC", "Trabajo de Curso", 2, false, false, true, false, "#F3E8FF"),
+        ("EF", "Examen Final", 2, false, true, true, false, "#FFE4E6"),
+    ];
+    types
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (code, label, hours, is_critical, is_exam, counts_as_class_hours, schedulable, color))| ActivityType {
+            code: code.to_string(),
+            label: label.to_string(),
+            hours,
+            is_critical,
+            is_exam,
+            counts_as_class_hours,
+            schedulable,
+            color: Some(color.to_string()),
+            sort_order: idx as i32 + 1,
+        })
+        .collect()
+}
+
+// Catálogo global, cargado desde la base de datos
+static ACTIVITY_CATALOG: Lazy<Mutex<Vec<ActivityType>>> = Lazy::new(|| {
+    Mutex::new(builtin_activity_types())
+});
+
+/// Reemplaza el catálogo en memoria (ordenado por `sort_order` y código)
+pub fn set_activity_types(mut types: Vec<ActivityType>) {
+    types.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.code.cmp(&b.code)));
+    let mut current = ACTIVITY_CATALOG.lock().unwrap();
+    *current = types;
+}
+
+/// Todos los tipos de actividad, en el orden del catálogo
+pub fn activity_types() -> Vec<ActivityType> {
+    ACTIVITY_CATALOG.lock().unwrap().clone()
+}
+
+/// Tipos que se pueden colocar en la cuadrícula de un fragmento
+pub fn schedulable_activity_types() -> Vec<ActivityType> {
+    activity_types().into_iter().filter(|t| t.schedulable).collect()
+}
+
+fn find_activity_type(activity: &str) -> Option<ActivityType> {
+    ACTIVITY_CATALOG.lock().unwrap().iter().find(|t| t.code == activity).cloned()
+}
+
+/// Indica si el código se puede colocar en la cuadrícula
+pub fn is_schedulable_type(activity: &str) -> bool {
+    find_activity_type(activity).is_some_and(|t| t.schedulable)
+}
+
+/// Nombre completo de un tipo de actividad (el propio código si no se conoce)
+pub fn activity_label(activity: &str) -> String {
+    find_activity_type(activity).map(|t| t.label).unwrap_or_else(|| activity.to_string())
+}
+
+/// Check if an activity type is critical (should be colored red)
+pub fn is_critical_type(activity: &str) -> bool {
+    find_activity_type(activity).is_some_and(|t| t.is_critical)
+}
+
+/// Indica si el tipo de actividad es una evaluación
+pub fn is_exam_type(activity: &str) -> bool {
+    find_activity_type(activity).is_some_and(|t| t.is_exam)
+}
+
+/// Horas presenciales que aporta una ocurrencia del tipo de actividad
+pub fn hours_for_type(activity: &str) -> i32 {
+    find_activity_type(activity)
+        .map(|t| t.class_hours())
+        .unwrap_or(DEFAULT_HOURS_PER_ACTIVITY)
+}
+
+/// Cantidades planificadas de una asignatura por código de actividad
+/// (filas de `asignatura_activity_plans`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ActivityPlan(BTreeMap<String, i32>);
+
+impl ActivityPlan {
+    /// Planned count for an activity code ("C", "CP", ...)
+    pub fn planned_count(&self, activity: &str) -> i32 {
+        self.0.get(activity).copied().unwrap_or(0)
+    }
+
+    /// Pares (código, cantidad) en orden de código
+    pub fn entries(&self) -> impl Iterator<Item = (&str, i32)> {
+        self.0.iter().map(|(code, planned)| (code.as_str(), *planned))
+    }
+
+    /// Códigos del plan que no están en el catálogo
+    pub fn unknown_codes(&self, catalog: &[ActivityType]) -> Vec<String> {
+        self.0
+            .keys()
+            .filter(|code| !catalog.iter().any(|t| &t.code == *code))
+            .cloned()
+            .collect()
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, i32)> for ActivityPlan {
+    fn from_iter<I: IntoIterator<Item = (S, i32)>>(iter: I) -> Self {
+        ActivityPlan(iter.into_iter().map(|(code, planned)| (code.into(), planned)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_catalog() {
+        let types = builtin_activity_types();
+        assert!(types.iter().all(|t| t.validate().is_ok()));
+
+        let grid: Vec<&str> = types.iter().filter(|t| t.schedulable).map(|t| t.code.as_str()).collect();
+        assert_eq!(grid, ["C", "CP", "S", "PL", "TE", "T", "PP", "EC"]);
+        assert_eq!(hours_for_type("TE"), 0);
+        assert_eq!(hours_for_type("XX"), DEFAULT_HOURS_PER_ACTIVITY);
+        assert!(is_critical_type("PP") && is_exam_type("PP"));
+        assert!(!is_schedulable_type("EF"));
+        assert_eq!(activity_label("CP"), "Clase Práctica");
+    }
+
+    #[test]
+    fn test_validate_activity_type() {
+        let mut visita = ActivityType {
+            code: "V".to_string(),
+            label: "Visita".to_string(),
+            hours: 4,
+            is_critical: false,
+            is_exam: false,
+            counts_as_class_hours: true,
+            schedulable: true,
+            color: Some("#a3e635".to_string()),
+            sort_order: 11,
+        };
+        assert!(visita.validate().is_ok());
+
+        visita.color = Some("verde".to_string());
+        assert!(visita.validate().is_err());
+        visita.color = None;
+        visita.code = "vis".to_string();
+        assert!(visita.validate().is_err());
+
+        let plan: ActivityPlan = [("C", 3), ("VIS", 1)].into_iter().collect();
+        assert_eq!(plan.planned_count("C"), 3);
+        assert_eq!(plan.unknown_codes(&builtin_activity_types()), vec!["VIS".to_string()]);
+    }
+}
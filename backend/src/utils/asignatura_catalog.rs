@@ -0,0 +1,353 @@
+//! Importación y exportación del catálogo de asignaturas (CSV y XLSX)
+//!
+//! Ambos formatos usan las mismas columnas (ver `catalog_columns`): una por
+//! cada tipo de actividad del catálogo, con su código en minúsculas. Al leer,
+//! las de actividades, `hours` y `weeks` son opcionales: si falta la columna o
+//! la celda está vacía, una asignatura existente conserva su valor.
+
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::collections::HashMap;
+
+use crate::database::asignaturas;
+use crate::utils::activity_types::{ActivityPlan, ActivityType};
+use crate::utils::table_import::{data_rows, RowError, TableHeader};
+use crate::utils::validation::validate_subject;
+
+/// Columnas obligatorias en la cabecera
+const REQUIRED_COLUMNS: &[&str] = &["name", "year", "semester", "leader_user_name"];
+
+/// Asignatura exportada: modelo, username de su jefe y plan de actividades
+pub type CatalogEntry = (asignaturas::Model, String, ActivityPlan);
+
+/// Columnas del catálogo, en el orden en que se exportan
+pub fn catalog_columns(activity_types: &[ActivityType]) -> Vec<String> {
+    REQUIRED_COLUMNS
+        .iter()
+        .map(|c| c.to_string())
+        .chain(activity_types.iter().map(|t| t.code.to_lowercase()))
+        .chain(["hours".to_string(), "weeks".to_string()])
+        .collect()
+}
+
+/// Fila del catálogo ya validada
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogRow {
+    /// Número de fila en el archivo (la cabecera es la 1)
+    pub row: usize,
+    pub name: String,
+    pub year: String,
+    pub semester: String,
+    pub leader_user_name: String,
+    /// Cantidades planificadas (solo las columnas con valor)
+    pub plan: ActivityPlan,
+    pub hours: Option<i32>,
+    pub weeks: Option<i32>,
+}
+
+impl CatalogRow {
+    /// Clave de upsert: (nombre, año, semestre)
+    pub fn key(&self) -> (String, String, String) {
+        (self.name.clone(), self.year.clone(), self.semester.clone())
+    }
+
+    /// Copia las horas y semanas indicadas al modelo (el plan se guarda aparte).
+    /// Las vacías no cambian una asignatura existente; una nueva empieza sin horas
+    pub fn apply_to(&self, model: &mut asignaturas::ActiveModel) {
+        use sea_orm::{ActiveValue, Set};
+        if let Some(hours) = self.hours {
+            model.hours = Set(hours);
+        } else if matches!(model.hours, ActiveValue::NotSet) {
+            model.hours = Set(0);
+        }
+        if let Some(weeks) = self.weeks {
+            model.weeks = Set(Some(weeks));
+        }
+    }
+
+    /// Plan de una asignatura existente con las cantidades de la fila encima
+    pub fn merged_plan(&self, existing: &ActivityPlan) -> ActivityPlan {
+        existing
+            .entries()
+            .filter(|(code, _)| self.plan.entries().all(|(c, _)| c != *code))
+            .chain(self.plan.entries())
+            .collect()
+    }
+}
+
+// ============================================================================
+// LECTURA
+// ============================================================================
+
+fn parse_number(value: &str, column: &str) -> Result<Option<i32>, String> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<i32>() {
+        Ok(n) if n >= 0 => Ok(Some(n)),
+        _ => Err(format!("'{}' no es un número válido en la columna '{}'", value, column)),
+    }
+}
+
+/// Convierte la tabla en filas del catálogo. Las filas vacías se ignoran y
+/// cada fila con errores se informa aparte
+pub fn parse_catalog(
+    table: &[Vec<String>],
+    activity_types: &[ActivityType],
+) -> Result<(Vec<CatalogRow>, Vec<RowError>), String> {
+    let (header, records) = TableHeader::split(table, REQUIRED_COLUMNS)?;
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (row, record) in data_rows(records) {
+        let field = |name: &str| header.field(record, name);
+
+        let parsed = (|| {
+            let name = field("name");
+            let validation = validate_subject(name);
+            if !validation.valid {
+                return Err(validation.error.unwrap_or_else(|| "Nombre de asignatura inválido".to_string()));
+            }
+            for column in ["year", "semester", "leader_user_name"] {
+                if field(column).is_empty() {
+                    return Err(format!("La columna '{}' es obligatoria", column));
+                }
+            }
+
+            let mut plan = Vec::new();
+            for activity in activity_types {
+                let column = activity.code.to_lowercase();
+                if let Some(planned) = parse_number(field(&column), &column)? {
+                    plan.push((activity.code.clone(), planned));
+                }
+            }
+            let weeks = parse_number(field("weeks"), "weeks")?;
+            if weeks == Some(0) {
+                return Err("El número de semanas debe ser mayor que 0".to_string());
+            }
+
+            Ok(CatalogRow {
+                row,
+                name: name.to_string(),
+                year: field("year").to_string(),
+                semester: field("semester").to_string(),
+                leader_user_name: field("leader_user_name").to_string(),
+                plan: plan.into_iter().collect(),
+                hours: parse_number(field("hours"), "hours")?,
+                weeks,
+            })
+        })();
+
+        match parsed {
+            Ok(r) => rows.push(r),
+            Err(message) => errors.push(RowError::new(row, message)),
+        }
+    }
+    Ok((rows, errors))
+}
+
+/// Descarta las filas cuyo jefe de asignatura ya dirige otra asignatura
+/// (`asignaturas.leader_id` es único): en la base de datos (`leading`, clave de
+/// la asignatura que dirige cada jefe) o en una fila anterior del archivo
+pub fn check_unique_leaders(
+    planned: Vec<(CatalogRow, i32)>,
+    leading: &HashMap<i32, (String, String, String)>,
+) -> (Vec<(CatalogRow, i32)>, Vec<RowError>) {
+    let mut accepted: Vec<(CatalogRow, i32)> = Vec::new();
+    let mut errors = Vec::new();
+    for (row, leader_id) in planned {
+        if let Some((name, year, semester)) = leading.get(&leader_id).filter(|key| **key != row.key()) {
+            errors.push(RowError::new(row.row, format!(
+                "El jefe de asignatura '{}' ya dirige '{}' ({} año, semestre {})",
+                row.leader_user_name, name, year, semester
+            )));
+            continue;
+        }
+        if let Some((previous, _)) = accepted.iter().find(|(_, id)| *id == leader_id) {
+            errors.push(RowError::new(row.row, format!(
+                "El jefe de asignatura '{}' ya está asignado a '{}' en la fila {}",
+                row.leader_user_name, previous.name, previous.row
+            )));
+            continue;
+        }
+        accepted.push((row, leader_id));
+    }
+    (accepted, errors)
+}
+
+// ============================================================================
+// ESCRITURA
+// ============================================================================
+
+/// Valores de una asignatura en el orden de `catalog_columns`
+fn catalog_values(entry: &CatalogEntry, activity_types: &[ActivityType]) -> Vec<String> {
+    let (asignatura, leader_user_name, plan) = entry;
+    let planned = |code: &str| plan.entries().find(|(c, _)| *c == code).map(|(_, n)| n.to_string()).unwrap_or_default();
+    vec![
+        asignatura.name.clone(),
+        asignatura.year.clone(),
+        asignatura.semester.clone(),
+        leader_user_name.to_string(),
+    ]
+    .into_iter()
+    .chain(activity_types.iter().map(|t| planned(&t.code)))
+    .chain([asignatura.hours.to_string(), asignatura.weeks.map(|n| n.to_string()).unwrap_or_default()])
+    .collect()
+}
+
+/// Catálogo en CSV (UTF-8, separado por comas)
+pub fn write_catalog_csv(entries: &[CatalogEntry], activity_types: &[ActivityType]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(catalog_columns(activity_types))?;
+    for entry in entries {
+        writer.write_record(catalog_values(entry, activity_types))?;
+    }
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+/// Catálogo en XLSX, con una hoja "Asignaturas"
+pub fn write_catalog_xlsx(entries: &[CatalogEntry], activity_types: &[ActivityType]) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Asignaturas")?;
+    let bold = Format::new().set_bold();
+
+    for (col, title) in catalog_columns(activity_types).iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, title, &bold)?;
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        let row = i as u32 + 1;
+        for (col, value) in catalog_values(entry, activity_types).into_iter().enumerate() {
+            // Las columnas numéricas se escriben como números para poder operar con ellas
+            match value.parse::<f64>() {
+                Ok(n) if col >= 4 => sheet.write_number(row, col as u16, n)?,
+                _ => sheet.write_string(row, col as u16, value)?,
+            };
+        }
+    }
+    sheet.set_column_width(0, 40)?;
+    sheet.set_column_width(3, 20)?;
+
+    workbook.save_to_buffer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::activity_types::builtin_activity_types;
+    use crate::utils::table_import::read_table;
+
+    fn entry() -> CatalogEntry {
+        let now = chrono::Utc::now().naive_utc();
+        let asignatura = asignaturas::Model {
+            id: 1,
+            leader_id: 2,
+            name: "Álgebra Lineal".to_string(),
+            year: "1ro".to_string(),
+            semester: "1".to_string(),
+            hours: 64,
+            date_start: now,
+            date_end: now,
+            weeks: Some(15),
+        };
+        let plan = [("C", 10), ("CP", 8), ("TE", 2), ("PP", 1)].into_iter().collect();
+        (asignatura, "jperez".to_string(), plan)
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let types = builtin_activity_types();
+        let csv = write_catalog_csv(&[entry()], &types).unwrap();
+        let (rows, errors) = parse_catalog(&read_table(csv).unwrap(), &types).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].row, 2);
+        assert_eq!(rows[0].name, "Álgebra Lineal");
+        assert_eq!(rows[0].leader_user_name, "jperez");
+        assert_eq!(rows[0].plan, entry().2);
+        assert_eq!((rows[0].hours, rows[0].weeks), (Some(64), Some(15)));
+    }
+
+    #[test]
+    fn test_xlsx_roundtrip() {
+        let types = builtin_activity_types();
+        let xlsx = write_catalog_xlsx(&[entry()], &types).unwrap();
+        let (rows, errors) = parse_catalog(&read_table(xlsx).unwrap(), &types).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(rows[0].plan.planned_count("TE"), 2);
+        assert_eq!(rows[0].weeks, Some(15));
+    }
+
+    #[test]
+    fn test_upsert_keeps_blank_columns() {
+        use sea_orm::{ActiveValue, Set};
+
+        // Solo cambia el jefe y la cantidad de conferencias
+        let csv = "name,year,semester,leader_user_name,c,cp,hours\n\
+                   Álgebra Lineal,1ro,1,mlopez,12,,\n";
+        let types = builtin_activity_types();
+        let (rows, _) = parse_catalog(&read_table(csv.as_bytes().to_vec()).unwrap(), &types).unwrap();
+        let row = &rows[0];
+        assert_eq!((row.hours, row.weeks), (None, None));
+
+        let (asignatura, _, plan) = entry();
+        let mut active: asignaturas::ActiveModel = asignatura.into();
+        row.apply_to(&mut active);
+        assert_eq!(active.hours, ActiveValue::Unchanged(64));
+        assert_eq!(active.weeks, ActiveValue::Unchanged(Some(15)));
+
+        let merged = row.merged_plan(&plan);
+        assert_eq!(merged.planned_count("C"), 12);
+        assert_eq!(merged.planned_count("CP"), 8);
+        assert_eq!(merged.planned_count("TE"), 2);
+
+        // Una asignatura nueva sin horas empieza en 0
+        let mut new = asignaturas::ActiveModel::default();
+        row.apply_to(&mut new);
+        assert_eq!(new.hours, Set(0));
+    }
+
+    #[test]
+    fn test_check_unique_leaders() {
+        let csv = "name,year,semester,leader_user_name\n\
+                   Álgebra Lineal,1ro,1,jperez\n\
+                   Física,1ro,1,mlopez\n\
+                   Química,1ro,1,mlopez\n\
+                   Cálculo,1ro,2,alopez\n";
+        let types = builtin_activity_types();
+        let (rows, _) = parse_catalog(&read_table(csv.as_bytes().to_vec()).unwrap(), &types).unwrap();
+        let ids = [2, 3, 3, 4];
+        let planned = rows.into_iter().zip(ids).collect();
+
+        // jperez ya dirige Álgebra Lineal (la misma fila) y alopez dirige Programación
+        let key = |name: &str, year: &str, semester: &str| (name.to_string(), year.to_string(), semester.to_string());
+        let leading = HashMap::from([
+            (2, key("Álgebra Lineal", "1ro", "1")),
+            (4, key("Programación", "1ro", "2")),
+        ]);
+        let (accepted, errors) = check_unique_leaders(planned, &leading);
+
+        assert_eq!(accepted.iter().map(|(r, _)| r.name.as_str()).collect::<Vec<_>>(), vec!["Álgebra Lineal", "Física"]);
+        assert_eq!(errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![4, 5]);
+        assert!(errors[0].message.contains("fila 3"));
+        assert!(errors[1].message.contains("Programación"));
+    }
+
+    #[test]
+    fn test_parse_catalog_reports_row_errors() {
+        let csv = "Name;Year;Semester;Leader_User_Name;C;Hours\n\
+                   Física;1ro;1;jperez;-3;40\n\
+                   ;;;;;\n\
+                   Química;2do;;mlopez;;\n\
+                   Cálculo;1ro;2;jperez;12;\n";
+        let types = builtin_activity_types();
+        let (rows, errors) = parse_catalog(&read_table(csv.as_bytes().to_vec()).unwrap(), &types).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].row, 5);
+        assert_eq!(rows[0].plan.planned_count("C"), 12);
+        assert_eq!(errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![2, 4]);
+        assert!(parse_catalog(&read_table(b"name,year\n".to_vec()).unwrap(), &types).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! Ciclo de vida de un balance de carga
+//!
+//! draft → open → in_review → approved → archived, y `reopen` para devolver a
+//! `open` un balance en revisión o aprobado. Cada transición tiene roles
+//! permitidos y precondiciones; el estado solo cambia a través de este módulo.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Estado de un balance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceStatus {
+    Draft,
+    Open,
+    InReview,
+    Approved,
+    Archived,
+}
+
+impl BalanceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BalanceStatus::Draft => "draft",
+            BalanceStatus::Open => "open",
+            BalanceStatus::InReview => "in_review",
+            BalanceStatus::Approved => "approved",
+            BalanceStatus::Archived => "archived",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "draft" => Some(BalanceStatus::Draft),
+            "open" => Some(BalanceStatus::Open),
+            "in_review" => Some(BalanceStatus::InReview),
+            "approved" => Some(BalanceStatus::Approved),
+            "archived" => Some(BalanceStatus::Archived),
+            _ => None,
+        }
+    }
+
+    /// Nombre para mostrar en documentos exportados
+    pub fn label(&self) -> &'static str {
+        match self {
+            BalanceStatus::Draft => "Borrador",
+            BalanceStatus::Open => "Abierto",
+            BalanceStatus::InReview => "En revisión",
+            BalanceStatus::Approved => "Aprobado",
+            BalanceStatus::Archived => "Archivado",
+        }
+    }
+
+    /// Los fragmentos solo se editan mientras el balance está en preparación o abierto
+    pub fn allows_fragment_edits(&self) -> bool {
+        matches!(self, BalanceStatus::Draft | BalanceStatus::Open)
+    }
+
+    /// Un balance aprobado o archivado no admite añadir ni retirar asignaturas;
+    /// el Leader tiene que reabrirlo antes
+    pub fn allows_subject_changes(&self) -> bool {
+        !matches!(self, BalanceStatus::Approved | BalanceStatus::Archived)
+    }
+}
+
+impl fmt::Display for BalanceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Acción que provoca una transición de estado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceAction {
+    Open,
+    SubmitForReview,
+    Approve,
+    Archive,
+    Reopen,
+}
+
+impl BalanceAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BalanceAction::Open => "open",
+            BalanceAction::SubmitForReview => "submit_for_review",
+            BalanceAction::Approve => "approve",
+            BalanceAction::Archive => "archive",
+            BalanceAction::Reopen => "reopen",
+        }
+    }
+
+    /// Estados desde los que se permite la acción
+    pub fn allowed_from(&self) -> &'static [BalanceStatus] {
+        match self {
+            BalanceAction::Open => &[BalanceStatus::Draft],
+            BalanceAction::SubmitForReview => &[BalanceStatus::Open],
+            BalanceAction::Approve => &[BalanceStatus::InReview],
+            BalanceAction::Archive => &[BalanceStatus::Approved],
+            BalanceAction::Reopen => &[BalanceStatus::InReview, BalanceStatus::Approved],
+        }
+    }
+
+    /// Estado resultante
+    pub fn target(&self) -> BalanceStatus {
+        match self {
+            BalanceAction::Open | BalanceAction::Reopen => BalanceStatus::Open,
+            BalanceAction::SubmitForReview => BalanceStatus::InReview,
+            BalanceAction::Approve => BalanceStatus::Approved,
+            BalanceAction::Archive => BalanceStatus::Archived,
+        }
+    }
+
+    /// Roles que pueden ejecutar la acción
+    pub fn allowed_roles(&self) -> &'static [&'static str] {
+        match self {
+            BalanceAction::Archive => &["leader", "admin"],
+            _ => &["leader"],
+        }
+    }
+}
+
+impl fmt::Display for BalanceAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Datos del balance necesarios para evaluar las precondiciones
+#[derive(Debug, Clone, Default)]
+pub struct TransitionContext {
+    pub total_fragments: usize,
+    pub completed_fragments: usize,
+    /// Violaciones de las reglas semanales (ver `balance_rules`)
+    pub rule_violations: usize,
+}
+
+/// Motivo por el que se rechaza una transición
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    /// La acción no es válida desde el estado actual
+    InvalidTransition { from: BalanceStatus, action: BalanceAction },
+    /// El rol del usuario no puede ejecutar la acción
+    Forbidden { action: BalanceAction },
+    /// No se cumplen las precondiciones de la transición
+    PreconditionFailed(String),
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitionError::InvalidTransition { from, action } => {
+                write!(f, "No se puede aplicar '{}' a un balance en estado '{}'", action, from)
+            }
+            TransitionError::Forbidden { action } => {
+                write!(f, "No tienes permiso para aplicar '{}' a este balance", action)
+            }
+            TransitionError::PreconditionFailed(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// Valida una transición y devuelve el nuevo estado
+pub fn plan_transition(
+    from: BalanceStatus,
+    action: BalanceAction,
+    role: &str,
+    ctx: &TransitionContext,
+) -> Result<BalanceStatus, TransitionError> {
+    if !action.allowed_from().contains(&from) {
+        return Err(TransitionError::InvalidTransition { from, action });
+    }
+
+    if !action.allowed_roles().contains(&role) {
+        return Err(TransitionError::Forbidden { action });
+    }
+
+    check_preconditions(action, ctx)?;
+    Ok(action.target())
+}
+
+/// Estado del balance tras añadir o retirar asignaturas.
+/// Un balance en revisión vuelve a `open` si deja de tener todos los fragmentos
+/// completados; en el resto de casos el estado no cambia (un balance aprobado
+/// no llega aquí, ver `allows_subject_changes`).
+pub fn status_after_subject_change(from: BalanceStatus, ctx: &TransitionContext) -> BalanceStatus {
+    let all_completed = ctx.total_fragments > 0 && ctx.completed_fragments == ctx.total_fragments;
+    match from {
+        BalanceStatus::InReview if !all_completed => BalanceStatus::Open,
+        status => status,
+    }
+}
+
+fn check_preconditions(action: BalanceAction, ctx: &TransitionContext) -> Result<(), TransitionError> {
+    let all_completed = ctx.total_fragments > 0 && ctx.completed_fragments == ctx.total_fragments;
+
+    match action {
+        BalanceAction::Open if ctx.total_fragments == 0 => Err(TransitionError::PreconditionFailed(
+            "El balance no tiene asignaturas".to_string(),
+        )),
+        BalanceAction::SubmitForReview | BalanceAction::Approve if !all_completed => {
+            Err(TransitionError::PreconditionFailed(format!(
+                "Todos los fragmentos deben estar completados ({} de {})",
+                ctx.completed_fragments, ctx.total_fragments
+            )))
+        }
+        BalanceAction::Approve if ctx.rule_violations > 0 => Err(TransitionError::PreconditionFailed(format!(
+            "El balance incumple {} regla(s) de la facultad",
+            ctx.rule_violations
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(total: usize, completed: usize) -> TransitionContext {
+        TransitionContext { total_fragments: total, completed_fragments: completed, rule_violations: 0 }
+    }
+
+    #[test]
+    fn test_full_lifecycle() {
+        let done = ctx(2, 2);
+        let mut status = BalanceStatus::Draft;
+        for action in [
+            BalanceAction::Open,
+            BalanceAction::SubmitForReview,
+            BalanceAction::Approve,
+            BalanceAction::Archive,
+        ] {
+            status = plan_transition(status, action, "leader", &done).unwrap();
+        }
+        assert_eq!(status, BalanceStatus::Archived);
+    }
+
+    #[test]
+    fn test_rejected_transitions() {
+        assert_eq!(
+            plan_transition(BalanceStatus::Draft, BalanceAction::Approve, "leader", &ctx(1, 1)),
+            Err(TransitionError::InvalidTransition { from: BalanceStatus::Draft, action: BalanceAction::Approve })
+        );
+        assert_eq!(
+            plan_transition(BalanceStatus::InReview, BalanceAction::Approve, "subjectLeader", &ctx(1, 1)),
+            Err(TransitionError::Forbidden { action: BalanceAction::Approve })
+        );
+        assert!(matches!(
+            plan_transition(BalanceStatus::Open, BalanceAction::SubmitForReview, "leader", &ctx(3, 2)),
+            Err(TransitionError::PreconditionFailed(_))
+        ));
+        assert!(plan_transition(BalanceStatus::Archived, BalanceAction::Reopen, "leader", &ctx(1, 1)).is_err());
+    }
+
+    #[test]
+    fn test_status_after_subject_change() {
+        assert_eq!(status_after_subject_change(BalanceStatus::InReview, &ctx(3, 2)), BalanceStatus::Open);
+        assert_eq!(status_after_subject_change(BalanceStatus::InReview, &ctx(2, 2)), BalanceStatus::InReview);
+        assert_eq!(status_after_subject_change(BalanceStatus::Draft, &ctx(0, 0)), BalanceStatus::Draft);
+        assert_eq!(status_after_subject_change(BalanceStatus::Open, &ctx(2, 0)), BalanceStatus::Open);
+    }
+
+    #[test]
+    fn test_approved_balance_rejects_subject_changes() {
+        assert!(!BalanceStatus::Approved.allows_subject_changes());
+        assert!(!BalanceStatus::Archived.allows_subject_changes());
+        assert!(BalanceStatus::InReview.allows_subject_changes());
+        assert!(BalanceStatus::Draft.allows_subject_changes());
+    }
+}
@@ -0,0 +1,176 @@
+//! Motor de reglas semanales del balance de carga
+//!
+//! Evalúa todos los fragmentos de un balance en conjunto, semana a semana,
+//! y devuelve las violaciones de las reglas de la facultad de forma
+//! estructurada (semana, día, asignaturas implicadas y regla incumplida).
+
+use serde::Serialize;
+
+use crate::utils::activity_types::is_critical_type;
+use crate::utils::fragment_data::FragmentData;
+
+/// Máximo de actividades críticas (T, TE, PP) permitidas en una misma semana
+pub const MAX_CRITICAL_PER_WEEK: usize = 2;
+
+/// Identificador de cada regla evaluada
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleId {
+    /// No puede haber 3 o más actividades críticas en la misma semana
+    MaxCriticalPerWeek,
+}
+
+/// Distribución semanal de una asignatura dentro del balance
+#[derive(Debug, Clone)]
+pub struct ScheduledSubject {
+    pub asignatura_id: i32,
+    pub name: String,
+    pub data: FragmentData,
+}
+
+/// Actividad concreta que participa en una violación
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ViolationEntry {
+    pub asignatura_id: i32,
+    pub asignatura_name: String,
+    pub day: usize,        // 1-based
+    pub activity: String,
+}
+
+/// Violación de una regla del balance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleViolation {
+    pub rule: RuleId,
+    pub week: usize,             // 1-based
+    pub day: Option<usize>,      // 1-based, si todas las actividades implicadas caen el mismo día
+    pub subjects: Vec<ViolationEntry>,
+    pub message: String,
+}
+
+impl RuleViolation {
+    /// Indica si la asignatura participa en la violación
+    pub fn involves(&self, asignatura_id: i32) -> bool {
+        self.subjects.iter().any(|s| s.asignatura_id == asignatura_id)
+    }
+}
+
+/// Evalúa todas las reglas sobre el conjunto de asignaturas del balance
+pub fn evaluate_balance(subjects: &[ScheduledSubject]) -> Vec<RuleViolation> {
+    let total_weeks = subjects
+        .iter()
+        .map(|s| s.data.weeks.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut violations = Vec::new();
+    for week_idx in 0..total_weeks {
+        if let Some(violation) = check_max_critical_per_week(subjects, week_idx) {
+            violations.push(violation);
+        }
+    }
+    violations
+}
+
+/// Regla: no más de MAX_CRITICAL_PER_WEEK actividades críticas en una semana
+fn check_max_critical_per_week(subjects: &[ScheduledSubject], week_idx: usize) -> Option<RuleViolation> {
+    let mut entries = Vec::new();
+
+    for subject in subjects {
+        let Some(week_data) = subject.data.weeks.get(week_idx) else {
+            continue;
+        };
+        for (day_idx, activity) in week_data.iter().enumerate() {
+            if let Some(act) = activity
+                && is_critical_type(act)
+            {
+                entries.push(ViolationEntry {
+                    asignatura_id: subject.asignatura_id,
+                    asignatura_name: subject.name.clone(),
+                    day: day_idx + 1,
+                    activity: act.clone(),
+                });
+            }
+        }
+    }
+
+    if entries.len() <= MAX_CRITICAL_PER_WEEK {
+        return None;
+    }
+
+    entries.sort_by_key(|e| e.day);
+    let day = entries.first().map(|e| e.day).filter(|&d| entries.iter().all(|e| e.day == d));
+    Some(RuleViolation {
+        rule: RuleId::MaxCriticalPerWeek,
+        week: week_idx + 1,
+        day,
+        message: format!(
+            "La semana {} tiene {} actividades críticas (T, TE, PP); el máximo permitido es {}",
+            week_idx + 1,
+            entries.len(),
+            MAX_CRITICAL_PER_WEEK
+        ),
+        subjects: entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(id: i32, weeks: Vec<Vec<&str>>) -> ScheduledSubject {
+        ScheduledSubject {
+            asignatura_id: id,
+            name: format!("Asignatura {}", id),
+            data: FragmentData {
+                weeks: weeks
+                    .into_iter()
+                    .map(|w| w.into_iter().map(|a| (!a.is_empty()).then(|| a.to_string())).collect())
+                    .collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_two_critical_in_week_is_allowed() {
+        let subjects = vec![
+            subject(1, vec![vec!["T", "C", "", ""]]),
+            subject(2, vec![vec!["", "PP", "", ""]]),
+        ];
+        assert!(evaluate_balance(&subjects).is_empty());
+    }
+
+    #[test]
+    fn test_three_critical_across_subjects_in_week() {
+        let subjects = vec![
+            subject(1, vec![vec!["C", "", "", ""], vec!["T", "", "", ""]]),
+            subject(2, vec![vec!["", "", "", ""], vec!["", "PP", "", ""]]),
+            subject(3, vec![vec!["", "", "", ""], vec!["", "", "", "TE"]]),
+        ];
+
+        let violations = evaluate_balance(&subjects);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, RuleId::MaxCriticalPerWeek);
+        assert_eq!(violations[0].week, 2);
+        assert_eq!(violations[0].subjects.len(), 3);
+        assert_eq!(violations[0].subjects[2].day, 4);
+        assert_eq!(violations[0].day, None);
+        assert!(violations[0].involves(3));
+        assert!(!violations[0].involves(4));
+    }
+
+    #[test]
+    fn test_violation_on_a_single_day() {
+        let subjects = vec![
+            subject(1, vec![vec!["", "T", "", ""]]),
+            subject(2, vec![vec!["", "PP", "", ""]]),
+            subject(3, vec![vec!["", "TE", "", ""]]),
+        ];
+
+        let violations = evaluate_balance(&subjects);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].day, Some(2));
+    }
+}
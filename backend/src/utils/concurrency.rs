@@ -0,0 +1,85 @@
+//! Control de concurrencia optimista
+//!
+//! Balances y fragmentos tienen una columna `version` que se incrementa en cada
+//! escritura. Los GET devuelven la versión en la cabecera `ETag` y las escrituras
+//! pueden enviarla en `If-Match` (o en el campo `version` del body); si no
+//! coincide con la del servidor la escritura se rechaza con 409 Conflict.
+
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::{self, Responder, Response};
+use std::num::ParseIntError;
+
+/// Formatea una versión como ETag fuerte: `"3"`
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// Interpreta el valor de `If-Match`: `"3"`, `W/"3"` o `3`.
+/// `*` equivale a no exigir versión.
+pub fn parse_if_match(value: &str) -> Result<Option<i32>, ParseIntError> {
+    let value = value.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    let value = value.strip_prefix("W/").unwrap_or(value);
+    value.trim_matches('"').parse::<i32>().map(Some)
+}
+
+/// Versión esperada por el cliente: la cabecera tiene prioridad sobre el body
+pub fn expected_version(if_match: &IfMatch, body_version: Option<i32>) -> Option<i32> {
+    if_match.0.or(body_version)
+}
+
+/// Cabecera `If-Match` opcional
+pub struct IfMatch(pub Option<i32>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfMatch {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.headers().get_one("If-Match") {
+            None => Outcome::Success(IfMatch(None)),
+            Some(value) => match parse_if_match(value) {
+                Ok(version) => Outcome::Success(IfMatch(version)),
+                Err(_) => Outcome::Error((Status::BadRequest, ())),
+            },
+        }
+    }
+}
+
+/// Respuesta con cabecera `ETag` con la versión del recurso
+pub struct WithETag<R> {
+    pub inner: R,
+    pub version: i32,
+}
+
+impl<'r, R: Responder<'r, 'static>> Responder<'r, 'static> for WithETag<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        Response::build_from(self.inner.respond_to(request)?)
+            .raw_header("ETag", etag(self.version))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_if_match() {
+        assert_eq!(parse_if_match("\"3\""), Ok(Some(3)));
+        assert_eq!(parse_if_match("W/\"12\""), Ok(Some(12)));
+        assert_eq!(parse_if_match("7"), Ok(Some(7)));
+        assert_eq!(parse_if_match("*"), Ok(None));
+        assert!(parse_if_match("\"abc\"").is_err());
+    }
+
+    #[test]
+    fn test_expected_version_prefers_header() {
+        assert_eq!(expected_version(&IfMatch(Some(2)), Some(5)), Some(2));
+        assert_eq!(expected_version(&IfMatch(None), Some(5)), Some(5));
+        assert_eq!(expected_version(&IfMatch(None), None), None);
+    }
+}
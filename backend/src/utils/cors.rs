@@ -30,11 +30,8 @@ impl Fairing for CORS {
         // Lógica para manejar CORS dinámico o wildcard
         let origin_to_allow = if let Some(origin) = request_origin {
             // Si la configuración es "*", permitimos el origen de la petición (para soportar credenciales)
-            if allowed_origins == "*" {
-                origin
-            } 
             // Si hay una lista separada por comas, verificamos si el origen está en la lista
-            else if allowed_origins.split(',').any(|o| o.trim() == origin) {
+            if allowed_origins == "*" || allowed_origins.split(',').any(|o| o.trim() == origin) {
                 origin
             } 
             // Si no coincide, devolvemos la configuración tal cual (fallará si no coincide)
@@ -53,8 +50,9 @@ impl Fairing for CORS {
         ));
         response.set_header(Header::new(
             "Access-Control-Allow-Headers",
-            "Content-Type, Authorization",
+            "Content-Type, Authorization, If-Match",
         ));
+        response.set_header(Header::new("Access-Control-Expose-Headers", "ETag"));
         response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
 
         // Si es una petición OPTIONS, aseguramos que el status sea 204 o 200
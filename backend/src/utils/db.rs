@@ -178,8 +178,11 @@ pub async fn change_user_password(
 // ============================================================================
 
 use crate::asignaturas;
+use crate::database::asignatura_activity_plans;
 use crate::routes::manager::{CreateAsignaturaRequest, UpdateAsignaturaRequest};
+use crate::utils::activity_types::ActivityPlan;
 use chrono::Utc;
+use std::collections::HashMap;
 
 /// Crear asignatura - busca el subject leader por username y crea con datos por defecto
 pub async fn create_asignatura(
@@ -205,16 +208,6 @@ pub async fn create_asignatura(
         name: Set(data.name.clone()),
         year: Set(data.year.clone()),
         semester: Set(data.semester.clone()),
-        c: Set(None),
-        cp: Set(None),
-        s: Set(None),
-        pl: Set(None),
-        te: Set(None),
-        t: Set(None),
-        pp: Set(None),
-        ec: Set(None),
-        tc: Set(None),
-        ef: Set(None),
         hours: Set(0),
         weeks: Set(Some(15)),
         date_start: Set(Utc::now().naive_utc()),
@@ -254,7 +247,7 @@ pub async fn update_asignatura(
     asignatura_id: i32,
     data: &UpdateAsignaturaRequest,
 ) -> Result<(), sea_orm::DbErr> {
-    use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+    use sea_orm::{ActiveModelTrait, EntityTrait, Set, TransactionTrait};
 
     // Buscar la asignatura
     let asignatura = asignaturas::Entity::find_by_id(asignatura_id)
@@ -262,25 +255,74 @@ pub async fn update_asignatura(
         .await?
         .ok_or(sea_orm::DbErr::RecordNotFound("Asignatura no encontrada".to_string()))?;
 
-    // Actualizar asignatura
+    // Actualizar asignatura y su plan de actividades en una sola transacción
+    let txn = db.begin().await?;
     let mut asignatura_active: asignaturas::ActiveModel = asignatura.into();
     asignatura_active.name = Set(data.name.clone());
     asignatura_active.year = Set(data.year.clone());
     asignatura_active.semester = Set(data.semester.clone());
-    asignatura_active.c = Set(data.c);
-    asignatura_active.cp = Set(data.cp);
-    asignatura_active.s = Set(data.s);
-    asignatura_active.pl = Set(data.pl);
-    asignatura_active.te = Set(data.te);
-    asignatura_active.t = Set(data.t);
-    asignatura_active.pp = Set(data.pp);
-    asignatura_active.ec = Set(data.ec);
-    asignatura_active.tc = Set(data.tc);
-    asignatura_active.ef = Set(data.ef);
     asignatura_active.hours = Set(data.hours);
     asignatura_active.weeks = Set(data.weeks);
 
-    asignatura_active.update(db).await?;
+    asignatura_active.update(&txn).await?;
+    if let Some(plan) = &data.plan {
+        replace_activity_plan(&txn, asignatura_id, plan).await?;
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Planes de actividades de las asignaturas indicadas (las que no tienen filas no aparecen)
+pub async fn load_activity_plans<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    asignatura_ids: &[i32],
+) -> Result<HashMap<i32, ActivityPlan>, sea_orm::DbErr> {
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    let rows = asignatura_activity_plans::Entity::find()
+        .filter(asignatura_activity_plans::Column::AsignaturaId.is_in(asignatura_ids.iter().copied()))
+        .all(db)
+        .await?;
+
+    let mut grouped: HashMap<i32, Vec<(String, i32)>> = HashMap::new();
+    for row in rows {
+        grouped.entry(row.asignatura_id).or_default().push((row.activity_code, row.planned));
+    }
+    Ok(grouped.into_iter().map(|(id, entries)| (id, entries.into_iter().collect())).collect())
+}
+
+/// Plan de actividades de una asignatura (vacío si no tiene)
+pub async fn load_activity_plan<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    asignatura_id: i32,
+) -> Result<ActivityPlan, sea_orm::DbErr> {
+    Ok(load_activity_plans(db, &[asignatura_id]).await?.remove(&asignatura_id).unwrap_or_default())
+}
+
+/// Sustituye el plan de actividades de una asignatura
+pub async fn replace_activity_plan<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    asignatura_id: i32,
+    plan: &ActivityPlan,
+) -> Result<(), sea_orm::DbErr> {
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, Set};
+
+    asignatura_activity_plans::Entity::delete_many()
+        .filter(asignatura_activity_plans::Column::AsignaturaId.eq(asignatura_id))
+        .exec(db)
+        .await?;
+
+    let rows: Vec<asignatura_activity_plans::ActiveModel> = plan
+        .entries()
+        .map(|(code, planned)| asignatura_activity_plans::ActiveModel {
+            asignatura_id: Set(asignatura_id),
+            activity_code: Set(code.to_string()),
+            planned: Set(planned),
+        })
+        .collect();
+    if !rows.is_empty() {
+        asignatura_activity_plans::Entity::insert_many(rows).exec(db).await?;
+    }
     Ok(())
 }
 
@@ -310,4 +352,75 @@ pub async fn list_subject_leaders(
         .filter(usuarios::Column::Role.eq("subjectLeader"))
         .all(db)
         .await
-}
\ No newline at end of file
+}
+/// Jefe de asignatura por id (None si no existe o no tiene el rol `subjectLeader`)
+pub async fn find_subject_leader<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+) -> Result<Option<usuarios::Model>, sea_orm::DbErr> {
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    usuarios::Entity::find_by_id(user_id)
+        .filter(usuarios::Column::Role.eq("subjectLeader"))
+        .one(db)
+        .await
+}
+
+/// Asigna el jefe de asignatura a todos los fragmentos de la asignatura en
+/// balances no archivados. Devuelve los ids de los fragmentos que cambiaron
+pub async fn propagate_subject_leader<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    asignatura_id: i32,
+    subject_leader_id: i32,
+) -> Result<Vec<i32>, sea_orm::DbErr> {
+    use crate::database::{balance_fragments, balances};
+    use sea_orm::sea_query::{Expr, Query};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QuerySelect};
+
+    let active_balances = Query::select()
+        .column(balances::Column::Id)
+        .from(balances::Entity)
+        .and_where(balances::Column::Status.ne("archived"))
+        .to_owned();
+
+    let fragment_ids: Vec<i32> = balance_fragments::Entity::find()
+        .select_only()
+        .column(balance_fragments::Column::Id)
+        .filter(balance_fragments::Column::AsignaturaId.eq(asignatura_id))
+        .filter(balance_fragments::Column::SubjectLeaderId.ne(subject_leader_id).or(balance_fragments::Column::SubjectLeaderId.is_null()))
+        .filter(balance_fragments::Column::BalanceId.in_subquery(active_balances))
+        .into_tuple()
+        .all(db)
+        .await?;
+    if fragment_ids.is_empty() {
+        return Ok(fragment_ids);
+    }
+
+    balance_fragments::Entity::update_many()
+        .col_expr(balance_fragments::Column::SubjectLeaderId, Expr::value(subject_leader_id))
+        .col_expr(balance_fragments::Column::Version, Expr::col(balance_fragments::Column::Version).add(1))
+        .col_expr(balance_fragments::Column::UpdatedAt, Expr::value(Utc::now().naive_utc()))
+        .filter(balance_fragments::Column::Id.is_in(fragment_ids.clone()))
+        .exec(db)
+        .await?;
+    Ok(fragment_ids)
+}
+
+/// Cambia el jefe de una asignatura y lo propaga a sus fragmentos abiertos
+/// (ver `propagate_subject_leader`). Devuelve los ids de los fragmentos que cambiaron
+pub async fn reassign_asignatura_leader(
+    db: &DatabaseConnection,
+    asignatura: asignaturas::Model,
+    subject_leader_id: i32,
+) -> Result<Vec<i32>, sea_orm::DbErr> {
+    use sea_orm::{ActiveModelTrait, Set, TransactionTrait};
+
+    let asignatura_id = asignatura.id;
+    let txn = db.begin().await?;
+    let mut active: asignaturas::ActiveModel = asignatura.into();
+    active.leader_id = Set(subject_leader_id);
+    active.update(&txn).await?;
+    let reassigned = propagate_subject_leader(&txn, asignatura_id, subject_leader_id).await?;
+    txn.commit().await?;
+    Ok(reassigned)
+}
@@ -2,55 +2,40 @@
 //! 
 //! Generates Excel files matching the Balance de Carga template format.
 //! Structure:
-//! - Single sheet per academic year with weekly distribution grid
-//! - Includes regular weeks, Consultas (4 cols), and Exámenes Finales (5 cols)
+//! - Grid sheet for the academic year with the weekly distribution
+//!   (regular weeks, Consultas and Exámenes Finales; column counts follow the
+//!   balance's week structure, 4/4/5 in the standard one)
+//! - "Resumen": planned vs scheduled counts and hours per subject, deficits highlighted
+//! - "Carga semanal": student load per week with totals
+//! - "Leyenda": activity codes and the critical-type coloring
+//!
+//! The period workbook (`generate_period_excel`) has a cover sheet and one
+//! grid sheet per year level (1ro–4to) of the same period.
 
 use rust_xlsxwriter::{
     Workbook, Format, FormatAlign, FormatBorder, Color,
     XlsxError,
 };
-use chrono::{NaiveDate, Duration, Datelike, Weekday};
-use serde_json::Value;
+use chrono::{NaiveDate, Datelike};
 
-/// Activity types used in the balance
-/// C - Conferencia, CP - Clase Práctica, S - Seminario, PL - Práctica de Laboratorio
-/// TE - Tarea Extraclase, T - Taller, PP - Prueba Parcial, EC - Examen Comprobatorio
-pub const ACTIVITY_TYPES: &[&str] = &["C", "CP", "S", "PL", "TE", "T", "PP", "EC"];
-
-/// Critical activity types that should be colored red
-/// These types cannot have 3 or more in the same week
-pub const CRITICAL_TYPES: &[&str] = &["T", "TE", "PP"];
+use crate::utils::activity_types::{is_critical_type, schedulable_activity_types, ActivityPlan};
+use crate::utils::balance_rules::{ScheduledSubject, MAX_CRITICAL_PER_WEEK};
+use crate::utils::fragment_data::{FragmentData, FragmentLayout};
+use crate::utils::load_analytics::{analyze_load, LoadThresholds};
+use crate::utils::reconciliation::reconcile;
+use crate::utils::week_calendar::{calculate_final_weeks_dates, calculate_week_dates, NonAcademicPeriod, WeekDateInfo};
 
 /// Data for a single fragment/subject in the balance
 #[derive(Debug, Clone)]
 pub struct FragmentExportData {
     pub name: String,
     pub hours: i32,
-    /// Weekly data: week_number -> day_number -> activity_type (e.g., "C", "CP")
-    pub weekly_data: Vec<Vec<Option<String>>>,
-    /// Consultas data (4 cells)
-    pub consultas_data: Vec<Option<String>>,
-    /// Exámenes Finales data (5 cells)
-    pub examenes_data: Vec<Option<String>>,
+    /// Distribution grid: regular weeks, Consultas and Exámenes Finales
+    pub data: FragmentData,
     /// Planned hours by activity type
     pub plan: ActivityPlan,
 }
 
-/// Planned hours for each activity type
-#[derive(Debug, Clone, Default)]
-pub struct ActivityPlan {
-    pub c: i32,
-    pub cp: i32,
-    pub s: i32,
-    pub pl: i32,
-    pub te: i32,
-    pub t: i32,
-    pub pp: i32,
-    pub ec: i32,
-    pub tc: i32,
-    pub ef: i32,
-}
-
 /// Balance export configuration
 #[derive(Debug, Clone)]
 pub struct BalanceExportConfig {
@@ -58,148 +43,32 @@ pub struct BalanceExportConfig {
     pub period: String,             // "1ero", "2do"
     pub academic_year_text: String, // "2025-2026"
     pub start_date: NaiveDate,
-    pub weeks: i32,
+    /// Grid structure: lective weeks, cells per week and working days
+    pub layout: FragmentLayout,
     pub fragments: Vec<FragmentExportData>,
     /// Non-academic periods (vacations, etc.)
-    pub non_academic_periods: Vec<(NaiveDate, NaiveDate, String)>,
+    pub non_academic_periods: Vec<NonAcademicPeriod>,
+    /// Start of each exam session of the academic calendar (final weeks land there)
+    pub exam_sessions: Vec<NaiveDate>,
 }
 
-/// Week date information for display
+/// Cover sheet row for one balance of the period
 #[derive(Debug, Clone)]
-struct WeekDateInfo {
-    pub start_date: NaiveDate,
-    #[allow(dead_code)] // May be used for display in future
-    pub display_range: String,
-}
-
-// ============================================================================
-// DATE CALCULATION HELPERS (matching frontend logic)
-// ============================================================================
-
-/// Check if a date falls within any non-academic period
-fn is_in_non_academic_period(date: NaiveDate, periods: &[(NaiveDate, NaiveDate, String)]) -> bool {
-    for (start, end, _) in periods {
-        if date >= *start && date <= *end {
-            return true;
-        }
-    }
-    false
-}
-
-/// Check if a date is a weekend (Saturday or Sunday)
-fn is_weekend(date: NaiveDate) -> bool {
-    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
-}
-
-/// Get the next working day (not weekend, not in non-academic period)
-fn get_next_working_day(date: NaiveDate, non_academic_periods: &[(NaiveDate, NaiveDate, String)]) -> NaiveDate {
-    let mut next = date;
-    while is_weekend(next) || is_in_non_academic_period(next, non_academic_periods) {
-        next += Duration::days(1);
-    }
-    next
-}
-
-/// Calculate week dates, skipping non-academic periods
-/// This mirrors the frontend's calculateWeekDates function
-fn calculate_week_dates(
-    start_date: NaiveDate,
-    total_weeks: i32,
-    non_academic_periods: &[(NaiveDate, NaiveDate, String)],
-) -> Vec<WeekDateInfo> {
-    let mut weeks = Vec::with_capacity(total_weeks as usize);
-    
-    // Start from the first working day
-    let mut current_date = get_next_working_day(start_date, non_academic_periods);
-    
-    for _ in 0..total_weeks {
-        // Get the start of this week (ensure it's a working day)
-        let week_start = get_next_working_day(current_date, non_academic_periods);
-        
-        // Find the end of the week (4 more working days for L, M, X, J, V = 5 days total)
-        let mut working_days = 0;
-        let mut week_end = week_start;
-        
-        while working_days < 4 {
-            week_end += Duration::days(1);
-            if !is_weekend(week_end) && !is_in_non_academic_period(week_end, non_academic_periods) {
-                working_days += 1;
-            }
-        }
-        
-        // Format display range
-        let display_range = format!(
-            "{:02}/{:02} - {:02}/{:02}",
-            week_start.day(), week_start.month(),
-            week_end.day(), week_end.month()
-        );
-        
-        weeks.push(WeekDateInfo {
-            start_date: week_start,
-            display_range,
-        });
-        
-        // Advance to the next week
-        current_date = week_end + Duration::days(1);
-        current_date = get_next_working_day(current_date, non_academic_periods);
-    }
-    
-    weeks
+pub struct PeriodCoverEntry {
+    pub name: String,
+    pub status: String,             // Display label ("Abierto", "Aprobado", ...)
+    pub deadline: Option<NaiveDate>,
+    pub completed_fragments: usize,
+    pub total_fragments: usize,
 }
 
-/// Calculate dates for final weeks (Consultas and Exámenes Finales)
-/// These come after all regular lective weeks
-fn calculate_final_weeks_dates(
-    start_date: NaiveDate,
-    lective_weeks: i32,
-    non_academic_periods: &[(NaiveDate, NaiveDate, String)],
-) -> Vec<WeekDateInfo> {
-    // First calculate lective weeks to know where final weeks start
-    let lective_week_dates = calculate_week_dates(start_date, lective_weeks, non_academic_periods);
-    
-    if lective_week_dates.is_empty() {
-        return vec![];
-    }
-    
-    // Get end of last lective week
-    let last_week = &lective_week_dates[lective_week_dates.len() - 1];
-    let last_week_end = last_week.start_date + Duration::days(4); // Approximate end
-    
-    let mut final_weeks = Vec::with_capacity(2);
-    let mut current_date = last_week_end + Duration::days(1);
-    current_date = get_next_working_day(current_date, non_academic_periods);
-    
-    // Generate 2 final weeks (Consultas and Exámenes)
-    for _ in 0..2 {
-        let week_start = get_next_working_day(current_date, non_academic_periods);
-        
-        // Find end of week (4 more working days)
-        let mut working_days = 0;
-        let mut week_end = week_start;
-        
-        while working_days < 4 {
-            week_end += Duration::days(1);
-            if !is_weekend(week_end) && !is_in_non_academic_period(week_end, non_academic_periods) {
-                working_days += 1;
-            }
-        }
-        
-        let display_range = format!(
-            "{:02}/{:02} - {:02}/{:02}",
-            week_start.day(), week_start.month(),
-            week_end.day(), week_end.month()
-        );
-        
-        final_weeks.push(WeekDateInfo {
-            start_date: week_start,
-            display_range,
-        });
-        
-        current_date = week_end + Duration::days(1);
-        current_date = get_next_working_day(current_date, non_academic_periods);
-    }
-    
-    final_weeks
+/// Whole-period export: every balance of `period` in `academic_year_text`
+#[derive(Debug, Clone)]
+pub struct PeriodExportConfig {
+    pub period: String,
+    pub academic_year_text: String,
+    /// One entry per balance, in sheet order
+    pub balances: Vec<(PeriodCoverEntry, BalanceExportConfig)>,
 }
 
 /// Excel style definitions matching the template
@@ -213,6 +82,9 @@ struct ExcelStyles {
     cell_activity_critical: Format,           // Critical types (T, TE, PP) - RED text
     cell_activity_critical_week_end: Format,  // Critical at end of week - RED text + right border
     cell_subject_name: Format,
+    cell_number: Format,
+    cell_number_deficit: Format,              // Below plan / over threshold - light red fill
+    cell_total: Format,
 }
 
 impl ExcelStyles {
@@ -304,6 +176,21 @@ impl ExcelStyles {
             .set_border(FormatBorder::Thin)
             .set_border_color(Color::Black);
 
+        // Numeric cells of the summary sheets
+        let cell_number = Format::new()
+            .set_font_name("Arial")
+            .set_font_size(10)
+            .set_align(FormatAlign::Center)
+            .set_border(FormatBorder::Thin)
+            .set_border_color(Color::Black);
+
+        let cell_number_deficit = cell_number
+            .clone()
+            .set_background_color(Color::RGB(0xFFC7CE))
+            .set_font_color(Color::RGB(0x9C0006));
+
+        let cell_total = cell_number.clone().set_bold().set_background_color(Color::RGB(0xD9D9D9));
+
         Self {
             header_bold,
             header_week,
@@ -314,13 +201,31 @@ impl ExcelStyles {
             cell_activity_critical,
             cell_activity_critical_week_end,
             cell_subject_name,
+            cell_number,
+            cell_number_deficit,
+            cell_total,
         }
     }
 }
 
-/// Check if an activity type is critical (should be colored red)
-fn is_critical_type(activity: &str) -> bool {
-    CRITICAL_TYPES.contains(&activity)
+/// Spanish month abbreviations
+const MONTH_NAMES: [&str; 12] = ["Ene", "Feb", "Mar", "Abr", "May", "Jun",
+                                 "Jul", "Ago", "Sep", "Oct", "Nov", "Dic"];
+
+/// Rango de fechas de la cabecera de una semana ("1-5 Sep", "29 Sep-3 Oct").
+/// El fin es el último día hábil de la semana (puede no ser viernes si hay feriados).
+pub fn header_date_range(week_info: &WeekDateInfo) -> String {
+    let start = week_info.start_date;
+    let end = week_info.end_date;
+    let start_month = MONTH_NAMES[start.month0() as usize];
+    let end_month = MONTH_NAMES[end.month0() as usize];
+
+    // Handle month boundary
+    if start.month() == end.month() {
+        format!("{}-{} {}", start.day(), end.day(), start_month)
+    } else {
+        format!("{} {}-{} {}", start.day(), start_month, end.day(), end_month)
+    }
 }
 
 /// Generate an Excel workbook from balance data
@@ -331,12 +236,86 @@ pub fn generate_balance_excel(config: &BalanceExportConfig) -> Result<Vec<u8>, X
     // Create data sheet for the academic year (no cover sheet needed)
     let sheet_name = format!("{}.ICS", config.academic_year);
     create_data_sheet(&mut workbook, &sheet_name, config, &styles)?;
+    create_summary_sheet(&mut workbook, config, &styles)?;
+    create_load_sheet(&mut workbook, config, &styles)?;
+    create_legend_sheet(&mut workbook, &styles)?;
 
     // Save to memory buffer
     let buffer = workbook.save_to_buffer()?;
     Ok(buffer)
 }
 
+/// Generate the period workbook: cover sheet plus one grid sheet per balance
+pub fn generate_period_excel(config: &PeriodExportConfig) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let styles = ExcelStyles::new();
+
+    // Sheet names must be unique: a second balance for the same year gets a suffix
+    let mut sheet_names: Vec<String> = Vec::with_capacity(config.balances.len());
+    for (_, balance) in &config.balances {
+        let base = format!("{}.ICS", balance.academic_year);
+        let mut name = base.clone();
+        let mut n = 2;
+        while sheet_names.iter().any(|s| s.eq_ignore_ascii_case(&name)) {
+            name = format!("{} ({})", base, n);
+            n += 1;
+        }
+        sheet_names.push(name);
+    }
+
+    create_cover_sheet(&mut workbook, config, &sheet_names, &styles)?;
+    for ((_, balance), sheet_name) in config.balances.iter().zip(&sheet_names) {
+        create_data_sheet(&mut workbook, sheet_name, balance, &styles)?;
+    }
+
+    workbook.save_to_buffer()
+}
+
+/// Cover sheet: completion status and deadline of each year level
+fn create_cover_sheet(
+    workbook: &mut Workbook,
+    config: &PeriodExportConfig,
+    sheet_names: &[String],
+    styles: &ExcelStyles,
+) -> Result<(), XlsxError> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Portada")?;
+
+    let title = Format::new().set_bold().set_font_name("Arial").set_font_size(14);
+    sheet.write_with_format(0, 0, "Balance de carga docente", &title)?;
+    sheet.write(1, 0, format!("Período {} - Curso {}", config.period, config.academic_year_text))?;
+    sheet.write(2, 0, format!("Generado: {}", chrono::Local::now().format("%d/%m/%Y %H:%M")))?;
+
+    let headers = ["Año", "Balance", "Estado", "Fecha límite", "Completados", "Total", "% completado", "Hoja"];
+    let widths = [8, 40, 14, 14, 12, 8, 14, 12];
+    for (col, (header, width)) in headers.iter().zip(widths).enumerate() {
+        sheet.write_with_format(4, col as u16, *header, &styles.header_bold)?;
+        sheet.set_column_width(col as u16, width)?;
+    }
+
+    for (idx, ((entry, balance), sheet_name)) in config.balances.iter().zip(sheet_names).enumerate() {
+        let row = 5 + idx as u32;
+        let deadline = entry.deadline.map(|d| d.format("%d/%m/%Y").to_string()).unwrap_or_else(|| "-".to_string());
+        let percent = (entry.completed_fragments * 100).checked_div(entry.total_fragments).unwrap_or(0) as u32;
+        let percent_style = if percent < 100 { &styles.cell_number_deficit } else { &styles.cell_number };
+
+        sheet.write_with_format(row, 0, &balance.academic_year, &styles.cell_number)?;
+        sheet.write_with_format(row, 1, &entry.name, &styles.cell_subject_name)?;
+        sheet.write_with_format(row, 2, &entry.status, &styles.cell_subject_name)?;
+        sheet.write_with_format(row, 3, deadline, &styles.cell_number)?;
+        sheet.write_with_format(row, 4, entry.completed_fragments as u32, &styles.cell_number)?;
+        sheet.write_with_format(row, 5, entry.total_fragments as u32, &styles.cell_number)?;
+        sheet.write_with_format(row, 6, percent, percent_style)?;
+        sheet.write_with_format(row, 7, sheet_name, &styles.cell_subject_name)?;
+    }
+
+    if config.balances.is_empty() {
+        sheet.write(5, 0, "No hay balances para este período")?;
+    }
+
+    Ok(())
+}
+
 /// Create a data sheet with the weekly distribution grid
 fn create_data_sheet(
     workbook: &mut Workbook,
@@ -347,10 +326,11 @@ fn create_data_sheet(
     let sheet = workbook.add_worksheet();
     sheet.set_name(sheet_name)?;
 
-    let days_per_week = 4; // 4 data columns per week (matches frontend data structure)
-    let consultas_cols = 4; // 4 columns for Consultas
-    let examenes_cols = 5;  // 5 columns for Exámenes Finales
-    let total_week_cols = (config.weeks as usize) * days_per_week;
+    let layout = &config.layout;
+    let days_per_week = layout.days_per_week; // data columns per week (matches the fragment grid)
+    let consultas_cols = layout.consultas_slots;
+    let examenes_cols = layout.exam_slots;
+    let total_week_cols = layout.week_slots();
     let total_cols = total_week_cols + consultas_cols + examenes_cols;
 
     // Set column widths
@@ -362,21 +342,18 @@ fn create_data_sheet(
     // Calculate week dates (skipping non-academic periods)
     let week_dates = calculate_week_dates(
         config.start_date,
-        config.weeks,
+        layout,
         &config.non_academic_periods,
     );
 
     // Calculate final weeks dates (Consultas and Exámenes)
     let final_week_dates = calculate_final_weeks_dates(
         config.start_date,
-        config.weeks,
+        layout,
         &config.non_academic_periods,
+        &config.exam_sessions,
     );
 
-    // Spanish month abbreviations
-    let month_names = ["Ene", "Feb", "Mar", "Abr", "May", "Jun", 
-                       "Jul", "Ago", "Sep", "Oct", "Nov", "Dic"];
-
     // === ROW 0: "Asignaturas" header + date ranges ===
     sheet.write_with_format(0, 0, "Asignaturas", &styles.header_bold)?;
     
@@ -384,63 +361,25 @@ fn create_data_sheet(
     for (week_idx, week_info) in week_dates.iter().enumerate() {
         let col_start = 1 + (week_idx as u16) * (days_per_week as u16);
         let col_end = col_start + (days_per_week as u16) - 1;
-        
-        // Calculate week end date: Mon to Fri = +4 calendar days (5 day work week)
-        let start_day = week_info.start_date.day();
-        let end_date = week_info.start_date + chrono::Duration::days(4); // Mon + 4 = Fri
-        let end_day = end_date.day();
-        
-        // Handle month boundary
-        let start_month_idx = (week_info.start_date.month() - 1) as usize;
-        let end_month_idx = (end_date.month() - 1) as usize;
-        
-        let date_range = if start_month_idx == end_month_idx {
-            format!("{}-{} {}", start_day, end_day, month_names[start_month_idx])
-        } else {
-            format!("{} {}-{} {}", start_day, month_names[start_month_idx], end_day, month_names[end_month_idx])
-        };
-        
-        sheet.merge_range(0, col_start, 0, col_end, &date_range, &styles.header_week)?;
+        sheet.merge_range(0, col_start, 0, col_end, &header_date_range(week_info), &styles.header_week)?;
     }
 
     // Consultas date range
-    let consultas_col_start = 1 + (config.weeks as u16) * (days_per_week as u16);
+    let consultas_col_start = 1 + total_week_cols as u16;
     let consultas_col_end = consultas_col_start + (consultas_cols as u16) - 1;
-    if let Some(consultas_info) = final_week_dates.get(0) {
-        let start_day = consultas_info.start_date.day();
-        let end_date = consultas_info.start_date + chrono::Duration::days(4);
-        let end_day = end_date.day();
-        let start_month_idx = (consultas_info.start_date.month() - 1) as usize;
-        let end_month_idx = (end_date.month() - 1) as usize;
-        
-        let date_range = if start_month_idx == end_month_idx {
-            format!("{}-{} {}", start_day, end_day, month_names[start_month_idx])
-        } else {
-            format!("{} {}-{} {}", start_day, month_names[start_month_idx], end_day, month_names[end_month_idx])
-        };
-        sheet.merge_range(0, consultas_col_start, 0, consultas_col_end, &date_range, &styles.header_week)?;
+    if let Some(consultas_info) = final_week_dates.first() {
+        sheet.merge_range(0, consultas_col_start, 0, consultas_col_end, &header_date_range(consultas_info), &styles.header_week)?;
     }
 
     // Exámenes Finales date range
     let examenes_col_start = consultas_col_end + 1;
     let examenes_col_end = examenes_col_start + (examenes_cols as u16) - 1;
     if let Some(examenes_info) = final_week_dates.get(1) {
-        let start_day = examenes_info.start_date.day();
-        let end_date = examenes_info.start_date + chrono::Duration::days(4);
-        let end_day = end_date.day();
-        let start_month_idx = (examenes_info.start_date.month() - 1) as usize;
-        let end_month_idx = (end_date.month() - 1) as usize;
-        
-        let date_range = if start_month_idx == end_month_idx {
-            format!("{}-{} {}", start_day, end_day, month_names[start_month_idx])
-        } else {
-            format!("{} {}-{} {}", start_day, month_names[start_month_idx], end_day, month_names[end_month_idx])
-        };
-        sheet.merge_range(0, examenes_col_start, 0, examenes_col_end, &date_range, &styles.header_week)?;
+        sheet.merge_range(0, examenes_col_start, 0, examenes_col_end, &header_date_range(examenes_info), &styles.header_week)?;
     }
 
     // === ROW 1: Week labels ("Semana 1", ..., "Consultas", "Exámenes Finales") ===
-    for week in 0..config.weeks {
+    for week in 0..layout.weeks {
         let col_start = 1 + (week as u16) * (days_per_week as u16);
         let col_end = col_start + (days_per_week as u16) - 1;
         let week_label = format!("Semana {}", week + 1);
@@ -453,9 +392,9 @@ fn create_data_sheet(
     // Exámenes Finales label
     sheet.merge_range(1, examenes_col_start, 1, examenes_col_end, "Exámenes Finales", &styles.header_week)?;
 
-    // === ROW 2: Day numbers (1, 2, 3, 4 repeating for weeks, 1-4 for consultas, 1-5 for exámenes) ===
+    // === ROW 2: Day numbers (1..days_per_week repeating for weeks, then consultas and exámenes) ===
     // Use thick right border on last day of each week for visual separation
-    for week in 0..config.weeks {
+    for week in 0..layout.weeks {
         for day in 0..days_per_week {
             let col = 1 + (week as u16) * (days_per_week as u16) + (day as u16);
             let is_last_day = day == days_per_week - 1;
@@ -464,7 +403,7 @@ fn create_data_sheet(
         }
     }
     
-    // Consultas day numbers (1-4) - last one with thick border
+    // Consultas day numbers - last one with thick border
     for day in 0..consultas_cols {
         let col = consultas_col_start + (day as u16);
         let is_last_day = day == consultas_cols - 1;
@@ -472,7 +411,7 @@ fn create_data_sheet(
         sheet.write_with_format(2, col, (day + 1) as i32, style)?;
     }
     
-    // Exámenes day numbers (1-5) - last one with thick border
+    // Exámenes day numbers - last one with thick border
     for day in 0..examenes_cols {
         let col = examenes_col_start + (day as u16);
         let is_last_day = day == examenes_cols - 1;
@@ -489,7 +428,7 @@ fn create_data_sheet(
         
         // Weekly data (regular weeks) - use thick border on last day of each week
         // Critical types (T, TE, PP) are colored red
-        for (week_idx, week_data) in fragment.weekly_data.iter().enumerate() {
+        for (week_idx, week_data) in fragment.data.weeks.iter().enumerate() {
             for (day_idx, activity) in week_data.iter().enumerate() {
                 let col = 1 + (week_idx as u16) * (days_per_week as u16) + (day_idx as u16);
                 let is_last_day = day_idx == days_per_week - 1;
@@ -511,12 +450,12 @@ fn create_data_sheet(
             }
         }
         
-        // Consultas data (4 cells after regular weeks)
+        // Consultas data (cells after regular weeks)
         for day in 0..consultas_cols {
             let col = consultas_col_start + (day as u16);
             let is_last_day = day == consultas_cols - 1;
             
-            if let Some(act) = &fragment.consultas_data[day] {
+            if let Some(act) = &fragment.data.consultas[day] {
                 let is_critical = is_critical_type(act);
                 let style = match (is_last_day, is_critical) {
                     (true, true) => &styles.cell_activity_critical_week_end,
@@ -536,7 +475,7 @@ fn create_data_sheet(
             let col = examenes_col_start + (day as u16);
             let is_last_day = day == examenes_cols - 1;
             
-            if let Some(act) = &fragment.examenes_data[day] {
+            if let Some(act) = &fragment.data.examenes[day] {
                 let is_critical = is_critical_type(act);
                 let style = match (is_last_day, is_critical) {
                     (true, true) => &styles.cell_activity_critical_week_end,
@@ -555,171 +494,239 @@ fn create_data_sheet(
     Ok(())
 }
 
-/// Parse fragment JSON data into weekly structure
-/// The JSON format is: { "values": ["C", "CP", "", "S", ...] } where every 4 items = 1 week
-/// Index 0-3 = week 1, index 4-7 = week 2, etc.
-/// After regular weeks: 4 cells for Consultas, then 5 cells for Exámenes Finales
-pub fn parse_fragment_data(data: &Value, weeks: i32) -> Vec<Vec<Option<String>>> {
-    let days_per_week = 4;
-    let mut weekly_data = Vec::with_capacity(weeks as usize);
-
-    // Get the values array from the data
-    let values: Vec<String> = data
-        .get("values")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .map(|v| v.as_str().unwrap_or("").to_string())
-                .collect()
-        })
-        .unwrap_or_default();
-
-    for week_num in 0..weeks as usize {
-        let mut week_data = vec![None; days_per_week];
-        
-        for day in 0..days_per_week {
-            let index = week_num * days_per_week + day;
-            if let Some(activity) = values.get(index) {
-                if !activity.is_empty() {
-                    week_data[day] = Some(activity.clone());
-                }
-            }
+/// Per-subject summary: planned vs scheduled count for each activity type and hours.
+/// Deficits (scheduled below plan) are highlighted.
+fn create_summary_sheet(
+    workbook: &mut Workbook,
+    config: &BalanceExportConfig,
+    styles: &ExcelStyles,
+) -> Result<(), XlsxError> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Resumen")?;
+    sheet.set_column_width(0, 33)?;
+
+    // Two header rows: activity type (merged) and Plan / Prog.
+    sheet.merge_range(0, 0, 1, 0, "Asignatura", &styles.header_bold)?;
+    let activity_types = schedulable_activity_types();
+    for (idx, activity) in activity_types.iter().enumerate() {
+        let col = 1 + 2 * idx as u16;
+        sheet.merge_range(0, col, 0, col + 1, &activity.code, &styles.header_week)?;
+        sheet.write_with_format(1, col, "Plan", &styles.header_day)?;
+        sheet.write_with_format(1, col + 1, "Prog.", &styles.header_day_week_end)?;
+    }
+    let hours_col = 1 + 2 * activity_types.len() as u16;
+    for (offset, title) in ["Horas plan.", "Horas prog.", "Diferencia", "Estado"].iter().enumerate() {
+        sheet.merge_range(0, hours_col + offset as u16, 1, hours_col + offset as u16, title, &styles.header_bold)?;
+        sheet.set_column_width(hours_col + offset as u16, 12)?;
+    }
+
+    for (idx, fragment) in config.fragments.iter().enumerate() {
+        let row = 2 + idx as u32;
+        let result = reconcile(&fragment.plan, fragment.hours, fragment.data.activities());
+
+        sheet.write_with_format(row, 0, &fragment.name, &styles.cell_subject_name)?;
+        for (a_idx, activity) in result.activities.iter().enumerate() {
+            let col = 1 + 2 * a_idx as u16;
+            let scheduled_style = if activity.difference < 0 { &styles.cell_number_deficit } else { &styles.cell_number };
+            sheet.write_with_format(row, col, activity.planned, &styles.cell_number)?;
+            sheet.write_with_format(row, col + 1, activity.scheduled, scheduled_style)?;
         }
 
-        weekly_data.push(week_data);
+        let hours_style = if result.hours_difference < 0 { &styles.cell_number_deficit } else { &styles.cell_number };
+        let status = if result.balanced { "Conciliado" } else { "Con diferencias" };
+        let status_style = if result.balanced { &styles.cell_subject_name } else { &styles.cell_number_deficit };
+        sheet.write_with_format(row, hours_col, result.planned_hours, &styles.cell_number)?;
+        sheet.write_with_format(row, hours_col + 1, result.scheduled_hours, hours_style)?;
+        sheet.write_with_format(row, hours_col + 2, result.hours_difference, hours_style)?;
+        sheet.write_with_format(row, hours_col + 3, status, status_style)?;
     }
 
-    weekly_data
+    Ok(())
 }
 
-/// Parse Consultas data (4 cells after regular weeks)
-pub fn parse_consultas_data(data: &Value, weeks: i32) -> Vec<Option<String>> {
-    let days_per_week = 4;
-    let consultas_start = (weeks as usize) * days_per_week;
-    let consultas_cols = 4;
-    
-    let values: Vec<String> = data
-        .get("values")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .map(|v| v.as_str().unwrap_or("").to_string())
-                .collect()
-        })
-        .unwrap_or_default();
-
-    let mut consultas = vec![None; consultas_cols];
-    for i in 0..consultas_cols {
-        let index = consultas_start + i;
-        if let Some(activity) = values.get(index) {
-            if !activity.is_empty() {
-                consultas[i] = Some(activity.clone());
-            }
+/// Student load per week: hours by activity type, critical activities and exams,
+/// with a totals row. Weeks over the default load thresholds are highlighted.
+fn create_load_sheet(
+    workbook: &mut Workbook,
+    config: &BalanceExportConfig,
+    styles: &ExcelStyles,
+) -> Result<(), XlsxError> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Carga semanal")?;
+
+    let subjects: Vec<ScheduledSubject> = config
+        .fragments
+        .iter()
+        .map(|f| ScheduledSubject { asignatura_id: 0, name: f.name.clone(), data: f.data.clone() })
+        .collect();
+    let week_dates = calculate_week_dates(config.start_date, &config.layout, &config.non_academic_periods);
+    let report = analyze_load(&subjects, config.layout.weeks, &week_dates, LoadThresholds::default());
+
+    let activity_types = schedulable_activity_types();
+    let headers: Vec<String> = ["Semana", "Fechas"]
+        .into_iter()
+        .map(String::from)
+        .chain(activity_types.iter().map(|a| format!("{} (h)", a.code)))
+        .chain(["Total horas", "Actividades críticas", "Evaluaciones"].into_iter().map(String::from))
+        .collect();
+    for (col, title) in headers.iter().enumerate() {
+        sheet.write_with_format(0, col as u16, title, &styles.header_bold)?;
+        sheet.set_column_width(col as u16, 10)?;
+    }
+    sheet.set_column_width(1, 16)?;
+    let totals_col = 2 + activity_types.len() as u16;
+
+    for (idx, week) in report.weeks.iter().enumerate() {
+        let row = 1 + idx as u32;
+        let dates = week_dates.get(idx).map(header_date_range).unwrap_or_default();
+        sheet.write_with_format(row, 0, format!("Semana {}", week.week), &styles.cell_subject_name)?;
+        sheet.write_with_format(row, 1, dates, &styles.cell_subject_name)?;
+        for (a_idx, activity) in activity_types.iter().enumerate() {
+            let hours = week.hours_by_type.get(activity.code.as_str()).copied().unwrap_or(0);
+            sheet.write_with_format(row, 2 + a_idx as u16, hours, &styles.cell_number)?;
         }
+
+        let flagged = |what: &str| if week.exceeded.contains(&what) { &styles.cell_number_deficit } else { &styles.cell_number };
+        sheet.write_with_format(row, totals_col, week.hours, flagged("hours"))?;
+        sheet.write_with_format(row, totals_col + 1, week.critical as u32, flagged("critical"))?;
+        sheet.write_with_format(row, totals_col + 2, week.exams as u32, flagged("exams"))?;
     }
-    
-    consultas
+
+    // Totals row
+    let row = 1 + report.weeks.len() as u32;
+    sheet.merge_range(row, 0, row, 1, "Total", &styles.header_bold)?;
+    for (a_idx, activity) in activity_types.iter().enumerate() {
+        let hours: i32 = report.weeks.iter().map(|w| w.hours_by_type.get(activity.code.as_str()).copied().unwrap_or(0)).sum();
+        sheet.write_with_format(row, 2 + a_idx as u16, hours, &styles.cell_total)?;
+    }
+    sheet.write_with_format(row, totals_col, report.weeks.iter().map(|w| w.hours).sum::<i32>(), &styles.cell_total)?;
+    sheet.write_with_format(row, totals_col + 1, report.weeks.iter().map(|w| w.critical as u32).sum::<u32>(), &styles.cell_total)?;
+    sheet.write_with_format(row, totals_col + 2, report.weeks.iter().map(|w| w.exams as u32).sum::<u32>(), &styles.cell_total)?;
+
+    let thresholds = report.thresholds;
+    sheet.write(
+        row + 2,
+        0,
+        format!(
+            "Se resaltan las semanas con más de {} horas, {} actividades críticas o {} evaluaciones.",
+            thresholds.max_hours, thresholds.max_critical, thresholds.max_exams
+        ),
+    )?;
+
+    Ok(())
 }
 
-/// Parse Exámenes Finales data (5 cells after Consultas)
-pub fn parse_examenes_data(data: &Value, weeks: i32) -> Vec<Option<String>> {
-    let days_per_week = 4;
-    let consultas_cols = 4;
-    let examenes_start = (weeks as usize) * days_per_week + consultas_cols;
-    let examenes_cols = 5;
-    
-    let values: Vec<String> = data
-        .get("values")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .map(|v| v.as_str().unwrap_or("").to_string())
-                .collect()
-        })
-        .unwrap_or_default();
-
-    let mut examenes = vec![None; examenes_cols];
-    for i in 0..examenes_cols {
-        let index = examenes_start + i;
-        if let Some(activity) = values.get(index) {
-            if !activity.is_empty() {
-                examenes[i] = Some(activity.clone());
-            }
-        }
+/// Legend: activity codes with their name and hours, and the coloring rules
+fn create_legend_sheet(workbook: &mut Workbook, styles: &ExcelStyles) -> Result<(), XlsxError> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Leyenda")?;
+    sheet.set_column_width(0, 10)?;
+    sheet.set_column_width(1, 28)?;
+    sheet.set_column_width(2, 8)?;
+    sheet.set_column_width(3, 12)?;
+
+    for (col, title) in ["Código", "Actividad", "Horas", "Tipo"].iter().enumerate() {
+        sheet.write_with_format(0, col as u16, *title, &styles.header_bold)?;
     }
-    
-    examenes
+    let activity_types = schedulable_activity_types();
+    for (idx, activity) in activity_types.iter().enumerate() {
+        let row = 1 + idx as u32;
+        let code_style = if activity.is_critical { &styles.cell_activity_critical } else { &styles.cell_activity };
+        let kind = match (activity.is_critical, activity.is_exam) {
+            (true, true) => "Crítica, evaluación",
+            (true, false) => "Crítica",
+            (false, true) => "Evaluación",
+            (false, false) => "",
+        };
+        sheet.write_with_format(row, 0, &activity.code, code_style)?;
+        sheet.write_with_format(row, 1, &activity.label, &styles.cell_subject_name)?;
+        sheet.write_with_format(row, 2, activity.class_hours(), &styles.cell_number)?;
+        sheet.write_with_format(row, 3, kind, &styles.cell_subject_name)?;
+    }
+
+    let row = 2 + activity_types.len() as u32;
+    let critical: Vec<&str> = activity_types.iter().filter(|t| t.is_critical).map(|t| t.code.as_str()).collect();
+    sheet.write(
+        row,
+        0,
+        format!(
+            "Las actividades críticas ({}) se marcan en rojo en la cuadrícula: no puede haber más de {} en la misma semana.",
+            critical.join(", "),
+            MAX_CRITICAL_PER_WEEK
+        ),
+    )?;
+    sheet.write(row + 1, 0, "En la hoja \"Resumen\" se resaltan los tipos y horas programados por debajo de lo planificado.")?;
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn test_parse_fragment_data() {
-        // New format: flat "values" array, 4 items per week
-        let data = serde_json::json!({
-            "values": ["C", "CP", "", "S", "C", "", "PL", ""]
-        });
-
-        let result = parse_fragment_data(&data, 2);
-        
-        assert_eq!(result.len(), 2);
-        assert_eq!(result[0], vec![Some("C".into()), Some("CP".into()), None, Some("S".into())]);
-        assert_eq!(result[1], vec![Some("C".into()), None, Some("PL".into()), None]);
+    use crate::utils::fragment_data::FragmentLayout;
+    use calamine::{Data, Reader, Xlsx};
+    use std::io::Cursor;
+
+    fn config() -> BalanceExportConfig {
+        let layout = FragmentLayout::for_weeks(2);
+        BalanceExportConfig {
+            academic_year: "1ro".to_string(),
+            period: "1ero".to_string(),
+            academic_year_text: "2025-2026".to_string(),
+            start_date: NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            layout,
+            fragments: vec![FragmentExportData {
+                name: "Álgebra".to_string(),
+                hours: 8,
+                data: FragmentData::from_stored(&serde_json::json!({ "values": ["C", "CP", "", "", "C", "PP"] }), &layout),
+                plan: [("C", 3), ("CP", 1), ("PP", 1)].into_iter().collect(),
+            }],
+            non_academic_periods: vec![],
+            exam_sessions: vec![],
+        }
     }
 
     #[test]
-    fn test_parse_fragment_data_empty() {
-        let data = serde_json::json!({});
-        let result = parse_fragment_data(&data, 2);
-        
-        assert_eq!(result.len(), 2);
-        assert_eq!(result[0], vec![None, None, None, None]);
-        assert_eq!(result[1], vec![None, None, None, None]);
-    }
+    fn test_period_workbook_sheets() {
+        let entry = PeriodCoverEntry {
+            name: "1ro Año - Período 1ero (2025-2026)".to_string(),
+            status: "Abierto".to_string(),
+            deadline: None,
+            completed_fragments: 1,
+            total_fragments: 4,
+        };
+        let mut second = config();
+        second.academic_year = "2do".to_string();
+        let period = PeriodExportConfig {
+            period: "1ero".to_string(),
+            academic_year_text: "2025-2026".to_string(),
+            balances: vec![(entry.clone(), config()), (entry.clone(), second), (entry, config())],
+        };
 
-    #[test]
-    fn test_calculate_week_dates_no_gaps() {
-        // Start on Monday Sep 1, 2025, no non-academic periods
-        let start_date = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
-        let weeks = calculate_week_dates(start_date, 3, &[]);
-        
-        assert_eq!(weeks.len(), 3);
-        assert_eq!(weeks[0].start_date, NaiveDate::from_ymd_opt(2025, 9, 1).unwrap());
-        assert_eq!(weeks[1].start_date, NaiveDate::from_ymd_opt(2025, 9, 8).unwrap());
-        assert_eq!(weeks[2].start_date, NaiveDate::from_ymd_opt(2025, 9, 15).unwrap());
-    }
+        let bytes = generate_period_excel(&period).unwrap();
+        let mut workbook: Xlsx<_> = Xlsx::new(Cursor::new(bytes)).unwrap();
 
-    #[test]
-    fn test_calculate_week_dates_skips_weekend() {
-        // Start on Saturday Sep 6, 2025 - should skip to Monday Sep 8
-        let start_date = NaiveDate::from_ymd_opt(2025, 9, 6).unwrap();
-        let weeks = calculate_week_dates(start_date, 2, &[]);
-        
-        assert_eq!(weeks.len(), 2);
-        assert_eq!(weeks[0].start_date, NaiveDate::from_ymd_opt(2025, 9, 8).unwrap()); // Skipped to Monday
-        assert_eq!(weeks[1].start_date, NaiveDate::from_ymd_opt(2025, 9, 15).unwrap());
+        assert_eq!(workbook.sheet_names(), vec!["Portada", "1ro.ICS", "2do.ICS", "1ro.ICS (2)"]);
+        let cover = workbook.worksheet_range("Portada").unwrap();
+        assert_eq!(cover.get_value((5, 6)), Some(&Data::Float(25.0)));
     }
 
     #[test]
-    fn test_calculate_week_dates_skips_non_academic() {
-        // Start on Monday Sep 1, 2025
-        // Non-academic period: Sep 8-14 (second week)
-        let start_date = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
-        let non_academic = vec![
-            (
-                NaiveDate::from_ymd_opt(2025, 9, 8).unwrap(),
-                NaiveDate::from_ymd_opt(2025, 9, 14).unwrap(),
-                "Receso".to_string()
-            )
-        ];
-        let weeks = calculate_week_dates(start_date, 3, &non_academic);
-        
-        assert_eq!(weeks.len(), 3);
-        assert_eq!(weeks[0].start_date, NaiveDate::from_ymd_opt(2025, 9, 1).unwrap());
-        // Week 2 should skip the non-academic period and land on Sep 15
-        assert_eq!(weeks[1].start_date, NaiveDate::from_ymd_opt(2025, 9, 15).unwrap());
-        assert_eq!(weeks[2].start_date, NaiveDate::from_ymd_opt(2025, 9, 22).unwrap());
+    fn test_workbook_has_summary_sheets() {
+        let bytes = generate_balance_excel(&config()).unwrap();
+        let mut workbook: Xlsx<_> = Xlsx::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(workbook.sheet_names(), vec!["1ro.ICS", "Resumen", "Carga semanal", "Leyenda"]);
+
+        // C: plan 3, programadas 2
+        let summary = workbook.worksheet_range("Resumen").unwrap();
+        assert_eq!(summary.get_value((2, 1)), Some(&Data::Float(3.0)));
+        assert_eq!(summary.get_value((2, 2)), Some(&Data::Float(2.0)));
+
+        // Semana 1: C + CP = 4 h; semana 2: C + PP = 4 h; total 8 h
+        let load = workbook.worksheet_range("Carga semanal").unwrap();
+        let total_col = 2 + schedulable_activity_types().len() as u32;
+        assert_eq!(load.get_value((1, total_col)), Some(&Data::Float(4.0)));
+        assert_eq!(load.get_value((3, total_col)), Some(&Data::Float(8.0)));
     }
 }
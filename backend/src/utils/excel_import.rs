@@ -0,0 +1,398 @@
+//! Importación de balances desde la plantilla Excel de la facultad
+//!
+//! Lee libros con el formato usado antes de este sistema (una hoja por año,
+//! p. ej. `1ro.ICS`): fila "Asignaturas" con las fechas de cada semana, fila
+//! de etiquetas ("Semana N", "SEMANA DE CONSULTAS", "EXÁMENES FINALES",
+//! "EXÁMENES EXTRA ...") y una fila por asignatura con la cuadrícula.
+//!
+//! El ancho de cada bloque se toma de la propia hoja (hay hojas con 4 y con 5
+//! días por semana). Al convertir a `FragmentData` se informan las celdas que
+//! no caben en la estructura del balance y los códigos desconocidos.
+
+use calamine::{Data, Range, Reader, Xlsx};
+use chrono::{Duration, NaiveDate};
+use std::fmt;
+use std::io::Cursor;
+
+use crate::utils::activity_types::is_schedulable_type;
+use crate::utils::fragment_data::{FragmentData, FragmentLayout, Slot};
+
+/// Texto de la primera celda de la fila de cabecera
+pub const HEADER_LABEL: &str = "Asignaturas";
+
+/// Sufijo de las hojas de la plantilla ("1ro.ICS")
+pub const SHEET_SUFFIX: &str = ".ICS";
+
+/// Bloque de columnas de la plantilla
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TemplateSection {
+    Week(usize),
+    Consultas,
+    Examenes,
+    /// Exámenes extraordinarios u otras columnas que no forman parte del balance
+    Other,
+}
+
+impl TemplateSection {
+    fn from_label(label: &str) -> Self {
+        let lower = label.to_lowercase();
+        if lower.contains("consulta") {
+            TemplateSection::Consultas
+        } else if lower.contains("extra") {
+            TemplateSection::Other
+        } else if lower.contains("final") || lower.contains("exámen") || lower.contains("examen") {
+            TemplateSection::Examenes
+        } else if lower.starts_with("semana") {
+            lower
+                .trim_start_matches("semana")
+                .trim()
+                .parse::<usize>()
+                .map(TemplateSection::Week)
+                .unwrap_or(TemplateSection::Other)
+        } else {
+            TemplateSection::Other
+        }
+    }
+}
+
+/// Fila de asignatura leída de la plantilla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateSubject {
+    pub row: usize,     // 1-based, como se ve en Excel
+    pub name: String,
+    /// Celdas de cada semana lectiva, con el ancho de la plantilla
+    pub weeks: Vec<Vec<Slot>>,
+    pub consultas: Vec<Slot>,
+    pub examenes: Vec<Slot>,
+}
+
+/// Hoja de la plantilla con cuadrícula de balance
+#[derive(Debug, Clone)]
+pub struct TemplateSheet {
+    pub name: String,
+    pub weeks: usize,
+    pub start_date: Option<NaiveDate>,
+    pub subjects: Vec<TemplateSubject>,
+}
+
+/// Error al leer la plantilla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    Unreadable(String),
+    SheetNotFound(String),
+    NoGrid,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Unreadable(e) => write!(f, "No se pudo leer el archivo Excel: {}", e),
+            TemplateError::SheetNotFound(name) => write!(f, "La hoja '{}' no existe o no tiene la cuadrícula del balance", name),
+            TemplateError::NoGrid => write!(f, "Ninguna hoja del archivo tiene la cuadrícula del balance"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Celda de la plantilla que no se pudo importar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellIssue {
+    pub position: String,
+    pub code: String,
+}
+
+/// Resultado de convertir una fila de la plantilla a la estructura del balance
+#[derive(Debug, Clone)]
+pub struct ImportedGrid {
+    pub data: FragmentData,
+    pub unknown_codes: Vec<CellIssue>,
+    /// Celdas ocupadas fuera de la estructura del balance
+    pub dropped: Vec<CellIssue>,
+}
+
+// ============================================================================
+// LECTURA
+// ============================================================================
+
+fn cell_text(cell: Option<&Data>) -> Option<String> {
+    let text = match cell? {
+        Data::Empty | Data::Error(_) => return None,
+        Data::String(s) => s.trim().to_string(),
+        other => other.to_string(),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// Fecha de una celda con número de serie de Excel (sistema 1900)
+fn cell_date(cell: Option<&Data>) -> Option<NaiveDate> {
+    let serial = match cell? {
+        Data::DateTime(dt) => dt.as_f64(),
+        Data::Float(f) => *f,
+        Data::Int(i) => *i as f64,
+        _ => return None,
+    };
+    NaiveDate::from_ymd_opt(1899, 12, 30)?.checked_add_signed(Duration::days(serial.trunc() as i64))
+}
+
+/// Lee la cuadrícula de una hoja; `None` si la hoja no tiene el formato de la plantilla
+fn parse_sheet(name: &str, range: &Range<Data>) -> Option<TemplateSheet> {
+    let rows: Vec<&[Data]> = range.rows().collect();
+    let first_row = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+
+    let header = (0..rows.len().saturating_sub(1)).find(|&idx| {
+        cell_text(rows[idx].first()).as_deref() == Some(HEADER_LABEL)
+            && rows[idx + 1]
+                .iter()
+                .filter_map(|c| cell_text(Some(c)))
+                .any(|label| matches!(TemplateSection::from_label(&label), TemplateSection::Week(_)))
+    })?;
+
+    // Bloques: cada etiqueta abarca hasta la siguiente
+    let labels: Vec<(usize, TemplateSection)> = rows[header + 1]
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter_map(|(col, cell)| cell_text(Some(cell)).map(|label| (col, TemplateSection::from_label(&label))))
+        .collect();
+    let width = range.width();
+    let blocks: Vec<(TemplateSection, usize, usize)> = labels
+        .iter()
+        .enumerate()
+        .map(|(idx, &(start, section))| {
+            let end = labels.get(idx + 1).map(|&(next, _)| next).unwrap_or(width);
+            (section, start, end - start)
+        })
+        .collect();
+
+    let mut week_blocks: Vec<(usize, usize, usize)> = blocks
+        .iter()
+        .filter_map(|&(section, start, cols)| match section {
+            TemplateSection::Week(n) => Some((n, start, cols)),
+            _ => None,
+        })
+        .collect();
+    week_blocks.sort_by_key(|&(n, _, _)| n);
+
+    let start_date = week_blocks.first().and_then(|&(_, start, _)| cell_date(rows[header].get(start)));
+    let find_block = |wanted: TemplateSection| blocks.iter().find(|(section, _, _)| *section == wanted).map(|&(_, start, cols)| (start, cols));
+    let consultas = find_block(TemplateSection::Consultas);
+    let examenes = find_block(TemplateSection::Examenes);
+
+    let read = |row: &[Data], block: Option<(usize, usize)>| -> Vec<Slot> {
+        block
+            .map(|(start, cols)| (start..start + cols).map(|col| cell_text(row.get(col))).collect())
+            .unwrap_or_default()
+    };
+
+    // Filas de asignaturas hasta la primera fila vacía o la tabla resumen
+    let mut subjects = Vec::new();
+    for (idx, row) in rows.iter().enumerate().skip(header + 3) {
+        let Some(subject_name) = cell_text(row.first()) else {
+            break;
+        };
+        if subject_name == HEADER_LABEL {
+            break;
+        }
+        subjects.push(TemplateSubject {
+            row: first_row + idx + 1,
+            name: subject_name,
+            weeks: week_blocks.iter().map(|&(_, start, cols)| read(row, Some((start, cols)))).collect(),
+            consultas: read(row, consultas),
+            examenes: read(row, examenes),
+        });
+    }
+
+    Some(TemplateSheet {
+        name: name.to_string(),
+        weeks: week_blocks.len(),
+        start_date,
+        subjects,
+    })
+}
+
+/// Lee todas las hojas del libro que tienen la cuadrícula del balance
+pub fn read_template(bytes: Vec<u8>) -> Result<Vec<TemplateSheet>, TemplateError> {
+    let mut workbook: Xlsx<_> = Xlsx::new(Cursor::new(bytes)).map_err(|e| TemplateError::Unreadable(e.to_string()))?;
+
+    let mut sheets = Vec::new();
+    for name in workbook.sheet_names() {
+        if let Ok(range) = workbook.worksheet_range(&name)
+            && let Some(sheet) = parse_sheet(&name, &range)
+        {
+            sheets.push(sheet);
+        }
+    }
+    Ok(sheets)
+}
+
+/// Elige la hoja a importar: la indicada, la del año (`{año}.ICS`) o la primera
+pub fn select_sheet(
+    sheets: Vec<TemplateSheet>,
+    requested: Option<&str>,
+    academic_year: Option<&str>,
+) -> Result<TemplateSheet, TemplateError> {
+    if let Some(name) = requested {
+        return sheets
+            .into_iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| TemplateError::SheetNotFound(name.to_string()));
+    }
+
+    let year_sheet = academic_year.map(|year| format!("{}{}", year, SHEET_SUFFIX));
+    let position = year_sheet
+        .and_then(|wanted| sheets.iter().position(|s| s.name == wanted))
+        .unwrap_or(0);
+    sheets.into_iter().nth(position).ok_or(TemplateError::NoGrid)
+}
+
+/// Año académico a partir del nombre de la hoja ("1ro.ICS" → "1ro")
+pub fn academic_year_from_sheet(name: &str) -> Option<&str> {
+    name.strip_suffix(SHEET_SUFFIX).filter(|year| !year.is_empty())
+}
+
+// ============================================================================
+// CONVERSIÓN
+// ============================================================================
+
+/// Nombre normalizado para emparejar asignaturas: sin tildes, minúsculas y
+/// espacios simples
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'ä' => 'a',
+            'é' | 'è' | 'ë' => 'e',
+            'í' | 'ì' | 'ï' => 'i',
+            'ó' | 'ò' | 'ö' => 'o',
+            'ú' | 'ù' | 'ü' => 'u',
+            'ñ' => 'n',
+            other => other,
+        })
+        .collect()
+}
+
+fn import_cells(
+    cells: &[Slot],
+    capacity: usize,
+    describe: impl Fn(usize) -> String,
+    target: &mut [Slot],
+    grid: &mut ImportedGrid,
+) {
+    for (day, cell) in cells.iter().enumerate() {
+        let Some(raw) = cell else {
+            continue;
+        };
+        let code = raw.trim().to_uppercase();
+        if day >= capacity {
+            grid.dropped.push(CellIssue { position: describe(day), code });
+        } else if is_schedulable_type(&code) {
+            target[day] = Some(code);
+        } else {
+            grid.unknown_codes.push(CellIssue { position: describe(day), code });
+        }
+    }
+}
+
+/// Convierte una fila de la plantilla a la estructura del balance
+pub fn to_fragment_data(subject: &TemplateSubject, layout: &FragmentLayout) -> ImportedGrid {
+    let mut grid = ImportedGrid {
+        data: FragmentData::empty(layout),
+        unknown_codes: Vec::new(),
+        dropped: Vec::new(),
+    };
+
+    for (week, cells) in subject.weeks.iter().enumerate() {
+        let describe = |day: usize| format!("semana {}, día {}", week + 1, day + 1);
+        if week < layout.weeks {
+            let mut target = std::mem::take(&mut grid.data.weeks[week]);
+            import_cells(cells, layout.days_per_week, describe, &mut target, &mut grid);
+            grid.data.weeks[week] = target;
+        } else {
+            import_cells(cells, 0, describe, &mut Vec::new(), &mut grid);
+        }
+    }
+
+    let mut consultas = std::mem::take(&mut grid.data.consultas);
+    import_cells(&subject.consultas, layout.consultas_slots, |day| format!("consultas, día {}", day + 1), &mut consultas, &mut grid);
+    grid.data.consultas = consultas;
+
+    let mut examenes = std::mem::take(&mut grid.data.examenes);
+    import_cells(&subject.examenes, layout.exam_slots, |day| format!("exámenes finales, día {}", day + 1), &mut examenes, &mut grid);
+    grid.data.examenes = examenes;
+
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Data {
+        Data::String(value.to_string())
+    }
+
+    /// Hoja con 2 semanas de 2 días, consultas (2), exámenes (2) y extra (1)
+    fn sample_range() -> Range<Data> {
+        let mut range = Range::new((0, 0), (6, 9));
+        range.set_value((0, 0), text("Asignaturas"));
+        range.set_value((0, 1), Data::Float(45908.0));
+        for (col, label) in [(1, "Semana 1"), (3, "Semana 2"), (5, "SEMANA DE CONSULTAS"), (7, "EXÁMENES FINALES"), (9, "EXÁMENES EXTRA I")] {
+            range.set_value((1, col), text(label));
+        }
+        range.set_value((3, 0), text("Filosofía "));
+        range.set_value((3, 1), text("C"));
+        range.set_value((3, 4), text("cp"));
+        range.set_value((3, 9), text("X"));
+        range.set_value((4, 0), text("Matemática Discreta"));
+        range.set_value((4, 7), text("EF"));
+        // La tabla resumen empieza tras una fila vacía
+        range.set_value((6, 0), text("Asignaturas"));
+        range
+    }
+
+    #[test]
+    fn test_parse_sheet() {
+        let sheet = parse_sheet("1ro.ICS", &sample_range()).unwrap();
+
+        assert_eq!(sheet.weeks, 2);
+        assert_eq!(sheet.start_date, NaiveDate::from_ymd_opt(2025, 9, 8));
+        assert_eq!(sheet.subjects.len(), 2);
+        let first = &sheet.subjects[0];
+        assert_eq!((first.row, first.name.as_str()), (4, "Filosofía"));
+        assert_eq!(first.weeks, vec![vec![Some("C".into()), None], vec![None, Some("cp".into())]]);
+        assert_eq!(sheet.subjects[1].examenes, vec![Some("EF".into()), None]);
+        assert_eq!(academic_year_from_sheet(&sheet.name), Some("1ro"));
+    }
+
+    #[test]
+    fn test_to_fragment_data_reports_issues() {
+        let subject = TemplateSubject {
+            row: 4,
+            name: "Filosofía".to_string(),
+            weeks: vec![
+                vec![Some("C".into()), Some("cp".into()), None, None, Some("S".into())],
+                vec![Some("PLAB".into()), None, None, None, None],
+            ],
+            consultas: vec![None, None, None, None, None],
+            examenes: vec![Some("EF".into())],
+        };
+
+        let grid = to_fragment_data(&subject, &FragmentLayout::for_weeks(1));
+
+        assert_eq!(grid.data.weeks[0], vec![Some("C".into()), Some("CP".into()), None, None]);
+        assert_eq!(grid.unknown_codes, vec![CellIssue { position: "exámenes finales, día 1".into(), code: "EF".into() }]);
+        assert_eq!(grid.dropped.len(), 2);
+        assert_eq!(grid.dropped[0].position, "semana 1, día 5");
+        assert_eq!(grid.dropped[1].code, "PLAB");
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("  Cálculo   diferencial e Integral "), "calculo diferencial e integral");
+        assert_eq!(normalize_name("Educación Física I"), normalize_name("educacion fisica i"));
+    }
+}
@@ -0,0 +1,94 @@
+//! Permisos de edición de fragmentos
+//!
+//! - SubjectLeader: solo su fragmento y hasta la fecha límite del balance,
+//!   salvo que el Leader del balance le haya concedido una prórroga.
+//! - Leader: su propio fragmento, o los de otros solo si el balance tiene
+//!   `allow_leader_edit` activado.
+
+use chrono::NaiveDate;
+
+/// Datos necesarios para decidir si un usuario puede editar un fragmento
+#[derive(Debug, Clone)]
+pub struct FragmentEditRequest<'a> {
+    pub user_id: i32,
+    pub role: &'a str,
+    pub subject_leader_id: Option<i32>,
+    pub allow_leader_edit: bool,
+    pub deadline: Option<NaiveDate>,
+    pub deadline_extension: Option<NaiveDate>,
+    pub today: NaiveDate,
+}
+
+/// Fecha límite efectiva del fragmento: la prórroga, si existe, sustituye a la del balance
+pub fn effective_deadline(deadline: Option<NaiveDate>, extension: Option<NaiveDate>) -> Option<NaiveDate> {
+    extension.or(deadline)
+}
+
+/// Devuelve el motivo de rechazo, o `Ok(())` si la edición está permitida
+pub fn check_fragment_edit(req: &FragmentEditRequest) -> Result<(), String> {
+    let is_owner = req.subject_leader_id == Some(req.user_id);
+
+    if req.role == "leader" || req.role == "admin" {
+        if is_owner || req.allow_leader_edit {
+            return Ok(());
+        }
+        return Err("El balance no permite que el Leader edite fragmentos de otros usuarios".to_string());
+    }
+
+    if !is_owner {
+        return Err("No tienes permiso para editar este fragmento".to_string());
+    }
+
+    if let Some(deadline) = effective_deadline(req.deadline, req.deadline_extension)
+        && req.today > deadline
+    {
+        return Err(format!("La fecha límite ({}) ha vencido", deadline));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 10, d).unwrap()
+    }
+
+    fn request(role: &str, owner: Option<i32>) -> FragmentEditRequest<'_> {
+        FragmentEditRequest {
+            user_id: 1,
+            role,
+            subject_leader_id: owner,
+            allow_leader_edit: false,
+            deadline: Some(date(10)),
+            deadline_extension: None,
+            today: date(15),
+        }
+    }
+
+    #[test]
+    fn test_subject_leader_deadline_and_extension() {
+        let mut req = request("subjectLeader", Some(1));
+        assert!(check_fragment_edit(&req).is_err());
+
+        req.deadline_extension = Some(date(20));
+        assert!(check_fragment_edit(&req).is_ok());
+
+        req.subject_leader_id = Some(2);
+        assert!(check_fragment_edit(&req).is_err());
+    }
+
+    #[test]
+    fn test_leader_requires_allow_leader_edit() {
+        let mut req = request("leader", Some(2));
+        assert!(check_fragment_edit(&req).is_err());
+
+        req.allow_leader_edit = true;
+        assert!(check_fragment_edit(&req).is_ok());
+
+        // Su propio fragmento siempre, y sin fecha límite
+        assert!(check_fragment_edit(&request("leader", Some(1))).is_ok());
+    }
+}
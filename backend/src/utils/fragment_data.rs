@@ -0,0 +1,575 @@
+//! Modelo tipado de los datos de un fragmento
+//!
+//! Los fragmentos se guardan en `balance_fragments.data` como
+//! `{ "version": 1, "values": ["C", "CP", "", ...] }`, un array plano donde
+//! cada 4 celdas forman una semana lectiva, seguidas de 4 celdas de Consultas
+//! y 5 de Exámenes Finales. Esas cantidades son las de la estructura estándar;
+//! cada balance puede definir la suya en `balances.week_structure` (ver
+//! `WeekStructure`). Este módulo convierte ese formato en una estructura con
+//! semanas, consultas y exámenes separados, y valida longitudes y códigos de
+//! actividad en la frontera de la API.
+
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+use crate::utils::activity_types::is_schedulable_type;
+
+/// Versión actual del esquema de datos de fragmento
+pub const FRAGMENT_SCHEMA_VERSION: u32 = 1;
+
+/// Celdas por semana lectiva (estructura estándar)
+pub const DAYS_PER_WEEK: usize = 4;
+/// Celdas de la semana de Consultas
+pub const CONSULTAS_SLOTS: usize = 4;
+/// Celdas de la semana de Exámenes Finales
+pub const EXAM_SLOTS: usize = 5;
+
+/// Máximo de celdas por semana que admite una estructura personalizada
+pub const MAX_SLOTS_PER_WEEK: usize = 7;
+
+/// Una celda de la cuadrícula: código de actividad o vacía
+pub type Slot = Option<String>;
+
+/// Días laborables de la semana. Se serializa como la lista de días ISO
+/// (1 = lunes ... 7 = domingo), p. ej. `[1, 2, 3, 4, 5]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<u8>", into = "Vec<u8>")]
+pub struct WorkingDays(u8);
+
+impl WorkingDays {
+    /// De lunes a viernes
+    pub const MONDAY_TO_FRIDAY: WorkingDays = WorkingDays(0b0001_1111);
+
+    /// Si el día de la semana es laborable
+    pub fn contains(self, weekday: Weekday) -> bool {
+        self.0 & (1 << weekday.num_days_from_monday()) != 0
+    }
+
+    /// Número de días laborables por semana
+    pub fn count(self) -> usize {
+        self.0.count_ones() as usize
+    }
+}
+
+impl Default for WorkingDays {
+    fn default() -> Self {
+        Self::MONDAY_TO_FRIDAY
+    }
+}
+
+impl TryFrom<Vec<u8>> for WorkingDays {
+    type Error = String;
+
+    fn try_from(days: Vec<u8>) -> Result<Self, Self::Error> {
+        let mut mask = 0u8;
+        for day in days {
+            if !(1..=7).contains(&day) {
+                return Err(format!("Día de la semana inválido: {} (use 1 = lunes ... 7 = domingo)", day));
+            }
+            mask |= 1 << (day - 1);
+        }
+        if mask == 0 {
+            return Err("Debe haber al menos un día laborable".to_string());
+        }
+        Ok(WorkingDays(mask))
+    }
+}
+
+impl From<WorkingDays> for Vec<u8> {
+    fn from(days: WorkingDays) -> Self {
+        (1..=7).filter(|day| days.0 & (1 << (day - 1)) != 0).collect()
+    }
+}
+
+/// Estructura semanal configurable de un balance (`balances.week_structure`).
+/// Los campos omitidos toman el valor de la estructura estándar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeekStructure {
+    /// Celdas de cada semana lectiva
+    pub slots_per_week: usize,
+    /// Días laborables; cada semana abarca tantos días hábiles como días laborables haya
+    pub working_days: WorkingDays,
+    /// Celdas de la semana de Consultas
+    pub consultas_slots: usize,
+    /// Celdas de la semana de Exámenes Finales
+    pub exam_slots: usize,
+}
+
+impl Default for WeekStructure {
+    fn default() -> Self {
+        Self {
+            slots_per_week: DAYS_PER_WEEK,
+            working_days: WorkingDays::MONDAY_TO_FRIDAY,
+            consultas_slots: CONSULTAS_SLOTS,
+            exam_slots: EXAM_SLOTS,
+        }
+    }
+}
+
+impl WeekStructure {
+    /// Cada celda corresponde a un día hábil, así que ninguna semana puede
+    /// tener más celdas que días laborables
+    pub fn validate(&self) -> Result<(), String> {
+        let days = self.working_days.count();
+        for (name, slots) in [
+            ("slots_per_week", self.slots_per_week),
+            ("consultas_slots", self.consultas_slots),
+            ("exam_slots", self.exam_slots),
+        ] {
+            if slots == 0 || slots > MAX_SLOTS_PER_WEEK {
+                return Err(format!("{} debe estar entre 1 y {}", name, MAX_SLOTS_PER_WEEK));
+            }
+            if slots > days {
+                return Err(format!("{} ({}) no puede superar los días laborables de la semana ({})", name, slots, days));
+            }
+        }
+        Ok(())
+    }
+
+    /// Lee la estructura guardada en el balance; sin valor (o inválida) se usa la estándar
+    pub fn from_stored(value: Option<&Value>) -> Self {
+        value
+            .and_then(|v| serde_json::from_value::<WeekStructure>(v.clone()).ok())
+            .filter(|structure| structure.validate().is_ok())
+            .unwrap_or_default()
+    }
+
+    /// Cuadrícula de un balance con `weeks` semanas lectivas
+    pub fn layout(&self, weeks: i32) -> FragmentLayout {
+        FragmentLayout {
+            weeks: weeks.max(0) as usize,
+            days_per_week: self.slots_per_week,
+            consultas_slots: self.consultas_slots,
+            exam_slots: self.exam_slots,
+            working_days: self.working_days,
+        }
+    }
+}
+
+/// Estructura de la cuadrícula de un balance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentLayout {
+    pub weeks: usize,
+    /// Celdas por semana lectiva
+    pub days_per_week: usize,
+    pub consultas_slots: usize,
+    pub exam_slots: usize,
+    pub working_days: WorkingDays,
+}
+
+impl FragmentLayout {
+    /// Estructura estándar para un balance con `weeks` semanas lectivas
+    pub fn for_weeks(weeks: i32) -> Self {
+        WeekStructure::default().layout(weeks)
+    }
+
+    /// Misma cuadrícula salvo el número de semanas: una distribución solo se
+    /// puede copiar entre balances así (`FragmentData::shift_weeks`)
+    pub fn same_grid(&self, other: &FragmentLayout) -> bool {
+        FragmentLayout { weeks: other.weeks, ..*self } == *other
+    }
+
+    /// Número de celdas de las semanas lectivas
+    pub fn week_slots(&self) -> usize {
+        self.weeks * self.days_per_week
+    }
+
+    /// Número total de celdas del array `values`
+    pub fn total_slots(&self) -> usize {
+        self.week_slots() + self.consultas_slots + self.exam_slots
+    }
+
+    /// Describe la posición de una celda del array plano (para mensajes de error)
+    pub fn describe_index(&self, index: usize) -> String {
+        let week_slots = self.week_slots();
+        if index < week_slots {
+            format!("semana {}, día {}", index / self.days_per_week + 1, index % self.days_per_week + 1)
+        } else if index < week_slots + self.consultas_slots {
+            format!("consultas, día {}", index - week_slots + 1)
+        } else {
+            format!("exámenes finales, día {}", index - week_slots - self.consultas_slots + 1)
+        }
+    }
+}
+
+/// Formato de intercambio de un fragmento (API y base de datos)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FragmentPayload {
+    #[serde(default)]
+    pub version: Option<u32>,
+    pub values: Vec<String>,
+}
+
+/// Error de validación de los datos de un fragmento
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FragmentDataError {
+    UnsupportedVersion(u32),
+    WrongLength { expected: usize, actual: usize },
+    UnknownActivity { position: String, code: String },
+}
+
+impl fmt::Display for FragmentDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FragmentDataError::UnsupportedVersion(v) => {
+                write!(f, "Versión de datos de fragmento no soportada: {} (se espera {})", v, FRAGMENT_SCHEMA_VERSION)
+            }
+            FragmentDataError::WrongLength { expected, actual } => {
+                write!(f, "El fragmento debe tener {} celdas y se recibieron {}", expected, actual)
+            }
+            FragmentDataError::UnknownActivity { position, code } => {
+                write!(f, "Código de actividad desconocido '{}' en {}", code, position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FragmentDataError {}
+
+/// Zona de la cuadrícula a la que pertenece una celda
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridSection {
+    Week,
+    Consultas,
+    Examenes,
+}
+
+impl GridSection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GridSection::Week => "week",
+            GridSection::Consultas => "consultas",
+            GridSection::Examenes => "examenes",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "week" => Some(GridSection::Week),
+            "consultas" => Some(GridSection::Consultas),
+            "examenes" => Some(GridSection::Examenes),
+            _ => None,
+        }
+    }
+}
+
+/// Cambio de una celda entre dos versiones de un fragmento
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CellChange {
+    pub section: GridSection,
+    pub week: Option<usize>,     // 1-based, solo en semanas lectivas
+    pub day: usize,              // 1-based
+    pub old: Slot,
+    pub new: Slot,
+}
+
+/// Datos tipados de un fragmento
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FragmentData {
+    pub version: u32,
+    /// week_number -> day_number -> activity
+    pub weeks: Vec<Vec<Slot>>,
+    pub consultas: Vec<Slot>,
+    pub examenes: Vec<Slot>,
+}
+
+impl FragmentData {
+    /// Fragmento vacío con la estructura indicada
+    pub fn empty(layout: &FragmentLayout) -> Self {
+        Self {
+            version: FRAGMENT_SCHEMA_VERSION,
+            weeks: vec![vec![None; layout.days_per_week]; layout.weeks],
+            consultas: vec![None; layout.consultas_slots],
+            examenes: vec![None; layout.exam_slots],
+        }
+    }
+
+    /// Valida un payload recibido por la API: versión, longitud exacta y códigos conocidos
+    pub fn from_payload(payload: FragmentPayload, layout: &FragmentLayout) -> Result<Self, FragmentDataError> {
+        let version = payload.version.unwrap_or(FRAGMENT_SCHEMA_VERSION);
+        if version != FRAGMENT_SCHEMA_VERSION {
+            return Err(FragmentDataError::UnsupportedVersion(version));
+        }
+
+        if payload.values.len() != layout.total_slots() {
+            return Err(FragmentDataError::WrongLength {
+                expected: layout.total_slots(),
+                actual: payload.values.len(),
+            });
+        }
+
+        let mut slots = Vec::with_capacity(payload.values.len());
+        for (index, value) in payload.values.into_iter().enumerate() {
+            let code = value.trim();
+            if code.is_empty() {
+                slots.push(None);
+            } else if is_schedulable_type(code) {
+                slots.push(Some(code.to_string()));
+            } else {
+                return Err(FragmentDataError::UnknownActivity {
+                    position: layout.describe_index(index),
+                    code: code.to_string(),
+                });
+            }
+        }
+
+        Ok(Self::from_slots(slots, layout))
+    }
+
+    /// Lee los datos guardados en la base de datos.
+    /// Tolera fragmentos vacíos (`{}`), arrays de otra longitud (p. ej. si cambió
+    /// el número de semanas) y valores numéricos del formato antiguo, que se
+    /// tratan como celdas vacías.
+    pub fn from_stored(data: &Value, layout: &FragmentLayout) -> Self {
+        let values: Vec<Slot> = data
+            .get("values")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .map(|v| v.as_str().filter(|s| !s.is_empty()).map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut slots: Vec<Slot> = values.into_iter().take(layout.total_slots()).collect();
+        slots.resize(layout.total_slots(), None);
+        Self::from_slots(slots, layout)
+    }
+
+    /// Reparte un array plano de celdas en semanas, consultas y exámenes
+    fn from_slots(slots: Vec<Slot>, layout: &FragmentLayout) -> Self {
+        let mut iter = slots.into_iter();
+        let weeks = (0..layout.weeks)
+            .map(|_| iter.by_ref().take(layout.days_per_week).collect())
+            .collect();
+        let consultas = iter.by_ref().take(layout.consultas_slots).collect();
+        let examenes = iter.take(layout.exam_slots).collect();
+
+        Self {
+            version: FRAGMENT_SCHEMA_VERSION,
+            weeks,
+            consultas,
+            examenes,
+        }
+    }
+
+    /// Todas las celdas en el orden del array plano
+    pub fn slots(&self) -> impl Iterator<Item = &Slot> {
+        self.weeks
+            .iter()
+            .flatten()
+            .chain(self.consultas.iter())
+            .chain(self.examenes.iter())
+    }
+
+    /// Códigos de actividad de las celdas ocupadas
+    pub fn activities(&self) -> impl Iterator<Item = &str> {
+        self.slots().filter_map(|s| s.as_deref())
+    }
+
+    /// Diferencias celda a celda respecto a una versión posterior
+    pub fn diff(&self, newer: &FragmentData) -> Vec<CellChange> {
+        let mut changes = Vec::new();
+
+        let weeks = self.weeks.len().max(newer.weeks.len());
+        for week in 0..weeks {
+            let old = self.weeks.get(week).map(Vec::as_slice).unwrap_or_default();
+            let new = newer.weeks.get(week).map(Vec::as_slice).unwrap_or_default();
+            diff_slots(old, new, GridSection::Week, Some(week + 1), &mut changes);
+        }
+        diff_slots(&self.consultas, &newer.consultas, GridSection::Consultas, None, &mut changes);
+        diff_slots(&self.examenes, &newer.examenes, GridSection::Examenes, None, &mut changes);
+
+        changes
+    }
+
+    /// Adapta la distribución a un balance con otro número de semanas.
+    /// La semana `n` pasa a ser la `n + offset`; las que quedan fuera se
+    /// descartan y las nuevas quedan vacías. Consultas y exámenes se conservan,
+    /// así que el destino debe tener la misma cuadrícula (`FragmentLayout::same_grid`).
+    pub fn shift_weeks(&self, offset: i32, weeks: usize) -> FragmentData {
+        let days = self.weeks.first().map(Vec::len).unwrap_or(DAYS_PER_WEEK);
+        let shifted = (0..weeks)
+            .map(|week| {
+                usize::try_from(week as i64 - offset as i64)
+                    .ok()
+                    .and_then(|source| self.weeks.get(source).cloned())
+                    .unwrap_or_else(|| vec![None; days])
+            })
+            .collect();
+
+        FragmentData {
+            version: self.version,
+            weeks: shifted,
+            consultas: self.consultas.clone(),
+            examenes: self.examenes.clone(),
+        }
+    }
+
+    /// Convierte a formato de intercambio
+    pub fn to_payload(&self) -> FragmentPayload {
+        FragmentPayload {
+            version: Some(self.version),
+            values: self.slots().map(|s| s.clone().unwrap_or_default()).collect(),
+        }
+    }
+
+    /// Convierte al JSON que se guarda en `balance_fragments.data`
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self.to_payload()).unwrap_or_else(|_| serde_json::json!({}))
+    }
+}
+
+fn diff_slots(old: &[Slot], new: &[Slot], section: GridSection, week: Option<usize>, changes: &mut Vec<CellChange>) {
+    for day in 0..old.len().max(new.len()) {
+        let old_slot = old.get(day).cloned().flatten();
+        let new_slot = new.get(day).cloned().flatten();
+        if old_slot != new_slot {
+            changes.push(CellChange { section, week, day: day + 1, old: old_slot, new: new_slot });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(values: &[&str]) -> FragmentPayload {
+        FragmentPayload {
+            version: None,
+            values: values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_from_stored() {
+        // Flat "values" array, 4 items per week
+        let data = serde_json::json!({
+            "values": ["C", "CP", "", "S", "C", "", "PL", ""]
+        });
+
+        let result = FragmentData::from_stored(&data, &FragmentLayout::for_weeks(2));
+
+        assert_eq!(result.weeks.len(), 2);
+        assert_eq!(result.weeks[0], vec![Some("C".into()), Some("CP".into()), None, Some("S".into())]);
+        assert_eq!(result.weeks[1], vec![Some("C".into()), None, Some("PL".into()), None]);
+        assert_eq!(result.consultas, vec![None; CONSULTAS_SLOTS]);
+        assert_eq!(result.examenes, vec![None; EXAM_SLOTS]);
+    }
+
+    #[test]
+    fn test_from_stored_empty() {
+        let data = serde_json::json!({});
+        let result = FragmentData::from_stored(&data, &FragmentLayout::for_weeks(2));
+
+        assert_eq!(result, FragmentData::empty(&FragmentLayout::for_weeks(2)));
+    }
+
+    #[test]
+    fn test_from_payload_splits_final_weeks() {
+        let layout = FragmentLayout::for_weeks(1);
+        let mut values = vec![""; layout.total_slots()];
+        values[0] = "C";
+        values[4] = "CP";
+        values[8] = "EC";
+
+        let data = FragmentData::from_payload(payload(&values), &layout).unwrap();
+
+        assert_eq!(data.weeks[0][0].as_deref(), Some("C"));
+        assert_eq!(data.consultas[0].as_deref(), Some("CP"));
+        assert_eq!(data.examenes[0].as_deref(), Some("EC"));
+        assert_eq!(data.to_payload().values, values);
+    }
+
+    #[test]
+    fn test_diff() {
+        let layout = FragmentLayout::for_weeks(2);
+        let old = FragmentData::from_stored(&serde_json::json!({ "values": ["C", "", "", "", "", "S"] }), &layout);
+        let new = FragmentData::from_stored(&serde_json::json!({ "values": ["CP", "", "", "", "", "S", "", "", "PP"] }), &layout);
+
+        assert_eq!(old.diff(&new), vec![
+            CellChange { section: GridSection::Week, week: Some(1), day: 1, old: Some("C".into()), new: Some("CP".into()) },
+            CellChange { section: GridSection::Consultas, week: None, day: 1, old: None, new: Some("PP".into()) },
+        ]);
+    }
+
+    #[test]
+    fn test_shift_weeks() {
+        let data = FragmentData::from_stored(&serde_json::json!({ "values": ["C", "", "", "", "S", "", "", "", "PL"] }), &FragmentLayout::for_weeks(2));
+
+        let truncated = data.shift_weeks(0, 1);
+        assert_eq!(truncated.weeks, vec![vec![Some("C".into()), None, None, None]]);
+        assert_eq!(truncated.consultas, data.consultas);
+
+        let shifted = data.shift_weeks(1, 3);
+        assert_eq!(shifted.weeks[0], vec![None; DAYS_PER_WEEK]);
+        assert_eq!(shifted.weeks[2], data.weeks[1]);
+
+        assert_eq!(data.shift_weeks(-1, 2).weeks[0], data.weeks[1]);
+    }
+
+    #[test]
+    fn test_same_grid() {
+        let standard = WeekStructure::default();
+        assert!(standard.layout(15).same_grid(&standard.layout(16)));
+
+        let fewer_consultas = WeekStructure { consultas_slots: 2, ..standard }.layout(15);
+        let more_exams = WeekStructure { exam_slots: 6, ..standard }.layout(15);
+        assert!(!standard.layout(15).same_grid(&fewer_consultas));
+        assert!(!standard.layout(15).same_grid(&more_exams));
+    }
+
+    #[test]
+    fn test_from_payload_rejects_wrong_length() {
+        let layout = FragmentLayout::for_weeks(2);
+        let err = FragmentData::from_payload(payload(&["C", "CP"]), &layout).unwrap_err();
+
+        assert_eq!(err, FragmentDataError::WrongLength { expected: 17, actual: 2 });
+    }
+
+    #[test]
+    fn test_from_payload_rejects_unknown_activity() {
+        let layout = FragmentLayout::for_weeks(1);
+        let mut values = vec![""; layout.total_slots()];
+        values[6] = "XX";
+
+        let err = FragmentData::from_payload(payload(&values), &layout).unwrap_err();
+
+        assert_eq!(err, FragmentDataError::UnknownActivity {
+            position: "consultas, día 3".to_string(),
+            code: "XX".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_week_structure() {
+        let structure: WeekStructure = serde_json::from_value(serde_json::json!({
+            "slots_per_week": 6,
+            "working_days": [6, 1, 2, 3, 4, 5]
+        }))
+        .unwrap();
+        assert!(structure.validate().is_ok());
+        assert_eq!(structure.consultas_slots, CONSULTAS_SLOTS);
+        assert!(structure.working_days.contains(Weekday::Sat));
+        assert_eq!(serde_json::to_value(structure).unwrap()["working_days"], serde_json::json!([1, 2, 3, 4, 5, 6]));
+
+        let layout = structure.layout(2);
+        assert_eq!(layout.total_slots(), 2 * 6 + CONSULTAS_SLOTS + EXAM_SLOTS);
+        assert_eq!(layout.describe_index(7), "semana 2, día 2");
+
+        // Más celdas que días laborables, o días fuera de rango
+        let too_many = WeekStructure { slots_per_week: 6, ..WeekStructure::default() };
+        assert!(too_many.validate().is_err());
+        assert!(serde_json::from_value::<WeekStructure>(serde_json::json!({ "working_days": [0, 8] })).is_err());
+        assert!(serde_json::from_value::<WeekStructure>(serde_json::json!({ "working_days": [] })).is_err());
+
+        // Sin estructura guardada, o con una inválida, se usa la estándar
+        assert_eq!(WeekStructure::from_stored(None).layout(3), FragmentLayout::for_weeks(3));
+        assert_eq!(WeekStructure::from_stored(Some(&serde_json::json!({ "slots_per_week": 9 }))), WeekStructure::default());
+    }
+}
@@ -0,0 +1,275 @@
+//! Revisión de fragmentos
+//!
+//! pending → in_progress → submitted → completed. El jefe de asignatura entrega
+//! su fragmento y el Leader del balance lo aprueba o pide cambios
+//! (`changes_requested`) con comentarios anclados a semanas o celdas; tras
+//! corregirlo, el jefe vuelve a entregarlo.
+
+use crate::utils::fragment_data::{FragmentLayout, GridSection};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Estado de un fragmento
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FragmentStatus {
+    Pending,
+    InProgress,
+    Submitted,
+    ChangesRequested,
+    Completed,
+}
+
+impl FragmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FragmentStatus::Pending => "pending",
+            FragmentStatus::InProgress => "in_progress",
+            FragmentStatus::Submitted => "submitted",
+            FragmentStatus::ChangesRequested => "changes_requested",
+            FragmentStatus::Completed => "completed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(FragmentStatus::Pending),
+            "in_progress" => Some(FragmentStatus::InProgress),
+            "submitted" => Some(FragmentStatus::Submitted),
+            "changes_requested" => Some(FragmentStatus::ChangesRequested),
+            "completed" => Some(FragmentStatus::Completed),
+            _ => None,
+        }
+    }
+
+    /// Un fragmento entregado no lo modifica su jefe de asignatura hasta que se
+    /// revise, ni uno aprobado hasta que el Leader pida cambios
+    pub fn locked_for_owner(&self) -> bool {
+        matches!(self, FragmentStatus::Submitted | FragmentStatus::Completed)
+    }
+}
+
+impl fmt::Display for FragmentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Acción del ciclo de revisión
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewAction {
+    Submit,
+    Approve,
+    RequestChanges,
+}
+
+impl ReviewAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewAction::Submit => "submit",
+            ReviewAction::Approve => "approve",
+            ReviewAction::RequestChanges => "request_changes",
+        }
+    }
+
+    /// Estados desde los que se permite la acción
+    pub fn allowed_from(&self) -> &'static [FragmentStatus] {
+        match self {
+            ReviewAction::Submit => &[FragmentStatus::Pending, FragmentStatus::InProgress, FragmentStatus::ChangesRequested],
+            ReviewAction::Approve => &[FragmentStatus::Submitted],
+            ReviewAction::RequestChanges => &[FragmentStatus::Submitted, FragmentStatus::Completed],
+        }
+    }
+
+    /// Estado resultante
+    pub fn target(&self) -> FragmentStatus {
+        match self {
+            ReviewAction::Submit => FragmentStatus::Submitted,
+            ReviewAction::Approve => FragmentStatus::Completed,
+            ReviewAction::RequestChanges => FragmentStatus::ChangesRequested,
+        }
+    }
+
+    /// Aprobar y pedir cambios corresponde al Leader del balance
+    pub fn is_reviewer_action(&self) -> bool {
+        !matches!(self, ReviewAction::Submit)
+    }
+}
+
+impl fmt::Display for ReviewAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Datos del fragmento necesarios para evaluar las precondiciones
+#[derive(Debug, Clone, Default)]
+pub struct ReviewContext {
+    /// El fragmento cuadra con lo planificado (ver `reconciliation`)
+    pub balanced: bool,
+    /// El Leader indicó un motivo para aprobar un fragmento descuadrado
+    pub has_override_reason: bool,
+    /// Hilos de comentarios sin resolver
+    pub unresolved_threads: usize,
+    /// Comentarios que acompañan a la acción
+    pub new_comments: usize,
+}
+
+/// Motivo por el que se rechaza una acción de revisión
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewError {
+    /// La acción no es válida desde el estado actual
+    InvalidTransition { from: FragmentStatus, action: ReviewAction },
+    /// El fragmento no cuadra con lo planificado
+    Unbalanced,
+    /// No se cumplen las precondiciones de la acción
+    PreconditionFailed(String),
+}
+
+impl fmt::Display for ReviewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewError::InvalidTransition { from, action } => {
+                write!(f, "No se puede aplicar '{}' a un fragmento en estado '{}'", action, from)
+            }
+            ReviewError::Unbalanced => f.write_str("El fragmento no cuadra con lo planificado"),
+            ReviewError::PreconditionFailed(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// Valida una acción de revisión y devuelve el nuevo estado
+pub fn plan_review(
+    from: FragmentStatus,
+    action: ReviewAction,
+    ctx: &ReviewContext,
+) -> Result<FragmentStatus, ReviewError> {
+    if !action.allowed_from().contains(&from) {
+        return Err(ReviewError::InvalidTransition { from, action });
+    }
+
+    match action {
+        ReviewAction::Submit if !ctx.balanced => return Err(ReviewError::Unbalanced),
+        ReviewAction::Approve if ctx.unresolved_threads > 0 => {
+            return Err(ReviewError::PreconditionFailed(format!(
+                "Quedan {} hilo(s) de comentarios sin resolver",
+                ctx.unresolved_threads
+            )));
+        }
+        ReviewAction::Approve if !ctx.balanced && !ctx.has_override_reason => return Err(ReviewError::Unbalanced),
+        ReviewAction::RequestChanges if ctx.new_comments == 0 => {
+            return Err(ReviewError::PreconditionFailed(
+                "Indica al menos un comentario con los cambios que se piden".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    Ok(action.target())
+}
+
+/// Posición de la cuadrícula a la que se refiere un comentario.
+/// Sin sección es un comentario general; con sección `week` y sin día se
+/// refiere a toda la semana.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CommentAnchor {
+    pub section: Option<GridSection>,
+    pub week: Option<usize>, // 1-based, solo en semanas lectivas
+    pub day: Option<usize>,  // 1-based
+}
+
+impl CommentAnchor {
+    /// Comprueba que el ancla existe en la cuadrícula del balance
+    pub fn validate(&self, layout: &FragmentLayout) -> Result<(), String> {
+        let days = match self.section {
+            None if self.week.is_none() && self.day.is_none() => return Ok(()),
+            None => return Err("Indica la sección de la cuadrícula a la que se refiere el comentario".to_string()),
+            Some(GridSection::Week) => {
+                match self.week {
+                    Some(week) if (1..=layout.weeks).contains(&week) => {}
+                    Some(week) => return Err(format!("La semana {} no existe en el balance (1-{})", week, layout.weeks)),
+                    None => return Err("Indica la semana a la que se refiere el comentario".to_string()),
+                }
+                layout.days_per_week
+            }
+            Some(_) if self.week.is_some() => {
+                return Err("Solo las semanas lectivas llevan número de semana".to_string());
+            }
+            Some(GridSection::Consultas) => layout.consultas_slots,
+            Some(GridSection::Examenes) => layout.exam_slots,
+        };
+
+        match self.day {
+            Some(day) if !(1..=days).contains(&day) => Err(format!("El día {} no existe en la sección (1-{})", day, days)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(balanced: bool, unresolved: usize, comments: usize) -> ReviewContext {
+        ReviewContext { balanced, has_override_reason: false, unresolved_threads: unresolved, new_comments: comments }
+    }
+
+    #[test]
+    fn test_review_cycle() {
+        let mut status = FragmentStatus::InProgress;
+        status = plan_review(status, ReviewAction::Submit, &ctx(true, 0, 0)).unwrap();
+        assert_eq!(status, FragmentStatus::Submitted);
+        status = plan_review(status, ReviewAction::RequestChanges, &ctx(true, 0, 2)).unwrap();
+        assert_eq!(status, FragmentStatus::ChangesRequested);
+        status = plan_review(status, ReviewAction::Submit, &ctx(true, 2, 0)).unwrap();
+        status = plan_review(status, ReviewAction::Approve, &ctx(true, 0, 0)).unwrap();
+        assert_eq!(status, FragmentStatus::Completed);
+    }
+
+    #[test]
+    fn test_owner_cannot_edit_approved_fragment() {
+        let approved = plan_review(FragmentStatus::Submitted, ReviewAction::Approve, &ctx(true, 0, 0)).unwrap();
+        assert!(approved.locked_for_owner());
+        assert!(FragmentStatus::Submitted.locked_for_owner());
+
+        // Solo pidiendo cambios vuelve a poder editarlo su jefe de asignatura
+        let reopened = plan_review(approved, ReviewAction::RequestChanges, &ctx(true, 0, 1)).unwrap();
+        assert!(!reopened.locked_for_owner());
+        assert!(!FragmentStatus::InProgress.locked_for_owner());
+    }
+
+    #[test]
+    fn test_review_preconditions() {
+        assert_eq!(
+            plan_review(FragmentStatus::InProgress, ReviewAction::Approve, &ctx(true, 0, 0)),
+            Err(ReviewError::InvalidTransition { from: FragmentStatus::InProgress, action: ReviewAction::Approve })
+        );
+        assert_eq!(plan_review(FragmentStatus::Pending, ReviewAction::Submit, &ctx(false, 0, 0)), Err(ReviewError::Unbalanced));
+        assert!(matches!(
+            plan_review(FragmentStatus::Submitted, ReviewAction::Approve, &ctx(true, 1, 0)),
+            Err(ReviewError::PreconditionFailed(_))
+        ));
+        assert!(matches!(
+            plan_review(FragmentStatus::Submitted, ReviewAction::RequestChanges, &ctx(true, 0, 0)),
+            Err(ReviewError::PreconditionFailed(_))
+        ));
+
+        let forced = ReviewContext { has_override_reason: true, ..ctx(false, 0, 0) };
+        assert_eq!(plan_review(FragmentStatus::Submitted, ReviewAction::Approve, &forced), Ok(FragmentStatus::Completed));
+    }
+
+    #[test]
+    fn test_comment_anchor() {
+        let layout = FragmentLayout::for_weeks(16);
+        let week = |week, day| CommentAnchor { section: Some(GridSection::Week), week, day };
+
+        assert!(CommentAnchor::default().validate(&layout).is_ok());
+        assert!(week(Some(3), None).validate(&layout).is_ok());
+        assert!(week(Some(16), Some(layout.days_per_week)).validate(&layout).is_ok());
+        assert!(week(Some(17), None).validate(&layout).is_err());
+        assert!(week(None, Some(1)).validate(&layout).is_err());
+        assert!(CommentAnchor { section: Some(GridSection::Examenes), week: Some(1), day: None }.validate(&layout).is_err());
+        assert!(CommentAnchor { section: None, week: Some(1), day: None }.validate(&layout).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Revisión inicial de un fragmento
+//!
+//! Las revisiones guardan el estado tras cada guardado, así que el contenido
+//! que un fragmento tenía antes de su primer guardado (datos existentes al
+//! desplegar el historial, balances creados, clonados o importados) no tendría
+//! revisión. Antes de escribir sobre un fragmento sin revisiones se guarda su
+//! contenido actual como revisión 1, atribuida al jefe de asignatura.
+
+use crate::database::{balance_fragment_revisions, balance_fragments, usuarios};
+use sea_orm::Set;
+
+/// Autor de la revisión inicial de un fragmento sin jefe de asignatura
+pub const BASELINE_AUTHOR: &str = "sistema";
+
+/// Revisión 1 con el contenido actual del fragmento
+pub fn baseline_revision(
+    fragment: &balance_fragments::Model,
+    subject_leader: Option<&usuarios::Model>,
+) -> balance_fragment_revisions::ActiveModel {
+    balance_fragment_revisions::ActiveModel {
+        fragment_id: Set(fragment.id),
+        revision_number: Set(1),
+        data: Set(fragment.data.clone()),
+        status: Set(fragment.status.clone()),
+        author_id: Set(subject_leader.map(|u| u.id)),
+        author_name: Set(subject_leader.map_or_else(|| BASELINE_AUTHOR.to_string(), |u| u.user_name.clone())),
+        restored_from: Set(None),
+        created_at: Set(fragment.updated_at.or(fragment.created_at)),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fragment_data::{FragmentData, FragmentLayout};
+
+    #[test]
+    fn test_baseline_restores_existing_content() {
+        let layout = FragmentLayout::for_weeks(2);
+        let fragment = balance_fragments::Model {
+            id: 5,
+            balance_id: 1,
+            asignatura_id: 3,
+            subject_leader_id: Some(9),
+            status: "completed".to_string(),
+            data: serde_json::json!({ "values": ["C", "", "", "", "", "S", "", "", "PP"] }),
+            completed_at: None,
+            created_at: None,
+            updated_at: None,
+            completion_override_reason: None,
+            deadline_extension: None,
+            version: 4,
+            archived_at: None,
+        };
+        let leader = usuarios::Model {
+            id: 9,
+            name: "Ana".to_string(),
+            email: "ana@example.com".to_string(),
+            token: String::new(),
+            created_at: None,
+            role: Some("subjectLeader".to_string()),
+            user_name: "ana".to_string(),
+            must_change_password: false,
+        };
+
+        let revision = baseline_revision(&fragment, Some(&leader));
+        assert_eq!(revision.revision_number.as_ref(), &1);
+        assert_eq!(revision.status.as_ref(), "completed");
+        assert_eq!(revision.author_name.as_ref(), "ana");
+
+        // Restaurarla devuelve la cuadrícula que había antes del primer guardado
+        let original = FragmentData::from_stored(&fragment.data, &layout);
+        let restored = FragmentData::from_stored(revision.data.as_ref(), &layout);
+        assert!(original.diff(&restored).is_empty());
+        assert_eq!(restored, original);
+
+        assert_eq!(baseline_revision(&fragment, None).author_name.as_ref(), BASELINE_AUTHOR);
+    }
+}
@@ -0,0 +1,159 @@
+//! Exportación iCalendar (RFC 5545)
+//!
+//! Genera un evento de día completo por cada actividad programada, con la
+//! fecha real de la celda según el calendario del balance (`week_calendar`).
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::utils::activity_types::activity_label;
+use crate::utils::week_calendar::BalanceCalendar;
+
+/// Identificador del producto en la cabecera PRODID
+pub const PRODID: &str = "-//Balance de Carga Docente//ES";
+
+/// Longitud máxima de una línea en octetos (RFC 5545, 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Evento de día completo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcalEvent {
+    pub uid: String,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: String,
+}
+
+/// Fragmento cuyas actividades se exportan
+pub struct FragmentEvents<'a> {
+    pub balance_id: i32,
+    pub balance_name: &'a str,
+    pub fragment_id: i32,
+    pub subject_name: &'a str,
+    /// Celdas del fragmento en el orden del array `values`
+    pub slots: Vec<Option<String>>,
+}
+
+/// Un evento por cada celda ocupada que tiene fecha en el calendario del balance
+pub fn fragment_events(fragment: &FragmentEvents, calendar: &BalanceCalendar) -> Vec<IcalEvent> {
+    calendar
+        .weeks
+        .iter()
+        .flat_map(|week| week.slots.iter().map(move |slot| (week, slot)))
+        .filter_map(|(week, slot)| {
+            let activity = fragment.slots.get(slot.index)?.as_deref()?;
+            let date = slot.date?;
+            Some(IcalEvent {
+                uid: format!("balance-{}-fragment-{}-slot-{}@balance-carga", fragment.balance_id, fragment.fragment_id, slot.index),
+                date,
+                summary: format!("{}: {} ({})", fragment.subject_name, activity_label(activity), activity),
+                description: format!("{} - semana {}, día {}", fragment.balance_name, week.week, slot.day),
+            })
+        })
+        .collect()
+}
+
+/// Escapa un valor de tipo TEXT
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Añade una línea plegándola a 75 octetos sin partir caracteres UTF-8
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Genera el documento VCALENDAR completo
+pub fn render_calendar(name: &str, events: &[IcalEvent], stamp: NaiveDateTime) -> String {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+
+    for event in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", event.uid));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut out, &format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")));
+        push_line(&mut out, &format!("DTEND;VALUE=DATE:{}", (event.date + Duration::days(1)).format("%Y%m%d")));
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&event.summary)));
+        push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(&event.description)));
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fragment_data::FragmentLayout;
+    use crate::utils::week_calendar::build_balance_calendar;
+
+    #[test]
+    fn test_fragment_events_use_slot_dates() {
+        let start = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap();
+        let calendar = build_balance_calendar(start, &FragmentLayout::for_weeks(1), &[], &[]);
+        let fragment = FragmentEvents {
+            balance_id: 7,
+            balance_name: "Balance 1ro",
+            fragment_id: 3,
+            subject_name: "Álgebra",
+            slots: vec![None, Some("PP".into()), None, None, Some("C".into())],
+        };
+
+        let events = fragment_events(&fragment, &calendar);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].date, NaiveDate::from_ymd_opt(2025, 9, 2).unwrap());
+        assert_eq!(events[0].summary, "Álgebra: Prueba Parcial (PP)");
+        // La celda 4 es el primer día de la semana de consultas
+        assert_eq!(events[1].date, NaiveDate::from_ymd_opt(2025, 9, 8).unwrap());
+        assert_eq!(events[1].uid, "balance-7-fragment-3-slot-4@balance-carga");
+    }
+
+    #[test]
+    fn test_render_calendar_escapes_and_folds() {
+        let event = IcalEvent {
+            uid: "x@balance-carga".to_string(),
+            date: NaiveDate::from_ymd_opt(2025, 9, 2).unwrap(),
+            summary: "Física; Química, Biología".to_string(),
+            description: "ñ".repeat(60),
+        };
+        let stamp = NaiveDate::from_ymd_opt(2025, 9, 1).unwrap().and_hms_opt(8, 0, 0).unwrap();
+
+        let ics = render_calendar("Balance", &[event], stamp);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("SUMMARY:Física\\; Química\\, Biología\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250902\r\nDTEND;VALUE=DATE:20250903\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
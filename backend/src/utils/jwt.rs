@@ -186,6 +186,9 @@ impl_role_guard!(SubjectLeaderUser, ["subjectLeader"]);
 // LeaderOrSubjectLeader guard - allows leader or subject leader users
 impl_role_guard!(LeaderOrSubjectLeaderUser, ["leader", "subjectLeader"]);
 
+// LeaderOrAdmin guard - allows leader or admin users
+impl_role_guard!(LeaderOrAdminUser, ["leader", "admin"]);
+
 // RESPUESTAS JSON PARA AUTENTICACIÓN
 #[derive(Serialize, Deserialize)]
 pub struct LoginResponse {
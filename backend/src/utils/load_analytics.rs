@@ -0,0 +1,227 @@
+//! Analítica de carga semanal del estudiante
+//!
+//! Combina todos los fragmentos de un balance y calcula, semana a semana y día
+//! a día, las horas de clase por tipo de actividad, las actividades críticas y
+//! las evaluaciones. Incluye métricas resumen (máximo, media y desviación típica)
+//! y las semanas que superan los umbrales configurados.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::utils::activity_types::{hours_for_type, is_critical_type, is_exam_type};
+use crate::utils::balance_rules::{ScheduledSubject, MAX_CRITICAL_PER_WEEK};
+use crate::utils::week_calendar::WeekDateInfo;
+
+/// Máximo de horas de clase por semana por defecto
+pub const DEFAULT_MAX_HOURS_PER_WEEK: i32 = 36;
+
+/// Máximo de evaluaciones (PP, EC) por semana por defecto
+pub const DEFAULT_MAX_EXAMS_PER_WEEK: usize = 2;
+
+/// Umbrales a partir de los cuales una semana se considera sobrecargada
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LoadThresholds {
+    pub max_hours: i32,
+    pub max_critical: usize,
+    pub max_exams: usize,
+}
+
+impl Default for LoadThresholds {
+    fn default() -> Self {
+        Self {
+            max_hours: DEFAULT_MAX_HOURS_PER_WEEK,
+            max_critical: MAX_CRITICAL_PER_WEEK,
+            max_exams: DEFAULT_MAX_EXAMS_PER_WEEK,
+        }
+    }
+}
+
+/// Carga de un día (columna) de la semana
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DayLoad {
+    pub day: usize,      // 1-based
+    pub hours: i32,
+    pub activities: usize,
+    pub critical: usize,
+    pub exams: usize,
+}
+
+/// Carga de una semana lectiva
+#[derive(Debug, Clone, Serialize)]
+pub struct WeekLoad {
+    pub week: usize,     // 1-based
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub hours: i32,
+    pub hours_by_type: BTreeMap<String, i32>,
+    pub critical: usize,
+    pub exams: usize,
+    pub days: Vec<DayLoad>,
+    /// Umbrales superados: "hours", "critical", "exams"
+    pub exceeded: Vec<&'static str>,
+}
+
+/// Máximo, media y desviación típica de una métrica a lo largo de las semanas
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LoadStats {
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+impl LoadStats {
+    pub fn from_values(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        Self {
+            max: values.iter().cloned().fold(f64::MIN, f64::max),
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+/// Métricas resumen del balance
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadSummary {
+    pub hours: LoadStats,
+    pub critical: LoadStats,
+    pub exams: LoadStats,
+    pub overloaded_weeks: Vec<usize>,
+}
+
+/// Resultado completo de la analítica de carga
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub thresholds: LoadThresholds,
+    pub weeks: Vec<WeekLoad>,
+    pub summary: LoadSummary,
+}
+
+/// Calcula la carga semanal del balance.
+/// `week_dates` proviene de `calculate_week_dates` para el mismo balance.
+pub fn analyze_load(
+    subjects: &[ScheduledSubject],
+    weeks: usize,
+    week_dates: &[WeekDateInfo],
+    thresholds: LoadThresholds,
+) -> LoadReport {
+    let mut week_loads = Vec::with_capacity(weeks);
+
+    for week_idx in 0..weeks {
+        let mut days: Vec<DayLoad> = Vec::new();
+        let mut hours_by_type = BTreeMap::new();
+
+        for subject in subjects {
+            let Some(week_data) = subject.data.weeks.get(week_idx) else {
+                continue;
+            };
+            for (day_idx, slot) in week_data.iter().enumerate() {
+                if days.len() <= day_idx {
+                    days.resize_with(day_idx + 1, DayLoad::default);
+                }
+                let Some(activity) = slot else {
+                    continue;
+                };
+                let hours = hours_for_type(activity);
+                let day = &mut days[day_idx];
+                day.hours += hours;
+                day.activities += 1;
+                day.critical += usize::from(is_critical_type(activity));
+                day.exams += usize::from(is_exam_type(activity));
+                *hours_by_type.entry(activity.clone()).or_insert(0) += hours;
+            }
+        }
+
+        for (idx, day) in days.iter_mut().enumerate() {
+            day.day = idx + 1;
+        }
+
+        let hours: i32 = days.iter().map(|d| d.hours).sum();
+        let critical: usize = days.iter().map(|d| d.critical).sum();
+        let exams: usize = days.iter().map(|d| d.exams).sum();
+
+        let mut exceeded = Vec::new();
+        if hours > thresholds.max_hours {
+            exceeded.push("hours");
+        }
+        if critical > thresholds.max_critical {
+            exceeded.push("critical");
+        }
+        if exams > thresholds.max_exams {
+            exceeded.push("exams");
+        }
+
+        let dates = week_dates.get(week_idx);
+        week_loads.push(WeekLoad {
+            week: week_idx + 1,
+            start_date: dates.map(|d| d.start_date),
+            end_date: dates.map(|d| d.end_date),
+            hours,
+            hours_by_type,
+            critical,
+            exams,
+            days,
+            exceeded,
+        });
+    }
+
+    let stats = |f: fn(&WeekLoad) -> f64| LoadStats::from_values(&week_loads.iter().map(f).collect::<Vec<_>>());
+    let summary = LoadSummary {
+        hours: stats(|w| w.hours as f64),
+        critical: stats(|w| w.critical as f64),
+        exams: stats(|w| w.exams as f64),
+        overloaded_weeks: week_loads.iter().filter(|w| !w.exceeded.is_empty()).map(|w| w.week).collect(),
+    };
+
+    LoadReport { thresholds, weeks: week_loads, summary }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fragment_data::{FragmentData, FragmentLayout};
+
+    fn subject(id: i32, values: &[&str]) -> ScheduledSubject {
+        ScheduledSubject {
+            asignatura_id: id,
+            name: format!("Asignatura {}", id),
+            data: FragmentData::from_stored(&serde_json::json!({ "values": values }), &FragmentLayout::for_weeks(2)),
+        }
+    }
+
+    #[test]
+    fn test_load_stats() {
+        let stats = LoadStats::from_values(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(stats.max, 9.0);
+        assert_eq!(stats.mean, 5.0);
+        assert_eq!(stats.std_dev, 2.0);
+        assert_eq!(LoadStats::from_values(&[]), LoadStats::default());
+    }
+
+    #[test]
+    fn test_analyze_load_combines_subjects() {
+        let subjects = vec![
+            subject(1, &["C", "PP", "", "TE", "C"]),
+            subject(2, &["CP", "T", "", "", "", "EC"]),
+        ];
+        let thresholds = LoadThresholds { max_hours: 8, ..Default::default() };
+
+        let report = analyze_load(&subjects, 2, &[], thresholds);
+
+        let week1 = &report.weeks[0];
+        assert_eq!(week1.hours, 8); // TE no cuenta como horas presenciales
+        assert_eq!(week1.critical, 3);
+        assert_eq!(week1.exams, 1);
+        assert_eq!(week1.days[1], DayLoad { day: 2, hours: 4, activities: 2, critical: 2, exams: 1 });
+        assert_eq!(week1.exceeded, vec!["critical"]);
+        assert_eq!(report.weeks[1].hours, 4);
+        assert_eq!(report.summary.overloaded_weeks, vec![1]);
+        assert_eq!(report.summary.hours.max, 8.0);
+    }
+}
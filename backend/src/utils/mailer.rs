@@ -0,0 +1,155 @@
+//! Envío de correo por SMTP
+//!
+//! `MailTransport` separa el envío del resto de las notificaciones: en
+//! producción se usa `SmtpMailer` con la configuración de `system_settings`
+//! (un servidor local tipo MailHog basta para pruebas, con `smtp_security = none`).
+
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::future::Future;
+use std::time::Duration;
+
+/// Cifrado de la conexión SMTP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Sin cifrar (servidores locales de pruebas)
+    None,
+    /// STARTTLS sobre el puerto indicado (normalmente 587)
+    StartTls,
+    /// TLS implícito (normalmente 465)
+    Tls,
+}
+
+impl SmtpSecurity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmtpSecurity::None => "none",
+            SmtpSecurity::StartTls => "starttls",
+            SmtpSecurity::Tls => "tls",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Some(SmtpSecurity::None),
+            "starttls" => Some(SmtpSecurity::StartTls),
+            "tls" => Some(SmtpSecurity::Tls),
+            _ => None,
+        }
+    }
+}
+
+/// Configuración del servidor SMTP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+}
+
+impl Default for SmtpSettings {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1025,
+            security: SmtpSecurity::None,
+            username: String::new(),
+            password: String::new(),
+            from: "Balance de Carga <no-reply@localhost>".to_string(),
+        }
+    }
+}
+
+/// Correo listo para enviar (texto plano)
+#[derive(Debug, Clone)]
+pub struct OutgoingEmail<'a> {
+    pub to_name: &'a str,
+    pub to_email: &'a str,
+    pub subject: &'a str,
+    pub body: &'a str,
+}
+
+/// Transporte de correo; devuelve el motivo del fallo para guardarlo en la cola
+pub trait MailTransport {
+    fn send(&self, email: &OutgoingEmail<'_>) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+/// Transporte SMTP
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(settings: &SmtpSettings) -> Result<Self, String> {
+        let from: Mailbox = settings
+            .from
+            .parse()
+            .map_err(|e| format!("Remitente '{}' inválido: {}", settings.from, e))?;
+
+        let mut builder = match settings.security {
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(settings.host.as_str()),
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+                .map_err(|e| format!("Servidor SMTP inválido: {}", e))?,
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host)
+                .map_err(|e| format!("Servidor SMTP inválido: {}", e))?,
+        }
+        .port(settings.port)
+        .timeout(Some(Duration::from_secs(15)));
+
+        if !settings.username.is_empty() {
+            builder = builder.credentials(Credentials::new(settings.username.clone(), settings.password.clone()));
+        }
+
+        Ok(Self { transport: builder.build(), from })
+    }
+}
+
+impl MailTransport for SmtpMailer {
+    async fn send(&self, email: &OutgoingEmail<'_>) -> Result<(), String> {
+        let to = email
+            .to_email
+            .parse()
+            .map(|address| Mailbox::new(Some(email.to_name.to_string()), address))
+            .map_err(|e| format!("Dirección '{}' inválida: {}", email.to_email, e))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body.to_string())
+            .map_err(|e| format!("Error al construir el correo: {}", e))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Error SMTP: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_security() {
+        assert_eq!(SmtpSecurity::parse("none"), Some(SmtpSecurity::None));
+        assert_eq!(SmtpSecurity::parse(" STARTTLS "), Some(SmtpSecurity::StartTls));
+        assert_eq!(SmtpSecurity::parse("tls"), Some(SmtpSecurity::Tls));
+        assert_eq!(SmtpSecurity::parse("ssl"), None);
+    }
+
+    #[test]
+    fn test_smtp_mailer_rejects_invalid_sender() {
+        let settings = SmtpSettings { from: "no es un correo".to_string(), ..Default::default() };
+        assert!(SmtpMailer::new(&settings).is_err());
+        assert!(SmtpMailer::new(&SmtpSettings::default()).is_ok());
+    }
+}
@@ -5,3 +5,23 @@ pub mod cors;
 pub mod rate_limiter;
 pub mod validation;
 pub mod excel_export;
+pub mod activity_types;
+pub mod balance_rules;
+pub mod reconciliation;
+pub mod fragment_data;
+pub mod fragment_revisions;
+pub mod balance_lifecycle;
+pub mod fragment_review;
+pub mod fragment_access;
+pub mod concurrency;
+pub mod load_analytics;
+pub mod academic_calendar;
+pub mod week_calendar;
+pub mod ical;
+pub mod pdf_export;
+pub mod excel_import;
+pub mod table_import;
+pub mod asignatura_catalog;
+pub mod user_provisioning;
+pub mod mailer;
+pub mod notifications;
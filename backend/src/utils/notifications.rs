@@ -0,0 +1,722 @@
+//! Notificaciones a los jefes de asignatura
+//!
+//! Cada evento de un fragmento (balance creado, fragmento asignado, editado por
+//! otra persona, aprobado, cambios pedidos, fecha límite cambiada o próxima) se
+//! guarda en la bandeja de la aplicación (`notifications`) y, si es de los que
+//! se avisan por correo, en `notification_outbox`. Un proceso en segundo plano
+//! envía la cola por SMTP y reintenta los fallidos con espera creciente, de
+//! modo que una caída del servidor de correo no pierde avisos.
+
+use crate::database::{asignaturas, balance_fragments, balances, notification_outbox, notifications, usuarios};
+use crate::utils::balance_lifecycle::BalanceStatus;
+use crate::utils::fragment_access::effective_deadline;
+use crate::utils::fragment_review::FragmentStatus;
+use crate::utils::mailer::{MailTransport, OutgoingEmail, SmtpMailer, SmtpSettings};
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Cada cuánto revisa la cola el proceso en segundo plano
+const WORKER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Avisos enviados por pasada del proceso en segundo plano
+const DELIVERY_BATCH: u64 = 50;
+
+/// Espera máxima entre reintentos
+const MAX_RETRY_DELAY_MINUTES: i64 = 6 * 60;
+
+/// Configuración de las notificaciones (categoría `notifications` de `system_settings`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub smtp: SmtpSettings,
+    /// URL del frontend para los enlaces de los correos (vacía = sin enlace)
+    pub app_url: String,
+    pub max_attempts: i32,
+    pub reminder_days: i64,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp: SmtpSettings::default(),
+            app_url: String::new(),
+            max_attempts: 5,
+            reminder_days: 3,
+        }
+    }
+}
+
+// Configuración global de notificaciones
+static NOTIFICATION_SETTINGS: Lazy<Mutex<NotificationSettings>> = Lazy::new(|| {
+    Mutex::new(NotificationSettings::default())
+});
+
+/// Actualiza la configuración de notificaciones
+pub fn set_notification_settings(settings: NotificationSettings) {
+    let mut current = NOTIFICATION_SETTINGS.lock().unwrap();
+    *current = settings;
+}
+
+/// Obtiene la configuración de notificaciones actual
+pub fn get_notification_settings() -> NotificationSettings {
+    NOTIFICATION_SETTINGS.lock().unwrap().clone()
+}
+
+/// Tipo de aviso
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    BalanceCreated,
+    FragmentAssigned,
+    FragmentEdited,
+    FragmentApproved,
+    DeadlineChanged,
+    DeadlineApproaching,
+    ChangesRequested,
+}
+
+impl NotificationEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEvent::BalanceCreated => "balance_created",
+            NotificationEvent::FragmentAssigned => "fragment_assigned",
+            NotificationEvent::FragmentEdited => "fragment_edited",
+            NotificationEvent::FragmentApproved => "fragment_approved",
+            NotificationEvent::DeadlineChanged => "deadline_changed",
+            NotificationEvent::DeadlineApproaching => "deadline_approaching",
+            NotificationEvent::ChangesRequested => "changes_requested",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "balance_created" => Some(NotificationEvent::BalanceCreated),
+            "fragment_assigned" => Some(NotificationEvent::FragmentAssigned),
+            "fragment_edited" => Some(NotificationEvent::FragmentEdited),
+            "fragment_approved" => Some(NotificationEvent::FragmentApproved),
+            "deadline_changed" => Some(NotificationEvent::DeadlineChanged),
+            "deadline_approaching" => Some(NotificationEvent::DeadlineApproaching),
+            "changes_requested" => Some(NotificationEvent::ChangesRequested),
+            _ => None,
+        }
+    }
+
+    /// Eventos que además se avisan por correo
+    pub fn by_email(&self) -> bool {
+        matches!(
+            self,
+            NotificationEvent::BalanceCreated
+                | NotificationEvent::FragmentAssigned
+                | NotificationEvent::DeadlineApproaching
+                | NotificationEvent::ChangesRequested
+        )
+    }
+}
+
+impl fmt::Display for NotificationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Estado de entrega de un aviso
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    Pending,
+    Sent,
+    /// Se agotaron los reintentos
+    Failed,
+}
+
+impl OutboxStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutboxStatus::Pending => "pending",
+            OutboxStatus::Sent => "sent",
+            OutboxStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(OutboxStatus::Pending),
+            "sent" => Some(OutboxStatus::Sent),
+            "failed" => Some(OutboxStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OutboxStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Fragmento al que se refiere un aviso
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FragmentNotice {
+    pub balance_id: i32,
+    pub balance_name: String,
+    pub asignatura_id: i32,
+    pub asignatura_name: String,
+    /// Fecha límite efectiva (con la prórroga, si existe)
+    pub deadline: Option<NaiveDate>,
+}
+
+/// Aviso con los datos de su plantilla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    BalanceCreated(FragmentNotice),
+    FragmentAssigned(FragmentNotice),
+    FragmentEdited { fragment: FragmentNotice, editor: String },
+    FragmentApproved { fragment: FragmentNotice, reviewer: String },
+    DeadlineChanged(FragmentNotice),
+    DeadlineApproaching { fragment: FragmentNotice, days_left: i64 },
+    ChangesRequested { fragment: FragmentNotice, reviewer: String, comments: Vec<String> },
+}
+
+/// Asunto y cuerpo (texto plano) de un correo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailContent {
+    pub subject: String,
+    pub body: String,
+}
+
+/// Título y texto breve de un aviso de la bandeja
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboxContent {
+    pub title: String,
+    pub message: String,
+}
+
+/// Fecha límite para mostrar en los avisos
+fn format_deadline(deadline: Option<NaiveDate>) -> String {
+    deadline
+        .map(|d| d.format("%d/%m/%Y").to_string())
+        .unwrap_or_else(|| "sin fecha límite".to_string())
+}
+
+impl Notification {
+    pub fn event(&self) -> NotificationEvent {
+        match self {
+            Notification::BalanceCreated(_) => NotificationEvent::BalanceCreated,
+            Notification::FragmentAssigned(_) => NotificationEvent::FragmentAssigned,
+            Notification::FragmentEdited { .. } => NotificationEvent::FragmentEdited,
+            Notification::FragmentApproved { .. } => NotificationEvent::FragmentApproved,
+            Notification::DeadlineChanged(_) => NotificationEvent::DeadlineChanged,
+            Notification::DeadlineApproaching { .. } => NotificationEvent::DeadlineApproaching,
+            Notification::ChangesRequested { .. } => NotificationEvent::ChangesRequested,
+        }
+    }
+
+    pub fn fragment(&self) -> &FragmentNotice {
+        match self {
+            Notification::BalanceCreated(fragment)
+            | Notification::FragmentAssigned(fragment)
+            | Notification::FragmentEdited { fragment, .. }
+            | Notification::FragmentApproved { fragment, .. }
+            | Notification::DeadlineChanged(fragment)
+            | Notification::DeadlineApproaching { fragment, .. }
+            | Notification::ChangesRequested { fragment, .. } => fragment,
+        }
+    }
+
+    /// Aviso breve para la bandeja de la aplicación
+    pub fn inbox(&self) -> InboxContent {
+        let fragment = self.fragment();
+        let (title, message) = match self {
+            Notification::BalanceCreated(_) => (
+                "Nuevo balance de carga".to_string(),
+                format!("Completa el fragmento de «{}» en «{}»", fragment.asignatura_name, fragment.balance_name),
+            ),
+            Notification::FragmentAssigned(_) => (
+                "Fragmento asignado".to_string(),
+                format!("Se te ha asignado «{}» en «{}»", fragment.asignatura_name, fragment.balance_name),
+            ),
+            Notification::FragmentEdited { editor, .. } => (
+                "Fragmento modificado".to_string(),
+                format!("{} ha modificado «{}» en «{}»", editor, fragment.asignatura_name, fragment.balance_name),
+            ),
+            Notification::FragmentApproved { reviewer, .. } => (
+                "Fragmento aprobado".to_string(),
+                format!("{} ha aprobado «{}» en «{}»", reviewer, fragment.asignatura_name, fragment.balance_name),
+            ),
+            Notification::DeadlineChanged(_) => (
+                "Fecha límite cambiada".to_string(),
+                format!(
+                    "«{}» en «{}»: {}",
+                    fragment.asignatura_name, fragment.balance_name, format_deadline(fragment.deadline)
+                ),
+            ),
+            Notification::DeadlineApproaching { days_left, .. } => (
+                "Fecha límite próxima".to_string(),
+                format!(
+                    "«{}» en «{}» vence el {} ({} día(s))",
+                    fragment.asignatura_name, fragment.balance_name, format_deadline(fragment.deadline), days_left
+                ),
+            ),
+            Notification::ChangesRequested { reviewer, comments, .. } => (
+                "Cambios pedidos".to_string(),
+                format!(
+                    "{} ha pedido cambios en «{}» en «{}» ({} comentario(s))",
+                    reviewer, fragment.asignatura_name, fragment.balance_name, comments.len()
+                ),
+            ),
+        };
+        InboxContent { title, message }
+    }
+
+    /// Redacta el correo para el destinatario
+    pub fn render(&self, recipient_name: &str, app_url: &str) -> EmailContent {
+        let fragment = self.fragment();
+        let (subject, intro) = match self {
+            Notification::BalanceCreated(_) => (
+                format!("Nuevo balance de carga: {}", fragment.balance_name),
+                format!(
+                    "Se ha creado el balance de carga «{}». Te corresponde completar el fragmento de la asignatura «{}».",
+                    fragment.balance_name, fragment.asignatura_name
+                ),
+            ),
+            Notification::FragmentAssigned(_) => (
+                format!("Fragmento asignado: {} ({})", fragment.asignatura_name, fragment.balance_name),
+                format!(
+                    "Se te ha asignado el fragmento de la asignatura «{}» en el balance de carga «{}».",
+                    fragment.asignatura_name, fragment.balance_name
+                ),
+            ),
+            Notification::FragmentEdited { editor, .. } => (
+                format!("Fragmento modificado: {} ({})", fragment.asignatura_name, fragment.balance_name),
+                format!(
+                    "{} ha modificado tu fragmento de la asignatura «{}» en el balance de carga «{}».",
+                    editor, fragment.asignatura_name, fragment.balance_name
+                ),
+            ),
+            Notification::FragmentApproved { reviewer, .. } => (
+                format!("Fragmento aprobado: {} ({})", fragment.asignatura_name, fragment.balance_name),
+                format!(
+                    "{} ha aprobado tu fragmento de la asignatura «{}» en el balance de carga «{}».",
+                    reviewer, fragment.asignatura_name, fragment.balance_name
+                ),
+            ),
+            Notification::DeadlineChanged(_) => (
+                format!("Fecha límite cambiada: {} ({})", fragment.asignatura_name, fragment.balance_name),
+                format!(
+                    "Ha cambiado la fecha límite del fragmento de la asignatura «{}» en el balance de carga «{}».",
+                    fragment.asignatura_name, fragment.balance_name
+                ),
+            ),
+            Notification::DeadlineApproaching { days_left, .. } => {
+                let when = match days_left {
+                    0 => "vence hoy".to_string(),
+                    1 => "vence mañana".to_string(),
+                    n => format!("vence en {} días", n),
+                };
+                (
+                    format!("Fecha límite próxima: {} ({})", fragment.asignatura_name, fragment.balance_name),
+                    format!(
+                        "El plazo para completar el fragmento de la asignatura «{}» en el balance de carga «{}» {} y todavía no lo has entregado.",
+                        fragment.asignatura_name, fragment.balance_name, when
+                    ),
+                )
+            }
+            Notification::ChangesRequested { reviewer, comments, .. } => {
+                let mut intro = format!(
+                    "{} ha revisado tu fragmento de la asignatura «{}» en el balance de carga «{}» y ha pedido cambios:\n",
+                    reviewer, fragment.asignatura_name, fragment.balance_name
+                );
+                for comment in comments {
+                    intro.push_str(&format!("\n  - {}", comment.trim()));
+                }
+                intro.push_str("\n\nCuando lo corrijas, vuelve a entregarlo para revisión.");
+                (format!("Cambios pedidos: {} ({})", fragment.asignatura_name, fragment.balance_name), intro)
+            }
+        };
+
+        let mut body = format!("Hola {},\n\n{}\n", recipient_name, intro);
+        if let Some(deadline) = fragment.deadline {
+            body.push_str(&format!("\nFecha límite de entrega: {}.\n", deadline.format("%d/%m/%Y")));
+        }
+        let app_url = app_url.trim().trim_end_matches('/');
+        if !app_url.is_empty() {
+            body.push_str(&format!(
+                "\nPuedes abrir el fragmento en:\n{}/balance/fragment?balanceId={}&asignaturaId={}\n",
+                app_url, fragment.balance_id, fragment.asignatura_id
+            ));
+        }
+        body.push_str("\nEste es un mensaje automático; por favor, no respondas a este correo.\n");
+
+        EmailContent { subject, body }
+    }
+}
+
+/// Próximo intento tras un envío fallido, o `None` si se agotaron los reintentos.
+/// La espera se duplica en cada intento (1, 2, 4... minutos) hasta un máximo de 6 horas
+pub fn schedule_retry(attempts: i32, max_attempts: i32, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    if attempts >= max_attempts {
+        return None;
+    }
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    let minutes = 2i64.pow(exponent).min(MAX_RETRY_DELAY_MINUTES);
+    Some(now + Duration::minutes(minutes))
+}
+
+/// Días que faltan para la fecha límite si ya toca avisar (`days_before` o menos y no vencida)
+pub fn reminder_days_left(deadline: NaiveDate, today: NaiveDate, days_before: i64) -> Option<i64> {
+    let days_left = (deadline - today).num_days();
+    (0..=days_before).contains(&days_left).then_some(days_left)
+}
+
+/// Encola un aviso para el destinatario. Con `dedup_key`, un aviso ya encolado
+/// con la misma clave no se repite. Devuelve si se encoló
+pub async fn enqueue<C: ConnectionTrait>(
+    db: &C,
+    recipient: &usuarios::Model,
+    notification: &Notification,
+    app_url: &str,
+    dedup_key: Option<String>,
+) -> Result<bool, DbErr> {
+    let content = notification.render(&recipient.name, app_url);
+    let entry = notification_outbox::ActiveModel {
+        event: Set(notification.event().as_str().to_string()),
+        recipient_id: Set(Some(recipient.id)),
+        recipient_email: Set(recipient.email.clone()),
+        recipient_name: Set(recipient.name.clone()),
+        subject: Set(content.subject),
+        body: Set(content.body),
+        status: Set(OutboxStatus::Pending.as_str().to_string()),
+        attempts: Set(0),
+        next_attempt_at: Set(Utc::now().naive_utc()),
+        dedup_key: Set(dedup_key),
+        ..Default::default()
+    };
+
+    let inserted = notification_outbox::Entity::insert(entry)
+        .on_conflict(OnConflict::column(notification_outbox::Column::DedupKey).do_nothing().to_owned())
+        .exec_without_returning(db)
+        .await?;
+    Ok(inserted > 0)
+}
+
+/// Jefe de asignatura y datos del aviso de cada fragmento (se omiten los
+/// fragmentos sin jefe o archivados)
+async fn load_fragment_notices<C: ConnectionTrait>(
+    db: &C,
+    fragment_ids: &[i32],
+) -> Result<Vec<(usuarios::Model, FragmentNotice)>, DbErr> {
+    let fragments = balance_fragments::Entity::find()
+        .filter(balance_fragments::Column::Id.is_in(fragment_ids.to_vec()))
+        .filter(balance_fragments::Column::ArchivedAt.is_null())
+        .filter(balance_fragments::Column::SubjectLeaderId.is_not_null())
+        .all(db)
+        .await?;
+    if fragments.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let balances: HashMap<i32, balances::Model> = balances::Entity::find()
+        .filter(balances::Column::Id.is_in(fragments.iter().map(|f| f.balance_id).collect::<Vec<_>>()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| (b.id, b))
+        .collect();
+    let asignaturas: HashMap<i32, String> = asignaturas::Entity::find()
+        .filter(asignaturas::Column::Id.is_in(fragments.iter().map(|f| f.asignatura_id).collect::<Vec<_>>()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|a| (a.id, a.name))
+        .collect();
+    let leaders: HashMap<i32, usuarios::Model> = usuarios::Entity::find()
+        .filter(usuarios::Column::Id.is_in(fragments.iter().filter_map(|f| f.subject_leader_id).collect::<Vec<_>>()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|u| (u.id, u))
+        .collect();
+
+    Ok(fragments
+        .into_iter()
+        .filter_map(|fragment| {
+            let balance = balances.get(&fragment.balance_id)?;
+            let leader = leaders.get(&fragment.subject_leader_id?)?;
+            let notice = FragmentNotice {
+                balance_id: balance.id,
+                balance_name: balance.name.clone(),
+                asignatura_id: fragment.asignatura_id,
+                asignatura_name: asignaturas.get(&fragment.asignatura_id).cloned().unwrap_or_default(),
+                deadline: effective_deadline(balance.deadline, fragment.deadline_extension),
+            };
+            Some((leader.clone(), notice))
+        })
+        .collect())
+}
+
+/// Guarda el aviso en la bandeja del usuario. Con `dedup_key`, un aviso ya
+/// guardado con la misma clave no se repite. Devuelve si se guardó
+pub async fn add_to_inbox<C: ConnectionTrait>(
+    db: &C,
+    user_id: i32,
+    notification: &Notification,
+    dedup_key: Option<String>,
+) -> Result<bool, DbErr> {
+    let content = notification.inbox();
+    let fragment = notification.fragment();
+    let entry = notifications::ActiveModel {
+        user_id: Set(user_id),
+        event: Set(notification.event().as_str().to_string()),
+        title: Set(content.title),
+        message: Set(content.message),
+        balance_id: Set(Some(fragment.balance_id)),
+        asignatura_id: Set(Some(fragment.asignatura_id)),
+        dedup_key: Set(dedup_key),
+        ..Default::default()
+    };
+
+    let inserted = notifications::Entity::insert(entry)
+        .on_conflict(OnConflict::column(notifications::Column::DedupKey).do_nothing().to_owned())
+        .exec_without_returning(db)
+        .await?;
+    Ok(inserted > 0)
+}
+
+/// Avisa al jefe de asignatura de cada fragmento: en la bandeja siempre y por
+/// correo si el evento lo requiere y el envío está activado. Quien provoca el
+/// evento (`actor_id`) no se avisa a sí mismo. Devuelve cuántos se avisaron
+pub async fn notify_fragments<C, F>(db: &C, actor_id: i32, fragment_ids: &[i32], build: F) -> Result<usize, DbErr>
+where
+    C: ConnectionTrait,
+    F: Fn(FragmentNotice) -> Notification,
+{
+    if fragment_ids.is_empty() {
+        return Ok(0);
+    }
+    let settings = get_notification_settings();
+
+    let mut notified = 0;
+    for (leader, notice) in load_fragment_notices(db, fragment_ids).await? {
+        if leader.id == actor_id {
+            continue;
+        }
+        let notification = build(notice);
+        add_to_inbox(db, leader.id, &notification, None).await?;
+        if settings.enabled && notification.event().by_email() {
+            enqueue(db, &leader, &notification, &settings.app_url, None).await?;
+        }
+        notified += 1;
+    }
+    Ok(notified)
+}
+
+/// Avisa de la fecha límite próxima de los fragmentos sin entregar: en la bandeja
+/// siempre y por correo si el envío está activado. Bandeja y correo se deduplican
+/// por separado, una vez por fragmento y fecha límite (una prórroga genera un
+/// nuevo aviso), así que activar el correo más tarde no repite la bandeja.
+/// Devuelve cuántos jefes de asignatura recibieron algún aviso nuevo
+pub async fn enqueue_deadline_reminders(
+    db: &DatabaseConnection,
+    settings: &NotificationSettings,
+    today: NaiveDate,
+) -> Result<usize, DbErr> {
+    let open_balances: Vec<i32> = balances::Entity::find()
+        .filter(balances::Column::Status.is_in([BalanceStatus::Draft.as_str(), BalanceStatus::Open.as_str()]))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| b.id)
+        .collect();
+    if open_balances.is_empty() {
+        return Ok(0);
+    }
+
+    let unsubmitted = [
+        FragmentStatus::Pending.as_str(),
+        FragmentStatus::InProgress.as_str(),
+        FragmentStatus::ChangesRequested.as_str(),
+    ];
+    let fragment_ids: Vec<i32> = balance_fragments::Entity::find()
+        .filter(balance_fragments::Column::BalanceId.is_in(open_balances))
+        .filter(balance_fragments::Column::Status.is_in(unsubmitted))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|f| f.id)
+        .collect();
+
+    let mut queued = 0;
+    for (leader, notice) in load_fragment_notices(db, &fragment_ids).await? {
+        let Some(deadline) = notice.deadline else { continue };
+        let Some(days_left) = reminder_days_left(deadline, today, settings.reminder_days) else { continue };
+
+        // La clave incluye al destinatario: si cambia el jefe de asignatura, el nuevo también recibe el aviso
+        let dedup_key = format!("deadline:{}:{}:{}:{}", notice.balance_id, notice.asignatura_id, deadline, leader.id);
+        let notification = Notification::DeadlineApproaching { fragment: notice, days_left };
+        let mut reminded = add_to_inbox(db, leader.id, &notification, Some(dedup_key.clone())).await?;
+        if settings.enabled {
+            reminded |= enqueue(db, &leader, &notification, &settings.app_url, Some(dedup_key)).await?;
+        }
+        if reminded {
+            queued += 1;
+        }
+    }
+    Ok(queued)
+}
+
+/// Envía los avisos pendientes cuyo próximo intento ya llegó. Devuelve cuántos se enviaron
+pub async fn deliver_pending<T: MailTransport>(
+    db: &DatabaseConnection,
+    transport: &T,
+    max_attempts: i32,
+) -> Result<usize, DbErr> {
+    let now = Utc::now().naive_utc();
+    let batch = notification_outbox::Entity::find()
+        .filter(notification_outbox::Column::Status.eq(OutboxStatus::Pending.as_str()))
+        .filter(notification_outbox::Column::NextAttemptAt.lte(now))
+        .order_by_asc(notification_outbox::Column::Id)
+        .limit(DELIVERY_BATCH)
+        .all(db)
+        .await?;
+
+    let mut sent = 0;
+    for entry in batch {
+        let result = transport
+            .send(&OutgoingEmail {
+                to_name: &entry.recipient_name,
+                to_email: &entry.recipient_email,
+                subject: &entry.subject,
+                body: &entry.body,
+            })
+            .await;
+
+        let attempts = entry.attempts + 1;
+        let mut active: notification_outbox::ActiveModel = entry.into();
+        active.attempts = Set(attempts);
+        match result {
+            Ok(()) => {
+                active.status = Set(OutboxStatus::Sent.as_str().to_string());
+                active.sent_at = Set(Some(Utc::now().naive_utc()));
+                active.last_error = Set(None);
+                sent += 1;
+            }
+            Err(e) => {
+                match schedule_retry(attempts, max_attempts, now) {
+                    Some(next) => active.next_attempt_at = Set(next),
+                    None => active.status = Set(OutboxStatus::Failed.as_str().to_string()),
+                }
+                active.last_error = Set(Some(e));
+            }
+        }
+        active.update(db).await?;
+    }
+    Ok(sent)
+}
+
+/// Lanza el proceso que genera los avisos de fecha límite y, con el correo
+/// activado, envía la cola
+pub fn spawn_worker(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(WORKER_INTERVAL);
+        loop {
+            interval.tick().await;
+            let settings = get_notification_settings();
+
+            if let Err(e) = enqueue_deadline_reminders(&db, &settings, Utc::now().date_naive()).await {
+                eprintln!("❌ Error al generar avisos de fecha límite: {}", e);
+            }
+            if !settings.enabled {
+                continue;
+            }
+
+            match SmtpMailer::new(&settings.smtp) {
+                Ok(mailer) => {
+                    if let Err(e) = deliver_pending(&db, &mailer, settings.max_attempts).await {
+                        eprintln!("❌ Error al enviar la cola de notificaciones: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("❌ Configuración SMTP inválida: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notice() -> FragmentNotice {
+        FragmentNotice {
+            balance_id: 7,
+            balance_name: "1º Primer Cuatrimestre 2025-2026".to_string(),
+            asignatura_id: 3,
+            asignatura_name: "Cálculo".to_string(),
+            deadline: NaiveDate::from_ymd_opt(2025, 10, 20),
+        }
+    }
+
+    #[test]
+    fn test_render_templates() {
+        let created = Notification::BalanceCreated(notice()).render("Ana", "https://balances.example/");
+        assert_eq!(created.subject, "Nuevo balance de carga: 1º Primer Cuatrimestre 2025-2026");
+        assert!(created.body.starts_with("Hola Ana,"));
+        assert!(created.body.contains("«Cálculo»"));
+        assert!(created.body.contains("Fecha límite de entrega: 20/10/2025."));
+        assert!(created.body.contains("https://balances.example/balance/fragment?balanceId=7&asignaturaId=3"));
+
+        let reminder = Notification::DeadlineApproaching { fragment: notice(), days_left: 1 }.render("Ana", "");
+        assert!(reminder.body.contains("vence mañana"));
+        assert!(!reminder.body.contains("balanceId="));
+
+        let changes = Notification::ChangesRequested {
+            fragment: notice(),
+            reviewer: "Luis".to_string(),
+            comments: vec!["Revisa la semana 3".to_string(), "Falta el examen".to_string()],
+        }
+        .render("Ana", "");
+        assert_eq!(changes.subject, "Cambios pedidos: Cálculo (1º Primer Cuatrimestre 2025-2026)");
+        assert!(changes.body.contains("  - Revisa la semana 3\n  - Falta el examen"));
+    }
+
+    #[test]
+    fn test_inbox_content() {
+        let edited = Notification::FragmentEdited { fragment: notice(), editor: "Luis".to_string() };
+        assert_eq!(edited.inbox().title, "Fragmento modificado");
+        assert_eq!(edited.inbox().message, "Luis ha modificado «Cálculo» en «1º Primer Cuatrimestre 2025-2026»");
+        assert!(!edited.event().by_email());
+
+        let moved = Notification::DeadlineChanged(FragmentNotice { deadline: None, ..notice() });
+        assert!(moved.inbox().message.ends_with("sin fecha límite"));
+        assert!(Notification::FragmentAssigned(notice()).event().by_email());
+        assert_eq!(NotificationEvent::parse("fragment_approved"), Some(NotificationEvent::FragmentApproved));
+    }
+
+    #[test]
+    fn test_schedule_retry() {
+        let now = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        assert_eq!(schedule_retry(1, 5, now), Some(now + Duration::minutes(1)));
+        assert_eq!(schedule_retry(3, 5, now), Some(now + Duration::minutes(4)));
+        assert_eq!(schedule_retry(5, 5, now), None);
+        assert_eq!(schedule_retry(20, 30, now), Some(now + Duration::minutes(MAX_RETRY_DELAY_MINUTES)));
+    }
+
+    #[test]
+    fn test_reminder_days_left() {
+        let deadline = NaiveDate::from_ymd_opt(2025, 10, 20).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2025, 10, d).unwrap();
+        assert_eq!(reminder_days_left(deadline, day(16), 3), None);
+        assert_eq!(reminder_days_left(deadline, day(17), 3), Some(3));
+        assert_eq!(reminder_days_left(deadline, day(20), 3), Some(0));
+        assert_eq!(reminder_days_left(deadline, day(21), 3), None);
+    }
+}
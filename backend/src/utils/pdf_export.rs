@@ -0,0 +1,347 @@
+//! PDF Export Module
+//!
+//! Versión imprimible del balance para el Consejo de Facultad. Dibuja la misma
+//! cuadrícula que la hoja de Excel (`excel_export::create_data_sheet`):
+//! - Bloque de cabecera con año, período y curso académico
+//! - Fechas de cada semana, semanas lectivas, Consultas y Exámenes Finales
+//! - Actividades críticas resaltadas en rojo
+//! - Pie con espacio para las firmas
+//!
+//! Se genera en Rust puro (printpdf con fuentes estándar PDF), sin servicios externos.
+
+use printpdf::path::PaintMode;
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, Mm, PdfDocument, PdfLayerReference,
+    Point, Rect, Rgb,
+};
+
+use crate::utils::activity_types::is_critical_type;
+use crate::utils::excel_export::{header_date_range, BalanceExportConfig};
+use crate::utils::week_calendar::{calculate_final_weeks_dates, calculate_week_dates};
+
+// Página A3 apaisada: la cuadrícula completa tiene 70+ columnas
+const PAGE_WIDTH: f32 = 420.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 12.0;
+
+const NAME_COLUMN_WIDTH: f32 = 55.0;
+const MAX_CELL_WIDTH: f32 = 8.0;
+const ROW_HEIGHT: f32 = 5.0;
+const HEADER_BLOCK_HEIGHT: f32 = 24.0;
+const FOOTER_HEIGHT: f32 = 34.0;
+
+const FONT_SIZE: f32 = 6.0;
+const PT_TO_MM: f32 = 0.3528;
+/// Ancho medio aproximado de un carácter de Helvetica, en em
+const AVG_CHAR_WIDTH: f32 = 0.55;
+
+// Mismos colores que la plantilla de Excel
+const HEADER_FILL: (f32, f32, f32) = (0.851, 0.851, 0.851);   // #D9D9D9
+const CRITICAL_FILL: (f32, f32, f32) = (1.0, 0.420, 0.420);   // #FF6B6B
+
+/// Grupo de columnas con cabecera común: una semana, Consultas o Exámenes Finales
+#[derive(Debug, Clone, PartialEq)]
+struct ColumnBlock {
+    label: String,
+    date_range: Option<String>,
+    first_col: usize,
+    cols: usize,
+}
+
+/// Bloques de columnas en el mismo orden que el array `values` del fragmento
+fn column_blocks(config: &BalanceExportConfig) -> Vec<ColumnBlock> {
+    let layout = &config.layout;
+    let week_dates = calculate_week_dates(config.start_date, layout, &config.non_academic_periods);
+    let final_week_dates = calculate_final_weeks_dates(config.start_date, layout, &config.non_academic_periods, &config.exam_sessions);
+
+    let mut blocks: Vec<ColumnBlock> = (0..layout.weeks)
+        .map(|week| ColumnBlock {
+            label: format!("Semana {}", week + 1),
+            date_range: week_dates.get(week).map(header_date_range),
+            first_col: week * layout.days_per_week,
+            cols: layout.days_per_week,
+        })
+        .collect();
+
+    let consultas_start = layout.week_slots();
+    blocks.push(ColumnBlock {
+        label: "Consultas".to_string(),
+        date_range: final_week_dates.first().map(header_date_range),
+        first_col: consultas_start,
+        cols: layout.consultas_slots,
+    });
+    blocks.push(ColumnBlock {
+        label: "Exámenes Finales".to_string(),
+        date_range: final_week_dates.get(1).map(header_date_range),
+        first_col: consultas_start + layout.consultas_slots,
+        cols: layout.exam_slots,
+    });
+
+    blocks
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * AVG_CHAR_WIDTH * PT_TO_MM
+}
+
+/// Recorta el texto para que quepa en `width` mm
+fn fit_text(text: &str, size: f32, width: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+    let max_chars = (width / (size * AVG_CHAR_WIDTH * PT_TO_MM)) as usize;
+    let mut fitted: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+    fitted.push_str("...");
+    fitted
+}
+
+/// Rectángulo en mm, desde la esquina superior izquierda de la página
+#[derive(Debug, Clone, Copy)]
+struct Area {
+    x: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+}
+
+impl Area {
+    fn new(x: f32, top: f32, width: f32, height: f32) -> Self {
+        Self { x, top, width, height }
+    }
+}
+
+/// Dibujo sobre una página con coordenadas desde la esquina superior izquierda
+struct Painter<'a> {
+    layer: PdfLayerReference,
+    regular: &'a IndirectFontRef,
+    bold: &'a IndirectFontRef,
+}
+
+impl Painter<'_> {
+    fn rgb((r, g, b): (f32, f32, f32)) -> Color {
+        Color::Rgb(Rgb::new(r, g, b, None))
+    }
+
+    fn cell(&self, area: Area, fill: Option<(f32, f32, f32)>) {
+        self.layer.set_outline_thickness(0.3);
+        self.layer.set_outline_color(Self::rgb((0.0, 0.0, 0.0)));
+        let rect = Rect::new(
+            Mm(area.x),
+            Mm(PAGE_HEIGHT - area.top - area.height),
+            Mm(area.x + area.width),
+            Mm(PAGE_HEIGHT - area.top),
+        );
+        match fill {
+            Some(color) => {
+                self.layer.set_fill_color(Self::rgb(color));
+                self.layer.add_rect(rect.with_mode(PaintMode::FillStroke));
+            }
+            None => self.layer.add_rect(rect.with_mode(PaintMode::Stroke)),
+        }
+    }
+
+    fn line(&self, x1: f32, top1: f32, x2: f32, top2: f32, thickness: f32) {
+        self.layer.set_outline_thickness(thickness);
+        self.layer.set_outline_color(Self::rgb((0.0, 0.0, 0.0)));
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(x1), Mm(PAGE_HEIGHT - top1)), false),
+                (Point::new(Mm(x2), Mm(PAGE_HEIGHT - top2)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    /// Texto con la línea base a `baseline` mm del borde superior
+    fn text(&self, text: &str, size: f32, x: f32, baseline: f32, bold: bool) {
+        self.layer.set_fill_color(Self::rgb((0.0, 0.0, 0.0)));
+        let font = if bold { self.bold } else { self.regular };
+        self.layer.use_text(text, size, Mm(x), Mm(PAGE_HEIGHT - baseline), font);
+    }
+
+    /// Texto centrado verticalmente en una celda; centrado horizontalmente si `center`
+    fn cell_text(&self, text: &str, size: f32, area: Area, bold: bool, center: bool) {
+        let padding = 1.0;
+        let text = fit_text(text, size, area.width - 2.0 * padding);
+        let text_x = if center { area.x + (area.width - text_width(&text, size)) / 2.0 } else { area.x + padding };
+        let baseline = area.top + area.height / 2.0 + size * PT_TO_MM * 0.35;
+        self.text(&text, size, text_x, baseline, bold);
+    }
+}
+
+/// Filas de asignaturas que caben en una página entre la cabecera y el pie
+fn rows_per_page() -> usize {
+    let rows_top = MARGIN + HEADER_BLOCK_HEIGHT + 3.0 * ROW_HEIGHT;
+    (((PAGE_HEIGHT - MARGIN - FOOTER_HEIGHT - rows_top) / ROW_HEIGHT) as usize).max(1)
+}
+
+/// Generate a PDF document from balance data
+pub fn generate_balance_pdf(config: &BalanceExportConfig) -> Result<Vec<u8>, printpdf::Error> {
+    let title = format!("Balance de Carga {} {} {}", config.academic_year, config.period, config.academic_year_text);
+    let (doc, first_page, first_layer) = PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Balance");
+    let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+
+    let blocks = column_blocks(config);
+    let total_cols: usize = blocks.iter().map(|b| b.cols).sum();
+    let cell_width = ((PAGE_WIDTH - 2.0 * MARGIN - NAME_COLUMN_WIDTH) / total_cols.max(1) as f32).min(MAX_CELL_WIDTH);
+
+    let grid_top = MARGIN + HEADER_BLOCK_HEIGHT;
+    let rows_top = grid_top + 3.0 * ROW_HEIGHT;
+    let chunks: Vec<_> = config.fragments.chunks(rows_per_page()).collect();
+    let page_count = chunks.len().max(1);
+
+    for page_idx in 0..page_count {
+        let layer = if page_idx == 0 {
+            doc.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Balance");
+            doc.get_page(page).get_layer(layer)
+        };
+        let painter = Painter { layer, regular: &regular, bold: &bold };
+
+        // === Bloque de cabecera ===
+        painter.text("Balance de Carga Docente", 14.0, MARGIN, MARGIN + 6.0, true);
+        painter.text(
+            &format!(
+                "Año: {}      Período: {}      Curso académico: {}      Semanas: {}",
+                config.academic_year, config.period, config.academic_year_text, config.layout.weeks
+            ),
+            9.0,
+            MARGIN,
+            MARGIN + 13.0,
+            false,
+        );
+        painter.text(
+            &format!("Inicio: {}      Página {} de {}", config.start_date.format("%d/%m/%Y"), page_idx + 1, page_count),
+            8.0,
+            MARGIN,
+            MARGIN + 18.0,
+            false,
+        );
+
+        // === Cabecera de la cuadrícula: fechas, semanas y días ===
+        let name_header = Area::new(MARGIN, grid_top, NAME_COLUMN_WIDTH, 3.0 * ROW_HEIGHT);
+        painter.cell(name_header, Some(HEADER_FILL));
+        painter.cell_text("Asignaturas", 8.0, name_header, true, false);
+
+        for block in &blocks {
+            let x = MARGIN + NAME_COLUMN_WIDTH + block.first_col as f32 * cell_width;
+            let width = block.cols as f32 * cell_width;
+
+            let dates = Area::new(x, grid_top, width, ROW_HEIGHT);
+            painter.cell(dates, Some(HEADER_FILL));
+            if let Some(range) = &block.date_range {
+                painter.cell_text(range, FONT_SIZE, dates, true, true);
+            }
+
+            let label = Area::new(x, grid_top + ROW_HEIGHT, width, ROW_HEIGHT);
+            painter.cell(label, Some(HEADER_FILL));
+            painter.cell_text(&block.label, FONT_SIZE, label, true, true);
+
+            for day in 0..block.cols {
+                let day_area = Area::new(x + day as f32 * cell_width, grid_top + 2.0 * ROW_HEIGHT, cell_width, ROW_HEIGHT);
+                painter.cell(day_area, None);
+                painter.cell_text(&(day + 1).to_string(), FONT_SIZE, day_area, false, true);
+            }
+        }
+
+        // === Filas de asignaturas ===
+        let fragments = chunks.get(page_idx).copied().unwrap_or_default();
+        for (row_idx, fragment) in fragments.iter().enumerate() {
+            let top = rows_top + row_idx as f32 * ROW_HEIGHT;
+            let name = Area::new(MARGIN, top, NAME_COLUMN_WIDTH, ROW_HEIGHT);
+            painter.cell(name, None);
+            painter.cell_text(&fragment.name, FONT_SIZE, name, false, false);
+
+            for (col, slot) in fragment.data.slots().enumerate().take(total_cols) {
+                let area = Area::new(MARGIN + NAME_COLUMN_WIDTH + col as f32 * cell_width, top, cell_width, ROW_HEIGHT);
+                match slot {
+                    Some(act) => {
+                        painter.cell(area, is_critical_type(act).then_some(CRITICAL_FILL));
+                        painter.cell_text(act, FONT_SIZE, area, false, true);
+                    }
+                    None => painter.cell(area, None),
+                }
+            }
+        }
+
+        // Borde grueso al final de cada semana (como en la plantilla)
+        let grid_bottom = rows_top + fragments.len() as f32 * ROW_HEIGHT;
+        for block in &blocks {
+            let x = MARGIN + NAME_COLUMN_WIDTH + (block.first_col + block.cols) as f32 * cell_width;
+            painter.line(x, grid_top, x, grid_bottom, 1.0);
+        }
+
+        // === Pie: leyenda y firmas ===
+        let footer_top = PAGE_HEIGHT - MARGIN - FOOTER_HEIGHT;
+        painter.cell(Area::new(MARGIN, footer_top, 4.0, 3.0), Some(CRITICAL_FILL));
+        painter.text("Actividades críticas", 7.0, MARGIN + 6.0, footer_top + 2.5, false);
+
+        let signatures = ["Elaborado por", "Revisado por", "Aprobado por el Consejo de Facultad"];
+        let slot_width = (PAGE_WIDTH - 2.0 * MARGIN) / signatures.len() as f32;
+        let line_top = PAGE_HEIGHT - MARGIN - 10.0;
+        for (idx, label) in signatures.iter().enumerate() {
+            let x = MARGIN + idx as f32 * slot_width;
+            painter.line(x + 5.0, line_top, x + slot_width - 15.0, line_top, 0.5);
+            painter.text(label, 8.0, x + 5.0, line_top + 4.0, true);
+            painter.text("Nombre, firma y fecha", 7.0, x + 5.0, line_top + 8.0, false);
+        }
+    }
+
+    doc.save_to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::activity_types::ActivityPlan;
+    use crate::utils::excel_export::FragmentExportData;
+    use crate::utils::fragment_data::{FragmentData, FragmentLayout, CONSULTAS_SLOTS, DAYS_PER_WEEK, EXAM_SLOTS};
+    use chrono::NaiveDate;
+
+    fn config(fragments: usize) -> BalanceExportConfig {
+        let layout = FragmentLayout::for_weeks(2);
+        BalanceExportConfig {
+            academic_year: "1ro".to_string(),
+            period: "1ero".to_string(),
+            academic_year_text: "2025-2026".to_string(),
+            start_date: NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            layout,
+            fragments: (0..fragments)
+                .map(|idx| FragmentExportData {
+                    name: format!("Asignatura {}", idx),
+                    hours: 64,
+                    data: FragmentData::from_stored(&serde_json::json!({ "values": ["C", "PP"] }), &layout),
+                    plan: ActivityPlan::default(),
+                })
+                .collect(),
+            non_academic_periods: vec![],
+            exam_sessions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_column_blocks_follow_grid_order() {
+        let blocks = column_blocks(&config(0));
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0].date_range.as_deref(), Some("1-5 Sep"));
+        assert_eq!(blocks[1].first_col, DAYS_PER_WEEK);
+        assert_eq!(blocks[2].label, "Consultas");
+        assert_eq!(blocks[2].first_col, 2 * DAYS_PER_WEEK);
+        assert_eq!(blocks[3].first_col, 2 * DAYS_PER_WEEK + CONSULTAS_SLOTS);
+        assert_eq!(blocks[3].cols, EXAM_SLOTS);
+    }
+
+    #[test]
+    fn test_generate_balance_pdf() {
+        let pdf = generate_balance_pdf(&config(3)).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        // Con más asignaturas de las que caben la cuadrícula continúa en otra página
+        assert!(rows_per_page() > 20);
+        let paged = generate_balance_pdf(&config(rows_per_page() + 1)).unwrap();
+        assert!(paged.len() > pdf.len());
+    }
+}
@@ -79,12 +79,11 @@ impl RateLimiter {
     pub fn is_blocked(&self, ip: IpAddr) -> bool {
         let attempts = self.attempts.lock().unwrap();
         
-        if let Some(attempt) = attempts.get(&ip) {
-            if let Some(blocked_until) = attempt.blocked_until {
-                if Instant::now() < blocked_until {
-                    return true;
-                }
-            }
+        if let Some(attempt) = attempts.get(&ip)
+            && let Some(blocked_until) = attempt.blocked_until
+            && Instant::now() < blocked_until
+        {
+            return true;
         }
         
         false
@@ -94,12 +93,12 @@ impl RateLimiter {
     pub fn get_block_remaining(&self, ip: IpAddr) -> Option<u64> {
         let attempts = self.attempts.lock().unwrap();
         
-        if let Some(attempt) = attempts.get(&ip) {
-            if let Some(blocked_until) = attempt.blocked_until {
-                let now = Instant::now();
-                if now < blocked_until {
-                    return Some((blocked_until - now).as_secs());
-                }
+        if let Some(attempt) = attempts.get(&ip)
+            && let Some(blocked_until) = attempt.blocked_until
+        {
+            let now = Instant::now();
+            if now < blocked_until {
+                return Some((blocked_until - now).as_secs());
             }
         }
         
@@ -112,12 +111,12 @@ impl RateLimiter {
     pub fn check_block_status(&self, ip: IpAddr) -> (bool, Option<u64>) {
         let attempts = self.attempts.lock().unwrap();
         
-        if let Some(attempt) = attempts.get(&ip) {
-            if let Some(blocked_until) = attempt.blocked_until {
-                let now = Instant::now();
-                if now < blocked_until {
-                    return (true, Some((blocked_until - now).as_secs()));
-                }
+        if let Some(attempt) = attempts.get(&ip)
+            && let Some(blocked_until) = attempt.blocked_until
+        {
+            let now = Instant::now();
+            if now < blocked_until {
+                return (true, Some((blocked_until - now).as_secs()));
             }
         }
         
@@ -0,0 +1,122 @@
+//! Conciliación entre lo planificado y lo programado en un fragmento
+//!
+//! Compara las cantidades planificadas por tipo de actividad de la asignatura
+//! (`asignatura_activity_plans`) y sus horas totales con lo que el fragmento tiene
+//! realmente distribuido en su cuadrícula.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::utils::activity_types::{schedulable_activity_types, ActivityPlan};
+
+/// Conciliación de un tipo de actividad
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ActivityReconciliation {
+    pub activity: String,
+    pub planned: i32,
+    pub scheduled: i32,
+    /// scheduled - planned: positivo = excedente, negativo = déficit
+    pub difference: i32,
+    pub planned_hours: i32,
+    pub scheduled_hours: i32,
+}
+
+/// Conciliación completa de un fragmento
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FragmentReconciliation {
+    pub activities: Vec<ActivityReconciliation>,
+    /// Horas totales de la asignatura (`asignaturas.hours`)
+    pub planned_hours: i32,
+    pub scheduled_hours: i32,
+    /// scheduled_hours - planned_hours: positivo = excedente, negativo = déficit
+    pub hours_difference: i32,
+    /// Códigos programados que no son tipos de actividad conocidos
+    pub unknown_activities: Vec<String>,
+    /// true si cada tipo coincide con lo planificado y no se exceden las horas
+    pub balanced: bool,
+}
+
+/// Concilia las actividades programadas de un fragmento con el plan de la asignatura.
+/// `scheduled` recibe todas las celdas del fragmento (semanas, consultas y exámenes).
+pub fn reconcile<'a>(
+    plan: &ActivityPlan,
+    planned_hours: i32,
+    scheduled: impl IntoIterator<Item = &'a str>,
+) -> FragmentReconciliation {
+    let catalog = schedulable_activity_types();
+    let mut counts: BTreeMap<&str, i32> = BTreeMap::new();
+    let mut unknown_activities = Vec::new();
+
+    for activity in scheduled {
+        if activity.is_empty() {
+            continue;
+        }
+        if catalog.iter().any(|t| t.code == activity) {
+            *counts.entry(activity).or_insert(0) += 1;
+        } else if !unknown_activities.iter().any(|u| u == activity) {
+            unknown_activities.push(activity.to_string());
+        }
+    }
+
+    let activities: Vec<ActivityReconciliation> = catalog
+        .iter()
+        .map(|activity_type| {
+            let activity = activity_type.code.as_str();
+            let planned = plan.planned_count(activity);
+            let scheduled = counts.get(activity).copied().unwrap_or(0);
+            let hours = activity_type.class_hours();
+            ActivityReconciliation {
+                activity: activity.to_string(),
+                planned,
+                scheduled,
+                difference: scheduled - planned,
+                planned_hours: planned * hours,
+                scheduled_hours: scheduled * hours,
+            }
+        })
+        .collect();
+
+    let scheduled_hours: i32 = activities.iter().map(|a| a.scheduled_hours).sum();
+    let hours_difference = scheduled_hours - planned_hours;
+    let balanced = unknown_activities.is_empty()
+        && activities.iter().all(|a| a.difference == 0)
+        && (planned_hours <= 0 || hours_difference <= 0);
+
+    FragmentReconciliation {
+        activities,
+        planned_hours,
+        scheduled_hours,
+        hours_difference,
+        unknown_activities,
+        balanced,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile_balanced() {
+        let plan: ActivityPlan = [("C", 2), ("CP", 1), ("TE", 1)].into_iter().collect();
+        let result = reconcile(&plan, 6, ["C", "", "CP", "C", "TE"]);
+
+        assert!(result.balanced);
+        assert_eq!(result.scheduled_hours, 6);
+        assert_eq!(result.hours_difference, 0);
+    }
+
+    #[test]
+    fn test_reconcile_deficit_and_surplus() {
+        let plan: ActivityPlan = [("C", 2), ("S", 1)].into_iter().collect();
+        let result = reconcile(&plan, 6, ["C", "PL", "XX"]);
+
+        assert!(!result.balanced);
+        let c = result.activities.iter().find(|a| a.activity == "C").unwrap();
+        assert_eq!(c.difference, -1);
+        let pl = result.activities.iter().find(|a| a.activity == "PL").unwrap();
+        assert_eq!(pl.difference, 1);
+        assert_eq!(result.unknown_activities, vec!["XX".to_string()]);
+        assert_eq!(result.hours_difference, -2);
+    }
+}
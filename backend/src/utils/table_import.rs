@@ -0,0 +1,107 @@
+//! Lectura de tablas subidas por el usuario (CSV o XLSX)
+//!
+//! Usado por las importaciones masivas: el archivo se convierte en filas de
+//! texto y las columnas se localizan por el nombre de la cabecera, sin
+//! distinguir mayúsculas.
+
+use calamine::{Data, Reader, Xlsx};
+use serde::Serialize;
+use std::io::Cursor;
+
+/// Error de validación de una fila
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowError {
+    /// Número de fila en el archivo (la cabecera es la 1)
+    pub row: usize,
+    pub message: String,
+}
+
+impl RowError {
+    pub fn new(row: usize, message: impl Into<String>) -> Self {
+        Self { row, message: message.into() }
+    }
+}
+
+/// Lee el archivo como tabla de texto. Los XLSX se detectan por la firma ZIP;
+/// el resto se trata como CSV (separado por comas o punto y coma)
+pub fn read_table(bytes: Vec<u8>) -> Result<Vec<Vec<String>>, String> {
+    if bytes.starts_with(b"PK") {
+        read_xlsx_table(bytes)
+    } else {
+        read_csv_table(&bytes)
+    }
+}
+
+fn read_csv_table(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let first_line = bytes.split(|b| *b == b'\n').next().unwrap_or_default();
+    let delimiter = if first_line.contains(&b';') && !first_line.contains(&b',') { b';' } else { b',' };
+
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(bytes)
+        .records()
+        .map(|record| {
+            record
+                .map(|r| r.iter().map(|field| field.trim().to_string()).collect())
+                .map_err(|e| format!("CSV inválido: {}", e))
+        })
+        .collect()
+}
+
+fn read_xlsx_table(bytes: Vec<u8>) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook: Xlsx<_> = Xlsx::new(Cursor::new(bytes)).map_err(|e| format!("Libro Excel inválido: {}", e))?;
+    let sheet = workbook.sheet_names().first().cloned().ok_or("El libro no tiene hojas")?;
+    let range = workbook.worksheet_range(&sheet).map_err(|e| format!("No se pudo leer la hoja '{}': {}", sheet, e))?;
+
+    Ok(range
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|cell| match cell {
+                    Data::Empty | Data::Error(_) => String::new(),
+                    other => other.to_string().trim().to_string(),
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Cabecera de la tabla: localiza columnas por nombre
+pub struct TableHeader {
+    columns: Vec<String>,
+}
+
+impl TableHeader {
+    /// Separa la cabecera del resto de filas y comprueba las columnas obligatorias
+    pub fn split<'a>(table: &'a [Vec<String>], required: &[&str]) -> Result<(Self, &'a [Vec<String>]), String> {
+        let Some((header, records)) = table.split_first() else {
+            return Err("El archivo está vacío".to_string());
+        };
+        let header = Self { columns: header.iter().map(|h| h.trim().to_lowercase()).collect() };
+        if let Some(missing) = required.iter().find(|c| header.index(c).is_none()) {
+            return Err(format!("Falta la columna '{}' en la cabecera", missing));
+        }
+        Ok((header, records))
+    }
+
+    fn index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+
+    /// Valor de la columna en la fila ("" si la columna o la celda no existen)
+    pub fn field<'a>(&self, record: &'a [String], name: &str) -> &'a str {
+        self.index(name).and_then(|i| record.get(i)).map(String::as_str).unwrap_or("")
+    }
+}
+
+/// Filas con datos junto con su número de fila en el archivo (se omiten las vacías)
+pub fn data_rows(records: &[Vec<String>]) -> impl Iterator<Item = (usize, &[String])> {
+    records
+        .iter()
+        .enumerate()
+        .filter(|(_, record)| record.iter().any(|f| !f.is_empty()))
+        .map(|(i, record)| (i + 2, record.as_slice()))
+}
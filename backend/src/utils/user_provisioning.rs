@@ -0,0 +1,151 @@
+//! Alta masiva de usuarios desde CSV
+//!
+//! Cada fila (username, name, email, role) se valida con `validate_new_user`
+//! usando la contraseña temporal generada para ese usuario. Las contraseñas
+//! cumplen la política vigente y solo se entregan en la hoja de credenciales.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+use crate::utils::table_import::{data_rows, RowError, TableHeader};
+use crate::utils::validation::{validate_new_user, PasswordPolicy};
+
+/// Roles que se pueden asignar en el alta masiva
+pub const PROVISIONING_ROLES: &[&str] = &["admin", "leader", "subjectLeader", "user"];
+
+/// Longitud mínima de las contraseñas temporales (más si la política lo exige)
+const TEMP_PASSWORD_LENGTH: usize = 16;
+
+// Sin caracteres ambiguos (0/O, 1/l/I) para poder copiarlas a mano
+const UPPERCASE: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const LOWERCASE: &[u8] = b"abcdefghijkmnopqrstuvwxyz";
+const DIGITS: &[u8] = b"23456789";
+const SPECIAL: &[u8] = b"!#%*+-=?@_";
+
+/// Usuario a crear, con su contraseña temporal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvisionedUser {
+    pub row: usize,
+    pub user_name: String,
+    pub name: String,
+    pub email: String,
+    pub role: String,
+    pub password: String,
+}
+
+/// Contraseña aleatoria con mayúsculas, minúsculas, dígitos y símbolos
+pub fn generate_temporary_password(policy: &PasswordPolicy) -> String {
+    let mut rng = rand::thread_rng();
+    let length = TEMP_PASSWORD_LENGTH.max(policy.min_length);
+    let all: Vec<u8> = [UPPERCASE, LOWERCASE, DIGITS, SPECIAL].concat();
+
+    // Un carácter de cada grupo garantiza la política; el resto es aleatorio
+    let mut chars: Vec<u8> = [UPPERCASE, LOWERCASE, DIGITS, SPECIAL]
+        .iter()
+        .map(|set| set[rng.gen_range(0..set.len())])
+        .collect();
+    while chars.len() < length {
+        chars.push(all[rng.gen_range(0..all.len())]);
+    }
+    chars.shuffle(&mut rng);
+    chars.into_iter().map(char::from).collect()
+}
+
+/// Valida las filas del CSV y genera la contraseña de cada usuario.
+/// Los usernames y emails repetidos (en el archivo o en `taken`) son errores de fila
+pub fn parse_users(
+    table: &[Vec<String>],
+    policy: &PasswordPolicy,
+    taken_user_names: &HashSet<String>,
+    taken_emails: &HashSet<String>,
+) -> Result<(Vec<ProvisionedUser>, Vec<RowError>), String> {
+    let (header, records) = TableHeader::split(table, &["username", "name", "email", "role"])?;
+
+    let mut users = Vec::new();
+    let mut errors = Vec::new();
+    let mut user_names = HashSet::new();
+    let mut emails = HashSet::new();
+    for (row, record) in data_rows(records) {
+        let user_name = header.field(record, "username");
+        let name = header.field(record, "name");
+        let email = header.field(record, "email");
+        let role = header.field(record, "role");
+        let password = generate_temporary_password(policy);
+
+        let validation = validate_new_user(user_name, name, email, &password);
+        let error = if !validation.valid {
+            validation.error.or_else(|| Some("Datos inválidos".to_string()))
+        } else if !PROVISIONING_ROLES.contains(&role) {
+            Some(format!("Rol inválido '{}' (use {})", role, PROVISIONING_ROLES.join(", ")))
+        } else if taken_user_names.contains(user_name) || !user_names.insert(user_name.to_string()) {
+            Some(format!("El username '{}' ya existe", user_name))
+        } else if taken_emails.contains(&email.to_lowercase()) || !emails.insert(email.to_lowercase()) {
+            Some(format!("El email '{}' ya está registrado", email))
+        } else {
+            None
+        };
+
+        match error {
+            Some(message) => errors.push(RowError::new(row, message)),
+            None => users.push(ProvisionedUser {
+                row,
+                user_name: user_name.to_string(),
+                name: name.to_string(),
+                email: email.to_string(),
+                role: role.to_string(),
+                password,
+            }),
+        }
+    }
+    Ok((users, errors))
+}
+
+/// Hoja de credenciales (CSV) para entregar a cada usuario
+pub fn credentials_csv(users: &[ProvisionedUser]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["username", "name", "email", "role", "temporary_password"])?;
+    for user in users {
+        writer.write_record([&user.user_name, &user.name, &user.email, &user.role, &user.password])?;
+    }
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::table_import::read_table;
+    use crate::utils::validation::is_valid_password;
+
+    #[test]
+    fn test_temporary_password_meets_policy() {
+        let policy = PasswordPolicy { min_length: 20, ..PasswordPolicy::default() };
+        for _ in 0..50 {
+            let password = generate_temporary_password(&policy);
+            assert_eq!(password.len(), 20);
+            assert!(is_valid_password(&password));
+        }
+    }
+
+    #[test]
+    fn test_parse_users_reports_row_errors() {
+        let csv = "Username,Name,Email,Role\n\
+                   jperez,Juan Pérez,jperez@uni.cu,subjectLeader\n\
+                   mlopez,María López,mlopez@uni.cu,profesor\n\
+                   jperez,Juan Otro,otro@uni.cu,leader\n\
+                   agarcia,Ana García,JPEREZ@uni.cu,leader\n\
+                   existente,Ya Existe,nuevo@uni.cu,admin\n";
+        let taken = HashSet::from(["existente".to_string()]);
+        let table = read_table(csv.as_bytes().to_vec()).unwrap();
+
+        let (users, errors) = parse_users(&table, &PasswordPolicy::default(), &taken, &HashSet::new()).unwrap();
+
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].user_name, "jperez");
+        assert_eq!(errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+
+        let sheet = String::from_utf8(credentials_csv(&users).unwrap()).unwrap();
+        assert!(sheet.starts_with("username,name,email,role,temporary_password\n"));
+        assert!(sheet.contains(&users[0].password));
+    }
+}
@@ -0,0 +1,321 @@
+//! Fechas reales de las semanas de un balance
+//!
+//! Única implementación del cálculo de fechas: la usan la exportación a Excel,
+//! la analítica de carga y `GET /api/balances/<id>/calendar`, que el frontend
+//! consume en lugar de repetir el cálculo.
+//!
+//! Cada semana empieza en el siguiente día hábil y abarca tantos días hábiles
+//! como días laborables tenga la estructura del balance (5 en la estándar, de
+//! lunes a viernes), saltando los días no laborables y los períodos no
+//! académicos. Tras las semanas lectivas vienen la semana de consultas y la de
+//! exámenes finales, que empiezan con la sesión de exámenes del calendario
+//! académico si hay una después de las semanas lectivas.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+
+use crate::utils::fragment_data::{FragmentLayout, GridSection, WorkingDays};
+
+/// Semanas tras las lectivas: consultas y exámenes finales
+pub const FINAL_WEEKS: usize = 2;
+
+/// Período sin clases: (inicio, fin, nombre), ambos extremos incluidos
+pub type NonAcademicPeriod = (NaiveDate, NaiveDate, String);
+
+/// Week date information for display
+#[derive(Debug, Clone)]
+pub struct WeekDateInfo {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Días hábiles de la semana, en orden
+    pub days: Vec<NaiveDate>,
+    pub display_range: String,
+}
+
+/// Fecha de una celda de la cuadrícula
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlotDate {
+    pub index: usize,    // Posición en el array `values` del fragmento
+    pub day: usize,      // 1-based
+    pub date: Option<NaiveDate>,
+}
+
+/// Semana del calendario del balance con la fecha de cada celda
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarWeek {
+    pub section: GridSection,
+    pub week: usize,     // 1-based; consultas y exámenes continúan la numeración
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub display_range: String,
+    pub slots: Vec<SlotDate>,
+}
+
+/// Día hábil saltado por caer en un período no académico
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedDate {
+    pub date: NaiveDate,
+    pub reason: String,
+}
+
+/// Calendario completo de un balance
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceCalendar {
+    pub weeks: Vec<CalendarWeek>,
+    pub skipped: Vec<SkippedDate>,
+}
+
+// ============================================================================
+// DATE CALCULATION HELPERS
+// ============================================================================
+
+/// Nombre del período no académico en el que cae la fecha, si hay alguno
+pub fn non_academic_reason(date: NaiveDate, periods: &[NonAcademicPeriod]) -> Option<&str> {
+    periods
+        .iter()
+        .find(|(start, end, _)| date >= *start && date <= *end)
+        .map(|(_, _, name)| name.as_str())
+}
+
+fn is_working_day(date: NaiveDate, working_days: WorkingDays, periods: &[NonAcademicPeriod]) -> bool {
+    working_days.contains(date.weekday()) && non_academic_reason(date, periods).is_none()
+}
+
+/// Get the next working day (a working weekday, not in non-academic period)
+fn get_next_working_day(date: NaiveDate, working_days: WorkingDays, periods: &[NonAcademicPeriod]) -> NaiveDate {
+    let mut next = date;
+    while !is_working_day(next, working_days, periods) {
+        next += Duration::days(1);
+    }
+    next
+}
+
+/// Semana de días hábiles que empieza en el primer día hábil desde `from`
+fn week_starting(from: NaiveDate, working_days: WorkingDays, periods: &[NonAcademicPeriod]) -> WeekDateInfo {
+    let start_date = get_next_working_day(from, working_days, periods);
+    let mut days = vec![start_date];
+    while days.len() < working_days.count() {
+        let last = days[days.len() - 1];
+        days.push(get_next_working_day(last + Duration::days(1), working_days, periods));
+    }
+    let end_date = days[days.len() - 1];
+
+    WeekDateInfo {
+        start_date,
+        end_date,
+        display_range: format!(
+            "{:02}/{:02} - {:02}/{:02}",
+            start_date.day(), start_date.month(),
+            end_date.day(), end_date.month()
+        ),
+        days,
+    }
+}
+
+/// Fechas de las semanas lectivas, saltando días no laborables y períodos no académicos
+pub fn calculate_week_dates(
+    start_date: NaiveDate,
+    layout: &FragmentLayout,
+    periods: &[NonAcademicPeriod],
+) -> Vec<WeekDateInfo> {
+    let mut weeks: Vec<WeekDateInfo> = Vec::with_capacity(layout.weeks);
+    let mut current_date = start_date;
+
+    for _ in 0..layout.weeks {
+        let week = week_starting(current_date, layout.working_days, periods);
+        current_date = week.end_date + Duration::days(1);
+        weeks.push(week);
+    }
+
+    weeks
+}
+
+/// Primer día de las semanas finales: el inicio de la primera sesión de
+/// exámenes posterior a las semanas lectivas o, si no hay, el día siguiente
+fn final_weeks_start(after_lective: NaiveDate, exam_sessions: &[NaiveDate]) -> NaiveDate {
+    exam_sessions
+        .iter()
+        .copied()
+        .filter(|start| *start >= after_lective)
+        .min()
+        .unwrap_or(after_lective)
+}
+
+/// Fechas de las semanas de consultas y exámenes finales, tras las semanas
+/// lectivas o en la sesión de exámenes (`exam_sessions`: inicio de cada una)
+pub fn calculate_final_weeks_dates(
+    start_date: NaiveDate,
+    layout: &FragmentLayout,
+    periods: &[NonAcademicPeriod],
+    exam_sessions: &[NaiveDate],
+) -> Vec<WeekDateInfo> {
+    let lective_week_dates = calculate_week_dates(start_date, layout, periods);
+    let Some(last_week) = lective_week_dates.last() else {
+        return vec![];
+    };
+
+    let mut final_weeks: Vec<WeekDateInfo> = Vec::with_capacity(FINAL_WEEKS);
+    let mut current_date = final_weeks_start(last_week.end_date + Duration::days(1), exam_sessions);
+    for _ in 0..FINAL_WEEKS {
+        let week = week_starting(current_date, layout.working_days, periods);
+        current_date = week.end_date + Duration::days(1);
+        final_weeks.push(week);
+    }
+
+    final_weeks
+}
+
+// ============================================================================
+// CALENDARIO DEL BALANCE
+// ============================================================================
+
+fn calendar_week(section: GridSection, week: usize, info: &WeekDateInfo, first_index: usize, slots: usize) -> CalendarWeek {
+    CalendarWeek {
+        section,
+        week,
+        start_date: info.start_date,
+        end_date: info.end_date,
+        display_range: info.display_range.clone(),
+        slots: (0..slots)
+            .map(|day| SlotDate { index: first_index + day, day: day + 1, date: info.days.get(day).copied() })
+            .collect(),
+    }
+}
+
+/// Calendario del balance: semanas lectivas, consultas y exámenes con la fecha
+/// de cada celda, y los días hábiles saltados por períodos no académicos
+pub fn build_balance_calendar(
+    start_date: NaiveDate,
+    layout: &FragmentLayout,
+    periods: &[NonAcademicPeriod],
+    exam_sessions: &[NaiveDate],
+) -> BalanceCalendar {
+    let lective = calculate_week_dates(start_date, layout, periods);
+    let finals = calculate_final_weeks_dates(start_date, layout, periods, exam_sessions);
+
+    let mut weeks: Vec<CalendarWeek> = lective
+        .iter()
+        .enumerate()
+        .map(|(idx, info)| calendar_week(GridSection::Week, idx + 1, info, idx * layout.days_per_week, layout.days_per_week))
+        .collect();
+
+    let week_slots = layout.week_slots();
+    if let Some(info) = finals.first() {
+        weeks.push(calendar_week(GridSection::Consultas, layout.weeks + 1, info, week_slots, layout.consultas_slots));
+    }
+    if let Some(info) = finals.get(1) {
+        weeks.push(calendar_week(GridSection::Examenes, layout.weeks + 2, info, week_slots + layout.consultas_slots, layout.exam_slots));
+    }
+
+    let mut skipped = Vec::new();
+    if let Some(last) = weeks.last() {
+        let mut date = start_date;
+        while date <= last.end_date {
+            if layout.working_days.contains(date.weekday())
+                && let Some(reason) = non_academic_reason(date, periods)
+            {
+                skipped.push(SkippedDate { date, reason: reason.to_string() });
+            }
+            date += Duration::days(1);
+        }
+    }
+
+    BalanceCalendar { weeks, skipped }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fragment_data::WeekStructure;
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_calculate_week_dates_no_gaps() {
+        // Start on Monday Sep 1, 2025, no non-academic periods
+        let weeks = calculate_week_dates(date(9, 1), &FragmentLayout::for_weeks(3), &[]);
+
+        assert_eq!(weeks.len(), 3);
+        assert_eq!(weeks[0].start_date, date(9, 1));
+        assert_eq!(weeks[0].end_date, date(9, 5));
+        assert_eq!(weeks[1].start_date, date(9, 8));
+        assert_eq!(weeks[2].start_date, date(9, 15));
+    }
+
+    #[test]
+    fn test_calculate_week_dates_skips_weekend() {
+        // Start on Saturday Sep 6, 2025 - should skip to Monday Sep 8
+        let weeks = calculate_week_dates(date(9, 6), &FragmentLayout::for_weeks(2), &[]);
+
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[0].start_date, date(9, 8)); // Skipped to Monday
+        assert_eq!(weeks[1].start_date, date(9, 15));
+    }
+
+    #[test]
+    fn test_calculate_week_dates_skips_non_academic() {
+        // Non-academic period: Sep 8-14 (second week)
+        let non_academic = vec![(date(9, 8), date(9, 14), "Receso".to_string())];
+        let weeks = calculate_week_dates(date(9, 1), &FragmentLayout::for_weeks(3), &non_academic);
+
+        assert_eq!(weeks.len(), 3);
+        assert_eq!(weeks[0].start_date, date(9, 1));
+        // Week 2 should skip the non-academic period and land on Sep 15
+        assert_eq!(weeks[1].start_date, date(9, 15));
+        assert_eq!(weeks[2].start_date, date(9, 22));
+    }
+
+    #[test]
+    fn test_build_balance_calendar() {
+        // Feriado el miércoles de la primera semana: la semana termina el lunes siguiente
+        let periods = vec![(date(9, 3), date(9, 3), "Feriado".to_string())];
+        let calendar = build_balance_calendar(date(9, 1), &FragmentLayout::for_weeks(2), &periods, &[]);
+
+        assert_eq!(calendar.weeks.len(), 4);
+        let first = &calendar.weeks[0];
+        assert_eq!(first.end_date, date(9, 8));
+        assert_eq!(first.slots[2], SlotDate { index: 2, day: 3, date: Some(date(9, 4)) });
+
+        let examenes = &calendar.weeks[3];
+        assert_eq!(examenes.section, GridSection::Examenes);
+        assert_eq!(examenes.slots.len(), 5);
+        assert_eq!(examenes.slots[0].index, 12);
+
+        assert_eq!(calendar.skipped, vec![SkippedDate { date: date(9, 3), reason: "Feriado".to_string() }]);
+    }
+
+    #[test]
+    fn test_final_weeks_in_exam_session() {
+        // Lectivas del 1 al 12 de septiembre; la sesión de exámenes empieza el 29
+        let layout = FragmentLayout::for_weeks(2);
+        let finals = calculate_final_weeks_dates(date(9, 1), &layout, &[], &[date(8, 1), date(9, 29)]);
+        assert_eq!(finals[0].start_date, date(9, 29));
+        assert_eq!(finals[1].start_date, date(10, 6));
+
+        // Sin sesión de exámenes, justo después de las semanas lectivas
+        let finals = calculate_final_weeks_dates(date(9, 1), &layout, &[], &[]);
+        assert_eq!(finals[0].start_date, date(9, 15));
+    }
+
+    #[test]
+    fn test_custom_working_days() {
+        // De lunes a sábado, 3 celdas por semana: cada semana abarca 6 días hábiles
+        let structure: WeekStructure = serde_json::from_value(serde_json::json!({
+            "slots_per_week": 3,
+            "working_days": [1, 2, 3, 4, 5, 6],
+            "consultas_slots": 2,
+            "exam_slots": 6
+        }))
+        .unwrap();
+        assert!(structure.validate().is_ok());
+        let calendar = build_balance_calendar(date(9, 1), &structure.layout(2), &[], &[]);
+
+        assert_eq!(calendar.weeks[0].end_date, date(9, 6));
+        assert_eq!(calendar.weeks[1].start_date, date(9, 8));
+        assert_eq!(calendar.weeks[1].slots[0].index, 3);
+        assert_eq!(calendar.weeks[2].slots.len(), 2);
+        assert_eq!(calendar.weeks[3].slots[5], SlotDate { index: 13, day: 6, date: Some(date(9, 27)) });
+    }
+}
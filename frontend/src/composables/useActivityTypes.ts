@@ -0,0 +1,62 @@
+/**
+ * Composable for the activity type catalog
+ * Loads the catalog once from the backend and shares it between views.
+ * Until it is loaded (or if the request fails) the constants are used.
+ */
+
+import { computed, ref } from 'vue'
+import { activityTypesService, type ActivityType } from '../services/activityTypes'
+import { HORAS_POR_TIPO, tiposActividadBalance } from '../utils/constants'
+
+// Catálogo compartido por todas las vistas
+const catalog = ref<ActivityType[] | null>(null)
+let loading: Promise<void> | null = null
+
+async function loadCatalog(force = false): Promise<void> {
+  if (catalog.value && !force) return
+  if (!loading || force) {
+    loading = activityTypesService.list().then(response => {
+      if (response.success && response.data) {
+        catalog.value = response.data
+      }
+    }).finally(() => {
+      loading = null
+    })
+  }
+  return loading
+}
+
+export function useActivityTypes() {
+  void loadCatalog()
+
+  /** Opciones de los selectores de la cuadrícula (con la celda vacía primero) */
+  const balanceOptions = computed(() => {
+    if (!catalog.value) return tiposActividadBalance
+    return [
+      { value: '', label: '-' },
+      ...catalog.value.filter(t => t.schedulable).map(t => ({ value: t.code, label: t.code })),
+    ]
+  })
+
+  /** Tipos que se planifican por asignatura (todos los del catálogo) */
+  const planTypes = computed(() =>
+    catalog.value
+      ? catalog.value.map(t => ({ code: t.code, label: t.label }))
+      : tiposActividadBalance.filter(t => t.value).map(t => ({ code: t.value, label: t.label }))
+  )
+
+  /** Horas presenciales por ocurrencia (2 si el código no se conoce) */
+  function hoursFor(code: string): number {
+    const type = catalog.value?.find(t => t.code === code)
+    if (type) return type.counts_as_class_hours ? type.hours : 0
+    return HORAS_POR_TIPO[code] ?? 2
+  }
+
+  return {
+    catalog,
+    balanceOptions,
+    planTypes,
+    hoursFor,
+    reload: () => loadCatalog(true),
+  }
+}
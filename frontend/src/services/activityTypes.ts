@@ -0,0 +1,57 @@
+/**
+ * Activity Types Service
+ *
+ * Catálogo de tipos de actividad (C, CP, S, ...): horas por ocurrencia,
+ * si son críticos o evaluaciones y si se colocan en la cuadrícula.
+ * Cualquier usuario autenticado lo consulta; solo el Leader lo modifica.
+ */
+
+import { httpGet, httpPost, httpPut, httpDelete, type ServiceResponse } from './http'
+
+/** Tipo de actividad del catálogo */
+export interface ActivityType {
+  code: string
+  label: string
+  hours: number
+  is_critical: boolean
+  is_exam: boolean
+  counts_as_class_hours: boolean
+  schedulable: boolean
+  color: string | null
+  sort_order: number
+}
+
+/** Campos editables (el código no se puede cambiar) */
+export type ActivityTypeInput = Omit<ActivityType, 'code'>
+
+export const activityTypesService = {
+  /**
+   * GET /activity-types - Catalog ordered by sort_order
+   */
+  async list(): Promise<ServiceResponse<ActivityType[]>> {
+    return httpGet<ActivityType[]>('/api/activity-types', 'Error al obtener los tipos de actividad')
+  },
+
+  /**
+   * POST /activity-types - Create an activity type (Leader only)
+   */
+  async create(data: ActivityType): Promise<ServiceResponse<ActivityType>> {
+    return httpPost<ActivityType>('/api/activity-types', data, 'Error al crear el tipo de actividad')
+  },
+
+  /**
+   * PUT /activity-types/<code> - Update an activity type (Leader only)
+   */
+  async update(code: string, data: ActivityTypeInput): Promise<ServiceResponse<ActivityType>> {
+    return httpPut<ActivityType>(`/api/activity-types/${encodeURIComponent(code)}`, data, 'Error al actualizar el tipo de actividad')
+  },
+
+  /**
+   * DELETE /activity-types/<code> - Delete an unused activity type (Leader only)
+   */
+  async delete(code: string): Promise<ServiceResponse<void>> {
+    return httpDelete(`/api/activity-types/${encodeURIComponent(code)}`, 'Error al eliminar el tipo de actividad')
+  },
+}
+
+export default activityTypesService
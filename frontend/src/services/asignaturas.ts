@@ -3,7 +3,7 @@
  * Maneja las operaciones CRUD de asignaturas con el backend
  */
 
-import { httpGet, httpPost, httpPut, httpDelete, type ServiceResponse } from './http'
+import { httpGet, httpPost, httpPut, httpDelete, httpDownload, httpUpload, type ServiceResponse } from './http'
 
 // ============================================================================
 // TIPOS
@@ -15,16 +15,8 @@ export interface Asignatura {
   name: string
   year: string
   semester: string
-  c: number | null
-  cp: number | null
-  s: number | null
-  pl: number | null
-  te: number | null
-  t: number | null
-  pp: number | null
-  ec: number | null
-  tc: number | null
-  ef: number | null
+  /** Cantidades planificadas por código de actividad (ver /api/activity-types) */
+  plan: Record<string, number>
   hours: number
   weeks: number | null
   date_start: string
@@ -42,20 +34,32 @@ export interface UpdateAsignaturaData {
   name: string
   year: string
   semester: string
-  c: number | null
-  cp: number | null
-  s: number | null
-  pl: number | null
-  te: number | null
-  t: number | null
-  pp: number | null
-  ec: number | null
-  tc: number | null
-  ef: number | null
+  /** Cantidades planificadas por código de actividad (ver /api/activity-types); si se omite no cambia */
+  plan?: Record<string, number>
   hours: number
   weeks: number | null
 }
 
+/** Resultado de cambiar el jefe de una asignatura */
+export interface LeaderReassignment {
+  asignatura_id: number
+  subject_leader_id: number
+  fragments_reassigned: number  // Fragmentos de balances no archivados que cambiaron de jefe
+}
+
+/** Formato del catálogo exportado */
+export type CatalogFormat = 'csv' | 'xlsx'
+
+/** Informe de importación del catálogo */
+export interface CatalogImportReport {
+  committed: boolean
+  created: number
+  updated: number
+  fragments_reassigned: number
+  rows: { row: number; name: string; year: string; semester: string; action: 'create' | 'update' }[]
+  errors: { row: number; message: string }[]
+}
+
 export interface SubjectLeader {
   id: number
   user_name: string
@@ -91,6 +95,18 @@ export const asignaturasService = {
     return httpPut(`/api/asignaturas/${id}`, data, 'Error al actualizar la asignatura')
   },
 
+  /**
+   * PUT /asignaturas/<id>/leader - Change the subject leader (Leader only)
+   * Propagates to every fragment of the subject in non-archived balances
+   */
+  async reassignLeader(id: number, subjectLeaderId: number): Promise<ServiceResponse<LeaderReassignment>> {
+    return httpPut<LeaderReassignment>(
+      `/api/asignaturas/${id}/leader`,
+      { subject_leader_id: subjectLeaderId },
+      'Error al cambiar el jefe de asignatura'
+    )
+  },
+
   /**
    * DELETE /asignaturas/<id> - Delete a subject (Leader only)
    */
@@ -98,6 +114,24 @@ export const asignaturasService = {
     return httpDelete(`/api/asignaturas/${id}`, 'Error al eliminar la asignatura')
   },
 
+  /**
+   * GET /asignaturas/export - Download the whole catalog (Leader only)
+   */
+  async exportCatalog(format: CatalogFormat = 'csv'): Promise<ServiceResponse<void>> {
+    return httpDownload(`/api/asignaturas/export?format=${format}`, `asignaturas.${format}`, 'Error al exportar el catálogo')
+  },
+
+  /**
+   * POST /asignaturas/import - Import the catalog from CSV or XLSX (Leader only)
+   * Without `commit` only the report is returned; upsert by (name, year, semester)
+   */
+  async importCatalog(file: File, commit = false): Promise<ServiceResponse<CatalogImportReport>> {
+    const form = new FormData()
+    form.append('file', file)
+    form.append('commit', String(commit))
+    return httpUpload<CatalogImportReport>('/api/asignaturas/import', form, 'Error al importar el catálogo')
+  },
+
   /**
    * GET /users/subject-leaders - List subject leaders (for selector)
    */
@@ -40,6 +40,7 @@ export const EVENT_TYPE_CONFIG: Record<string, { label: string; color: string; b
   ACCESS_DENIED: { label: 'Acceso denegado', color: 'text-orange-600', bgColor: 'bg-orange-500' },
   SETTINGS_UPDATED: { label: 'Config. actualizada', color: 'text-purple-600', bgColor: 'bg-purple-500' },
   EXPORT: { label: 'Exportación', color: 'text-teal-600', bgColor: 'bg-teal-500' },
+  STATUS_CHANGE: { label: 'Cambio de estado', color: 'text-indigo-600', bgColor: 'bg-indigo-500' },
 }
 
 export const CATEGORY_CONFIG: Record<string, { label: string; color: string }> = {
@@ -8,7 +8,7 @@
  * - SubjectLeaders llenan sus fragmentos correspondientes
  */
 
-import { httpGet, httpPost, httpPut, httpDelete, type ServiceResponse } from './http'
+import { httpGet, httpPost, httpPut, httpDelete, httpDownload, httpUpload, type ServiceResponse } from './http'
 
 // ============================================================================
 // TIPOS
@@ -29,24 +29,34 @@ export interface AsignaturaInfo {
   year: string
   semester: string
   hours: number
-  // Cantidades planificadas por tipo de clase
-  c: number | null   // Conferencias
-  cp: number | null  // Clases Prácticas
-  s: number | null   // Seminarios
-  pl: number | null  // Prácticas de Laboratorio
-  te: number | null  // Trabajo Extraclase
-  t: number | null   // Tutorías
-  pp: number | null  // Pruebas Parciales
-  ec: number | null  // Examen de Culminación
-  tc: number | null  // Trabajo de Curso
-  ef: number | null  // Examen Final
+  // Cantidades planificadas por código de actividad ('C', 'CP', ...)
+  plan: Record<string, number>
 }
 
+/** Estado del ciclo de vida del balance */
+export type BalanceStatus = 'draft' | 'open' | 'in_review' | 'approved' | 'archived'
+
+/** Acción que cambia el estado del balance */
+export type BalanceAction = 'open' | 'submit_for_review' | 'approve' | 'archive' | 'reopen'
+
+/** Resultado de una transición de estado */
+export interface BalanceTransitionResult {
+  balance_id: number
+  action: BalanceAction
+  from: BalanceStatus
+  to: BalanceStatus
+}
+
+/** Estado de un fragmento (ciclo de revisión: entregado → aprobado o cambios solicitados) */
+export type FragmentStatus = 'pending' | 'in_progress' | 'submitted' | 'changes_requested' | 'completed'
+
 /** Progreso del balance */
 export interface BalanceProgress {
   total: number
   pending: number
   in_progress: number
+  submitted: number
+  changes_requested: number
   completed: number
   percentage: number
 }
@@ -59,8 +69,11 @@ export interface Fragment {
   asignatura: AsignaturaInfo | null
   subject_leader_id: number | null
   subject_leader: SubjectLeaderInfo | null
-  status: 'pending' | 'in_progress' | 'completed'
+  status: FragmentStatus
   data: Record<string, unknown>
+  completion_override_reason: string | null
+  deadline_extension: string | null  // Prórroga concedida por el Leader
+  version: number
   completed_at: string | null
   created_at: string | null
   updated_at: string | null
@@ -73,6 +86,26 @@ export interface NonAcademicPeriod {
   name: string   // Descripción del período
 }
 
+/**
+ * Estructura semanal del balance: celdas por semana lectiva, días laborables
+ * (ISO, 1 = lunes ... 7 = domingo) y celdas de consultas y exámenes.
+ * El array `values` de cada fragmento tiene weeks * slots_per_week + consultas_slots + exam_slots celdas
+ */
+export interface WeekStructure {
+  slots_per_week: number
+  working_days: number[]
+  consultas_slots: number
+  exam_slots: number
+}
+
+/** Estructura estándar (la de los balances sin estructura propia) */
+export const DEFAULT_WEEK_STRUCTURE: WeekStructure = {
+  slots_per_week: 4,
+  working_days: [1, 2, 3, 4, 5],
+  consultas_slots: 4,
+  exam_slots: 5,
+}
+
 /** Balance con fragmentos (respuesta detallada) */
 export interface Balance {
   id: number
@@ -83,10 +116,13 @@ export interface Balance {
   academic_year_text: string
   start_date: string
   weeks: number
-  status: 'draft' | 'in_progress' | 'completed'
+  status: BalanceStatus
   deadline: string | null
   allow_leader_edit: boolean
   non_academic_periods: NonAcademicPeriod[]
+  calendar_id: number | null  // Calendario académico institucional
+  week_structure: WeekStructure
+  version: number
   created_at: string | null
   updated_at: string | null
   fragments: Fragment[]
@@ -103,7 +139,8 @@ export interface BalanceListItem {
   academic_year_text: string
   start_date: string
   weeks: number
-  status: 'draft' | 'in_progress' | 'completed'
+  week_structure: WeekStructure
+  status: BalanceStatus
   deadline: string | null
   non_academic_periods: NonAcademicPeriod[]
   created_at: string | null
@@ -117,7 +154,7 @@ export interface PendingFragment {
   balance_name: string
   asignatura_id: number
   asignatura_name: string
-  status: 'pending' | 'in_progress'
+  status: Exclude<FragmentStatus, 'completed'>  // Los pendientes nunca vienen completados
   deadline: string | null
 }
 
@@ -137,6 +174,20 @@ export interface CreateBalanceRequest {
   allow_leader_edit?: boolean
   asignaturas: SelectedSubject[]
   non_academic_periods?: NonAcademicPeriod[]
+  calendar_id?: number  // Por defecto, el calendario del curso académico
+  week_structure?: Partial<WeekStructure>  // Por defecto, la estándar
+}
+
+/** Request para crear el balance del siguiente curso a partir de otro (Leader) */
+export interface CloneBalanceRequest {
+  academic_year_text: string
+  start_date: string
+  weeks?: number             // Por defecto, las del balance original
+  deadline?: string
+  non_academic_periods?: NonAcademicPeriod[]
+  copy_distribution?: boolean // Copiar la distribución de cada fragmento
+  week_offset?: number       // Desplazamiento de semanas al copiar
+  week_structure?: Partial<WeekStructure> // Por defecto, la del balance original
 }
 
 /** Request para actualizar metadatos del balance (Leader) */
@@ -148,20 +199,224 @@ export interface UpdateBalanceRequest {
   weeks?: number
   deadline?: string
   allow_leader_edit?: boolean
-  status?: 'draft' | 'in_progress' | 'completed'
   non_academic_periods?: NonAcademicPeriod[]
+  calendar_id?: number
+  week_structure?: Partial<WeekStructure>  // Solo si ningún fragmento tiene datos (409 si no)
+  version?: number  // Versión leída; si no coincide el servidor responde 409
+}
+
+/** Opciones de importación desde la plantilla Excel de la facultad */
+export interface ImportTemplateOptions {
+  sheet?: string               // Por defecto, '{año}.ICS' o la primera hoja
+  commit?: boolean             // false = solo informe (dry-run)
+  // Solo al crear un balance nuevo
+  academic_year?: string
+  period?: string
+  academic_year_text?: string
+  deadline?: string
+}
+
+/** Asignatura de la plantilla emparejada con una asignatura existente */
+export interface ImportedSubject {
+  row: number
+  name: string
+  asignatura_id: number
+  asignatura_name: string
+  activities: number
+  existing_fragment: boolean
+}
+
+/** Celda de la plantilla que no se importa */
+export interface ImportCellIssue {
+  row: number
+  subject: string
+  position: string
+  code: string
+}
+
+/** Informe de importación */
+export interface ImportReport {
+  sheet: string
+  committed: boolean
+  balance_id: number | null
+  start_date: string | null
+  weeks_in_file: number
+  weeks_in_balance: number
+  week_mismatch: boolean
+  subjects: ImportedSubject[]
+  unmatched_subjects: { row: number; name: string }[]
+  unknown_codes: ImportCellIssue[]
+  dropped_cells: ImportCellIssue[]
+  locked_subjects: { row: number; name: string; asignatura_id: number; status: string }[]
+  rule_violations: { rule: string; week: number; day: number | null; message: string }[]
+}
+
+/** Carga de un día (columna) de la semana */
+export interface DayLoad {
+  day: number
+  hours: number
+  activities: number
+  critical: number
+  exams: number
+}
+
+/** Carga semanal del estudiante */
+export interface WeekLoad {
+  week: number
+  start_date: string | null
+  end_date: string | null
+  hours: number
+  hours_by_type: Record<string, number>
+  critical: number
+  exams: number
+  days: DayLoad[]
+  exceeded: Array<'hours' | 'critical' | 'exams'>
+}
+
+export interface LoadStats {
+  max: number
+  mean: number
+  std_dev: number
+}
+
+/** Umbrales de sobrecarga semanal */
+export interface LoadThresholds {
+  max_hours: number
+  max_critical: number
+  max_exams: number
+}
+
+/** Analítica de carga semanal de un balance */
+export interface LoadReport {
+  thresholds: LoadThresholds
+  weeks: WeekLoad[]
+  summary: {
+    hours: LoadStats
+    critical: LoadStats
+    exams: LoadStats
+    overloaded_weeks: number[]
+  }
+}
+
+/** Fecha de una celda de la cuadrícula */
+export interface CalendarSlot {
+  index: number        // Posición en el array `values` del fragmento
+  day: number
+  date: string | null  // 'YYYY-MM-DD'
+}
+
+/** Semana del calendario del balance */
+export interface CalendarWeek {
+  section: 'week' | 'consultas' | 'examenes'
+  week: number
+  start_date: string
+  end_date: string
+  display_range: string
+  slots: CalendarSlot[]
+}
+
+/** Calendario del balance calculado por el backend (única fuente de fechas) */
+export interface BalanceCalendar {
+  weeks: CalendarWeek[]
+  skipped: Array<{ date: string; reason: string }>
+}
+
+/** Revisión guardada de un fragmento */
+export interface FragmentRevision {
+  revision_number: number
+  status: FragmentStatus
+  author_id: number | null
+  author_name: string
+  restored_from: number | null
+  created_at: string | null
+}
+
+/** Cambio de una celda entre dos revisiones */
+export interface CellChange {
+  section: 'week' | 'consultas' | 'examenes'
+  week: number | null
+  day: number
+  old: string | null
+  new: string | null
+}
+
+/** Diferencias entre dos revisiones */
+export interface RevisionDiff {
+  from: number
+  to: number
+  changes: CellChange[]
+}
+
+/** Acción del ciclo de revisión de un fragmento */
+export type ReviewAction = 'submit' | 'approve' | 'request_changes'
+
+/** Posición de la cuadrícula a la que se refiere un comentario (sin sección = general) */
+export interface CommentAnchor {
+  section: 'week' | 'consultas' | 'examenes' | null
+  week: number | null  // 1-based, solo en semanas lectivas
+  day: number | null   // 1-based, null = toda la semana
+}
+
+/** Comentario de revisión */
+export interface ReviewComment {
+  id: number
+  parent_id: number | null
+  anchor: CommentAnchor
+  body: string
+  author_id: number | null
+  author_name: string
+  resolved_at: string | null
+  resolved_by_name: string | null
+  created_at: string | null
+}
+
+/** Hilo de comentarios: comentario raíz con sus respuestas */
+export interface CommentThread extends ReviewComment {
+  resolved: boolean
+  replies: ReviewComment[]
+}
+
+/** Comentario nuevo: abre un hilo o responde a uno (parent_id) */
+export interface NewReviewComment {
+  body: string
+  anchor?: CommentAnchor
+  parent_id?: number
+}
+
+/** Request de una acción de revisión */
+export interface FragmentReviewRequest {
+  action: ReviewAction
+  comments?: NewReviewComment[]
+  override_reason?: string  // Aprobar un fragmento que no cuadra con lo planificado
+  version?: number
+}
+
+/** Resultado de una acción de revisión */
+export interface FragmentReviewResult {
+  balance_id: number
+  asignatura_id: number
+  action: ReviewAction
+  from: FragmentStatus
+  to: FragmentStatus
+  version: number
+  comments: ReviewComment[]
 }
 
 /** Request para actualizar un fragmento (SubjectLeader) */
 export interface UpdateFragmentRequest {
   data: Record<string, unknown>
-  status?: 'pending' | 'in_progress' | 'completed'
+  status?: 'pending' | 'in_progress' | 'completed'  // 'completed' solo el Leader; el resto entrega con review()
+  override_reason?: string
+  version?: number  // Versión leída; si no coincide el servidor responde 409
 }
 
 // ============================================================================
 // SERVICIO DE BALANCES
 // ============================================================================
 
+/** Formato de exportación del balance */
+export type ExportFormat = 'xlsx' | 'pdf'
+
 export const balancesService = {
   /**
    * Listar todos los balances
@@ -186,6 +441,13 @@ export const balancesService = {
     return httpPost<Balance>('/api/balances', data, 'Error al crear el balance')
   },
 
+  /**
+   * Crear el balance de un nuevo curso a partir de uno existente (Solo Leader)
+   */
+  async clone(id: number, data: CloneBalanceRequest): Promise<ServiceResponse<Balance>> {
+    return httpPost<Balance>(`/api/balances/${id}/clone`, data, 'Error al clonar el balance')
+  },
+
   /**
    * Actualizar metadatos de un balance (Solo Leader)
    */
@@ -193,6 +455,47 @@ export const balancesService = {
     return httpPut<void>(`/api/balances/${id}`, data, 'Error al actualizar el balance')
   },
 
+  /**
+   * Cambiar el estado del balance según su ciclo de vida (Solo Leader)
+   */
+  async transition(id: number, action: BalanceAction, version?: number): Promise<ServiceResponse<BalanceTransitionResult>> {
+    return httpPost<BalanceTransitionResult>(`/api/balances/${id}/transitions`, { action, version }, 'Error al cambiar el estado del balance')
+  },
+
+  /**
+   * Añadir asignaturas a un balance existente (Solo Leader)
+   * Las asignaturas retiradas antes recuperan su fragmento archivado
+   */
+  async addAsignaturas(id: number, asignaturas: SelectedSubject[], version?: number): Promise<ServiceResponse<Balance>> {
+    return httpPost<Balance>(`/api/balances/${id}/asignaturas`, { asignaturas, version }, 'Error al añadir asignaturas al balance')
+  },
+
+  /**
+   * Retirar una asignatura del balance; su fragmento queda archivado (Solo Leader)
+   */
+  async removeAsignatura(id: number, asignaturaId: number): Promise<ServiceResponse<void>> {
+    return httpDelete(`/api/balances/${id}/asignaturas/${asignaturaId}`, 'Error al retirar la asignatura del balance')
+  },
+
+  /**
+   * Calendario del balance: fechas reales de cada semana y celda
+   */
+  async getCalendar(id: number): Promise<ServiceResponse<BalanceCalendar>> {
+    return httpGet<BalanceCalendar>(`/api/balances/${id}/calendar`, 'Error al obtener el calendario del balance')
+  },
+
+  /**
+   * Carga semanal del estudiante con umbrales opcionales (Solo Leader)
+   */
+  async getLoad(id: number, thresholds: Partial<LoadThresholds> = {}): Promise<ServiceResponse<LoadReport>> {
+    const params = new URLSearchParams()
+    Object.entries(thresholds).forEach(([key, value]) => {
+      if (value !== undefined) params.set(key, String(value))
+    })
+    const query = params.toString()
+    return httpGet<LoadReport>(`/api/balances/${id}/load${query ? `?${query}` : ''}`, 'Error al obtener la carga semanal')
+  },
+
   /**
    * Eliminar un balance y sus fragmentos (Solo Leader)
    */
@@ -201,12 +504,12 @@ export const balancesService = {
   },
 
   /**
-   * Exportar balance a Excel
-   * Descarga un archivo .xlsx con el balance formateado
+   * Exportar balance a Excel o PDF
+   * Descarga un archivo .xlsx o .pdf (para imprimir y firmar) con el balance formateado
    */
-  async exportToExcel(id: number): Promise<{ success: boolean; message?: string }> {
+  async exportBalance(id: number, format: ExportFormat = 'xlsx'): Promise<{ success: boolean; message?: string }> {
     try {
-      const response = await fetch(`/api/balances/${id}/export`, {
+      const response = await fetch(`/api/balances/${id}/export?format=${format}`, {
         method: 'GET',
         credentials: 'include', // Include cookies for auth
       })
@@ -223,7 +526,7 @@ export const balancesService = {
 
       // Get filename from Content-Disposition header or use default
       const contentDisposition = response.headers.get('Content-Disposition')
-      let filename = 'balance_export.xlsx'
+      let filename = `balance_export.${format}`
       if (contentDisposition) {
         const match = contentDisposition.match(/filename="(.+)"/)
         if (match && match[1]) {
@@ -248,6 +551,39 @@ export const balancesService = {
       return { success: false, message: 'Error de conexión al exportar' }
     }
   },
+
+  /**
+   * Exportar en un libro Excel todos los balances del período (1ro–4to) con portada (Solo Leader)
+   */
+  async exportPeriod(academicYearText: string, period: string): Promise<ServiceResponse<void>> {
+    const query = new URLSearchParams({ academic_year_text: academicYearText, period })
+    return httpDownload(`/api/periods/export?${query}`, `balance_periodo_${period}.xlsx`, 'Error al exportar el período')
+  },
+
+  /**
+   * URL del calendario .ics del balance completo (enlace directo, usa la cookie de sesión)
+   */
+  icsUrl(id: number): string {
+    return `/api/balances/${id}/ics`
+  },
+
+  /**
+   * Importar la plantilla Excel de la facultad (Solo Leader)
+   * Sin `balanceId` crea un balance nuevo; sin `commit` solo devuelve el informe.
+   * Si el servidor rechaza la importación, el informe se devuelve igualmente en `data`.
+   */
+  async importTemplate(file: File, options: ImportTemplateOptions = {}, balanceId?: number): Promise<ServiceResponse<ImportReport>> {
+    const form = new FormData()
+    form.append('file', file)
+    for (const [key, value] of Object.entries(options)) {
+      if (value !== undefined && value !== '') {
+        form.append(key, String(value))
+      }
+    }
+
+    const endpoint = balanceId ? `/api/balances/${balanceId}/import` : '/api/balances/import'
+    return httpUpload<ImportReport>(endpoint, form, 'Error al importar la plantilla')
+  },
 }
 
 // ============================================================================
@@ -262,6 +598,24 @@ export const fragmentsService = {
     return httpGet<PendingFragment[]>('/api/fragments/pending', 'Error al obtener fragmentos pendientes')
   },
 
+  /**
+   * Fragmentos sin jefe de asignatura en balances no archivados (Leader)
+   */
+  async getUnassigned(): Promise<ServiceResponse<PendingFragment[]>> {
+    return httpGet<PendingFragment[]>('/api/fragments/unassigned', 'Error al obtener fragmentos sin jefe de asignatura')
+  },
+
+  /**
+   * Entregar un fragmento a otro jefe de asignatura (Leader del balance)
+   */
+  async reassign(balanceId: number, asignaturaId: number, subjectLeaderId: number): Promise<ServiceResponse<void>> {
+    return httpPut(
+      `/api/balances/${balanceId}/fragments/${asignaturaId}/leader`,
+      { subject_leader_id: subjectLeaderId },
+      'Error al reasignar el fragmento'
+    )
+  },
+
   /**
    * Obtener un fragmento específico
    */
@@ -286,6 +640,106 @@ export const fragmentsService = {
       'Error al actualizar el fragmento'
     )
   },
+
+  /**
+   * Listar revisiones de un fragmento (más reciente primero)
+   */
+  async listRevisions(balanceId: number, asignaturaId: number): Promise<ServiceResponse<FragmentRevision[]>> {
+    return httpGet<FragmentRevision[]>(
+      `/api/balances/${balanceId}/fragments/${asignaturaId}/revisions`,
+      'Error al obtener las revisiones'
+    )
+  },
+
+  /**
+   * Comparar dos revisiones celda a celda
+   */
+  async diffRevisions(
+    balanceId: number,
+    asignaturaId: number,
+    from: number,
+    to: number
+  ): Promise<ServiceResponse<RevisionDiff>> {
+    return httpGet<RevisionDiff>(
+      `/api/balances/${balanceId}/fragments/${asignaturaId}/revisions/diff?from=${from}&to=${to}`,
+      'Error al comparar las revisiones'
+    )
+  },
+
+  /**
+   * Restaurar una revisión antigua como revisión nueva
+   */
+  async restoreRevision(
+    balanceId: number,
+    asignaturaId: number,
+    revisionNumber: number
+  ): Promise<ServiceResponse<FragmentRevision>> {
+    return httpPost<FragmentRevision>(
+      `/api/balances/${balanceId}/fragments/${asignaturaId}/revisions/${revisionNumber}/restore`,
+      {},
+      'Error al restaurar la revisión'
+    )
+  },
+
+  /**
+   * Entregar, aprobar o pedir cambios en un fragmento
+   * Entrega el jefe de asignatura; aprueba o pide cambios el Leader del balance
+   */
+  async review(
+    balanceId: number,
+    asignaturaId: number,
+    data: FragmentReviewRequest
+  ): Promise<ServiceResponse<FragmentReviewResult>> {
+    return httpPost<FragmentReviewResult>(
+      `/api/balances/${balanceId}/fragments/${asignaturaId}/review`,
+      data,
+      'Error en la revisión del fragmento'
+    )
+  },
+
+  /**
+   * Listar los hilos de comentarios de un fragmento
+   */
+  async listComments(balanceId: number, asignaturaId: number, unresolvedOnly = false): Promise<ServiceResponse<CommentThread[]>> {
+    return httpGet<CommentThread[]>(
+      `/api/balances/${balanceId}/fragments/${asignaturaId}/comments${unresolvedOnly ? '?unresolved=true' : ''}`,
+      'Error al obtener los comentarios'
+    )
+  },
+
+  /**
+   * Abrir un hilo de comentarios o responder a uno existente
+   */
+  async addComment(balanceId: number, asignaturaId: number, comment: NewReviewComment): Promise<ServiceResponse<ReviewComment>> {
+    return httpPost<ReviewComment>(
+      `/api/balances/${balanceId}/fragments/${asignaturaId}/comments`,
+      comment,
+      'Error al guardar el comentario'
+    )
+  },
+
+  /**
+   * Resolver o reabrir un hilo de comentarios
+   */
+  async resolveThread(
+    balanceId: number,
+    asignaturaId: number,
+    commentId: number,
+    resolved: boolean
+  ): Promise<ServiceResponse<ReviewComment>> {
+    return httpPut<ReviewComment>(
+      `/api/balances/${balanceId}/fragments/${asignaturaId}/comments/${commentId}/resolve`,
+      { resolved },
+      'Error al actualizar el hilo'
+    )
+  },
+
+  /**
+   * URL del calendario .ics de un fragmento
+   */
+  icsUrl(balanceId: number, asignaturaId: number): string {
+    return `/api/balances/${balanceId}/fragments/${asignaturaId}/ics`
+  },
 }
 
 export default balancesService
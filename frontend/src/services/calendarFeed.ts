@@ -0,0 +1,39 @@
+/**
+ * Servicio de suscripción iCalendar
+ * URL personal con token secreto que lista los fragmentos que lidera el usuario
+ * en los balances abiertos. Regenerarla invalida la anterior.
+ */
+
+import { httpGet, httpPost, httpDelete, type ServiceResponse } from './http'
+
+/** Suscripción del usuario actual */
+export interface CalendarFeedInfo {
+  token: string
+  url: string    // Ruta relativa, p. ej. '/api/calendar-feed/<token>.ics'
+  created_at: string | null
+}
+
+export const calendarFeedService = {
+  /**
+   * Suscripción actual (null si no hay ninguna)
+   */
+  async get(): Promise<ServiceResponse<CalendarFeedInfo | null>> {
+    return httpGet<CalendarFeedInfo | null>('/api/calendar-feed', 'Error al obtener la suscripción')
+  },
+
+  /**
+   * Crear o regenerar la URL de suscripción
+   */
+  async regenerate(): Promise<ServiceResponse<CalendarFeedInfo>> {
+    return httpPost<CalendarFeedInfo>('/api/calendar-feed', {}, 'Error al generar la suscripción')
+  },
+
+  /**
+   * Revocar la URL de suscripción
+   */
+  async revoke(): Promise<ServiceResponse<void>> {
+    return httpDelete('/api/calendar-feed', 'Error al revocar la suscripción')
+  },
+}
+
+export default calendarFeedService
@@ -0,0 +1,107 @@
+/**
+ * Servicio del Calendario Académico
+ * Feriados, vacaciones y sesiones de exámenes por curso académico,
+ * compartidos por todos los balances del curso.
+ */
+
+import { httpGet, httpPost, httpPut, httpDelete, type ServiceResponse } from './http'
+
+// ============================================================================
+// TIPOS
+// ============================================================================
+
+/** Tipo de período del calendario */
+export type CalendarPeriodKind = 'holiday' | 'vacation' | 'exam_session'
+
+/** Período del calendario (para crear) */
+export interface CalendarPeriodInput {
+  kind: CalendarPeriodKind
+  name: string
+  start: string  // 'YYYY-MM-DD'
+  end: string    // 'YYYY-MM-DD'
+}
+
+/** Período del calendario guardado */
+export interface CalendarPeriod extends CalendarPeriodInput {
+  id: number
+}
+
+/** Calendario académico de un curso */
+export interface AcademicCalendar {
+  id: number
+  academic_year_text: string
+  name: string
+  periods: CalendarPeriod[]
+  created_at: string | null
+  updated_at: string | null
+  balances_linked?: number  // Solo al crear: balances existentes del curso que pasan a usarlo
+}
+
+/** Request para crear un calendario (Leader o Admin) */
+export interface CreateCalendarRequest {
+  academic_year_text: string
+  name: string
+  periods?: CalendarPeriodInput[]
+}
+
+/** Request para actualizar un calendario; `periods` sustituye a todos los existentes */
+export interface UpdateCalendarRequest {
+  academic_year_text?: string
+  name?: string
+  periods?: CalendarPeriodInput[]
+}
+
+// ============================================================================
+// SERVICIO
+// ============================================================================
+
+export const calendarsService = {
+  /**
+   * Listar los calendarios académicos
+   */
+  async list(): Promise<ServiceResponse<AcademicCalendar[]>> {
+    return httpGet<AcademicCalendar[]>('/api/calendars', 'Error al obtener los calendarios')
+  },
+
+  /**
+   * Obtener un calendario académico
+   */
+  async get(id: number): Promise<ServiceResponse<AcademicCalendar>> {
+    return httpGet<AcademicCalendar>(`/api/calendars/${id}`, 'Error al obtener el calendario')
+  },
+
+  /**
+   * Crear el calendario de un curso académico (Leader o Admin)
+   */
+  async create(data: CreateCalendarRequest): Promise<ServiceResponse<AcademicCalendar>> {
+    return httpPost<AcademicCalendar>('/api/calendars', data, 'Error al crear el calendario')
+  },
+
+  /**
+   * Actualizar un calendario académico (Leader o Admin)
+   */
+  async update(id: number, data: UpdateCalendarRequest): Promise<ServiceResponse<AcademicCalendar>> {
+    return httpPut<AcademicCalendar>(`/api/calendars/${id}`, data, 'Error al actualizar el calendario')
+  },
+
+  /**
+   * Eliminar un calendario académico (Leader o Admin)
+   */
+  async delete(id: number): Promise<ServiceResponse<void>> {
+    return httpDelete<void>(`/api/calendars/${id}`, 'Error al eliminar el calendario')
+  },
+
+  /**
+   * Añadir un período a un calendario (Leader o Admin)
+   */
+  async addPeriod(id: number, period: CalendarPeriodInput): Promise<ServiceResponse<AcademicCalendar>> {
+    return httpPost<AcademicCalendar>(`/api/calendars/${id}/periods`, period, 'Error al añadir el período')
+  },
+
+  /**
+   * Eliminar un período de un calendario (Leader o Admin)
+   */
+  async deletePeriod(id: number, periodId: number): Promise<ServiceResponse<void>> {
+    return httpDelete<void>(`/api/calendars/${id}/periods/${periodId}`, 'Error al eliminar el período')
+  },
+}
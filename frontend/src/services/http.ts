@@ -170,3 +170,85 @@ export async function httpDelete<T = void>(
 ): Promise<ServiceResponse<T>> {
   return httpRequest<T>({ endpoint, method: 'DELETE', errorMessage })
 }
+
+/**
+ * POST multipart/form-data (file uploads)
+ * The browser sets the Content-Type with the multipart boundary.
+ * Unlike httpRequest, `data` is returned on errors too (e.g. import reports).
+ */
+export async function httpUpload<T>(
+  endpoint: string,
+  form: FormData,
+  errorMessage = 'Error al subir el archivo'
+): Promise<ServiceResponse<T>> {
+  try {
+    const response = await fetch(getApiUrl(endpoint), {
+      method: 'POST',
+      credentials: 'include',
+      body: form,
+    })
+
+    try {
+      const data: ApiResponseWithData<T> = await response.json()
+      const success = response.ok && data.alert !== 'error'
+      return {
+        success,
+        message: data.message || (success ? undefined : errorMessage),
+        data: data.data ?? undefined,
+      }
+    } catch {
+      return { success: false, message: `Error HTTP: ${response.status}` }
+    }
+  } catch (error) {
+    console.error(`Error in ${endpoint}:`, error)
+    return { success: false, message: `Error de conexión: ${errorMessage}` }
+  }
+}
+
+/**
+ * GET a file and trigger the browser download
+ */
+export async function httpDownload(
+  endpoint: string,
+  fallbackFilename: string,
+  errorMessage = 'Error al descargar el archivo'
+): Promise<ServiceResponse<void>> {
+  try {
+    const response = await fetch(getApiUrl(endpoint), {
+      method: 'GET',
+      credentials: 'include',
+    })
+
+    if (!response.ok) {
+      try {
+        const error = await response.json()
+        return { success: false, message: error.message || errorMessage }
+      } catch {
+        return { success: false, message: `Error HTTP: ${response.status}` }
+      }
+    }
+
+    await saveResponseFile(response, fallbackFilename)
+    return { success: true }
+  } catch (error) {
+    console.error(`Error in ${endpoint}:`, error)
+    return { success: false, message: `Error de conexión: ${errorMessage}` }
+  }
+}
+
+/**
+ * Save a file response with the browser download dialog
+ * The filename is taken from Content-Disposition when present.
+ */
+export async function saveResponseFile(response: Response, fallbackFilename: string): Promise<void> {
+  const match = response.headers.get('Content-Disposition')?.match(/filename="(.+)"/)
+  const blob = await response.blob()
+  const url = window.URL.createObjectURL(blob)
+  const a = document.createElement('a')
+  a.href = url
+  a.download = match?.[1] ?? fallbackFilename
+  document.body.appendChild(a)
+  a.click()
+  window.URL.revokeObjectURL(url)
+  document.body.removeChild(a)
+}
@@ -0,0 +1,110 @@
+/**
+ * Notifications Service
+ *
+ * Provides API calls for the in-app notification inbox and the email
+ * notification outbox (admin only).
+ */
+
+import { httpGet, httpPost, httpPut, type ServiceResponse } from './http'
+
+/** Event that generated a notification */
+export type NotificationEvent =
+  | 'balance_created'
+  | 'fragment_assigned'
+  | 'fragment_edited'
+  | 'fragment_approved'
+  | 'deadline_changed'
+  | 'deadline_approaching'
+  | 'changes_requested'
+
+/** Notification in the current user's inbox */
+export interface AppNotification {
+  id: number
+  event: NotificationEvent
+  title: string
+  message: string
+  balance_id: number | null
+  asignatura_id: number | null
+  read_at: string | null
+  created_at: string | null
+}
+
+export const notificationsService = {
+  /**
+   * Latest notifications of the current user, optionally only unread ones
+   */
+  async list(unread = false, limit?: number): Promise<ServiceResponse<AppNotification[]>> {
+    const params = new URLSearchParams()
+    if (unread) params.set('unread', 'true')
+    if (limit) params.set('limit', limit.toString())
+    const query = params.toString() ? `?${params.toString()}` : ''
+    return httpGet<AppNotification[]>(`/api/notifications${query}`, 'Error al obtener las notificaciones')
+  },
+
+  /**
+   * Number of unread notifications of the current user
+   */
+  async unreadCount(): Promise<ServiceResponse<{ unread: number }>> {
+    return httpGet<{ unread: number }>('/api/notifications/unread-count', 'Error al contar las notificaciones')
+  },
+
+  /**
+   * Mark a notification as read
+   */
+  async markRead(id: number): Promise<ServiceResponse<void>> {
+    return httpPut<void>(`/api/notifications/${id}/read`, {}, 'Error al marcar la notificación como leída')
+  },
+
+  /**
+   * Mark every notification of the current user as read
+   */
+  async markAllRead(): Promise<ServiceResponse<void>> {
+    return httpPut<void>('/api/notifications/read-all', {}, 'Error al marcar las notificaciones como leídas')
+  },
+}
+
+/** Delivery status of a queued email */
+export type OutboxStatus = 'pending' | 'sent' | 'failed'
+
+/** Queued email notification */
+export interface OutboxEntry {
+  id: number
+  event: 'balance_created' | 'fragment_assigned' | 'deadline_approaching' | 'changes_requested'
+  recipient_id: number | null
+  recipient_email: string
+  recipient_name: string
+  subject: string
+  body: string
+  status: OutboxStatus
+  attempts: number
+  next_attempt_at: string
+  last_error: string | null
+  sent_at: string | null
+  created_at: string | null
+}
+
+export const outboxService = {
+  /**
+   * Latest queued emails, optionally filtered by status (admin only)
+   */
+  async list(status?: OutboxStatus): Promise<ServiceResponse<OutboxEntry[]>> {
+    const query = status ? `?status=${status}` : ''
+    return httpGet<OutboxEntry[]>(`/api/notifications/outbox${query}`, 'Error al obtener la cola de notificaciones')
+  },
+
+  /**
+   * Queue a failed email again with a fresh set of attempts (admin only)
+   */
+  async retry(id: number): Promise<ServiceResponse<void>> {
+    return httpPost<void>(`/api/notifications/outbox/${id}/retry`, {}, 'Error al reintentar el aviso')
+  },
+
+  /**
+   * Send a test email to the current admin using the saved SMTP settings
+   */
+  async sendTest(): Promise<ServiceResponse<void>> {
+    return httpPost<void>('/api/notifications/test', {}, 'Error al enviar el correo de prueba')
+  },
+}
+
+export default outboxService
@@ -20,6 +20,7 @@ export interface SettingsGrouped {
   session: SettingResponse[]
   password: SettingResponse[]
   audit: SettingResponse[]
+  notifications: SettingResponse[]
 }
 
 /** Public settings response */
@@ -3,7 +3,8 @@
  * Maneja todas las operaciones CRUD de usuarios con el backend
  */
 
-import { httpGet, httpPost, httpPut, httpDelete, type ServiceResponse } from './http'
+import { httpGet, httpPost, httpPut, httpDelete, saveResponseFile, type ServiceResponse } from './http'
+import { getApiUrl } from '../config/api'
 import type { UserWithToken } from '../types'
 
 // In admin context, users always include token from database
@@ -27,6 +28,12 @@ export interface UpdateUserRequest {
   created_at?: string
 }
 
+/** Error de validación de una fila del CSV */
+export interface ImportRowError {
+  row: number
+  message: string
+}
+
 // ============================================================================
 // SERVICIO (Object literal pattern - standardized)
 // ============================================================================
@@ -59,6 +66,38 @@ export const usersService = {
   async delete(id: number): Promise<ServiceResponse<void>> {
     return httpDelete(`/api/users/${id}`, 'Error al eliminar usuario')
   },
+
+  /**
+   * POST /users/import - Bulk create users from a CSV (Admin only)
+   * Columns: username, name, email, role. On success the credentials sheet is
+   * downloaded; if any row is invalid nobody is created and `data` has the row errors.
+   */
+  async importCsv(file: File): Promise<ServiceResponse<ImportRowError[]>> {
+    const form = new FormData()
+    form.append('file', file)
+    try {
+      const response = await fetch(getApiUrl('/api/users/import'), {
+        method: 'POST',
+        credentials: 'include',
+        body: form,
+      })
+
+      if (!response.ok) {
+        try {
+          const error = await response.json()
+          return { success: false, message: error.message || 'Error al importar usuarios', data: error.data ?? undefined }
+        } catch {
+          return { success: false, message: `Error HTTP: ${response.status}` }
+        }
+      }
+
+      await saveResponseFile(response, 'credenciales.csv')
+      return { success: true, message: 'Usuarios creados. Entregue a cada uno su contraseña temporal' }
+    } catch (error) {
+      console.error('Error importing users:', error)
+      return { success: false, message: 'Error de conexión al importar usuarios' }
+    }
+  },
 }
 
 export default usersService
@@ -21,6 +21,13 @@ import {
   type UpdateBalanceRequest,
   type UpdateFragmentRequest,
   type SelectedSubject,
+  type BalanceCalendar,
+  type WeekStructure,
+  type FragmentStatus,
+  type CommentThread,
+  type NewReviewComment,
+  type ReviewAction,
+  DEFAULT_WEEK_STRUCTURE,
 } from '../services/balances'
 
 // Re-exportar tipos para uso externo
@@ -80,24 +87,15 @@ export interface EditableFragment {
   asignaturaName: string
   asignaturaHours: number  // Horas planificadas de la asignatura
   // Cantidades planificadas por tipo de clase
-  asignaturaPlan: {
-    c: number | null
-    cp: number | null
-    s: number | null
-    pl: number | null
-    te: number | null
-    t: number | null
-    pp: number | null
-    ec: number | null
-    tc: number | null
-    ef: number | null
-  }
+  asignaturaPlan: Record<string, number>
   weeks: number
+  weekStructure: WeekStructure  // Celdas por semana, consultas y exámenes
   startDate: string  // Fecha de inicio del balance 'YYYY-MM-DD'
-  nonAcademicPeriods: NonAcademicPeriod[] // Períodos no académicos del balance
-  status: 'pending' | 'in_progress' | 'completed'
+  calendar: BalanceCalendar | null  // Fechas de las semanas calculadas por el backend
+  status: FragmentStatus
   deadline: string | null
   data: Record<string, unknown>
+  version: number  // Versión leída del servidor (control de concurrencia)
 }
 
 // Module-level constant for valid activity types
@@ -121,9 +119,15 @@ export const useBalanceStore = defineStore('balance', () => {
   /** Fragmentos pendientes del usuario actual (para Dashboard SubjectLeader) */
   const pendingFragments = ref<PendingFragment[]>([])
   
+  /** Fragmentos sin jefe de asignatura en balances no archivados (Leader) */
+  const unassignedFragments = ref<PendingFragment[]>([])
+  
   /** Fragmento actualmente en edición (SubjectLeader) */
   const currentFragment = ref<EditableFragment | null>(null)
   
+  /** Hilos de comentarios de revisión del fragmento en edición */
+  const fragmentComments = ref<CommentThread[]>([])
+  
   /** Cálculos del balance actual */
   const calculations = ref<FragmentCalculation[]>([])
   
@@ -387,6 +391,27 @@ export const useBalanceStore = defineStore('balance', () => {
   /**
    * Cargar fragmentos pendientes (para Dashboard)
    */
+  async function fetchUnassignedFragments(): Promise<boolean> {
+    const response = await fragmentsService.getUnassigned()
+    if (response.success && response.data) {
+      unassignedFragments.value = response.data
+      return true
+    }
+    error.value = response.message || 'Error al cargar fragmentos sin jefe de asignatura'
+    return false
+  }
+
+  /** Entrega un fragmento a otro jefe y refresca la lista de fragmentos sin jefe */
+  async function reassignFragment(balanceId: number, asignaturaId: number, subjectLeaderId: number): Promise<{ success: boolean; message: string }> {
+    const response = await fragmentsService.reassign(balanceId, asignaturaId, subjectLeaderId)
+    if (response.success) {
+      unassignedFragments.value = unassignedFragments.value.filter(
+        f => !(f.balance_id === balanceId && f.asignatura_id === asignaturaId)
+      )
+    }
+    return { success: response.success, message: response.message || '' }
+  }
+
   async function fetchPendingFragments(): Promise<boolean> {
     isLoading.value = true
     error.value = null
@@ -424,7 +449,10 @@ export const useBalanceStore = defineStore('balance', () => {
         return false
       }
 
-      const fragmentResponse = await fragmentsService.get(balanceId, asignaturaId)
+      const [fragmentResponse, calendarResponse] = await Promise.all([
+        fragmentsService.get(balanceId, asignaturaId),
+        balancesService.getCalendar(balanceId),
+      ])
       if (fragmentResponse.success && fragmentResponse.data) {
         const fragment = fragmentResponse.data
         const balance = balanceResponse.data
@@ -437,26 +465,18 @@ export const useBalanceStore = defineStore('balance', () => {
           asignaturaId: fragment.asignatura_id,
           asignaturaName: asig?.name || 'Asignatura',
           asignaturaHours: asig?.hours || 0,
-          asignaturaPlan: {
-            c: asig?.c ?? null,
-            cp: asig?.cp ?? null,
-            s: asig?.s ?? null,
-            pl: asig?.pl ?? null,
-            te: asig?.te ?? null,
-            t: asig?.t ?? null,
-            pp: asig?.pp ?? null,
-            ec: asig?.ec ?? null,
-            tc: asig?.tc ?? null,
-            ef: asig?.ef ?? null,
-          },
+          asignaturaPlan: asig?.plan ?? {},
           weeks: balance.weeks,
+          weekStructure: balance.week_structure ?? DEFAULT_WEEK_STRUCTURE,
           startDate: balance.start_date,
-          nonAcademicPeriods: balance.non_academic_periods || [],
+          calendar: calendarResponse.data ?? null,
           status: fragment.status,
           deadline: balance.deadline,
           data: fragment.data || {},
+          version: fragment.version,
         }
         isDirty.value = false
+        await fetchFragmentComments()
         return true
       } else {
         error.value = fragmentResponse.message || 'Fragmento no encontrado'
@@ -501,8 +521,9 @@ export const useBalanceStore = defineStore('balance', () => {
 
   /**
    * Guardar fragmento (SubjectLeader)
+   * Con `submit` se entrega además para que el Leader del balance lo revise
    */
-  async function saveFragment(markAsCompleted: boolean = false): Promise<{ success: boolean; message: string }> {
+  async function saveFragment(submit: boolean = false): Promise<{ success: boolean; message: string }> {
     if (!currentFragment.value) {
       return { success: false, message: 'No hay fragmento para guardar' }
     }
@@ -511,9 +532,11 @@ export const useBalanceStore = defineStore('balance', () => {
     error.value = null
 
     try {
+      // Con cambios solicitados el estado se mantiene hasta la nueva entrega
       const request: UpdateFragmentRequest = {
         data: currentFragment.value.data,
-        status: markAsCompleted ? 'completed' : 'in_progress',
+        status: currentFragment.value.status === 'changes_requested' ? undefined : 'in_progress',
+        version: currentFragment.value.version,
       }
 
       const response = await fragmentsService.update(
@@ -523,15 +546,21 @@ export const useBalanceStore = defineStore('balance', () => {
       )
 
       if (response.success) {
-        currentFragment.value.status = request.status || 'in_progress'
+        currentFragment.value.status = request.status || currentFragment.value.status
+        currentFragment.value.version += 1
         isDirty.value = false
+
+        if (submit) {
+          const review = await reviewFragment('submit')
+          if (!review.success) return review
+        }
         
         // Refrescar pendientes
         await fetchPendingFragments()
         
         return { 
           success: true, 
-          message: markAsCompleted ? 'Fragmento completado' : 'Fragmento guardado' 
+          message: submit ? 'Fragmento entregado para revisión' : 'Fragmento guardado' 
         }
       } else {
         error.value = response.message || 'Error al guardar'
@@ -546,6 +575,75 @@ export const useBalanceStore = defineStore('balance', () => {
     }
   }
 
+  /**
+   * Entregar, aprobar o pedir cambios en el fragmento actual
+   * Los comentarios enviados con la acción se añaden a los hilos cargados
+   */
+  async function reviewFragment(
+    action: ReviewAction,
+    comments: NewReviewComment[] = [],
+    overrideReason?: string
+  ): Promise<{ success: boolean; message: string }> {
+    if (!currentFragment.value) {
+      return { success: false, message: 'No hay fragmento para revisar' }
+    }
+
+    const fragment = currentFragment.value
+    const response = await fragmentsService.review(fragment.balanceId, fragment.asignaturaId, {
+      action,
+      comments,
+      override_reason: overrideReason,
+      version: fragment.version,
+    })
+    if (response.success && response.data) {
+      fragment.status = response.data.to
+      fragment.version = response.data.version
+      await fetchFragmentComments()
+    }
+    return { success: response.success, message: response.message || '' }
+  }
+
+  /** Cargar los hilos de comentarios del fragmento actual */
+  async function fetchFragmentComments(): Promise<boolean> {
+    if (!currentFragment.value) return false
+    const response = await fragmentsService.listComments(currentFragment.value.balanceId, currentFragment.value.asignaturaId)
+    if (response.success && response.data) {
+      fragmentComments.value = response.data
+      return true
+    }
+    return false
+  }
+
+  /** Abrir un hilo o responder a uno en el fragmento actual */
+  async function addFragmentComment(comment: NewReviewComment): Promise<{ success: boolean; message: string }> {
+    if (!currentFragment.value) {
+      return { success: false, message: 'No hay fragmento abierto' }
+    }
+    const response = await fragmentsService.addComment(currentFragment.value.balanceId, currentFragment.value.asignaturaId, comment)
+    if (response.success) {
+      await fetchFragmentComments()
+    }
+    return { success: response.success, message: response.message || '' }
+  }
+
+  /** Resolver o reabrir un hilo del fragmento actual */
+  async function resolveCommentThread(commentId: number, resolved: boolean): Promise<{ success: boolean; message: string }> {
+    if (!currentFragment.value) {
+      return { success: false, message: 'No hay fragmento abierto' }
+    }
+    const response = await fragmentsService.resolveThread(
+      currentFragment.value.balanceId,
+      currentFragment.value.asignaturaId,
+      commentId,
+      resolved
+    )
+    if (response.success) {
+      const thread = fragmentComments.value.find(t => t.id === commentId)
+      if (thread) thread.resolved = resolved
+    }
+    return { success: response.success, message: response.message || '' }
+  }
+
   // ============================================================================
   // ACTIONS - CÁLCULOS
   // ============================================================================
@@ -591,6 +689,7 @@ export const useBalanceStore = defineStore('balance', () => {
     currentBalance.value = null
     editableBalance.value = null
     currentFragment.value = null
+    fragmentComments.value = []
     calculations.value = []
     isDirty.value = false
     error.value = null
@@ -609,6 +708,7 @@ export const useBalanceStore = defineStore('balance', () => {
    */
   function closeFragment() {
     currentFragment.value = null
+    fragmentComments.value = []
     isDirty.value = false
   }
 
@@ -625,7 +725,9 @@ export const useBalanceStore = defineStore('balance', () => {
     currentBalance,
     editableBalance,
     pendingFragments,
+    unassignedFragments,
     currentFragment,
+    fragmentComments,
     calculations,
     isDirty,
     isLoading,
@@ -651,10 +753,16 @@ export const useBalanceStore = defineStore('balance', () => {
 
     // Actions - Fragments
     fetchPendingFragments,
+    fetchUnassignedFragments,
+    reassignFragment,
     loadFragment,
     updateFragmentData,
     updateFragmentCell,
     saveFragment,
+    reviewFragment,
+    fetchFragmentComments,
+    addFragmentComment,
+    resolveCommentThread,
 
     // Actions - Cálculos
     calculateAllFragments,
@@ -0,0 +1,133 @@
+/**
+ * Store de Notificaciones
+ * Bandeja de avisos del usuario (fragmentos asignados, editados, aprobados,
+ * cambios de fecha límite...) y su contador de no leídos
+ */
+
+import { defineStore } from 'pinia'
+import { ref, computed } from 'vue'
+import { notificationsService, type AppNotification } from '../services/notifications'
+
+export const useNotificationsStore = defineStore('notifications', () => {
+  // State
+  const notifications = ref<AppNotification[]>([])
+  const unreadCount = ref(0)
+  const isLoading = ref(false)
+  const error = ref<string | null>(null)
+
+  // Getters
+  const hasUnread = computed(() => unreadCount.value > 0)
+
+  // Actions
+
+  /**
+   * Cargar los últimos avisos del usuario
+   */
+  async function fetchNotifications() {
+    isLoading.value = true
+    error.value = null
+
+    try {
+      const response = await notificationsService.list()
+
+      if (response.success && response.data) {
+        notifications.value = response.data
+        return { success: true }
+      }
+
+      error.value = response.message || 'Error al cargar las notificaciones'
+      return { success: false, message: error.value }
+    } catch (err) {
+      error.value = 'Error de conexión'
+      return { success: false, message: error.value }
+    } finally {
+      isLoading.value = false
+    }
+  }
+
+  /**
+   * Actualizar el contador de no leídos (se consulta periódicamente)
+   */
+  async function fetchUnreadCount() {
+    try {
+      const response = await notificationsService.unreadCount()
+      if (response.success && response.data) {
+        unreadCount.value = response.data.unread
+      }
+    } catch {
+      // El contador se vuelve a pedir en la siguiente consulta
+    }
+  }
+
+  /**
+   * Marcar un aviso como leído
+   */
+  async function markRead(id: number) {
+    const notification = notifications.value.find(n => n.id === id)
+    if (notification?.read_at) {
+      return { success: true }
+    }
+
+    try {
+      const response = await notificationsService.markRead(id)
+
+      if (response.success) {
+        if (notification) {
+          notification.read_at = new Date().toISOString()
+        }
+        unreadCount.value = Math.max(0, unreadCount.value - 1)
+        return { success: true }
+      }
+
+      return { success: false, message: response.message || 'Error al marcar la notificación' }
+    } catch (err) {
+      return { success: false, message: 'Error de conexión' }
+    }
+  }
+
+  /**
+   * Marcar todos los avisos como leídos
+   */
+  async function markAllRead() {
+    try {
+      const response = await notificationsService.markAllRead()
+
+      if (response.success) {
+        const now = new Date().toISOString()
+        notifications.value.forEach(n => {
+          n.read_at ??= now
+        })
+        unreadCount.value = 0
+        return { success: true, message: response.message }
+      }
+
+      return { success: false, message: response.message || 'Error al marcar las notificaciones' }
+    } catch (err) {
+      return { success: false, message: 'Error de conexión' }
+    }
+  }
+
+  function clearNotifications() {
+    notifications.value = []
+    unreadCount.value = 0
+    error.value = null
+  }
+
+  return {
+    // State
+    notifications,
+    unreadCount,
+    isLoading,
+    error,
+
+    // Getters
+    hasUnread,
+
+    // Actions
+    fetchNotifications,
+    fetchUnreadCount,
+    markRead,
+    markAllRead,
+    clearNotifications,
+  }
+})
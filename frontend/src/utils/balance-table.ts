@@ -4,6 +4,8 @@
  * BalanceViewTable, and BalanceViewFinalTable components
  */
 
+import type { BalanceCalendar, CalendarWeek } from '../services/balances'
+
 /**
  * Converts legacy numeric values to string or returns string as-is.
  * Used for rendering cell values in balance tables.
@@ -121,7 +123,7 @@ export interface WeekGroup {
 }
 
 // Cache for generated week groups to avoid redundant calculations
-// Key format: `${totalWeeks}-${groupSize}`
+// Key format: `${totalWeeks}-${groupSize}-${cellsPerWeek}`
 const weekGroupsCache = new Map<string, WeekGroup[]>()
 
 /**
@@ -130,10 +132,11 @@ const weekGroupsCache = new Map<string, WeekGroup[]>()
  * 
  * @param totalWeeks - Total number of weeks in the balance
  * @param groupSize - Number of weeks per group (default: 4)
+ * @param cellsPerWeek - Cells per week, `week_structure.slots_per_week` (default: 4)
  * @returns Array of week groups with start/end indices and week numbers
  */
-export function generateWeekGroups(totalWeeks: number, groupSize: number = 4): WeekGroup[] {
-  const cacheKey = `${totalWeeks}-${groupSize}`
+export function generateWeekGroups(totalWeeks: number, groupSize: number = 4, cellsPerWeek: number = 4): WeekGroup[] {
+  const cacheKey = `${totalWeeks}-${groupSize}-${cellsPerWeek}`
   
   // Return cached result if available
   const cached = weekGroupsCache.get(cacheKey)
@@ -154,7 +157,7 @@ export function generateWeekGroups(totalWeeks: number, groupSize: number = 4): W
       start,
       end,
       weeks,
-      startIndex: i * 4, // 4 cells per week (Monday, Tuesday, Wednesday, Thursday)
+      startIndex: i * cellsPerWeek,
     })
   }
   
@@ -177,32 +180,17 @@ export function generateWeekGroups(totalWeeks: number, groupSize: number = 4): W
 }
 
 // ============================================================================
-// Week Dates Calculation
+// Week Dates (calculadas por el backend: GET /api/balances/<id>/calendar)
 // ============================================================================
 
-/** Período no académico */
-export interface NonAcademicPeriod {
-  start: string  // 'YYYY-MM-DD'
-  end: string    // 'YYYY-MM-DD'
-  name: string
-}
-
 /** Información de fecha de una semana */
 export interface WeekDateInfo {
-  weekNumber: number       // Número de semana (1-15)
-  startDate: Date          // Fecha de inicio (lunes)
-  endDate: Date            // Fecha de fin (jueves, ya que son 4 días lectivos)
+  weekNumber: number       // Número de semana (1-15); consultas y exámenes continúan la numeración
+  startDate: Date          // Primer día hábil de la semana
+  endDate: Date            // Último día hábil de la semana
   displayStart: string     // Formato corto para mostrar: "12/01"
-  displayEnd: string       // Formato corto: "15/01"
-  displayRange: string     // Formato completo: "12/01 - 15/01"
-}
-
-/**
- * Normaliza una fecha a medianoche local (sin componente de hora)
- * Esto evita problemas de zona horaria al comparar fechas
- */
-function normalizeToLocalMidnight(date: Date): Date {
-  return new Date(date.getFullYear(), date.getMonth(), date.getDate())
+  displayEnd: string       // Formato corto: "16/01"
+  displayRange: string     // Formato completo: "12/01 - 16/01"
 }
 
 /**
@@ -217,228 +205,40 @@ function parseLocalDate(dateStr: string): Date {
   return new Date(year, month - 1, day) // month es 0-indexed
 }
 
-/**
- * Verifica si una fecha cae dentro de algún período no académico
- * Usa comparación de fechas normalizadas para evitar problemas de zona horaria
- */
-function isInNonAcademicPeriod(date: Date, periods: NonAcademicPeriod[]): boolean {
-  // Normalizar la fecha a medianoche local para comparación consistente
-  const normalizedDate = normalizeToLocalMidnight(date)
-  const dateTime = normalizedDate.getTime()
-  
-  for (const period of periods) {
-    // Parsear las fechas del período como fechas locales
-    const start = parseLocalDate(period.start).getTime()
-    const end = parseLocalDate(period.end).getTime()
-    
-    // Incluye tanto la fecha de inicio como la de fin en el período no académico
-    if (dateTime >= start && dateTime <= end) {
-      return true
-    }
-  }
-  return false
-}
-
-/**
- * Verifica si una fecha es fin de semana (sábado=6 o domingo=0)
- */
-function isWeekend(date: Date): boolean {
-  const day = date.getDay()
-  return day === 0 || day === 6
+function formatShort(d: Date): string {
+  const day = d.getDate().toString().padStart(2, '0')
+  const month = (d.getMonth() + 1).toString().padStart(2, '0')
+  return `${day}/${month}`
 }
 
 /**
- * Obtener el siguiente día hábil (no fin de semana, no en período no académico)
+ * Convierte una semana del calendario del backend al formato de las tablas
  */
-function getNextWorkingDay(date: Date, nonAcademicPeriods: NonAcademicPeriod[]): Date {
-  const next = new Date(date)
-  while (isWeekend(next) || isInNonAcademicPeriod(next, nonAcademicPeriods)) {
-    next.setDate(next.getDate() + 1)
+export function toWeekDateInfo(week: CalendarWeek): WeekDateInfo {
+  const startDate = parseLocalDate(week.start_date)
+  const endDate = parseLocalDate(week.end_date)
+  return {
+    weekNumber: week.week,
+    startDate,
+    endDate,
+    displayStart: formatShort(startDate),
+    displayEnd: formatShort(endDate),
+    displayRange: week.display_range,
   }
-  return next
 }
 
 /**
- * Calcula las fechas de cada semana académica
- * 
- * @param startDate - Fecha de inicio del semestre (YYYY-MM-DD)
- * @param totalWeeks - Número total de semanas lectivas
- * @param nonAcademicPeriods - Períodos no académicos (vacaciones, feriados)
- * @returns Array con información de fechas para cada semana
+ * Fechas de las semanas lectivas del calendario del balance
  */
-export function calculateWeekDates(
-  startDate: string,
-  totalWeeks: number,
-  nonAcademicPeriods: NonAcademicPeriod[] = []
-): WeekDateInfo[] {
-  const weeks: WeekDateInfo[] = []
-  // Usar parseLocalDate para evitar problemas de zona horaria
-  let currentDate = parseLocalDate(startDate)
-  
-  // Asegurar que empezamos en día hábil
-  currentDate = getNextWorkingDay(currentDate, nonAcademicPeriods)
-  
-  for (let weekNum = 1; weekNum <= totalWeeks; weekNum++) {
-    // Inicio de la semana (asegurar que es día hábil - lunes)
-    const weekStart = getNextWorkingDay(currentDate, nonAcademicPeriods)
-    
-    // Encontrar el final de la semana (5 días laborables: L, M, X, J, V)
-    // Avanzar 4 días desde el inicio (para llegar al viernes)
-    let workingDays = 0
-    let weekEnd = new Date(weekStart)
-    
-    while (workingDays < 4) { // 4 porque ya tenemos el día de inicio (total 5 días)
-      weekEnd.setDate(weekEnd.getDate() + 1)
-      if (!isWeekend(weekEnd) && !isInNonAcademicPeriod(weekEnd, nonAcademicPeriods)) {
-        workingDays++
-      }
-    }
-    
-    // Formatear fechas para mostrar
-    const formatShort = (d: Date) => {
-      const day = d.getDate().toString().padStart(2, '0')
-      const month = (d.getMonth() + 1).toString().padStart(2, '0')
-      return `${day}/${month}`
-    }
-    
-    weeks.push({
-      weekNumber: weekNum,
-      startDate: weekStart,
-      endDate: weekEnd,
-      displayStart: formatShort(weekStart),
-      displayEnd: formatShort(weekEnd),
-      displayRange: `${formatShort(weekStart)} - ${formatShort(weekEnd)}`,
-    })
-    
-    // Avanzar al siguiente lunes (saltar el fin de semana)
-    currentDate = new Date(weekEnd)
-    currentDate.setDate(currentDate.getDate() + 1)
-    currentDate = getNextWorkingDay(currentDate, nonAcademicPeriods)
-  }
-  
-  return weeks
+export function getWeekDates(calendar: BalanceCalendar | null): WeekDateInfo[] {
+  if (!calendar) return []
+  return calendar.weeks.filter(w => w.section === 'week').map(toWeekDateInfo)
 }
 
 /**
- * Calcula las fechas de las semanas finales (consultas y exámenes)
- * Continúa después de las semanas lectivas
+ * Fechas de las semanas finales (consultas y exámenes) del calendario del balance
  */
-export function calculateFinalWeeksDates(
-  startDate: string,
-  lectiveWeeks: number,
-  nonAcademicPeriods: NonAcademicPeriod[] = [],
-  finalWeeksCount: number = 2 // Semana de consultas + Semana de exámenes
-): WeekDateInfo[] {
-  // Primero calculamos las semanas lectivas para saber dónde terminan
-  const lectiveWeekDates = calculateWeekDates(startDate, lectiveWeeks, nonAcademicPeriods)
-  
-  if (lectiveWeekDates.length === 0) return []
-  
-  // La semana final empieza el lunes después de la última semana lectiva
-  const lastLectiveWeek = lectiveWeekDates[lectiveWeekDates.length - 1]
-  if (!lastLectiveWeek) return []
-  
-  let currentDate = new Date(lastLectiveWeek.endDate)
-  currentDate.setDate(currentDate.getDate() + 1)
-  currentDate = getNextWorkingDay(currentDate, nonAcademicPeriods)
-  
-  const finalWeeks: WeekDateInfo[] = []
-  
-  for (let i = 0; i < finalWeeksCount; i++) {
-    const weekStart = getNextWorkingDay(currentDate, nonAcademicPeriods)
-    
-    let workingDays = 0
-    let weekEnd = new Date(weekStart)
-    
-    while (workingDays < 4) {
-      weekEnd.setDate(weekEnd.getDate() + 1)
-      if (!isWeekend(weekEnd) && !isInNonAcademicPeriod(weekEnd, nonAcademicPeriods)) {
-        workingDays++
-      }
-    }
-    
-    const formatShort = (d: Date) => {
-      const day = d.getDate().toString().padStart(2, '0')
-      const month = (d.getMonth() + 1).toString().padStart(2, '0')
-      return `${day}/${month}`
-    }
-    
-    finalWeeks.push({
-      weekNumber: lectiveWeeks + i + 1, // Continúa la numeración
-      startDate: weekStart,
-      endDate: weekEnd,
-      displayStart: formatShort(weekStart),
-      displayEnd: formatShort(weekEnd),
-      displayRange: `${formatShort(weekStart)} - ${formatShort(weekEnd)}`,
-    })
-    
-    currentDate = new Date(weekEnd)
-    currentDate.setDate(currentDate.getDate() + 1)
-    currentDate = getNextWorkingDay(currentDate, nonAcademicPeriods)
-  }
-  
-  return finalWeeks
-}
-
-/**
- * Cache para fechas de semanas calculadas
- */
-const weekDatesCache = new Map<string, WeekDateInfo[]>()
-const finalWeekDatesCache = new Map<string, WeekDateInfo[]>()
-
-/**
- * Versión cacheada de calculateWeekDates
- */
-export function getWeekDates(
-  startDate: string,
-  totalWeeks: number,
-  nonAcademicPeriods: NonAcademicPeriod[] = []
-): WeekDateInfo[] {
-  const cacheKey = `${startDate}-${totalWeeks}-${JSON.stringify(nonAcademicPeriods)}`
-  
-  const cached = weekDatesCache.get(cacheKey)
-  if (cached) {
-    return cached
-  }
-  
-  const result = calculateWeekDates(startDate, totalWeeks, nonAcademicPeriods)
-  
-  // Limitar tamaño del cache
-  if (weekDatesCache.size >= 20) {
-    const firstKey = weekDatesCache.keys().next().value
-    if (firstKey) {
-      weekDatesCache.delete(firstKey)
-    }
-  }
-  
-  weekDatesCache.set(cacheKey, result)
-  return result
-}
-
-/**
- * Versión cacheada de calculateFinalWeeksDates
- */
-export function getFinalWeeksDates(
-  startDate: string,
-  lectiveWeeks: number,
-  nonAcademicPeriods: NonAcademicPeriod[] = []
-): WeekDateInfo[] {
-  const cacheKey = `final-${startDate}-${lectiveWeeks}-${JSON.stringify(nonAcademicPeriods)}`
-  
-  const cached = finalWeekDatesCache.get(cacheKey)
-  if (cached) {
-    return cached
-  }
-  
-  const result = calculateFinalWeeksDates(startDate, lectiveWeeks, nonAcademicPeriods)
-  
-  if (finalWeekDatesCache.size >= 20) {
-    const firstKey = finalWeekDatesCache.keys().next().value
-    if (firstKey) {
-      finalWeekDatesCache.delete(firstKey)
-    }
-  }
-  
-  finalWeekDatesCache.set(cacheKey, result)
-  return result
+export function getFinalWeeksDates(calendar: BalanceCalendar | null): WeekDateInfo[] {
+  if (!calendar) return []
+  return calendar.weeks.filter(w => w.section !== 'week').map(toWeekDateInfo)
 }